@@ -0,0 +1,286 @@
+//! Dispatch benchmarks.
+//!
+//! Run with `cargo bench --features stats`. CI runs each benchmark
+//! once with `cargo bench --features stats -- --test`.
+
+use std::time::SystemTime;
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use redux_rs::{
+    chain_reducers, ActionKind, ActionWithMeta, BoundedRecorder, Breakpoint, CacheableCondition,
+    EnablingCondition, FrozenTime, Instant, Store,
+};
+
+#[derive(Clone)]
+struct Add(u64);
+
+impl EnablingCondition<u64> for Add {
+    fn is_enabled(&self, _: &u64) -> bool {
+        self.0 != 0
+    }
+}
+
+fn reducer(state: &mut u64, action: &ActionWithMeta<Add>) {
+    *state = state.wrapping_add(action.action.0);
+}
+
+fn chained_reducer(state: &mut u64, action: &ActionWithMeta<Add>) {
+    chain_reducers!(state, action, reducer, reducer, reducer, reducer, reducer);
+}
+
+/// Store with a clock which doesn't need a syscall, so that clock
+/// reads don't dominate.
+fn store(reducer: fn(&mut u64, &ActionWithMeta<Add>)) -> Store<u64, FrozenTime, Add> {
+    let service = FrozenTime::new(Instant::now());
+    Store::new(reducer, |_, _| {}, service, SystemTime::UNIX_EPOCH, 0)
+}
+
+fn dispatch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dispatch");
+
+    let mut trivial = store(reducer);
+    group.bench_function("trivial", |b| {
+        b.iter(|| trivial.dispatch(black_box(Add(1))))
+    });
+
+    let mut chained = store(chained_reducer);
+    group.bench_function("chained_5", |b| {
+        b.iter(|| chained.dispatch(black_box(Add(1))))
+    });
+
+    let mut observed = store(reducer);
+    observed.enable_stats(|_| "Add");
+    observed.set_recorder(Some(Box::new(BoundedRecorder::new(1024))));
+    group.bench_function("stats_and_recorder", |b| {
+        b.iter(|| observed.dispatch(black_box(Add(1))))
+    });
+
+    let mut rejecting = store(reducer);
+    group.bench_function("rejected", |b| {
+        b.iter(|| rejecting.dispatch(black_box(Add(0))))
+    });
+
+    // Hits 4 breakpoints without producing follow-ups, so the list of
+    // hits is the only one built, see `SmallVec`.
+    let mut breaking = store(reducer);
+    for _ in 0..4 {
+        breaking.add_breakpoint(Breakpoint::Action(|_| true));
+    }
+    group.bench_function("no_followups", |b| {
+        b.iter(|| {
+            breaking.dispatch(black_box(Add(1)));
+            breaking.resume();
+        })
+    });
+
+    group.finish();
+}
+
+/// Kinds of [Event], two of the 50 subscribers are interested in each.
+const TOPICS: [&str; 25] = [
+    "t0", "t1", "t2", "t3", "t4", "t5", "t6", "t7", "t8", "t9", "t10", "t11", "t12", "t13", "t14",
+    "t15", "t16", "t17", "t18", "t19", "t20", "t21", "t22", "t23", "t24",
+];
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Topic(u8);
+
+impl From<Topic> for &'static str {
+    fn from(topic: Topic) -> Self {
+        TOPICS[usize::from(topic.0)]
+    }
+}
+
+#[derive(Clone)]
+struct Event(u8);
+
+impl EnablingCondition<u64> for Event {}
+
+impl ActionKind for Event {
+    type Kind = Topic;
+
+    fn kind(&self) -> Topic {
+        Topic(self.0)
+    }
+}
+
+fn event_store() -> Store<u64, FrozenTime, Event> {
+    let service = FrozenTime::new(Instant::now());
+    Store::new(|_, _| {}, |_, _| {}, service, SystemTime::UNIX_EPOCH, 0)
+}
+
+fn subscribers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("subscribers_50");
+    let topics = TOPICS.len() as u8;
+
+    // Listeners checking the kind themselves.
+    let mut unfiltered = event_store();
+    for i in 0..50 {
+        let topic = i % topics;
+        unfiltered.subscribe(move |state: &u64, action: &ActionWithMeta<Event>| {
+            if action.action.0 == topic {
+                black_box(state);
+            }
+        });
+    }
+    let mut next = 0;
+    group.bench_function("unfiltered", |b| {
+        b.iter(|| {
+            next = (next + 1) % topics;
+            unfiltered.dispatch(black_box(Event(next)))
+        })
+    });
+
+    let mut filtered = event_store();
+    for i in 0..50 {
+        filtered.subscribe_filtered(&[Topic(i % topics)], |state: &u64, _: &_| {
+            black_box(state);
+        });
+    }
+    group.bench_function("filtered", |b| {
+        b.iter(|| {
+            next = (next + 1) % topics;
+            filtered.dispatch(black_box(Event(next)))
+        })
+    });
+
+    group.finish();
+}
+
+fn drain(c: &mut Criterion) {
+    c.bench_function("drain_100k", |b| {
+        b.iter_batched(
+            || {
+                let mut store = store(reducer);
+                store.pause_with_capacity(100_000);
+                for i in 1..=100_000 {
+                    store.dispatch(Add(i));
+                }
+                store
+            },
+            |mut store| {
+                store.resume();
+                store
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+/// Store observed the way a debugging session would, with subscribers
+/// and state history.
+fn observed_store() -> Store<u64, FrozenTime, Add> {
+    let mut store = store(reducer);
+    for _ in 0..10 {
+        store.subscribe(|state: &u64, _: &_| {
+            black_box(state);
+        });
+    }
+    store.enable_state_history(1024);
+    store
+}
+
+/// Catching up with a recording, all the way through optional
+/// subsystems, and past them with `fast_forward`, which came out about
+/// 10x faster with these subsystems when added.
+fn catch_up(c: &mut Criterion) {
+    let mut recorder = store(reducer);
+    recorder.set_recorder(Some(Box::new(BoundedRecorder::new(100_000))));
+    for i in 1..=100_000 {
+        recorder.dispatch(Add(i));
+    }
+    let header = recorder.recording_header();
+    let recording: Vec<_> = recorder.recent_actions().cloned().collect();
+    let until = recorder.last_action_id();
+
+    let mut group = c.benchmark_group("catch_up_100k");
+    group.bench_function("replay", |b| {
+        b.iter_batched(
+            observed_store,
+            |mut store| {
+                store.replay(&header, &recording).unwrap();
+                store
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("fast_forward", |b| {
+        b.iter_batched(
+            observed_store,
+            |mut store| {
+                store.fast_forward(recording.iter().cloned(), until);
+                store
+            },
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+/// Node whose list of peers rarely changes, with a version bumped
+/// whenever it does.
+struct Node {
+    peers: Vec<bool>,
+    peers_version: u64,
+    blocks: u64,
+}
+
+#[derive(Clone)]
+struct Block;
+
+/// Scans all the peers, for every block.
+impl EnablingCondition<Node> for Block {
+    fn is_enabled(&self, node: &Node) -> bool {
+        node.peers.iter().filter(|ready| **ready).count() * 2 >= node.peers.len()
+    }
+}
+
+impl CacheableCondition<Node> for Block {
+    fn dep_fingerprint(&self, node: &Node) -> u64 {
+        node.peers_version
+    }
+}
+
+fn node_store() -> Store<Node, FrozenTime, Block> {
+    let node = Node {
+        peers: vec![true; 1000],
+        peers_version: 0,
+        blocks: 0,
+    };
+    let service = FrozenTime::new(Instant::now());
+    Store::new(
+        |node, _| node.blocks += 1,
+        |_, _| {},
+        service,
+        SystemTime::UNIX_EPOCH,
+        node,
+    )
+}
+
+/// Enabling condition depending on rarely changing state, evaluated
+/// for every action, or cached, which came out about 7x faster
+/// when added.
+fn condition_cache(c: &mut Criterion) {
+    let mut group = c.benchmark_group("condition_1k_peers");
+
+    let mut evaluated = node_store();
+    group.bench_function("evaluated", |b| {
+        b.iter(|| evaluated.dispatch(black_box(Block)))
+    });
+
+    let mut cached = node_store();
+    cached.enable_condition_cache(|_| "Block");
+    group.bench_function("cached", |b| b.iter(|| cached.dispatch(black_box(Block))));
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    dispatch,
+    subscribers,
+    drain,
+    catch_up,
+    condition_cache
+);
+criterion_main!(benches);
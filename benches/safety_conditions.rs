@@ -0,0 +1,58 @@
+//! Safety conditions benchmarks, sequential against parallel.
+//!
+//! Run with `cargo bench --features rayon --bench safety_conditions`.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use redux_rs::SafetyConditions;
+
+const PARTS: usize = 32;
+const PART_LEN: usize = 16 * 1024;
+
+/// State made of independent parts, each checked by its own condition.
+struct State {
+    parts: Vec<Vec<u64>>,
+}
+
+fn sorted<const I: usize>(state: &State) -> Result<(), usize> {
+    match state.parts[I].windows(2).position(|w| w[0] > w[1]) {
+        Some(position) => Err(position),
+        None => Ok(()),
+    }
+}
+
+macro_rules! conditions {
+    ($($i:literal)*) => {
+        SafetyConditions::new()$(.with(sorted::<$i>))*
+    };
+}
+
+fn conditions() -> SafetyConditions<State, usize> {
+    conditions!(
+        0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15
+        16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31
+    )
+}
+
+fn safety_conditions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("safety_conditions");
+
+    let state = State {
+        parts: (0..PARTS).map(|_| (0..PART_LEN as u64).collect()).collect(),
+    };
+    let sequential = conditions();
+    let parallel = conditions().parallel();
+    assert_eq!(sequential.len(), PARTS);
+    assert_eq!(sequential.check(&state), parallel.check(&state));
+
+    group.bench_function("sequential_32", |b| {
+        b.iter(|| sequential.check(black_box(&state)))
+    });
+    group.bench_function("parallel_32", |b| {
+        b.iter(|| parallel.check(black_box(&state)))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, safety_conditions);
+criterion_main!(benches);
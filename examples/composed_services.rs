@@ -0,0 +1,143 @@
+//! Store with a bundle of services: clock, randomness and a network,
+//! which is faked here. Effects ask for the service they need through
+//! `HasService`, so the bundle can be swapped for a test one.
+//!
+//! Run with `cargo run --example composed_services`.
+
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime};
+
+use redux_rs::{
+    forward_service, ActionWithMeta, EnablingCondition, FrozenTime, HasService, Instant,
+    RandomnessService, SeededRandomness, Store,
+};
+
+/// Network which loses every other message.
+#[derive(Default)]
+struct FakeNetwork {
+    lose_next: bool,
+    delivered: VecDeque<u64>,
+}
+
+impl FakeNetwork {
+    fn send(&mut self, request_id: u64) {
+        self.lose_next = !self.lose_next;
+        if !self.lose_next {
+            self.delivered.push_back(request_id);
+        }
+    }
+}
+
+struct Services {
+    time: FrozenTime,
+    rng: SeededRandomness,
+    network: FakeNetwork,
+}
+
+impl HasService<FrozenTime> for Services {
+    fn service(&mut self) -> &mut FrozenTime {
+        &mut self.time
+    }
+}
+
+impl HasService<SeededRandomness> for Services {
+    fn service(&mut self) -> &mut SeededRandomness {
+        &mut self.rng
+    }
+}
+
+impl HasService<FakeNetwork> for Services {
+    fn service(&mut self) -> &mut FakeNetwork {
+        &mut self.network
+    }
+}
+
+forward_service!(TimeService for Services => FrozenTime);
+forward_service!(RandomnessService for Services => SeededRandomness);
+
+#[derive(Debug)]
+enum Action {
+    Send { attempt: u32 },
+    Sent { request_id: u64, attempt: u32 },
+    Delivered { request_id: u64 },
+}
+
+#[derive(Default)]
+struct State {
+    attempts: u32,
+    pending: Option<u64>,
+    delivered: Option<u64>,
+}
+
+impl EnablingCondition<State> for Action {
+    fn is_enabled(&self, state: &State) -> bool {
+        state.delivered.is_none()
+    }
+}
+
+fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+    match &action.action {
+        Action::Send { attempt } => state.attempts = *attempt,
+        Action::Sent { request_id, .. } => state.pending = Some(*request_id),
+        Action::Delivered { request_id } => {
+            state.pending = None;
+            state.delivered = Some(*request_id);
+        }
+    }
+}
+
+/// Only needs randomness and a network, from whatever bundle.
+fn send<S>(service: &mut S) -> u64
+where
+    S: RandomnessService + HasService<FakeNetwork>,
+{
+    let request_id = service.next_u64();
+    HasService::<FakeNetwork>::service(service).send(request_id);
+    request_id
+}
+
+fn effects(store: &mut Store<State, Services, Action>, action: &ActionWithMeta<Action>) {
+    match &action.action {
+        Action::Send { attempt } => {
+            let request_id = send(&mut store.service);
+            store.dispatch(Action::Sent {
+                request_id,
+                attempt: *attempt,
+            });
+        }
+        Action::Sent { attempt, .. } => {
+            if let Some(request_id) = store.service_as::<FakeNetwork>().delivered.pop_front() {
+                store.dispatch(Action::Delivered { request_id });
+                return;
+            }
+            // Retry with jitter.
+            let jitter = store.service.next_u64_below(100);
+            store
+                .service_as::<FrozenTime>()
+                .advance(Duration::from_millis(100 + jitter));
+            store.dispatch(Action::Send {
+                attempt: attempt + 1,
+            });
+        }
+        Action::Delivered { .. } => {}
+    }
+}
+
+fn main() {
+    let services = Services {
+        time: FrozenTime::new(Instant::now()),
+        rng: SeededRandomness::new(42),
+        network: FakeNetwork::default(),
+    };
+    let mut store = Store::new(reducer, effects, services, SystemTime::now(), State::default());
+    store.dispatch(Action::Send { attempt: 1 });
+
+    let uptime = store.uptime();
+    let state = store.state();
+    println!(
+        "request {:x} delivered after {} attempts, {:?} since start",
+        state.delivered.unwrap(),
+        state.attempts,
+        uptime,
+    );
+}
@@ -0,0 +1,65 @@
+//! Counter store connected to the Redux DevTools.
+//!
+//! Start the socket server with `npx remotedev-server --port 8000`,
+//! open the devtools with "Use custom (local) server" pointing to it,
+//! then run `cargo run --example devtools_counter --features devtools`.
+
+use std::thread;
+use std::time::Duration;
+
+use redux_rs::{ActionWithMeta, DevToolsConnector, EnablingCondition, Store};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+enum Action {
+    Increment,
+    Decrement,
+}
+
+#[derive(Serialize, Default, Clone)]
+struct State {
+    counter: i64,
+}
+
+impl EnablingCondition<State> for Action {}
+
+fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+    match &action.action {
+        Action::Increment => state.counter += 1,
+        Action::Decrement => state.counter -= 1,
+    }
+}
+
+fn classify(action: &Action) -> &'static str {
+    match action {
+        Action::Increment => "Increment",
+        Action::Decrement => "Decrement",
+    }
+}
+
+fn main() -> std::io::Result<()> {
+    let mut devtools =
+        DevToolsConnector::connect("ws://localhost:8000/socketcluster/", "counter", classify)?;
+
+    let mut store = Store::simple(reducer, |_, _| {}, State::default());
+    store.set_recorder(Some(Box::new(devtools.recorder())));
+    store.enable_time_travel(64);
+    devtools.poll(&mut store);
+
+    for i in 0.. {
+        if !store.is_detached() {
+            if i % 3 == 2 {
+                store.dispatch(Action::Decrement);
+            } else {
+                store.dispatch(Action::Increment);
+            }
+        }
+        devtools.poll(&mut store);
+        if !devtools.is_connected() {
+            break;
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! Counter store as the model of an egui app, driven by [FrameDriver].
+//!
+//! Run with `cargo run --example egui_counter --features eframe`.
+
+use std::time::Duration;
+
+use eframe::egui;
+use redux_rs::{
+    ActionWithMeta, EnablingCondition, FrameDriver, Store, UiDispatcher, UI_INBOX_CAPACITY,
+};
+
+#[derive(Debug)]
+enum Action {
+    Increment,
+    Decrement,
+    HideToast,
+}
+
+#[derive(Default)]
+struct State {
+    counter: i64,
+    toast: Option<&'static str>,
+}
+
+impl EnablingCondition<State> for Action {
+    fn is_enabled(&self, state: &State) -> bool {
+        match self {
+            Action::Decrement => state.counter > 0,
+            _ => true,
+        }
+    }
+}
+
+fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+    match &action.action {
+        Action::Increment => {
+            state.counter += 1;
+            if state.counter % 10 == 0 {
+                state.toast = Some("Another ten!");
+            }
+        }
+        Action::Decrement => state.counter -= 1,
+        Action::HideToast => state.toast = None,
+    }
+}
+
+/// Service keeps a dispatcher, so that effects can set timers.
+struct Service {
+    ui: Option<UiDispatcher<Action>>,
+}
+
+impl redux_rs::TimeService for Service {}
+
+fn effects(store: &mut Store<State, Service, Action>, action: &ActionWithMeta<Action>) {
+    if let (Action::Increment, 0) = (&action.action, store.state().counter % 10) {
+        if let Some(ui) = &store.service.ui {
+            ui.dispatch_after(Duration::from_secs(2), Action::HideToast);
+        }
+    }
+}
+
+struct App {
+    driver: FrameDriver<State, Service, Action>,
+    ui: UiDispatcher<Action>,
+}
+
+impl App {
+    fn new() -> Self {
+        let store = Store::new(
+            reducer,
+            effects,
+            Service { ui: None },
+            std::time::SystemTime::now(),
+            State::default(),
+        );
+        let mut driver = FrameDriver::new(store, UI_INBOX_CAPACITY);
+        let ui = driver.dispatcher();
+        driver.store_mut().service.ui = Some(ui.clone());
+        Self { driver, ui }
+    }
+}
+
+impl eframe::App for App {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        let dispatcher = &self.ui;
+        let state = self.driver.begin_frame();
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading(state.counter.to_string());
+            ui.horizontal(|ui| {
+                if ui.button("-").clicked() {
+                    dispatcher.dispatch(Action::Decrement);
+                }
+                if ui.button("+").clicked() {
+                    dispatcher.dispatch(Action::Increment);
+                }
+            });
+            if let Some(toast) = state.toast {
+                ui.label(toast);
+            }
+        });
+
+        self.driver.end_frame();
+        if self.driver.pending_timers() > 0 {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+    }
+}
+
+fn main() -> eframe::Result<()> {
+    eframe::run_native(
+        "redux-rs counter",
+        eframe::NativeOptions::default(),
+        Box::new(|_| Box::new(App::new())),
+    )
+}
@@ -0,0 +1,132 @@
+//! Application made of two feature modules, peers and a mempool, each
+//! with its own state, actions, reducer and effects. The application
+//! only says where they live in its state and action.
+//!
+//! Run with `cargo run --example modules`.
+
+use redux_rs::{embed, lens, EnablingCondition, ModuleRegistry, StoreBuilder};
+
+mod peers {
+    use redux_rs::{ActionWithMeta, Module, ScopedStore, StoreLike};
+
+    #[derive(Debug, Default)]
+    pub struct State {
+        pub connecting: Vec<u16>,
+        pub connected: Vec<u16>,
+    }
+
+    #[derive(Debug)]
+    pub enum Action {
+        Connect(u16),
+        Connected(u16),
+    }
+
+    pub struct Peers;
+
+    impl Module for Peers {
+        type SubState = State;
+        type SubAction = Action;
+
+        fn initial_state() -> State {
+            State::default()
+        }
+
+        fn reducer(state: &mut State, action: &ActionWithMeta<&Action>) {
+            match action.action {
+                Action::Connect(port) => state.connecting.push(*port),
+                Action::Connected(port) => {
+                    state.connecting.retain(|p| p != port);
+                    state.connected.push(*port);
+                }
+            }
+        }
+
+        /// Connecting always succeeds.
+        fn effects<Parent: StoreLike>(
+            store: &mut ScopedStore<'_, Parent, State, Action>,
+            action: &ActionWithMeta<&Action>,
+        ) {
+            if let Action::Connect(port) = action.action {
+                store.dispatch(Action::Connected(*port));
+            }
+        }
+    }
+}
+
+mod mempool {
+    use redux_rs::{ActionWithMeta, Module};
+
+    #[derive(Debug, Default)]
+    pub struct State {
+        pub operations: Vec<String>,
+        pub capacity: usize,
+    }
+
+    #[derive(Debug)]
+    pub enum Action {
+        Add(String),
+    }
+
+    /// Keeps at most 2 operations, has no effects.
+    pub struct Mempool;
+
+    impl Module for Mempool {
+        type SubState = State;
+        type SubAction = Action;
+
+        fn initial_state() -> State {
+            State {
+                operations: Vec::new(),
+                capacity: 2,
+            }
+        }
+
+        fn reducer(state: &mut State, action: &ActionWithMeta<&Action>) {
+            match action.action {
+                Action::Add(operation) if state.operations.len() < state.capacity => {
+                    state.operations.push(operation.clone())
+                }
+                Action::Add(_) => {}
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    peers: peers::State,
+    mempool: mempool::State,
+}
+
+#[derive(Debug)]
+enum Action {
+    Peers(peers::Action),
+    Mempool(mempool::Action),
+}
+
+impl EnablingCondition<State> for Action {}
+
+fn main() {
+    let mut modules = ModuleRegistry::<State, (), Action>::new();
+    modules
+        .register::<peers::Peers>(lens!(peers), embed!(Action::Peers))
+        .register::<mempool::Mempool>(lens!(mempool), embed!(Action::Mempool));
+
+    // No root reducer needed, modules handle all the actions.
+    let mut store = StoreBuilder::new()
+        .service(())
+        .initial_state(State::default())
+        .modules(modules)
+        .build()
+        .unwrap();
+
+    store.dispatch(Action::Peers(peers::Action::Connect(9732)));
+    for operation in ["op1", "op2", "op3"] {
+        store.dispatch(Action::Mempool(mempool::Action::Add(operation.into())));
+    }
+
+    let state = store.state();
+    assert_eq!(state.peers.connected, [9732]);
+    assert_eq!(state.mempool.operations, ["op1", "op2"]);
+    println!("{:#?}", state);
+}
@@ -0,0 +1,29 @@
+use redux_rs::EnablingCondition;
+
+use crate::peers::PeersAction;
+use crate::State;
+
+/// All actions of the application, one variant per feature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Peers(PeersAction),
+    /// State failed the safety condition, see `State`.
+    SafetyViolated {
+        error: String,
+    },
+}
+
+impl From<PeersAction> for Action {
+    fn from(action: PeersAction) -> Self {
+        Self::Peers(action)
+    }
+}
+
+impl EnablingCondition<State> for Action {
+    fn is_enabled(&self, state: &State) -> bool {
+        match self {
+            Self::Peers(action) => action.is_enabled(state),
+            Self::SafetyViolated { .. } => true,
+        }
+    }
+}
@@ -0,0 +1,8 @@
+use redux_rs::ActionWithMeta;
+
+use crate::peers::peers_effects;
+use crate::{Action, Store};
+
+pub fn effects(store: &mut Store, action: &ActionWithMeta<Action>) {
+    peers_effects(store, action);
+}
@@ -0,0 +1,159 @@
+//! Reference application: a peer connection manager connecting to fake
+//! peers over a fake network, with timeouts, retries with backoff and
+//! a limit on the number of peers.
+//!
+//! Laid out the way an application is recommended to be: a module per
+//! feature (`peers`) with its actions, state, reducer and effects in
+//! separate files, composed by the root ones next to this file. Shows:
+//!
+//! - enabling conditions rejecting actions which don't apply, e.g. late
+//!   timeouts, so that reducers and effects needn't check for them,
+//! - a safety condition over the whole state, with violations turned
+//!   into actions,
+//! - a bundle of services, a clock and a network, both faked, driven
+//!   by an event loop over virtual time, so the run is deterministic,
+//! - timers for timeouts and retries,
+//! - recording the actions and replaying them into the same state.
+//!
+//! Run with `cargo run --example peer_manager`. Its assertions also run
+//! as a test with `cargo test --example peer_manager`.
+
+mod action;
+mod effects;
+mod peers;
+mod reducer;
+mod service;
+mod state;
+
+use std::time::{Duration, SystemTime};
+
+use redux_rs::{BoundedRecorder, FrozenTime, Instant, StoreError, TimeService};
+
+pub use action::Action;
+use effects::effects;
+use peers::{PeerStatus, PeersAction, MAX_PEERS};
+use reducer::reducer;
+use service::{Behaviour, FakeNetwork, NetworkEvent, Services};
+pub use state::State;
+
+pub type Store = redux_rs::Store<State, Services, Action>;
+
+fn store(network: FakeNetwork) -> Store {
+    let services = Services {
+        time: FrozenTime::new(Instant::now()),
+        network,
+    };
+    let mut store = Store::new(
+        reducer,
+        effects,
+        services,
+        SystemTime::UNIX_EPOCH,
+        State::default(),
+    );
+    store.set_recorder(Some(Box::new(BoundedRecorder::new(1024))));
+    store.enable_safety_check();
+    store.set_error_action(Some(|error| match error {
+        StoreError::SafetyViolation { error, .. } => Some(Action::SafetyViolated { error }),
+        _ => None,
+    }));
+    store
+}
+
+/// Event loop: jump to the next network event or timer, turn network
+/// events into actions and fire due timers, until `deadline`.
+fn run_until(store: &mut Store, deadline: Instant) {
+    loop {
+        let now = match (
+            store.service.network.next_event(),
+            store.next_timer_deadline(),
+        ) {
+            (Some(a), Some(b)) => a.min(b),
+            (Some(a), None) | (None, Some(a)) => a,
+            (None, None) => break,
+        };
+        if now > deadline {
+            break;
+        }
+        store.service.time.advance_to(now);
+
+        for event in store.service.network.poll(now) {
+            let action = match event {
+                NetworkEvent::Connected(addr) => PeersAction::ConnectSuccess { addr },
+                NetworkEvent::HandshakeReceived(addr) => PeersAction::HandshakeSuccess { addr },
+            };
+            store.dispatch(Action::from(action));
+        }
+        store.fire_timers();
+    }
+    store.service.time.advance_to(deadline);
+}
+
+fn since_start(id: redux_rs::ActionId) -> Duration {
+    Duration::from_nanos(id.into())
+}
+
+fn scenario() {
+    let network = FakeNetwork::default()
+        .with_peer(1, Behaviour::Healthy)
+        .with_peer(2, Behaviour::Flaky { failures: 1 })
+        .with_peer(3, Behaviour::Unreachable)
+        .with_peer(4, Behaviour::Mute);
+    let mut store = store(network);
+    let start = store.service.monotonic_time();
+
+    for addr in 1..=3 {
+        assert!(store.dispatch(Action::from(PeersAction::ConnectInit { addr })));
+    }
+    // Limit reached.
+    assert_eq!(store.state().peers.active(), MAX_PEERS);
+    assert!(!store.dispatch(Action::from(PeersAction::ConnectInit { addr: 4 })));
+
+    run_until(&mut store, start + Duration::from_secs(10));
+    let peers = &store.state().peers;
+
+    // Connected and handshaken after a round trip each.
+    match peers.get(1) {
+        Some(PeerStatus::Ready { since }) => assert_eq!(since_start(since).as_millis(), 100),
+        status => panic!("peer 1 is {:?}", status),
+    }
+    // First attempt timed out, the retry after a second succeeded.
+    match peers.get(2) {
+        Some(PeerStatus::Ready { since }) => assert_eq!(since_start(since).as_millis(), 1600),
+        status => panic!("peer 2 is {:?}", status),
+    }
+    // Three attempts, 1s and 2s apart, then given up on.
+    assert_eq!(peers.get(3), Some(PeerStatus::Failed));
+
+    // Room for another peer, which connects but never handshakes.
+    assert!(store.dispatch(Action::from(PeersAction::ConnectInit { addr: 4 })));
+    run_until(&mut store, start + Duration::from_secs(20));
+    assert_eq!(store.state().peers.get(4), Some(PeerStatus::Failed));
+    assert_eq!(store.state().peers.ready(), 2);
+    assert!(store.state().safety_violations.is_empty());
+
+    for action in store.recent_actions() {
+        println!(
+            "{:>8}ms  {:?}",
+            since_start(action.id).as_millis(),
+            action.action
+        );
+    }
+
+    // Replaying the recording, without effects or services, ends up
+    // in the very same state.
+    let header = store.recording_header();
+    let recording: Vec<_> = store.recent_actions().cloned().collect();
+    let mut replayed = self::store(FakeNetwork::default());
+    replayed.replay(&header, &recording).unwrap();
+    assert_eq!(replayed.state(), store.state());
+    println!("replayed {} actions into the same state", recording.len());
+}
+
+fn main() {
+    scenario();
+}
+
+#[test]
+fn peer_manager() {
+    scenario();
+}
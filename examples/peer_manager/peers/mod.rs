@@ -0,0 +1,14 @@
+//! Peer connections: connect, handshake, then ready, with every step
+//! timing out into a retry with backoff, until giving up.
+
+mod peers_actions;
+pub use peers_actions::*;
+
+mod peers_state;
+pub use peers_state::*;
+
+mod peers_reducer;
+pub use peers_reducer::*;
+
+mod peers_effects;
+pub use peers_effects::*;
@@ -0,0 +1,60 @@
+use redux_rs::EnablingCondition;
+
+use super::{PeerStatus, MAX_PEERS};
+use crate::service::PeerAddr;
+use crate::State;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeersAction {
+    /// Start connecting to a peer we don't know yet.
+    ConnectInit {
+        addr: PeerAddr,
+    },
+    ConnectSuccess {
+        addr: PeerAddr,
+    },
+    HandshakeSuccess {
+        addr: PeerAddr,
+    },
+    /// Attempt `attempt` didn't connect in time.
+    ConnectTimeout {
+        addr: PeerAddr,
+        attempt: u32,
+    },
+    /// Attempt `attempt` didn't complete the handshake in time.
+    HandshakeTimeout {
+        addr: PeerAddr,
+        attempt: u32,
+    },
+    Retry {
+        addr: PeerAddr,
+    },
+}
+
+/// Actions which don't make sense in the current state are rejected
+/// before they reach the reducer, e.g. a timeout of an attempt which
+/// completed meanwhile, so neither the reducer nor effects need to
+/// check for it.
+impl EnablingCondition<State> for PeersAction {
+    fn is_enabled(&self, state: &State) -> bool {
+        let peers = &state.peers;
+        match self {
+            Self::ConnectInit { addr } => peers.get(*addr).is_none() && peers.active() < MAX_PEERS,
+            Self::ConnectSuccess { addr } => {
+                matches!(peers.get(*addr), Some(PeerStatus::Connecting { .. }))
+            }
+            Self::HandshakeSuccess { addr } => {
+                matches!(peers.get(*addr), Some(PeerStatus::Handshaking { .. }))
+            }
+            Self::ConnectTimeout { addr, attempt } => matches!(
+                peers.get(*addr),
+                Some(PeerStatus::Connecting { attempt: current, .. }) if current == *attempt
+            ),
+            Self::HandshakeTimeout { addr, attempt } => matches!(
+                peers.get(*addr),
+                Some(PeerStatus::Handshaking { attempt: current, .. }) if current == *attempt
+            ),
+            Self::Retry { addr } => matches!(peers.get(*addr), Some(PeerStatus::Backoff { .. })),
+        }
+    }
+}
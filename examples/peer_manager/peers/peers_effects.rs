@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use redux_rs::{ActionWithMeta, TimeService};
+
+use super::{PeerStatus, PeersAction};
+use crate::service::PeerAddr;
+use crate::{Action, Store};
+
+pub const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Delay before retrying after attempt `attempt` timed out, doubling
+/// with every attempt.
+pub fn backoff(attempt: u32) -> Duration {
+    Duration::from_secs(1) * 2u32.pow(attempt - 1)
+}
+
+/// Side effects only: talking to the network and scheduling timeouts.
+/// What happened is reported back as actions.
+pub fn peers_effects(store: &mut Store, action: &ActionWithMeta<Action>) {
+    let action = match &action.action {
+        Action::Peers(action) => action,
+        _ => return,
+    };
+    match *action {
+        PeersAction::ConnectInit { addr } | PeersAction::Retry { addr } => {
+            let attempt = match store.state().peers.get(addr) {
+                Some(PeerStatus::Connecting { attempt, .. }) => attempt,
+                _ => return,
+            };
+            let now = store.service.monotonic_time();
+            store.service.network.connect(addr, now);
+            store.schedule_timer(
+                CONNECT_TIMEOUT,
+                PeersAction::ConnectTimeout { addr, attempt }.into(),
+            );
+        }
+        PeersAction::ConnectSuccess { addr } => {
+            let attempt = match store.state().peers.get(addr) {
+                Some(PeerStatus::Handshaking { attempt, .. }) => attempt,
+                _ => return,
+            };
+            let now = store.service.monotonic_time();
+            store.service.network.send_handshake(addr, now);
+            store.schedule_timer(
+                HANDSHAKE_TIMEOUT,
+                PeersAction::HandshakeTimeout { addr, attempt }.into(),
+            );
+        }
+        PeersAction::ConnectTimeout { addr, .. } | PeersAction::HandshakeTimeout { addr, .. } => {
+            schedule_retry(store, addr);
+        }
+        PeersAction::HandshakeSuccess { .. } => {}
+    }
+}
+
+fn schedule_retry(store: &mut Store, addr: PeerAddr) {
+    if let Some(PeerStatus::Backoff { attempt }) = store.state().peers.get(addr) {
+        store.schedule_timer(backoff(attempt), PeersAction::Retry { addr }.into());
+    }
+}
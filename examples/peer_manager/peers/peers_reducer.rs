@@ -0,0 +1,53 @@
+use redux_rs::ActionWithMeta;
+
+use super::{PeerStatus, PeersAction, PeersState, MAX_ATTEMPTS};
+use crate::Action;
+
+/// Only state transitions, enabling conditions already checked that
+/// the action applies.
+pub fn peers_reducer(state: &mut PeersState, action: &ActionWithMeta<Action>) {
+    let id = action.id;
+    let action = match &action.action {
+        Action::Peers(action) => action,
+        _ => return,
+    };
+    match *action {
+        PeersAction::ConnectInit { addr } => {
+            state.list.insert(
+                addr,
+                PeerStatus::Connecting {
+                    attempt: 1,
+                    since: id,
+                },
+            );
+        }
+        PeersAction::ConnectSuccess { addr } => {
+            if let Some(status) = state.list.get_mut(&addr) {
+                if let PeerStatus::Connecting { attempt, .. } = *status {
+                    *status = PeerStatus::Handshaking { attempt, since: id };
+                }
+            }
+        }
+        PeersAction::HandshakeSuccess { addr } => {
+            state.list.insert(addr, PeerStatus::Ready { since: id });
+        }
+        PeersAction::ConnectTimeout { addr, attempt }
+        | PeersAction::HandshakeTimeout { addr, attempt } => {
+            let status = match attempt {
+                MAX_ATTEMPTS => PeerStatus::Failed,
+                _ => PeerStatus::Backoff { attempt },
+            };
+            state.list.insert(addr, status);
+        }
+        PeersAction::Retry { addr } => {
+            if let Some(status) = state.list.get_mut(&addr) {
+                if let PeerStatus::Backoff { attempt } = *status {
+                    *status = PeerStatus::Connecting {
+                        attempt: attempt + 1,
+                        since: id,
+                    };
+                }
+            }
+        }
+    }
+}
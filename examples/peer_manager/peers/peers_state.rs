@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+
+use redux_rs::ActionId;
+
+use crate::service::PeerAddr;
+
+/// Connection attempts to a peer before giving up on it.
+pub const MAX_ATTEMPTS: u32 = 3;
+
+/// Peers kept connected at most.
+pub const MAX_PEERS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerStatus {
+    Connecting {
+        attempt: u32,
+        since: ActionId,
+    },
+    Handshaking {
+        attempt: u32,
+        since: ActionId,
+    },
+    /// Waiting to retry after attempt `attempt` timed out.
+    Backoff {
+        attempt: u32,
+    },
+    Ready {
+        since: ActionId,
+    },
+    /// Gave up after [MAX_ATTEMPTS].
+    Failed,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PeersState {
+    pub list: BTreeMap<PeerAddr, PeerStatus>,
+}
+
+impl PeersState {
+    pub fn get(&self, addr: PeerAddr) -> Option<PeerStatus> {
+        self.list.get(&addr).copied()
+    }
+
+    /// Peers connected, or being connected to, which count towards
+    /// [MAX_PEERS].
+    pub fn active(&self) -> usize {
+        self.list
+            .values()
+            .filter(|status| !matches!(status, PeerStatus::Failed))
+            .count()
+    }
+
+    pub fn ready(&self) -> usize {
+        self.list
+            .values()
+            .filter(|status| matches!(status, PeerStatus::Ready { .. }))
+            .count()
+    }
+}
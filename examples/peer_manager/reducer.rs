@@ -0,0 +1,12 @@
+use redux_rs::ActionWithMeta;
+
+use crate::peers::peers_reducer;
+use crate::{Action, State};
+
+pub fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+    peers_reducer(&mut state.peers, action);
+
+    if let Action::SafetyViolated { error } = &action.action {
+        state.safety_violations.push(error.clone());
+    }
+}
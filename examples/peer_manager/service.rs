@@ -0,0 +1,109 @@
+//! Services of the application: a clock and a network. Both are fakes
+//! here, so that the whole application runs deterministically, over
+//! virtual time.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use redux_rs::{forward_service, FrozenTime, HasService, Instant};
+
+pub type PeerAddr = u16;
+
+/// How long it takes the network to deliver anything.
+pub const LATENCY: Duration = Duration::from_millis(50);
+
+/// How a fake peer reacts to us.
+#[derive(Debug, Clone, Copy)]
+pub enum Behaviour {
+    /// Accepts the connection and completes the handshake.
+    Healthy,
+    /// Never accepts the connection.
+    Unreachable,
+    /// Refuses to connect the first `failures` times.
+    Flaky { failures: u32 },
+    /// Accepts the connection, but never completes the handshake.
+    Mute,
+}
+
+/// Event reported by the network, turned into an action by the event
+/// loop, see `main`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetworkEvent {
+    Connected(PeerAddr),
+    HandshakeReceived(PeerAddr),
+}
+
+/// Network of fake peers, delivering events after [LATENCY].
+#[derive(Default)]
+pub struct FakeNetwork {
+    peers: BTreeMap<PeerAddr, Behaviour>,
+    /// Sorted by delivery time.
+    in_flight: Vec<(Instant, NetworkEvent)>,
+}
+
+impl FakeNetwork {
+    pub fn with_peer(mut self, addr: PeerAddr, behaviour: Behaviour) -> Self {
+        self.peers.insert(addr, behaviour);
+        self
+    }
+
+    pub fn connect(&mut self, addr: PeerAddr, now: Instant) {
+        match self.peers.get_mut(&addr) {
+            Some(Behaviour::Healthy) | Some(Behaviour::Mute) => {}
+            Some(Behaviour::Flaky { failures }) if *failures == 0 => {}
+            Some(Behaviour::Flaky { failures }) => {
+                *failures -= 1;
+                return;
+            }
+            Some(Behaviour::Unreachable) | None => return,
+        }
+        self.deliver(now + LATENCY, NetworkEvent::Connected(addr));
+    }
+
+    pub fn send_handshake(&mut self, addr: PeerAddr, now: Instant) {
+        if let Some(Behaviour::Mute) = self.peers.get(&addr) {
+            return;
+        }
+        self.deliver(now + LATENCY, NetworkEvent::HandshakeReceived(addr));
+    }
+
+    /// Delivery time of the next event.
+    pub fn next_event(&self) -> Option<Instant> {
+        self.in_flight.first().map(|(at, _)| *at)
+    }
+
+    /// Events delivered by `now`.
+    pub fn poll(&mut self, now: Instant) -> Vec<NetworkEvent> {
+        let due = self.in_flight.partition_point(|(at, _)| *at <= now);
+        self.in_flight
+            .drain(..due)
+            .map(|(_, event)| event)
+            .collect()
+    }
+
+    fn deliver(&mut self, at: Instant, event: NetworkEvent) {
+        let index = self.in_flight.partition_point(|(other, _)| *other <= at);
+        self.in_flight.insert(index, (at, event));
+    }
+}
+
+/// Bundle of services of the store. Effects ask for the part they need
+/// through `HasService`, so it can be swapped for real services.
+pub struct Services {
+    pub time: FrozenTime,
+    pub network: FakeNetwork,
+}
+
+impl HasService<FrozenTime> for Services {
+    fn service(&mut self) -> &mut FrozenTime {
+        &mut self.time
+    }
+}
+
+impl HasService<FakeNetwork> for Services {
+    fn service(&mut self) -> &mut FakeNetwork {
+        &mut self.network
+    }
+}
+
+forward_service!(TimeService for Services => FrozenTime);
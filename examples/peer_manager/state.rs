@@ -0,0 +1,24 @@
+use redux_rs::SafetyCondition;
+
+use crate::peers::{PeersState, MAX_PEERS};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct State {
+    pub peers: PeersState,
+    pub safety_violations: Vec<String>,
+}
+
+/// Invariants which must hold after every action, checked by the store
+/// once enabled with `Store::enable_safety_check`. Enabling conditions
+/// are what keeps them holding.
+impl SafetyCondition for State {
+    type Error = String;
+
+    fn check_safety_condition(&self) -> Result<(), String> {
+        let active = self.peers.active();
+        if active > MAX_PEERS {
+            return Err(format!("{} active peers, at most {}", active, MAX_PEERS));
+        }
+        Ok(())
+    }
+}
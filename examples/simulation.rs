@@ -0,0 +1,134 @@
+//! Request with timeout and retry, simulated over virtual time against a
+//! network which loses the first two requests. Runs instantly, however
+//! long the timeouts.
+//!
+//! Run with `cargo run --example simulation`.
+
+use std::time::{Duration, SystemTime};
+
+use redux_rs::{
+    forward_service, ActionWithMeta, BoundedRecorder, EnablingCondition, FrozenTime, HasService,
+    Instant, Simulation, Store,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+const LATENCY: Duration = Duration::from_millis(300);
+
+/// Network losing the first `lose` requests, delivering responses to the
+/// rest after [LATENCY].
+struct FakeNetwork {
+    lose: u32,
+}
+
+struct Services {
+    time: FrozenTime,
+    network: FakeNetwork,
+}
+
+impl HasService<FrozenTime> for Services {
+    fn service(&mut self) -> &mut FrozenTime {
+        &mut self.time
+    }
+}
+
+forward_service!(TimeService for Services => FrozenTime);
+
+#[derive(Debug, Clone)]
+enum Action {
+    Start,
+    Send { attempt: u32 },
+    Timeout { attempt: u32 },
+    Response { attempt: u32 },
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Attempt waiting for the response.
+    pending: Option<u32>,
+    /// Attempt which got the response, and when.
+    done: Option<(u32, Duration)>,
+    started_at: u64,
+}
+
+impl EnablingCondition<State> for Action {
+    fn is_enabled(&self, state: &State) -> bool {
+        match self {
+            Action::Start => state.pending.is_none() && state.done.is_none(),
+            Action::Send { .. } => state.done.is_none(),
+            Action::Timeout { attempt } | Action::Response { attempt } => {
+                state.pending == Some(*attempt)
+            }
+        }
+    }
+}
+
+fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+    match action.action {
+        Action::Start => state.started_at = action.id.into(),
+        Action::Send { attempt } => state.pending = Some(attempt),
+        Action::Timeout { .. } => state.pending = None,
+        Action::Response { attempt } => {
+            let elapsed = u64::from(action.id) - state.started_at;
+            state.pending = None;
+            state.done = Some((attempt, Duration::from_nanos(elapsed)));
+        }
+    }
+}
+
+fn effects(store: &mut Store<State, Services, Action>, action: &ActionWithMeta<Action>) {
+    match action.action {
+        Action::Start => {
+            store.dispatch(Action::Send { attempt: 0 });
+        }
+        Action::Send { attempt } => {
+            store.schedule_timer(TIMEOUT, Action::Timeout { attempt });
+            let network = &mut store.service.network;
+            if network.lose > 0 {
+                network.lose -= 1;
+            } else {
+                store.schedule_timer(LATENCY, Action::Response { attempt });
+            }
+        }
+        Action::Timeout { attempt } => {
+            store.dispatch(Action::Send {
+                attempt: attempt + 1,
+            });
+        }
+        Action::Response { .. } => {}
+    }
+}
+
+fn main() {
+    let services = Services {
+        time: FrozenTime::new(Instant::now()),
+        network: FakeNetwork { lose: 2 },
+    };
+    let mut store = Store::new(
+        reducer,
+        effects,
+        services,
+        SystemTime::UNIX_EPOCH,
+        State::default(),
+    );
+    store.set_recorder(Some(Box::new(BoundedRecorder::new(64))));
+
+    let mut sim = Simulation::new(store);
+    sim.schedule_input(Duration::from_secs(1), Action::Start);
+    let report = sim.run_until(Duration::from_secs(10 * 60));
+
+    let store = sim.store();
+    for action in store.recent_actions() {
+        println!(
+            "{:>15?}  {:?}",
+            action.duration_since_epoch(),
+            action.action
+        );
+    }
+    println!("{:?}", report);
+
+    // Two timeouts, then the third attempt succeeds. Ids of actions
+    // dispatched at the same virtual time are 1ns apart.
+    let (attempt, took) = store.state().done.unwrap();
+    assert_eq!(attempt, 2);
+    assert_eq!(took.as_millis(), (TIMEOUT * 2 + LATENCY).as_millis());
+}
@@ -0,0 +1,686 @@
+//! Derive macros for `redux-rs`, re-exported by it with the `derive`
+//! feature, so that e.g. `redux_rs::EnablingCondition` names both the
+//! trait and its derive.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, parse_quote, Attribute, Data, DeriveInput, Error, Expr, ExprMatch, Fields,
+    Ident, LitStr, Pat, Path, Type, Variant,
+};
+
+/// Implements `EnablingCondition<State>` for an action enum by
+/// delegating to the action wrapped by each variant, which must
+/// implement `EnablingCondition` for the same `State`.
+///
+/// `State` is given with `#[enabling(state = "...")]` on the enum. Unit
+/// variants are always enabled, unless annotated with
+/// `#[enabling(with = "...")]` naming a `fn(&State) -> bool`.
+///
+/// ```
+/// use redux_rs::EnablingCondition;
+/// use redux_rs_derive::EnablingCondition;
+///
+/// struct State {
+///     connected: bool,
+/// }
+///
+/// struct Send(u32);
+///
+/// impl EnablingCondition<State> for Send {
+///     fn is_enabled(&self, state: &State) -> bool {
+///         state.connected
+///     }
+/// }
+///
+/// fn is_disconnected(state: &State) -> bool {
+///     !state.connected
+/// }
+///
+/// #[derive(EnablingCondition)]
+/// #[enabling(state = "State")]
+/// enum Action {
+///     Send(Send),
+///     #[enabling(with = "is_disconnected")]
+///     Connect,
+///     Tick,
+/// }
+///
+/// let state = State { connected: false };
+/// assert!(!Action::Send(Send(1)).is_enabled(&state));
+/// assert!(Action::Connect.is_enabled(&state));
+/// assert!(Action::Tick.is_enabled(&state));
+/// ```
+#[proc_macro_derive(EnablingCondition, attributes(enabling))]
+pub fn derive_enabling_condition(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_enabling_condition(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_enabling_condition(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "EnablingCondition can only be derived for enums",
+            ))
+        }
+    };
+    let state = match enabling_attr::<Type>(&input.attrs, "state")? {
+        Some(state) => state,
+        None => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "missing `#[enabling(state = \"...\")]` attribute",
+            ))
+        }
+    };
+    let arms = data
+        .variants
+        .iter()
+        .map(|variant| variant_arm(&state, variant))
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::redux_rs::EnablingCondition<#state> for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn is_enabled(&self, state: &#state) -> bool {
+                match *self {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
+
+/// Match arm checking the enabling condition of the variant.
+fn variant_arm(state: &Type, variant: &Variant) -> syn::Result<TokenStream2> {
+    let ident = &variant.ident;
+    let with = enabling_attr::<Path>(&variant.attrs, "with")?;
+    match (&variant.fields, with) {
+        (Fields::Unit, None) => Ok(quote! { Self::#ident => true, }),
+        (Fields::Unit, Some(with)) => Ok(quote! { Self::#ident => #with(state), }),
+        (Fields::Unnamed(fields), None) if fields.unnamed.len() == 1 => {
+            let ty = &fields.unnamed[0].ty;
+            // Spanned to the wrapped type, so that a missing impl is
+            // reported there.
+            Ok(quote_spanned! {ty.span()=>
+                Self::#ident(ref action) => {
+                    <#ty as ::redux_rs::EnablingCondition<#state>>::is_enabled(action, state)
+                }
+            })
+        }
+        (_, Some(_)) => Err(Error::new_spanned(
+            variant,
+            "`#[enabling(with = \"...\")]` is only supported on unit variants",
+        )),
+        (fields, None) => Err(Error::new_spanned(
+            fields,
+            "variant must wrap a single type implementing EnablingCondition",
+        )),
+    }
+}
+
+/// Value of `#[enabling(key = "...")]`, parsed as `T`.
+fn enabling_attr<T: syn::parse::Parse>(attrs: &[Attribute], key: &str) -> syn::Result<Option<T>> {
+    let mut value = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("enabling")) {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident(key) {
+                return Err(meta.error(format!("expected `{} = \"...\"`", key)));
+            }
+            let lit: LitStr = meta.value()?.parse()?;
+            value = Some(lit.parse()?);
+            Ok(())
+        })?;
+    }
+    Ok(value)
+}
+
+/// Implements `SafetyCondition` for a state struct by checking the
+/// safety condition of each of its fields, in declaration order,
+/// stopping at the first violation.
+///
+/// Fields must implement `SafetyCondition`, unless opted out with
+/// `#[safety(skip)]`. Errors are wrapped in a generated
+/// `<Struct>SafetyError` enum, with a variant named after each checked
+/// field (`Field0`, `Field1`, ... for tuple structs), implementing
+/// `Debug` and `Display` when the wrapped errors do.
+///
+/// Generated code uses only `core`.
+///
+/// ```
+/// use redux_rs::SafetyCondition;
+/// use redux_rs_derive::SafetyCondition;
+///
+/// struct Peers(Vec<u16>);
+///
+/// impl SafetyCondition for Peers {
+///     type Error = usize;
+///
+///     fn check_safety_condition(&self) -> Result<(), usize> {
+///         match self.0.len() {
+///             len if len > 2 => Err(len),
+///             _ => Ok(()),
+///         }
+///     }
+/// }
+///
+/// struct Balance(i64);
+///
+/// impl SafetyCondition for Balance {
+///     type Error = &'static str;
+///
+///     fn check_safety_condition(&self) -> Result<(), &'static str> {
+///         match self.0 {
+///             balance if balance < 0 => Err("negative balance"),
+///             _ => Ok(()),
+///         }
+///     }
+/// }
+///
+/// #[derive(SafetyCondition)]
+/// struct State {
+///     peers: Peers,
+///     balance: Balance,
+///     #[safety(skip)]
+///     ticks: u64,
+/// }
+///
+/// let state = State {
+///     peers: Peers(vec![1, 2]),
+///     balance: Balance(-1),
+///     ticks: 0,
+/// };
+/// let error = state.check_safety_condition().unwrap_err();
+/// assert!(matches!(error, StateSafetyError::Balance("negative balance")));
+/// assert_eq!(error.to_string(), "safety condition of `balance` violated: negative balance");
+/// ```
+#[proc_macro_derive(SafetyCondition, attributes(safety))]
+pub fn derive_safety_condition(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_safety_condition(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_safety_condition(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let data = match &input.data {
+        Data::Struct(data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "SafetyCondition can only be derived for structs",
+            ))
+        }
+    };
+
+    // Checked fields: accessor, error variant, type.
+    let mut checked = Vec::new();
+    for (index, field) in data.fields.iter().enumerate() {
+        if safety_skip(&field.attrs)? {
+            continue;
+        }
+        let (member, variant) = match &field.ident {
+            Some(ident) => (quote!(#ident), camel_case(&ident.to_string())),
+            None => {
+                let index = syn::Index::from(index);
+                (quote!(#index), format!("Field{}", index.index))
+            }
+        };
+        let name = match &field.ident {
+            Some(ident) => ident.to_string(),
+            None => index.to_string(),
+        };
+        let variant = Ident::new(&variant, field.span());
+        checked.push((member, variant, name, &field.ty));
+    }
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let error = format_ident!("{}SafetyError", name);
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Fields' types must implement the trait, which for generic ones
+    // has to be required, and for others reports a missing impl at the
+    // field.
+    let bounds = checked.iter().map(|(_, _, _, ty)| {
+        quote_spanned! {ty.span()=> #ty: ::redux_rs::SafetyCondition, }
+    });
+    let predicates = where_clause
+        .into_iter()
+        .flat_map(|clause| clause.predicates.iter());
+    let bounded_where = quote! { where #(#predicates,)* #(#bounds)* };
+
+    let variants = checked.iter().map(|(_, variant, _, ty)| {
+        quote! { #variant(<#ty as ::redux_rs::SafetyCondition>::Error), }
+    });
+    let checks = checked.iter().map(|(member, variant, _, ty)| {
+        quote! {
+            <#ty as ::redux_rs::SafetyCondition>::check_safety_condition(&self.#member)
+                .map_err(#error::#variant)?;
+        }
+    });
+    let error_bounds = |bound: TokenStream2| {
+        let bounds = checked.iter().map(move |(_, _, _, ty)| {
+            quote! { <#ty as ::redux_rs::SafetyCondition>::Error: #bound, }
+        });
+        quote! { #bounded_where #(#bounds)* }
+    };
+    let debug_where = error_bounds(quote!(::core::fmt::Debug));
+    let display_where = error_bounds(quote!(::core::fmt::Display));
+    let debug_arms = checked.iter().map(|(_, variant, _, _)| {
+        let variant_name = variant.to_string();
+        quote! { Self::#variant(error) => f.debug_tuple(#variant_name).field(error).finish(), }
+    });
+    let display_arms = checked.iter().map(|(_, variant, name, _)| {
+        let message = format!("safety condition of `{}` violated: {{}}", name);
+        quote! { Self::#variant(error) => ::core::write!(f, #message, error), }
+    });
+
+    // Generic parameters not appearing in checked fields would be
+    // unused by the error.
+    let phantom = {
+        let params = generics.params.iter().filter_map(|param| match param {
+            syn::GenericParam::Type(param) => {
+                let ident = &param.ident;
+                Some(quote!(#ident))
+            }
+            syn::GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                Some(quote!(&#lifetime ()))
+            }
+            syn::GenericParam::Const(_) => None,
+        });
+        quote! {
+            #[doc(hidden)]
+            __Unused(
+                ::core::marker::PhantomData<fn() -> (#(#params,)*)>,
+                ::core::convert::Infallible,
+            ),
+        }
+    };
+    let error_doc = format!(
+        "Violated safety condition of a field of [{}], generated by `#[derive(SafetyCondition)]`.",
+        name
+    );
+
+    Ok(quote! {
+        #[doc = #error_doc]
+        #vis enum #error #generics #bounded_where {
+            #(#variants)*
+            #phantom
+        }
+
+        impl #impl_generics ::core::fmt::Debug for #error #ty_generics #debug_where {
+            #[allow(unused_variables)]
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    #(#debug_arms)*
+                    Self::__Unused(_, never) => match *never {},
+                }
+            }
+        }
+
+        impl #impl_generics ::core::fmt::Display for #error #ty_generics #display_where {
+            #[allow(unused_variables)]
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                match self {
+                    #(#display_arms)*
+                    Self::__Unused(_, never) => match *never {},
+                }
+            }
+        }
+
+        impl #impl_generics ::redux_rs::SafetyCondition for #name #ty_generics #bounded_where {
+            type Error = #error #ty_generics;
+
+            fn check_safety_condition(&self) -> ::core::result::Result<(), Self::Error> {
+                #(#checks)*
+                ::core::result::Result::Ok(())
+            }
+        }
+    })
+}
+
+/// Whether the field has `#[safety(skip)]`.
+fn safety_skip(attrs: &[Attribute]) -> syn::Result<bool> {
+    let mut skip = false;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("safety")) {
+        attr.parse_nested_meta(|meta| {
+            if !meta.path.is_ident("skip") {
+                return Err(meta.error("expected `skip`"));
+            }
+            skip = true;
+            Ok(())
+        })?;
+    }
+    Ok(skip)
+}
+
+/// Generates the boilerplate of a top-level action enum:
+///
+/// - `From<Inner>` for every variant wrapping a single type.
+/// - Fieldless `<Enum>Kind` enum, with a variant for each variant of the
+///   action, and the `ActionKind` impl returning it. Kinds convert into
+///   `&'static str`, so they work with `redux_rs::kind_name`.
+/// - `as_<variant>(&self) -> Option<&Inner>` and
+///   `into_<variant>(self) -> Option<Inner>` for every variant wrapping
+///   a single type.
+///
+/// Generated code uses only `core`.
+///
+/// ```
+/// use redux_rs::ActionKind as _;
+/// use redux_rs::{kind_name, ActionWithMeta, EnablingCondition, Store};
+/// use redux_rs_derive::redux_actions;
+///
+/// mod counter {
+///     pub enum CounterAction {
+///         Increment,
+///         Decrement,
+///     }
+///
+///     pub fn reducer(state: &mut i8, action: &CounterAction) {
+///         match action {
+///             CounterAction::Increment => *state += 1,
+///             CounterAction::Decrement => *state -= 1,
+///         }
+///     }
+/// }
+///
+/// mod history {
+///     pub struct HistoryClear;
+/// }
+///
+/// #[derive(Default)]
+/// struct State {
+///     counter: i8,
+///     history: Vec<&'static str>,
+/// }
+///
+/// #[redux_actions]
+/// enum Action {
+///     Counter(counter::CounterAction),
+///     HistoryClear(history::HistoryClear),
+///     Reset,
+/// }
+/// impl EnablingCondition<State> for Action {}
+///
+/// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+///     if let Some(counter_action) = action.action.as_counter() {
+///         counter::reducer(&mut state.counter, counter_action);
+///     }
+///     match action.action.kind() {
+///         ActionKind::HistoryClear => state.history.clear(),
+///         ActionKind::Reset => state.counter = 0,
+///         ActionKind::Counter => state.history.push(kind_name(&action.action)),
+///     }
+/// }
+///
+/// let mut store = Store::simple(reducer, |_, _| {}, State::default());
+/// store.dispatch(counter::CounterAction::Increment.into());
+/// store.dispatch(counter::CounterAction::Increment.into());
+/// store.dispatch(counter::CounterAction::Decrement.into());
+/// assert_eq!(store.state().counter, 1);
+/// assert_eq!(store.state().history, ["Counter", "Counter", "Counter"]);
+///
+/// store.dispatch(history::HistoryClear.into());
+/// store.dispatch(Action::Reset);
+/// assert_eq!(store.state().counter, 0);
+/// assert!(store.state().history.is_empty());
+///
+/// assert!(Action::Reset.into_history_clear().is_none());
+/// ```
+#[proc_macro_attribute]
+pub fn redux_actions(args: TokenStream, input: TokenStream) -> TokenStream {
+    if !args.is_empty() {
+        let args = TokenStream2::from(args);
+        return Error::new_spanned(args, "#[redux_actions] takes no arguments")
+            .into_compile_error()
+            .into();
+    }
+    let input = parse_macro_input!(input as DeriveInput);
+    let generated = expand_redux_actions(&input).unwrap_or_else(Error::into_compile_error);
+    quote!(#input #generated).into()
+}
+
+fn expand_redux_actions(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "#[redux_actions] can only be applied to enums",
+            ))
+        }
+    };
+    let name = &input.ident;
+    let vis = &input.vis;
+    let kind = format_ident!("{}Kind", name);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let variants: Vec<&Ident> = data.variants.iter().map(|variant| &variant.ident).collect();
+    let name_arms = variants.iter().map(|variant| {
+        let variant_name = variant.to_string();
+        quote! { #kind::#variant => #variant_name, }
+    });
+    let kind_arms = data.variants.iter().map(|variant| {
+        let ident = &variant.ident;
+        match variant.fields {
+            Fields::Unit => quote! { Self::#ident => #kind::#ident, },
+            Fields::Unnamed(_) => quote! { Self::#ident(..) => #kind::#ident, },
+            Fields::Named(_) => quote! { Self::#ident { .. } => #kind::#ident, },
+        }
+    });
+
+    let wrapping: Vec<(&Ident, &Type)> = data
+        .variants
+        .iter()
+        .filter_map(|variant| match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                Some((&variant.ident, &fields.unnamed[0].ty))
+            }
+            _ => None,
+        })
+        .collect();
+    let from_impls = wrapping.iter().map(|(ident, ty)| {
+        quote! {
+            impl #impl_generics ::core::convert::From<#ty> for #name #ty_generics #where_clause {
+                #[inline(always)]
+                fn from(action: #ty) -> Self {
+                    Self::#ident(action)
+                }
+            }
+        }
+    });
+    let accessors = wrapping.iter().map(|(ident, ty)| {
+        let snake = snake_case(&ident.to_string());
+        let as_fn = Ident::new(&format!("as_{}", snake), ident.span());
+        let into_fn = Ident::new(&format!("into_{}", snake), ident.span());
+        quote! {
+            #[inline(always)]
+            #vis fn #as_fn(&self) -> ::core::option::Option<&#ty> {
+                match self {
+                    Self::#ident(action) => ::core::option::Option::Some(action),
+                    #[allow(unreachable_patterns)]
+                    _ => ::core::option::Option::None,
+                }
+            }
+
+            #[inline(always)]
+            #vis fn #into_fn(self) -> ::core::option::Option<#ty> {
+                match self {
+                    Self::#ident(action) => ::core::option::Option::Some(action),
+                    #[allow(unreachable_patterns)]
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    });
+
+    let kind_doc = format!("Kind of [{}], generated by `#[redux_actions]`.", name);
+    Ok(quote! {
+        #[doc = #kind_doc]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #vis enum #kind {
+            #(#variants,)*
+        }
+
+        impl ::core::convert::From<#kind> for &'static str {
+            fn from(kind: #kind) -> Self {
+                match kind {
+                    #(#name_arms)*
+                }
+            }
+        }
+
+        impl #impl_generics ::redux_rs::ActionKind for #name #ty_generics #where_clause {
+            type Kind = #kind;
+
+            #[inline(always)]
+            fn kind(&self) -> #kind {
+                match self {
+                    #(#kind_arms)*
+                }
+            }
+        }
+
+        #(#from_impls)*
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #(#accessors)*
+        }
+    })
+}
+
+/// `match` over an action enum without a catch-all arm, so that adding a
+/// variant breaks the build until every `handle_actions!` over the enum
+/// handles it, or explicitly ignores it with `=> ignore!()`.
+///
+/// Arms matching anything (`_`, or a binding like `other`) are an error,
+/// unless marked with `#[allow_unhandled]`.
+///
+/// ```
+/// use redux_rs::ActionWithMeta;
+/// use redux_rs_derive::handle_actions;
+///
+/// enum Action {
+///     Increment,
+///     Decrement,
+///     Log(String),
+/// }
+///
+/// fn reducer(state: &mut i32, action: &ActionWithMeta<Action>) {
+///     handle_actions!(match &action.action {
+///         Action::Increment => *state += 1,
+///         Action::Decrement => *state -= 1,
+///         // Only effects care.
+///         Action::Log(_) => ignore!(),
+///     })
+/// }
+///
+/// fn is_counting(action: &Action) -> bool {
+///     handle_actions!(match action {
+///         Action::Increment | Action::Decrement => true,
+///         #[allow_unhandled]
+///         _ => false,
+///     })
+/// }
+/// # assert!(!is_counting(&Action::Log(String::new())));
+/// ```
+#[proc_macro]
+pub fn handle_actions(input: TokenStream) -> TokenStream {
+    let mut expr = parse_macro_input!(input as ExprMatch);
+    match check_handled_arms(&mut expr) {
+        Ok(()) => quote!(#expr).into(),
+        Err(error) => error.into_compile_error().into(),
+    }
+}
+
+/// Rejects unmarked catch-all arms, strips `#[allow_unhandled]` and
+/// expands `ignore!()`.
+fn check_handled_arms(expr: &mut ExprMatch) -> syn::Result<()> {
+    for arm in &mut expr.arms {
+        let attrs = arm.attrs.len();
+        arm.attrs
+            .retain(|attr| !attr.path().is_ident("allow_unhandled"));
+        let allowed = arm.attrs.len() != attrs;
+        if !allowed && arm.guard.is_none() && is_catch_all(&arm.pat) {
+            return Err(Error::new_spanned(
+                &arm.pat,
+                "catch-all arm would hide unhandled actions, list the variants \
+                 (with `=> ignore!()` for ignored ones) or mark the arm with `#[allow_unhandled]`",
+            ));
+        }
+        if is_ignore(&arm.body) {
+            *arm.body = parse_quote!({});
+        }
+    }
+    Ok(())
+}
+
+fn is_catch_all(pat: &Pat) -> bool {
+    match pat {
+        Pat::Wild(_) => true,
+        // Binding, rather than a unit variant brought into scope.
+        Pat::Ident(binding) => {
+            binding.subpat.is_none()
+                && binding
+                    .ident
+                    .to_string()
+                    .starts_with(|c: char| c.is_lowercase() || c == '_')
+        }
+        Pat::Or(or) => or.cases.iter().any(is_catch_all),
+        Pat::Paren(paren) => is_catch_all(&paren.pat),
+        Pat::Reference(reference) => is_catch_all(&reference.pat),
+        _ => false,
+    }
+}
+
+fn is_ignore(body: &Expr) -> bool {
+    match body {
+        Expr::Macro(call) => call.mac.path.is_ident("ignore") && call.mac.tokens.is_empty(),
+        _ => false,
+    }
+}
+
+/// `PeerConnect` -> `peer_connect`, `HTTPRequest` -> `http_request`.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len() + 4);
+    for (i, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+/// `mempool_state` -> `MempoolState`.
+fn camel_case(name: &str) -> String {
+    let name = name.strip_prefix("r#").unwrap_or(name);
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
@@ -0,0 +1,17 @@
+use redux_rs_derive::handle_actions;
+
+enum Action {
+    Increment,
+    Decrement,
+}
+
+fn reducer(state: &mut i32, action: &Action) {
+    handle_actions!(match action {
+        Action::Increment => *state += 1,
+        _ => ignore!(),
+    })
+}
+
+fn main() {
+    reducer(&mut 0, &Action::Decrement);
+}
@@ -0,0 +1,18 @@
+use redux_rs_derive::handle_actions;
+
+enum Action {
+    Increment,
+    Decrement,
+    Reset,
+}
+
+fn reducer(state: &mut i32, action: &Action) {
+    handle_actions!(match action {
+        Action::Increment => *state += 1,
+        Action::Decrement => ignore!(),
+    })
+}
+
+fn main() {
+    reducer(&mut 0, &Action::Reset);
+}
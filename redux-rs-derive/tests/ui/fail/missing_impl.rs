@@ -0,0 +1,13 @@
+use redux_rs_derive::EnablingCondition;
+
+struct State;
+
+struct Connect;
+
+#[derive(EnablingCondition)]
+#[enabling(state = "State")]
+enum Action {
+    Connect(Connect),
+}
+
+fn main() {}
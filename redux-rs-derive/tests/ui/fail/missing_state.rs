@@ -0,0 +1,8 @@
+use redux_rs_derive::EnablingCondition;
+
+#[derive(EnablingCondition)]
+enum Action {
+    Tick,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use redux_rs_derive::EnablingCondition;
+
+struct State;
+
+#[derive(EnablingCondition)]
+#[enabling(state = "State")]
+enum Action {
+    Connect(u16, u16),
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use redux_rs_derive::EnablingCondition;
+
+struct State;
+
+#[derive(EnablingCondition)]
+#[enabling(state = "State")]
+struct Action;
+
+fn main() {}
@@ -0,0 +1,6 @@
+use redux_rs_derive::redux_actions;
+
+#[redux_actions]
+struct Action;
+
+fn main() {}
@@ -0,0 +1,10 @@
+use redux_rs_derive::SafetyCondition;
+
+struct Peers;
+
+#[derive(SafetyCondition)]
+struct State {
+    peers: Peers,
+}
+
+fn main() {}
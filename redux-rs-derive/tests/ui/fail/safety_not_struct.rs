@@ -0,0 +1,8 @@
+use redux_rs_derive::SafetyCondition;
+
+#[derive(SafetyCondition)]
+enum State {
+    Idle,
+}
+
+fn main() {}
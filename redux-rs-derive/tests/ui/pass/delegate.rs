@@ -0,0 +1,53 @@
+use redux_rs::EnablingCondition;
+use redux_rs_derive::EnablingCondition;
+
+pub struct State {
+    pub peers: usize,
+    pub paused: bool,
+}
+
+pub struct Connect;
+
+impl EnablingCondition<State> for Connect {
+    fn is_enabled(&self, state: &State) -> bool {
+        state.peers < 2
+    }
+}
+
+/// Always enabled, relies on the default.
+pub struct Disconnect;
+
+impl EnablingCondition<State> for Disconnect {}
+
+fn is_paused(state: &State) -> bool {
+    state.paused
+}
+
+#[derive(EnablingCondition)]
+#[enabling(state = "crate::State")]
+pub enum Action {
+    Connect(Connect),
+    Disconnect(Disconnect),
+    #[enabling(with = "is_paused")]
+    Resume,
+    Tick,
+}
+
+/// Variants can wrap other derived enums.
+#[derive(EnablingCondition)]
+#[enabling(state = "State")]
+pub enum Root {
+    Action(Action),
+}
+
+fn main() {
+    let state = State {
+        peers: 2,
+        paused: false,
+    };
+    assert!(!Action::Connect(Connect).is_enabled(&state));
+    assert!(Action::Disconnect(Disconnect).is_enabled(&state));
+    assert!(!Action::Resume.is_enabled(&state));
+    assert!(Action::Tick.is_enabled(&state));
+    assert!(!Root::Action(Action::Connect(Connect)).is_enabled(&state));
+}
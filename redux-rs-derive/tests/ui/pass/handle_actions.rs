@@ -0,0 +1,48 @@
+use redux_rs::ActionWithMeta;
+use redux_rs_derive::handle_actions;
+
+pub enum Action {
+    Increment,
+    Add(i32),
+    Log(String),
+}
+
+fn reducer(state: &mut i32, action: &ActionWithMeta<Action>) {
+    handle_actions!(match &action.action {
+        Action::Increment => *state += 1,
+        Action::Add(value) if *value < 0 => ignore!(),
+        Action::Add(value) => *state += value,
+        Action::Log(_) => ignore!(),
+    })
+}
+
+fn describe(action: &Action) -> &'static str {
+    handle_actions!(match action {
+        Action::Increment => "increment",
+        #[allow_unhandled]
+        other => match other {
+            Action::Add(_) => "add",
+            _ => "other",
+        },
+    })
+}
+
+fn main() {
+    let mut state = 0;
+    let meta = |action| ActionWithMeta {
+        id: redux_rs::ActionId::ZERO,
+        depth: 0,
+        elapsed_since_prev: Default::default(),
+        provenance: Default::default(),
+        module: Default::default(),
+        namespace: 0,
+        action,
+    };
+    reducer(&mut state, &meta(Action::Increment));
+    reducer(&mut state, &meta(Action::Add(5)));
+    reducer(&mut state, &meta(Action::Add(-5)));
+    reducer(&mut state, &meta(Action::Log("ignored".to_owned())));
+    assert_eq!(state, 6);
+    assert_eq!(describe(&Action::Add(1)), "add");
+    assert_eq!(describe(&Action::Log(String::new())), "other");
+}
@@ -0,0 +1,41 @@
+use redux_rs::kind_name;
+use redux_rs::ActionKind as _;
+use redux_rs_derive::redux_actions;
+
+pub struct PeerConnect(pub u16);
+pub struct MempoolAdd;
+
+#[redux_actions]
+pub enum Action {
+    PeerConnect(PeerConnect),
+    MempoolAdd(MempoolAdd),
+    HTTPRequest(u8),
+    Tick,
+    Named { at: u64 },
+}
+
+/// Generic enums get the same impls.
+#[redux_actions]
+pub enum Wrapper<T> {
+    Inner(T),
+    Empty,
+}
+
+fn main() {
+    let action: Action = PeerConnect(9732).into();
+    assert_eq!(action.kind(), ActionKind::PeerConnect);
+    assert_eq!(action.as_peer_connect().map(|connect| connect.0), Some(9732));
+    assert!(action.as_mempool_add().is_none());
+    assert_eq!(action.into_peer_connect().map(|connect| connect.0), Some(9732));
+
+    assert!(Action::from(MempoolAdd).into_mempool_add().is_some());
+    assert_eq!(Action::from(7u8).as_http_request(), Some(&7));
+    assert_eq!(kind_name(&Action::Tick), "Tick");
+    assert_eq!(Action::Named { at: 1 }.kind(), ActionKind::Named);
+    assert!(ActionKind::PeerConnect < ActionKind::Tick);
+
+    let wrapper = Wrapper::from(5u32);
+    assert_eq!(wrapper.kind(), WrapperKind::Inner);
+    assert_eq!(wrapper.as_inner(), Some(&5));
+    assert_eq!(Wrapper::<u32>::Empty.into_inner(), None);
+}
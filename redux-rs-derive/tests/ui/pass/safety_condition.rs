@@ -0,0 +1,89 @@
+use redux_rs::SafetyCondition;
+use redux_rs_derive::SafetyCondition;
+
+#[derive(Default)]
+pub struct Peers {
+    pub connected: Vec<u16>,
+}
+
+impl SafetyCondition for Peers {
+    type Error = usize;
+
+    fn check_safety_condition(&self) -> Result<(), usize> {
+        match self.connected.len() {
+            len if len > 2 => Err(len),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Mempool {
+    pub operations: Vec<u32>,
+}
+
+impl SafetyCondition for Mempool {
+    type Error = String;
+
+    fn check_safety_condition(&self) -> Result<(), String> {
+        match self.operations.iter().find(|operation| **operation == 0) {
+            Some(_) => Err("zero operation".to_owned()),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Default, SafetyCondition)]
+pub struct State {
+    pub peers: Peers,
+    pub mempool_state: Mempool,
+    #[safety(skip)]
+    pub ticks: u64,
+}
+
+/// Generic state, whose parameter only needs the trait if it's checked.
+#[derive(SafetyCondition)]
+pub struct Node<T, U>
+where
+    T: Default,
+{
+    pub inner: T,
+    #[safety(skip)]
+    pub extra: U,
+}
+
+#[derive(SafetyCondition)]
+pub struct Pair(pub Peers, pub Mempool);
+
+fn main() {
+    let mut state = State::default();
+    assert!(state.check_safety_condition().is_ok());
+
+    // Second field fails, first one is fine.
+    state.mempool_state.operations = vec![1, 0];
+    state.ticks = 10;
+    let error = state.check_safety_condition().unwrap_err();
+    assert!(matches!(&error, StateSafetyError::MempoolState(message) if message == "zero operation"));
+    assert_eq!(format!("{:?}", error), "MempoolState(\"zero operation\")");
+    assert_eq!(
+        error.to_string(),
+        "safety condition of `mempool_state` violated: zero operation"
+    );
+
+    // Fields are checked in declaration order.
+    state.peers.connected = vec![1, 2, 3];
+    let error = state.check_safety_condition().unwrap_err();
+    assert!(matches!(error, StateSafetyError::Peers(3)));
+
+    let node = Node {
+        inner: state,
+        extra: (),
+    };
+    let error = node.check_safety_condition().unwrap_err();
+    assert!(matches!(error, NodeSafetyError::Inner(StateSafetyError::Peers(3))));
+
+    let pair = Pair(Peers::default(), Mempool { operations: vec![0] });
+    let error = pair.check_safety_condition().unwrap_err();
+    assert_eq!(error.to_string(), "safety condition of `1` violated: zero operation");
+    assert!(matches!(error, PairSafetyError::Field1(_)));
+}
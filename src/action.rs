@@ -1,5 +1,9 @@
+use core::time::Duration;
 use enum_dispatch::enum_dispatch;
-use std::time::{Duration, SystemTime};
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+use crate::{ModuleId, Provenance};
 
 /// Time in nanoseconds from [std::time::UNIX_EPOCH].
 ///
@@ -50,12 +54,43 @@ pub struct ActionWithMeta<Action> {
     pub id: ActionId,
     /// Recursion depth of a given action.
     pub depth: u32,
+    /// Time passed since the previous action, which equals the
+    /// difference between their ids.
+    pub elapsed_since_prev: Duration,
+    /// Where the action came from, filled in by the store.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub provenance: Provenance,
+    /// Registered module the action belongs to, filled in by the store.
+    /// See [ModuleId].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub module: ModuleId,
+    /// Id namespace of the store which processed the action, 0 unless
+    /// it's a fork, see `Store::fork`. Ids are only unique within a
+    /// namespace.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub namespace: u64,
+
+    /// Total bytes allocated by the process when action was dispatched.
+    ///
+    /// Read from jemalloc with `jemallocator` feature, from
+    /// [crate::CountingAllocator] with `memory-counter`, otherwise 0.
+    #[cfg(feature = "memory")]
+    pub total_allocated: u64,
+    /// Whether `total_allocated` was read for this action, or reused
+    /// from an earlier one. See [crate::MemorySampling].
+    #[cfg(feature = "memory")]
+    pub allocated_sampled: bool,
 
     #[cfg_attr(feature = "serde", serde(flatten))]
     pub action: Action,
 }
 
+/// Old name of [ActionWithMeta].
+#[deprecated(note = "use `ActionWithMeta` instead")]
+pub type ActionWithId<Action> = ActionWithMeta<Action>;
+
 impl<Action> ActionWithMeta<Action> {
+    #[cfg(feature = "std")]
     #[inline(always)]
     pub fn time(&self) -> SystemTime {
         SystemTime::UNIX_EPOCH + self.duration_since_epoch()
@@ -71,12 +106,93 @@ impl<Action> ActionWithMeta<Action> {
         Duration::from_nanos(self.time_as_nanos())
     }
 
+    /// Time passed since the previous action.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, FrozenTime, Instant, Store};
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// struct Action;
+    /// impl EnablingCondition<Vec<Duration>> for Action {}
+    ///
+    /// fn reducer(state: &mut Vec<Duration>, action: &ActionWithMeta<Action>) {
+    ///     state.push(action.elapsed_since_prev());
+    /// }
+    ///
+    /// let service = FrozenTime::new(Instant::now());
+    /// let mut store = Store::new(reducer, |_, _| {}, service, SystemTime::UNIX_EPOCH, vec![]);
+    ///
+    /// for millis in [5, 0, 20] {
+    ///     store.service.advance(Duration::from_millis(millis));
+    ///     store.dispatch(Action);
+    /// }
+    ///
+    /// assert_eq!(
+    ///     store.state(),
+    ///     // Ids are unique, so at least 1ns passes between two actions.
+    ///     &[Duration::from_millis(5), Duration::from_nanos(1), Duration::from_millis(20)]
+    /// );
+    /// ```
+    #[inline(always)]
+    pub fn elapsed_since_prev(&self) -> Duration {
+        self.elapsed_since_prev
+    }
+
     #[inline(always)]
     pub fn duration_since(&self, other: &ActionWithMeta<Action>) -> Duration {
         self.id.duration_since(other.id)
     }
+
+    /// Same metadata with a different action.
+    #[inline(always)]
+    pub(crate) fn with_action<T>(&self, action: T) -> ActionWithMeta<T> {
+        ActionWithMeta {
+            id: self.id,
+            depth: self.depth,
+            elapsed_since_prev: self.elapsed_since_prev,
+            provenance: self.provenance,
+            module: self.module,
+            namespace: self.namespace,
+            #[cfg(feature = "memory")]
+            total_allocated: self.total_allocated,
+            #[cfg(feature = "memory")]
+            allocated_sampled: self.allocated_sampled,
+            action,
+        }
+    }
+}
+
+/// Classifies actions into kinds, usually a fieldless enum mirroring
+/// the variants of the action enum.
+///
+/// With the `derive` feature, `#[redux_actions]` generates the kind enum
+/// and this impl.
+pub trait ActionKind {
+    type Kind: Copy + Eq + Ord;
+
+    fn kind(&self) -> Self::Kind;
+}
+
+/// Function signature for classifying actions into kinds, e.g. for
+/// statistics and metrics.
+///
+/// For actions implementing [ActionKind], [kind_name] can be used.
+pub type ActionClassifier<Action> = fn(&Action) -> &'static str;
+
+/// Name of the action's kind, for actions whose kind converts into
+/// `&'static str`.
+///
+/// Usable as `fn(&Action) -> &'static str` classifier.
+pub fn kind_name<Action>(action: &Action) -> &'static str
+where
+    Action: ActionKind,
+    Action::Kind: Into<&'static str>,
+{
+    action.kind().into()
 }
 
+/// With the `derive` feature, it can be derived for action enums whose
+/// variants wrap actions implementing it, see `redux_rs_derive`.
 #[enum_dispatch]
 pub trait EnablingCondition<State> {
     /// Enabling condition for the Action.
@@ -0,0 +1,244 @@
+#[cfg(not(feature = "std"))]
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use crate::ActionClassifier;
+
+/// Section of an audited state, noting whether it was accessed mutably.
+///
+/// Reads go through [Deref], writes through [DerefMut], so the reducer
+/// uses the section as if it was the value itself.
+#[derive(Debug, Default, Clone)]
+pub struct Section<T> {
+    value: T,
+    written: bool,
+}
+
+impl<T> Section<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            value,
+            written: false,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Whether the section was accessed mutably since the last call.
+    /// Used by [crate::audited_state].
+    pub fn take_written(&mut self) -> bool {
+        core::mem::take(&mut self.written)
+    }
+}
+
+impl<T> Deref for Section<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for Section<T> {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut T {
+        self.written = true;
+        &mut self.value
+    }
+}
+
+/// State made of named [Section]s, whose writes are audited.
+///
+/// Usually implemented with [crate::audited_state].
+pub trait AuditedState {
+    /// Names of the sections, in the order of the columns of
+    /// [AuditReport::matrix].
+    const SECTIONS: &'static [&'static str];
+
+    /// Call `written` with the name of every section accessed mutably
+    /// since the last call.
+    fn take_written(&mut self, written: &mut dyn FnMut(&'static str));
+}
+
+/// Implement [AuditedState] for a struct whose listed fields are
+/// [Section]s, named after the fields.
+///
+/// ```
+/// use redux_rs::{audited_state, AuditedState, Section};
+///
+/// #[derive(Default)]
+/// struct State {
+///     peers: Section<Vec<u64>>,
+///     head: Section<u32>,
+/// }
+///
+/// audited_state!(State { peers, head });
+///
+/// let mut state = State::default();
+/// assert_eq!(State::SECTIONS, ["peers", "head"]);
+///
+/// assert_eq!(*state.head, 0);
+/// state.peers.push(1);
+/// let mut written = vec![];
+/// state.take_written(&mut |section| written.push(section));
+/// assert_eq!(written, ["peers"]);
+/// ```
+#[macro_export]
+macro_rules! audited_state {
+    ($state:ty { $($section:ident),+ $(,)? }) => {
+        impl $crate::AuditedState for $state {
+            const SECTIONS: &'static [&'static str] = &[$(stringify!($section)),+];
+
+            fn take_written(&mut self, written: &mut dyn FnMut(&'static str)) {
+                $(
+                    if self.$section.take_written() {
+                        written(stringify!($section));
+                    }
+                )+
+            }
+        }
+    };
+}
+
+/// Sections written by actions of a single kind.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct KindAudit {
+    /// Actions of the kind passed through the reducer.
+    pub actions: u64,
+    /// Number of those actions which wrote each section, only sections
+    /// written at least once are present.
+    pub writes: BTreeMap<&'static str, u64>,
+}
+
+/// Which sections of the state actions of each kind write, see
+/// [crate::Store::audit_report].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AuditReport {
+    sections: &'static [&'static str],
+    kinds: BTreeMap<&'static str, KindAudit>,
+}
+
+impl AuditReport {
+    fn new(sections: &'static [&'static str]) -> Self {
+        Self {
+            sections,
+            kinds: BTreeMap::new(),
+        }
+    }
+
+    /// Names of the sections, see [AuditedState::SECTIONS].
+    #[inline(always)]
+    pub fn sections(&self) -> &'static [&'static str] {
+        self.sections
+    }
+
+    pub fn kind(&self, kind: &str) -> Option<&KindAudit> {
+        self.kinds.get(kind)
+    }
+
+    /// Audited kinds, sorted by name.
+    pub fn kinds(&self) -> impl Iterator<Item = (&'static str, &KindAudit)> {
+        self.kinds.iter().map(|(kind, audit)| (*kind, audit))
+    }
+
+    /// Number of actions of `kind` which wrote `section`.
+    pub fn writes(&self, kind: &str, section: &str) -> u64 {
+        self.kind(kind)
+            .and_then(|audit| audit.writes.get(section))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Row per kind, sorted by name, with the number of actions which
+    /// wrote each section, in the order of [AuditReport::sections].
+    pub fn matrix(&self) -> Vec<(&'static str, Vec<u64>)> {
+        self.kinds
+            .keys()
+            .map(|kind| {
+                let row = self
+                    .sections
+                    .iter()
+                    .map(|section| self.writes(kind, section))
+                    .collect();
+                (*kind, row)
+            })
+            .collect()
+    }
+}
+
+/// Matrix as a table, with a column per section.
+impl fmt::Display for AuditReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "kind")?;
+        for section in self.sections {
+            write!(f, "\t{}", section)?;
+        }
+        for (kind, row) in self.matrix() {
+            write!(f, "\n{}", kind)?;
+            for writes in row {
+                write!(f, "\t{}", writes)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Audit of the state, see [crate::Store::enable_audit].
+pub(crate) struct Audit<State, Action> {
+    classifier: ActionClassifier<Action>,
+    take_written: fn(&mut State, &mut dyn FnMut(&'static str)),
+    report: AuditReport,
+}
+
+impl<State: AuditedState, Action> Audit<State, Action> {
+    pub fn new(classifier: ActionClassifier<Action>) -> Self {
+        Self {
+            classifier,
+            take_written: State::take_written,
+            report: AuditReport::new(State::SECTIONS),
+        }
+    }
+}
+
+impl<State, Action> Audit<State, Action> {
+    /// Forget writes made outside of the reducer.
+    pub fn reset(&self, state: &mut State) {
+        (self.take_written)(state, &mut |_| {});
+    }
+
+    /// Attribute writes made by the reducer to the kind of `action`.
+    pub fn record(&mut self, state: &mut State, action: &Action) {
+        let audit = self
+            .report
+            .kinds
+            .entry((self.classifier)(action))
+            .or_default();
+        audit.actions += 1;
+        (self.take_written)(state, &mut |section| {
+            *audit.writes.entry(section).or_default() += 1;
+        });
+    }
+
+    #[inline(always)]
+    pub fn report(&self) -> &AuditReport {
+        &self.report
+    }
+}
+
+impl<State, Action> Clone for Audit<State, Action> {
+    fn clone(&self) -> Self {
+        Self {
+            classifier: self.classifier,
+            take_written: self.take_written,
+            report: self.report.clone(),
+        }
+    }
+}
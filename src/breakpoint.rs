@@ -0,0 +1,194 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::small_vec::SmallVec;
+use crate::{ActionWithMeta, Store};
+
+/// Condition on which the store pauses, see `Store::add_breakpoint`.
+pub enum Breakpoint<State, Action> {
+    /// Hit when an action matching the predicate is processed.
+    Action(fn(&ActionWithMeta<Action>) -> bool),
+    /// Hit when the state right after the reducer matches the predicate.
+    State(fn(&State) -> bool),
+}
+
+impl<State, Action> Breakpoint<State, Action> {
+    #[inline(always)]
+    fn matches(&self, state: &State, action: &ActionWithMeta<Action>) -> bool {
+        match self {
+            Self::Action(predicate) => predicate(action),
+            Self::State(predicate) => predicate(state),
+        }
+    }
+}
+
+impl<State, Action> Clone for Breakpoint<State, Action> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<State, Action> Copy for Breakpoint<State, Action> {}
+
+/// Identifies a breakpoint added to the store, so it can be removed.
+#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BreakpointId(u32);
+
+/// Function signature for a hook called when a breakpoint is hit.
+///
+/// Gets the already paused store, with the state right after the
+/// reducer of the action which hit the breakpoint, the breakpoint and
+/// the action.
+pub type BreakpointHook<State, Service, Action> =
+    fn(&Store<State, Service, Action>, BreakpointId, &ActionWithMeta<Action>);
+
+struct Entry<State, Action> {
+    id: BreakpointId,
+    breakpoint: Breakpoint<State, Action>,
+    one_shot: bool,
+}
+
+impl<State, Action> Clone for Entry<State, Action> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            breakpoint: self.breakpoint,
+            one_shot: self.one_shot,
+        }
+    }
+}
+
+pub(crate) struct Breakpoints<State, Service, Action> {
+    entries: Vec<Entry<State, Action>>,
+    next_id: u32,
+    pub hook: Option<BreakpointHook<State, Service, Action>>,
+    #[cfg(feature = "breakpoint_dump")]
+    pub dump: Option<DumpWriter<State, Action>>,
+}
+
+impl<State, Service, Action> Breakpoints<State, Service, Action> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            next_id: 0,
+            hook: None,
+            #[cfg(feature = "breakpoint_dump")]
+            dump: None,
+        }
+    }
+
+    pub fn add(&mut self, breakpoint: Breakpoint<State, Action>, one_shot: bool) -> BreakpointId {
+        let id = BreakpointId(self.next_id);
+        self.next_id += 1;
+        self.entries.push(Entry {
+            id,
+            breakpoint,
+            one_shot,
+        });
+        id
+    }
+
+    pub fn remove(&mut self, id: BreakpointId) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|entry| entry.id != id);
+        self.entries.len() != len
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.entries.reserve(additional);
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Breakpoints hit by the action, in the order they were added.
+    /// One-shot breakpoints which were hit get removed.
+    pub fn check(
+        &mut self,
+        state: &State,
+        action: &ActionWithMeta<Action>,
+    ) -> SmallVec<BreakpointId, 4> {
+        let mut hits = SmallVec::new();
+        self.entries.retain(|entry| {
+            if !entry.breakpoint.matches(state, action) {
+                return true;
+            }
+            hits.push(entry.id);
+            !entry.one_shot
+        });
+        hits
+    }
+}
+
+impl<State, Service, Action> Clone for Breakpoints<State, Service, Action> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            next_id: self.next_id,
+            hook: self.hook,
+            // Dump sink can't be cloned, clone starts without one.
+            #[cfg(feature = "breakpoint_dump")]
+            dump: None,
+        }
+    }
+}
+
+/// What gets written when a breakpoint is hit, if
+/// `Store::set_breakpoint_dump` is set.
+#[cfg(feature = "breakpoint_dump")]
+#[derive(serde::Serialize)]
+pub struct BreakpointDump<'a, State, Action> {
+    pub breakpoint: BreakpointId,
+    pub action: &'a ActionWithMeta<Action>,
+    /// State right after the reducer of `action`.
+    pub state: &'a State,
+    /// Actions kept by the recorder, oldest first. Empty without one.
+    pub recent_actions: Vec<&'a ActionWithMeta<Action>>,
+}
+
+/// Writes [BreakpointDump]s as JSON, one per line.
+#[cfg(feature = "breakpoint_dump")]
+pub(crate) struct DumpWriter<State, Action> {
+    pub write: fn(&BreakpointDump<State, Action>, &mut dyn std::io::Write) -> std::io::Result<()>,
+    pub sink: Box<dyn std::io::Write + Send>,
+}
+
+#[cfg(feature = "breakpoint_dump")]
+impl<State, Action> DumpWriter<State, Action> {
+    pub fn new(sink: Box<dyn std::io::Write + Send>) -> Self
+    where
+        State: serde::Serialize,
+        Action: serde::Serialize,
+    {
+        Self {
+            write: write_dump::<State, Action>,
+            sink,
+        }
+    }
+
+    pub fn dump(&mut self, dump: &BreakpointDump<State, Action>) -> std::io::Result<()> {
+        (self.write)(dump, &mut self.sink)?;
+        self.sink.flush()
+    }
+}
+
+#[cfg(feature = "breakpoint_dump")]
+fn write_dump<State, Action>(
+    dump: &BreakpointDump<State, Action>,
+    sink: &mut dyn std::io::Write,
+) -> std::io::Result<()>
+where
+    State: serde::Serialize,
+    Action: serde::Serialize,
+{
+    serde_json::to_writer(&mut *sink, dump)?;
+    sink.write_all(b"\n")
+}
@@ -0,0 +1,441 @@
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, string::String};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+use crate::effects::no_effects;
+use crate::module::Modules;
+use crate::reducer::no_reducer;
+use crate::safety_condition::FormattedCheck;
+use crate::{
+    ActionId, DispatchInterceptor, Effects, ModuleRegistry, Recorder, Reducer, SafetyCondition,
+    Store, TimeService,
+};
+
+/// Error returned by [StoreBuilder::build] when a required piece is
+/// missing or the initial state is invalid.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum BuildError {
+    MissingReducer,
+    MissingService,
+    MissingInitialState,
+    /// Initial state violates the safety condition, see
+    /// [StoreBuilder::check_initial_state]. Error is formatted with `Debug`.
+    UnsafeInitialState(String),
+    /// Persisted snapshot couldn't be loaded or deserialized, see
+    /// `StoreBuilder::with_persistence`.
+    SnapshotLoad(String),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let missing = match self {
+            Self::MissingReducer => "reducer",
+            Self::MissingService => "service",
+            Self::MissingInitialState => "initial state",
+            Self::UnsafeInitialState(error) => {
+                return write!(f, "initial state violates safety condition: {}", error);
+            }
+            Self::SnapshotLoad(error) => {
+                return write!(f, "failed to load persisted snapshot: {}", error);
+            }
+        };
+        write!(f, "store builder is missing {}", missing)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BuildError {}
+
+/// Builder for [Store].
+///
+/// Reducer, service and initial state are required. Everything else
+/// has a default:
+/// - effects: no effects.
+/// - initial time: [TimeService::system_time] with `std`, unix epoch otherwise.
+/// - no recorder, no dispatch interceptor.
+/// - no preallocated buffers.
+///
+/// # Allocations
+///
+/// Dispatching doesn't allocate by itself, once the internal buffers
+/// it uses have grown to size. To avoid growing them while
+/// dispatching, preallocate them with [StoreBuilder::paused_queue_capacity],
+/// [StoreBuilder::intercepted_capacity] and
+/// [StoreBuilder::breakpoint_capacity].
+///
+/// Reducer and effects, of course, may still allocate, as well as these
+/// subsystems when enabled:
+/// - recorders other than [crate::RingRecorder], [crate::BoundedRecorder]
+///   and [crate::EncodingRecorder] (once its buffer has grown), or when
+///   cloning the action allocates.
+/// - time travel, and state history if its projection allocates.
+/// - stats, on the first action of every kind.
+/// - determinism check, breakpoint dumps, JSON logging and devtools.
+/// - caught panics, poisoning and error actions with messages.
+/// - more actions queued while paused than preallocated for.
+///
+/// # Example
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, BoundedRecorder, EnablingCondition, Store, StoreBuilder, TimeService};
+///
+/// #[derive(Clone)]
+/// enum Action {
+///     Increment,
+/// }
+///
+/// impl EnablingCondition<u32> for Action {}
+///
+/// struct Service;
+/// impl TimeService for Service {}
+///
+/// fn reducer(state: &mut u32, action: &ActionWithMeta<Action>) {
+///     match &action.action {
+///         Action::Increment => *state += 1,
+///     }
+/// }
+///
+/// fn effects(store: &mut Store<u32, Service, Action>, _: &ActionWithMeta<Action>) {
+///     if *store.state() < 3 {
+///         store.dispatch(Action::Increment);
+///     }
+/// }
+///
+/// let mut store = StoreBuilder::new()
+///     .reducer(reducer)
+///     .effects(effects)
+///     .service(Service)
+///     .initial_state(0)
+///     .recorder(Box::new(BoundedRecorder::new(16)))
+///     .build()
+///     .unwrap();
+///
+/// store.dispatch(Action::Increment);
+/// assert_eq!(*store.state(), 3);
+/// assert_eq!(store.recent_actions().count(), 3);
+/// ```
+///
+/// Missing required pieces are reported by [StoreBuilder::build]:
+///
+/// ```
+/// use redux_rs::{BuildError, StoreBuilder, TimeService};
+///
+/// struct Service;
+/// impl TimeService for Service {}
+///
+/// let result = StoreBuilder::<u32, Service, ()>::new()
+///     .service(Service)
+///     .initial_state(0)
+///     .build();
+/// assert_eq!(result.err(), Some(BuildError::MissingReducer));
+/// ```
+pub struct StoreBuilder<State, Service, Action> {
+    reducer: Option<Reducer<State, Action>>,
+    effects: Effects<State, Service, Action>,
+    service: Option<Service>,
+    initial_state: Option<State>,
+    initial_id: Option<ActionId>,
+
+    recorder: Option<Box<dyn Recorder<Action> + Send>>,
+    dispatch_interceptor: Option<DispatchInterceptor<State, Action>>,
+    initial_state_check: Option<FormattedCheck<State>>,
+    #[cfg(feature = "persistence")]
+    persistence: Option<(crate::persistence::Persistence<State>, LoadSnapshot<State>)>,
+    modules: Option<ModuleRegistry<State, Service, Action>>,
+
+    paused_queue_capacity: Option<usize>,
+    intercepted_capacity: usize,
+    breakpoint_capacity: usize,
+}
+
+impl<State, Service, Action> StoreBuilder<State, Service, Action>
+where
+    Service: TimeService,
+{
+    pub fn new() -> Self {
+        Self {
+            reducer: None,
+            effects: no_effects,
+            service: None,
+            initial_state: None,
+            initial_id: None,
+
+            recorder: None,
+            dispatch_interceptor: None,
+            initial_state_check: None,
+            #[cfg(feature = "persistence")]
+            persistence: None,
+            modules: None,
+
+            paused_queue_capacity: None,
+            intercepted_capacity: 0,
+            breakpoint_capacity: 0,
+        }
+    }
+
+    pub fn reducer(mut self, reducer: Reducer<State, Action>) -> Self {
+        self.reducer = Some(reducer);
+        self
+    }
+
+    pub fn effects(mut self, effects: Effects<State, Service, Action>) -> Self {
+        self.effects = effects;
+        self
+    }
+
+    pub fn service(mut self, service: Service) -> Self {
+        self.service = Some(service);
+        self
+    }
+
+    pub fn initial_state(mut self, initial_state: State) -> Self {
+        self.initial_state = Some(initial_state);
+        self
+    }
+
+    /// Wall clock time of store creation. Defaults to [TimeService::system_time].
+    #[cfg(feature = "std")]
+    pub fn initial_time(mut self, initial_time: SystemTime) -> Self {
+        let nanos = crate::time::system_time_to_nanos(initial_time);
+        self.initial_id = Some(ActionId::new_unchecked(nanos));
+        self
+    }
+
+    /// Starting id, see [Store::new_with_initial_id].
+    ///
+    /// Overrides [StoreBuilder::initial_time].
+    pub fn initial_id(mut self, initial_id: ActionId) -> Self {
+        self.initial_id = Some(initial_id);
+        self
+    }
+
+    pub fn recorder(mut self, recorder: Box<dyn Recorder<Action> + Send>) -> Self {
+        self.recorder = Some(recorder);
+        self
+    }
+
+    pub fn dispatch_interceptor(mut self, interceptor: DispatchInterceptor<State, Action>) -> Self {
+        self.dispatch_interceptor = Some(interceptor);
+        self
+    }
+
+    /// Modules composed into the reducer and effects, see
+    /// [ModuleRegistry]. Their initial sub-states are set in the initial
+    /// state, unless it's loaded from a snapshot.
+    ///
+    /// Reducer is optional if there are modules.
+    pub fn modules(mut self, modules: ModuleRegistry<State, Service, Action>) -> Self {
+        self.modules = Some(modules);
+        self
+    }
+
+    /// Capacity of the queue used while paused, preallocated. See
+    /// [Store::reserve_paused_queue].
+    pub fn paused_queue_capacity(mut self, capacity: usize) -> Self {
+        self.paused_queue_capacity = Some(capacity);
+        self
+    }
+
+    /// Preallocate space for actions captured by the dispatch interceptor.
+    pub fn intercepted_capacity(mut self, capacity: usize) -> Self {
+        self.intercepted_capacity = capacity;
+        self
+    }
+
+    /// Preallocate space for breakpoints.
+    pub fn breakpoint_capacity(mut self, capacity: usize) -> Self {
+        self.breakpoint_capacity = capacity;
+        self
+    }
+
+    #[cfg(feature = "std")]
+    fn default_initial_id(service: &mut Service) -> ActionId {
+        let nanos = crate::time::system_time_to_nanos(service.system_time());
+        ActionId::new_unchecked(nanos)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn default_initial_id(_: &mut Service) -> ActionId {
+        ActionId::ZERO
+    }
+
+    pub fn build(self) -> Result<Store<State, Service, Action>, BuildError> {
+        let reducer = match (self.reducer, &self.modules) {
+            (Some(reducer), _) => reducer,
+            (None, Some(_)) => no_reducer,
+            (None, None) => return Err(BuildError::MissingReducer),
+        };
+        let mut service = self.service.ok_or(BuildError::MissingService)?;
+        let mut initial_state = self.initial_state.ok_or(BuildError::MissingInitialState)?;
+        let modules = self
+            .modules
+            .map(|modules| Modules::new(modules, &mut initial_state));
+        #[cfg(feature = "persistence")]
+        let mut persistence = self.persistence;
+        #[cfg(feature = "persistence")]
+        let initial_state = match persistence.as_mut() {
+            Some((persistence, load)) => load(persistence.sink_mut())
+                .map_err(BuildError::SnapshotLoad)?
+                .unwrap_or(initial_state),
+            None => initial_state,
+        };
+        if let Some(check) = self.initial_state_check {
+            check(&initial_state).map_err(BuildError::UnsafeInitialState)?;
+        }
+        let initial_id = match self.initial_id {
+            Some(initial_id) => initial_id,
+            None => Self::default_initial_id(&mut service),
+        };
+
+        let mut store =
+            Store::new_with_initial_id(reducer, self.effects, service, initial_id, initial_state);
+        store.set_recorder(self.recorder);
+        store.set_dispatch_interceptor(self.dispatch_interceptor);
+        if let Some(capacity) = self.paused_queue_capacity {
+            store.reserve_paused_queue(capacity);
+        }
+        store.reserve_intercepted(self.intercepted_capacity);
+        store.reserve_breakpoints(self.breakpoint_capacity);
+        #[cfg(feature = "persistence")]
+        if let Some((persistence, _)) = persistence {
+            store.set_persistence(persistence);
+        }
+        if let Some(modules) = modules {
+            store.set_modules(modules);
+        }
+
+        Ok(store)
+    }
+}
+
+impl<State, Service, Action> StoreBuilder<State, Service, Action>
+where
+    State: SafetyCondition,
+    State::Error: fmt::Debug,
+    Service: TimeService,
+{
+    /// Make [StoreBuilder::build] fail with
+    /// [BuildError::UnsafeInitialState] if the initial state violates
+    /// the safety condition.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, BuildError, SafetyCondition, StoreBuilder};
+    ///
+    /// struct Percent(u8);
+    ///
+    /// impl SafetyCondition for Percent {
+    ///     type Error = &'static str;
+    ///
+    ///     fn check_safety_condition(&self) -> Result<(), Self::Error> {
+    ///         if self.0 > 100 {
+    ///             return Err("over 100%");
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// fn reducer(_: &mut Percent, _: &ActionWithMeta<()>) {}
+    ///
+    /// let build = |percent| {
+    ///     StoreBuilder::new()
+    ///         .reducer(reducer)
+    ///         .service(())
+    ///         .initial_state(Percent(percent))
+    ///         .check_initial_state()
+    ///         .build()
+    /// };
+    ///
+    /// assert!(build(100).is_ok());
+    /// assert_eq!(
+    ///     build(101).err(),
+    ///     Some(BuildError::UnsafeInitialState("\"over 100%\"".into()))
+    /// );
+    /// ```
+    pub fn check_initial_state(mut self) -> Self {
+        self.initial_state_check = Some(crate::safety_condition::check_formatted::<State>);
+        self
+    }
+}
+
+#[cfg(feature = "persistence")]
+type LoadSnapshot<State> = fn(&mut dyn crate::SnapshotSink) -> Result<Option<State>, String>;
+
+#[cfg(feature = "persistence")]
+impl<State, Service, Action> StoreBuilder<State, Service, Action>
+where
+    State: serde::Serialize + serde::de::DeserializeOwned,
+    Service: TimeService,
+{
+    /// Start from the snapshot in `config.sink`, if there is one,
+    /// instead of the initial state, and keep persisting the state, see
+    /// [Store::enable_persistence].
+    ///
+    /// Fails to build with [BuildError::SnapshotLoad] if the snapshot
+    /// can't be loaded.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, Cadence, EnablingCondition, FileSink, PersistenceConfig, StoreBuilder};
+    ///
+    /// struct Add(u32);
+    /// impl EnablingCondition<u32> for Add {}
+    ///
+    /// fn reducer(state: &mut u32, action: &ActionWithMeta<Add>) {
+    ///     *state += action.action.0;
+    /// }
+    ///
+    /// let dir = std::env::temp_dir().join(format!("redux-rs-persistence-{}", std::process::id()));
+    /// std::fs::create_dir_all(&dir).unwrap();
+    /// let sink = FileSink::new(dir.join("state.json"));
+    ///
+    /// let start = |sink: &FileSink| {
+    ///     StoreBuilder::new()
+    ///         .reducer(reducer)
+    ///         .service(())
+    ///         .initial_state(0)
+    ///         .with_persistence(PersistenceConfig {
+    ///             every: Cadence::EveryNActions(1),
+    ///             sink: Box::new(sink.clone()),
+    ///         })
+    ///         .build()
+    /// };
+    ///
+    /// // Nothing persisted yet, starts from the initial state.
+    /// let mut store = start(&sink).unwrap();
+    /// store.dispatch(Add(5));
+    /// store.dispatch(Add(2));
+    /// drop(store);
+    /// assert_eq!(*start(&sink).unwrap().state(), 7);
+    ///
+    /// // Crash while saving, after writing the temporary file, but
+    /// // before renaming it: previous snapshot is still there.
+    /// std::fs::write(sink.tmp_path(), b"{\"trunc").unwrap();
+    /// let mut store = start(&sink).unwrap();
+    /// assert_eq!(*store.state(), 7);
+    /// store.dispatch(Add(1));
+    /// assert_eq!(*start(&sink).unwrap().state(), 8);
+    ///
+    /// // Corrupted snapshot fails the build rather than being ignored.
+    /// std::fs::write(sink.path(), b"{").unwrap();
+    /// assert!(matches!(start(&sink).err(), Some(redux_rs::BuildError::SnapshotLoad(_))));
+    ///
+    /// std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn with_persistence(mut self, config: crate::PersistenceConfig) -> Self {
+        self.persistence = Some((
+            crate::persistence::Persistence::new(config),
+            crate::persistence::load::<State>,
+        ));
+        self
+    }
+}
+
+impl<State, Service, Action> Default for StoreBuilder<State, Service, Action>
+where
+    Service: TimeService,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,113 @@
+use core::fmt;
+use core::ops::BitOr;
+
+/// Which optional subsystems of the store are currently enabled or
+/// attached, see `Store::capabilities`.
+///
+/// Lets effects and middleware skip work nobody would see, e.g.
+/// building summaries when there's no recorder nor devtools.
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capabilities(u16);
+
+impl Capabilities {
+    pub const NONE: Self = Self(0);
+    /// Recorder is set, see `Store::set_recorder`.
+    pub const RECORDER: Self = Self(1);
+    /// Recorder sends actions to the devtools, see
+    /// `DevToolsConnector::recorder`.
+    pub const DEVTOOLS: Self = Self(1 << 1);
+    /// See `Store::enable_stats`.
+    pub const STATS: Self = Self(1 << 2);
+    /// See `Store::set_metrics`.
+    pub const METRICS: Self = Self(1 << 3);
+    /// See `Store::enable_time_travel`.
+    pub const TIME_TRAVEL: Self = Self(1 << 4);
+    /// Store shows a past state, replayed by `Store::travel_to`.
+    pub const DETACHED: Self = Self(1 << 5);
+    /// See `Store::enable_state_history`.
+    pub const STATE_HISTORY: Self = Self(1 << 6);
+    /// See `Store::enable_safety_check`.
+    pub const SAFETY_CHECK: Self = Self(1 << 7);
+    /// At least one listener is subscribed, see `Store::subscribe`.
+    pub const LISTENERS: Self = Self(1 << 8);
+    /// See `Store::pause`.
+    pub const PAUSED: Self = Self(1 << 9);
+    /// See `Store::poison`.
+    pub const POISONED: Self = Self(1 << 10);
+
+    const NAMES: [(Self, &'static str); 11] = [
+        (Self::RECORDER, "RECORDER"),
+        (Self::DEVTOOLS, "DEVTOOLS"),
+        (Self::STATS, "STATS"),
+        (Self::METRICS, "METRICS"),
+        (Self::TIME_TRAVEL, "TIME_TRAVEL"),
+        (Self::DETACHED, "DETACHED"),
+        (Self::STATE_HISTORY, "STATE_HISTORY"),
+        (Self::SAFETY_CHECK, "SAFETY_CHECK"),
+        (Self::LISTENERS, "LISTENERS"),
+        (Self::PAUSED, "PAUSED"),
+        (Self::POISONED, "POISONED"),
+    ];
+
+    /// Whether all of `other` is enabled.
+    #[inline(always)]
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Whether any of `other` is enabled.
+    #[inline(always)]
+    pub fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    #[inline(always)]
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Same capabilities, without `other`.
+    #[inline(always)]
+    pub fn without(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+
+    #[inline(always)]
+    pub(crate) fn set(&mut self, other: Self, enabled: bool) {
+        if enabled {
+            self.0 |= other.0;
+        } else {
+            self.0 &= !other.0;
+        }
+    }
+}
+
+impl BitOr for Capabilities {
+    type Output = Self;
+
+    #[inline(always)]
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+/// Names of the enabled capabilities, e.g. `RECORDER | PAUSED`.
+impl fmt::Debug for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("NONE");
+        }
+        let mut first = true;
+        for (flag, name) in Self::NAMES {
+            if self.contains(flag) {
+                if !first {
+                    f.write_str(" | ")?;
+                }
+                f.write_str(name)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
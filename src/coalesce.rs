@@ -0,0 +1,49 @@
+/// Key identifying actions which make each other redundant while
+/// queued, e.g. peer id for "message received from peer" actions.
+pub type CoalesceKey = u64;
+
+/// What to keep when an action is queued while another one with the
+/// same [CoalesceKey] is already in the queue.
+///
+/// Whatever is kept takes the place of the action queued first.
+pub enum CoalescePolicy<Action> {
+    /// Replace the queued action with the new one.
+    KeepLatest,
+    /// Drop the new action.
+    KeepFirst,
+    /// Replace the queued action with `merge(queued, new)`.
+    Merge(fn(Action, Action) -> Action),
+}
+
+impl<Action> Clone for CoalescePolicy<Action> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Action> Copy for CoalescePolicy<Action> {}
+
+/// Coalescing of actions queued while the store is paused, see
+/// `Store::set_coalescing`.
+///
+/// Finding the queued action with the same key is a linear scan of the
+/// queue, newest first.
+pub struct Coalescing<Action> {
+    /// Key of the action, `None` if it must never be coalesced.
+    pub key: fn(&Action) -> Option<CoalesceKey>,
+    pub policy: CoalescePolicy<Action>,
+}
+
+impl<Action> Coalescing<Action> {
+    pub fn new(key: fn(&Action) -> Option<CoalesceKey>, policy: CoalescePolicy<Action>) -> Self {
+        Self { key, policy }
+    }
+}
+
+impl<Action> Clone for Coalescing<Action> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Action> Copy for Coalescing<Action> {}
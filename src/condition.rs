@@ -0,0 +1,143 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+type Predicate<State> = Box<dyn Fn(&State) -> bool + Send>;
+
+struct Waiter<State> {
+    id: u64,
+    predicate: Predicate<State>,
+    waker: Option<Waker>,
+}
+
+struct Registry<State> {
+    /// Sorted by id.
+    waiters: Vec<Waiter<State>>,
+    next_id: u64,
+}
+
+/// Registry of futures waiting for a condition on the state, see
+/// `Store::notify_when`.
+///
+/// Shared with the futures, so that they can store their waker and
+/// deregister when dropped.
+pub(crate) struct Conditions<State> {
+    registry: Arc<Mutex<Registry<State>>>,
+}
+
+impl<State> Conditions<State> {
+    pub fn new() -> Self {
+        Self {
+            registry: Arc::new(Mutex::new(Registry {
+                waiters: Vec::new(),
+                next_id: 0,
+            })),
+        }
+    }
+
+    pub fn register(&self, predicate: Predicate<State>) -> StateCondition<State> {
+        let mut registry = lock(&self.registry);
+        let id = registry.next_id;
+        registry.next_id += 1;
+        registry.waiters.push(Waiter {
+            id,
+            predicate,
+            waker: None,
+        });
+        StateCondition {
+            registry: Some(self.registry.clone()),
+            id,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        lock(&self.registry).waiters.len()
+    }
+
+    /// Evaluate predicates of all the waiting futures against the
+    /// state, once, waking and deregistering the satisfied ones.
+    pub fn check(&self, state: &State) {
+        let mut wakers = Vec::new();
+        {
+            let mut registry = lock(&self.registry);
+            registry.waiters.retain_mut(|waiter| {
+                if !(waiter.predicate)(state) {
+                    return true;
+                }
+                wakers.extend(waiter.waker.take());
+                false
+            });
+        }
+        // Outside the lock, in case a waker polls right away.
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+fn lock<State>(registry: &Mutex<Registry<State>>) -> MutexGuard<'_, Registry<State>> {
+    // Predicates run under the lock, a panicking one leaves the
+    // registry consistent.
+    registry
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Future returned by `Store::notify_when`.
+pub(crate) struct StateCondition<State> {
+    /// `None` if satisfied already.
+    registry: Option<Arc<Mutex<Registry<State>>>>,
+    id: u64,
+}
+
+impl<State> StateCondition<State> {
+    pub fn ready() -> Self {
+        Self {
+            registry: None,
+            id: 0,
+        }
+    }
+}
+
+impl<State> Future for StateCondition<State> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let id = self.id;
+        let registry = match self.registry.as_ref() {
+            Some(registry) => registry,
+            None => return Poll::Ready(()),
+        };
+        let mut registry = lock(registry);
+        match registry
+            .waiters
+            .binary_search_by_key(&id, |waiter| waiter.id)
+        {
+            Ok(index) => {
+                registry.waiters[index].waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            // Only satisfied waiters are removed while the future lives.
+            Err(_) => {
+                drop(registry);
+                self.registry = None;
+                Poll::Ready(())
+            }
+        }
+    }
+}
+
+impl<State> Drop for StateCondition<State> {
+    fn drop(&mut self) {
+        if let Some(registry) = self.registry.take() {
+            let mut registry = lock(&registry);
+            if let Ok(index) = registry
+                .waiters
+                .binary_search_by_key(&self.id, |waiter| waiter.id)
+            {
+                registry.waiters.remove(index);
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use crate::{ActionClassifier, EnablingCondition};
+
+/// Enabling condition whose result can be cached, see
+/// `Store::enable_condition_cache`.
+///
+/// Meant for conditions which are pure functions of a rarely changing
+/// part of the state, e.g. whether the node is bootstrapped, but are
+/// evaluated for every dispatched action.
+pub trait CacheableCondition<State>: EnablingCondition<State> {
+    /// Fingerprint of everything [EnablingCondition::is_enabled] of the
+    /// action depends on, the action's own fields included. Must change
+    /// whenever the result might, otherwise stale results are served.
+    fn dep_fingerprint(&self, state: &State) -> u64;
+
+    /// Whether the result for this action may be cached at all. If not,
+    /// the condition is evaluated every time. Cacheable by default.
+    fn is_cacheable(&self) -> bool {
+        true
+    }
+}
+
+/// Counters of the enabling condition cache, see
+/// `Store::condition_cache_stats`.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub struct ConditionCacheStats {
+    /// Results served from the cache.
+    pub hits: u64,
+    /// Conditions evaluated as the fingerprint changed, or the kind had
+    /// no result cached yet.
+    pub misses: u64,
+    /// Conditions evaluated as the action isn't cacheable.
+    pub uncacheable: u64,
+}
+
+/// Last fingerprint and result of the enabling condition per action
+/// kind.
+pub(crate) struct ConditionCache<State, Action> {
+    classifier: ActionClassifier<Action>,
+    fingerprint: fn(&Action, &State) -> Option<u64>,
+    condition: fn(&Action, &State) -> bool,
+    results: BTreeMap<&'static str, (u64, bool)>,
+    stats: ConditionCacheStats,
+}
+
+impl<State, Action> ConditionCache<State, Action> {
+    pub fn new(classifier: ActionClassifier<Action>) -> Self
+    where
+        Action: CacheableCondition<State>,
+    {
+        Self {
+            classifier,
+            fingerprint: fingerprint::<State, Action>,
+            condition: Action::is_enabled,
+            results: BTreeMap::new(),
+            stats: ConditionCacheStats::default(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn stats(&self) -> ConditionCacheStats {
+        self.stats
+    }
+
+    /// [EnablingCondition::is_enabled], unless the fingerprint is the
+    /// same as when it was last evaluated for the action's kind.
+    #[inline(always)]
+    pub fn is_enabled(&mut self, action: &Action, state: &State) -> bool {
+        let fingerprint = match (self.fingerprint)(action, state) {
+            Some(fingerprint) => fingerprint,
+            None => {
+                self.stats.uncacheable += 1;
+                return (self.condition)(action, state);
+            }
+        };
+        let kind = (self.classifier)(action);
+        if let Some(&(cached, enabled)) = self.results.get(kind) {
+            if cached == fingerprint {
+                self.stats.hits += 1;
+                return enabled;
+            }
+        }
+        self.stats.misses += 1;
+        let enabled = (self.condition)(action, state);
+        self.results.insert(kind, (fingerprint, enabled));
+        enabled
+    }
+}
+
+/// Whether `T` is `Action`, i.e. the condition the cache evaluates is
+/// the dispatched type's own. Without `'static` bounds there's no
+/// `TypeId`, names of distinct types differ (up to lifetimes).
+#[inline(always)]
+pub(crate) fn is_same_type<T, Action>() -> bool {
+    core::mem::size_of::<T>() == core::mem::size_of::<Action>()
+        && core::any::type_name::<T>() == core::any::type_name::<Action>()
+}
+
+/// Enabling condition of a dispatched action, see [convert].
+pub(crate) enum Condition {
+    /// Evaluated before the conversion.
+    Evaluated(bool),
+    /// Left to the cache.
+    Cached,
+}
+
+impl Condition {
+    /// Whether the action is enabled, `cache` being the one passed to
+    /// [convert].
+    #[inline(always)]
+    pub fn evaluate<State, Action>(
+        self,
+        cache: Option<&mut ConditionCache<State, Action>>,
+        action: &Action,
+        state: &State,
+    ) -> bool {
+        match (self, cache) {
+            (Self::Evaluated(enabled), _) => enabled,
+            (Self::Cached, Some(cache)) => cache.is_enabled(action, state),
+            (Self::Cached, None) => unreachable!("condition left to a missing cache"),
+        }
+    }
+}
+
+/// Dispatched action converted into `Action`, with its enabling
+/// condition left to the cache if there is one and `T` is `Action`.
+///
+/// Other types may have stricter conditions than the `Action` they're
+/// converted into, so theirs is evaluated while it's still a `T`, which
+/// doesn't touch the cache.
+#[inline(always)]
+pub(crate) fn convert<T, State, Action>(
+    cache: Option<&ConditionCache<State, Action>>,
+    action: T,
+    state: &State,
+) -> (Action, Condition)
+where
+    T: Into<Action> + EnablingCondition<State>,
+{
+    if cache.is_some() && is_same_type::<T, Action>() {
+        (action.into(), Condition::Cached)
+    } else {
+        let enabled = action.is_enabled(state);
+        (action.into(), Condition::Evaluated(enabled))
+    }
+}
+
+fn fingerprint<State, Action>(action: &Action, state: &State) -> Option<u64>
+where
+    Action: CacheableCondition<State>,
+{
+    if action.is_cacheable() {
+        Some(action.dep_fingerprint(state))
+    } else {
+        None
+    }
+}
+
+impl<State, Action> Clone for ConditionCache<State, Action> {
+    fn clone(&self) -> Self {
+        Self {
+            classifier: self.classifier,
+            fingerprint: self.fingerprint,
+            condition: self.condition,
+            results: self.results.clone(),
+            stats: self.stats,
+        }
+    }
+}
@@ -0,0 +1,106 @@
+#[cfg(not(feature = "std"))]
+use alloc::sync::Arc;
+use core::any::Any;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+use crate::module::ModuleReducer;
+use crate::ActionWithMeta;
+
+/// Function signature for a reducer of a store with read-only
+/// configuration, see [crate::Store::new_with_config].
+pub type ConfigReducer<Config, State, Action> = fn(&Config, &mut State, &ActionWithMeta<Action>);
+
+/// Hash of the configuration, stable across builds and platforms, as
+/// stored in [crate::RecordingHeader].
+pub fn config_hash<Config: Hash>(config: &Config) -> u64 {
+    let mut hasher = Fnv1a::default();
+    config.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 64-bit FNV-1a. Unlike `DefaultHasher`, its output doesn't change
+/// between Rust versions.
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Error returned by `Store::replay`.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum ReplayError {
+    /// Recording was made with a different configuration than the one
+    /// the store was created with.
+    ConfigMismatch { recorded: u64, supplied: u64 },
+    /// Recording was made with a configuration, but the store has none.
+    MissingConfig { recorded: u64 },
+    /// Recording was made with a different seed of randomness than the
+    /// one recorded by the store, see `Store::record_rng_seed`.
+    SeedMismatch { recorded: u64, supplied: u64 },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ConfigMismatch { recorded, supplied } => write!(
+                f,
+                "recording was made with config {:016x}, but store has config {:016x}",
+                recorded, supplied
+            ),
+            Self::MissingConfig { recorded } => write!(
+                f,
+                "recording was made with config {:016x}, but store has no config",
+                recorded
+            ),
+            Self::SeedMismatch { recorded, supplied } => write!(
+                f,
+                "recording was made with seed {}, but store has seed {}",
+                recorded, supplied
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReplayError {}
+
+/// Configuration of the store, with its type erased.
+#[derive(Clone)]
+pub(crate) struct StoreConfig {
+    pub value: Arc<dyn Any + Send + Sync>,
+    pub hash: u64,
+}
+
+/// Reducer which gets the configuration, run by the store like a
+/// module's reducer.
+pub(crate) struct Configured<Config, State, Action> {
+    pub config: Arc<Config>,
+    pub reducer: ConfigReducer<Config, State, Action>,
+}
+
+impl<Config, State, Action> ModuleReducer<State, Action> for Configured<Config, State, Action> {
+    fn init(&self, _: &mut State) {}
+
+    #[inline(always)]
+    fn reduce(&self, state: &mut State, action: &ActionWithMeta<Action>) {
+        (self.reducer)(&self.config, state, action);
+    }
+}
@@ -0,0 +1,114 @@
+use core::fmt;
+
+use crate::{ActionId, ActionWithMeta, DivergenceReport, ReplayError};
+
+/// State along with the id of the last action it includes, see
+/// `Store::snapshot` and `Store::recover`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StoreSnapshot<State> {
+    pub state: State,
+    pub last_action_id: ActionId,
+}
+
+/// What `Store::recover` tolerates when the snapshot and the recording
+/// tail don't line up exactly. Refuses both by default.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub struct RecoveryPolicy {
+    /// Skip actions of the tail already included in the snapshot,
+    /// instead of refusing to recover.
+    pub skip_overlap: bool,
+    /// Go on over missing actions, instead of refusing to recover, or
+    /// stopping, if they're missing inside the tail.
+    ///
+    /// Actions dropped after they got their ids, e.g. by the rate
+    /// limiter or the dispatch interceptor, are never recorded, so a
+    /// store using those needs it.
+    pub allow_gaps: bool,
+}
+
+/// Why `Store::recover` refused to recover. State of the store is left
+/// untouched.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum RecoveryError {
+    Replay(ReplayError),
+    /// Tail starts with an action the snapshot already includes.
+    Overlap {
+        snapshot: ActionId,
+        action_id: ActionId,
+    },
+    /// Tail starts with an action following `follows`, rather than the
+    /// last action of the snapshot.
+    Gap {
+        snapshot: ActionId,
+        action_id: ActionId,
+        follows: ActionId,
+    },
+}
+
+impl From<ReplayError> for RecoveryError {
+    fn from(error: ReplayError) -> Self {
+        Self::Replay(error)
+    }
+}
+
+impl fmt::Display for RecoveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Replay(error) => error.fmt(f),
+            Self::Overlap {
+                snapshot,
+                action_id,
+            } => write!(
+                f,
+                "recording tail starts with action {}, already in the snapshot taken after action {}",
+                u64::from(*action_id),
+                u64::from(*snapshot)
+            ),
+            Self::Gap {
+                snapshot,
+                action_id,
+                follows,
+            } => write!(
+                f,
+                "recording tail starts with action {} following action {}, but the snapshot was taken after action {}",
+                u64::from(*action_id),
+                u64::from(*follows),
+                u64::from(*snapshot)
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RecoveryError {}
+
+/// Outcome of `Store::recover`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveryReport {
+    /// Last action included in the snapshot.
+    pub snapshot: ActionId,
+    /// Actions of the tail skipped as already included in the snapshot,
+    /// see [RecoveryPolicy::skip_overlap].
+    pub skipped: usize,
+    /// Actions of the tail passed through the reducer.
+    pub applied: usize,
+    /// Places where actions were missing and recovery went on, see
+    /// [RecoveryPolicy::allow_gaps].
+    pub gaps: usize,
+    /// Whether recovery stopped before the end of the tail, because
+    /// actions were missing inside it.
+    pub truncated: bool,
+    /// Last action included in the recovered state. Ids of newly
+    /// dispatched actions continue from it.
+    pub last_action_id: ActionId,
+    /// Where the recovered state first differed from the recording,
+    /// with `Store::recover_verified`.
+    pub divergence: Option<DivergenceReport>,
+}
+
+/// Id of the action dispatched right before `action`.
+pub(crate) fn follows<Action>(action: &ActionWithMeta<Action>) -> ActionId {
+    let elapsed = action.elapsed_since_prev.as_nanos() as u64;
+    ActionId::new_unchecked(u64::from(action.id).saturating_sub(elapsed))
+}
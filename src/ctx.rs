@@ -0,0 +1,127 @@
+//! Capability tokens of the `strict` feature.
+//!
+//! Reducer and effects of a store created with
+//! [crate::Store::new_strict] get a [ReducerCtx] and an [EffectsCtx]
+//! respectively, see [crate::StrictReducer] and [crate::StrictEffects].
+//! Neither can be constructed, cloned or kept outside of the call which
+//! got it, so whatever requires one can only be done from the matching
+//! function:
+//!
+//! - helpers mutating the state take a `&ReducerCtx`, so they can't be
+//!   called from effects, nor from anywhere else outside the reducer.
+//! - helpers talking to the outside world take a `&EffectsCtx`, so
+//!   they can't be called from the reducer. Effects get the store as a
+//!   [crate::StrictStore], without the `service` field, and
+//!   [crate::StrictStore::service_with] is the service accessor taking
+//!   one.
+//!
+//! The feature only adds API, stores created otherwise and their
+//! [crate::Reducer], [crate::Effects] and [crate::Store::service] work
+//! the same with it enabled.
+//!
+//! # Migration
+//!
+//! Add the token as the first argument of the reducer and effects,
+//! create the store with [crate::Store::new_strict], and pass the
+//! effects one to helpers:
+//!
+//! ```text
+//! -fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+//! +fn reducer(ctx: &ReducerCtx, state: &mut State, action: &ActionWithMeta<Action>) {
+//!
+//! -fn effects(store: &mut Store<State, Service, Action>, action: &ActionWithMeta<Action>) {
+//! -    store.service.network.send(..);
+//! +fn effects(ctx: &EffectsCtx, store: &mut StrictStore<State, Service, Action>, action: &ActionWithMeta<Action>) {
+//! +    store.service_with(ctx).network.send(..);
+//!
+//! -let store = Store::new(reducer, effects, service, now, state);
+//! +let store = Store::new_strict(reducer, effects, service, now, state);
+//! ```
+//!
+//! [crate::Module] reducers and effects don't change, [crate::chain_reducers]
+//! has no counterpart, sub-reducers are called directly passing `ctx`
+//! along.
+
+/// Proof of running inside the reducer, see the [module](self) docs.
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EffectsCtx, EnablingCondition, ReducerCtx, Store, StrictStore};
+/// use std::time::SystemTime;
+///
+/// struct Deposit(u64);
+/// impl EnablingCondition<u64> for Deposit {}
+///
+/// /// Can't be called from effects, there's no `ReducerCtx` there.
+/// fn add(_: &ReducerCtx, balance: &mut u64, amount: u64) {
+///     *balance += amount;
+/// }
+///
+/// fn reducer(ctx: &ReducerCtx, balance: &mut u64, action: &ActionWithMeta<Deposit>) {
+///     add(ctx, balance, action.action.0);
+/// }
+///
+/// fn effects(_: &EffectsCtx, _: &mut StrictStore<u64, (), Deposit>, _: &ActionWithMeta<Deposit>) {}
+///
+/// let mut store = Store::new_strict(reducer, effects, (), SystemTime::UNIX_EPOCH, 0);
+/// store.dispatch(Deposit(10));
+/// assert_eq!(*store.state(), 10);
+/// ```
+#[derive(Debug)]
+pub struct ReducerCtx {
+    _private: (),
+}
+
+impl ReducerCtx {
+    #[inline(always)]
+    pub(crate) const fn new() -> Self {
+        Self { _private: () }
+    }
+}
+
+/// Proof of running inside effects, see the [module](self) docs.
+///
+/// ```
+/// use redux_rs::{
+///     ActionWithMeta, EffectsCtx, EnablingCondition, ReducerCtx, Store, StrictStore, TimeService,
+/// };
+/// use std::time::SystemTime;
+///
+/// #[derive(Default)]
+/// struct Service {
+///     sent: Vec<u64>,
+/// }
+///
+/// impl TimeService for Service {}
+///
+/// struct Publish(u64);
+/// impl EnablingCondition<()> for Publish {}
+///
+/// fn reducer(_: &ReducerCtx, _: &mut (), _: &ActionWithMeta<Publish>) {}
+///
+/// fn effects(
+///     ctx: &EffectsCtx,
+///     store: &mut StrictStore<(), Service, Publish>,
+///     action: &ActionWithMeta<Publish>,
+/// ) {
+///     store.service_with(ctx).sent.push(action.action.0);
+/// }
+///
+/// let service = Service::default();
+/// let mut store = Store::new_strict(reducer, effects, service, SystemTime::UNIX_EPOCH, ());
+/// store.dispatch(Publish(1));
+/// store.dispatch(Publish(2));
+///
+/// let ((), service, _) = store.into_parts();
+/// assert_eq!(service.sent, [1, 2]);
+/// ```
+#[derive(Debug)]
+pub struct EffectsCtx {
+    _private: (),
+}
+
+impl EffectsCtx {
+    #[inline(always)]
+    pub(crate) const fn new() -> Self {
+        Self { _private: () }
+    }
+}
@@ -0,0 +1,131 @@
+#[cfg(not(feature = "std"))]
+use alloc::collections::{BTreeSet, VecDeque};
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::collections::{BTreeSet, VecDeque};
+
+use crate::Instant;
+
+/// Key identifying actions which duplicate each other, e.g. id of an
+/// event delivered by an upstream.
+pub type DedupKey = u64;
+
+/// How long a key is remembered after the action first seen with it.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum DedupWindow {
+    /// Time measured with the service's clock.
+    Time(Duration),
+    /// Number of actions dispatched after it, whether they have a key,
+    /// and whether they got processed, or not.
+    Actions(u64),
+}
+
+/// Key of an action which isn't a duplicate, along with when it was
+/// checked, see [Dedup::check].
+pub(crate) struct Seen(DedupKey, Option<Instant>, u64);
+
+/// Suppression of duplicate actions, see `Store::set_dedup`.
+///
+/// Keys are forgotten once they fall out of the window, on every
+/// dispatch, so memory is bounded by how many keyed actions fit into it.
+#[derive(Debug, Clone)]
+pub struct Dedup<Action> {
+    /// Key of the action, `None` if it must never be suppressed.
+    key: fn(&Action) -> Option<DedupKey>,
+    window: DedupWindow,
+    /// Keys along with when they were first seen, and how many actions
+    /// were dispatched by then, oldest first.
+    seen: VecDeque<(DedupKey, Option<Instant>, u64)>,
+    /// Keys in `seen`.
+    keys: BTreeSet<DedupKey>,
+    dispatched: u64,
+    suppressed: u64,
+}
+
+impl<Action> Dedup<Action> {
+    pub fn new(key: fn(&Action) -> Option<DedupKey>, window: DedupWindow) -> Self {
+        Self {
+            key,
+            window,
+            seen: VecDeque::new(),
+            keys: BTreeSet::new(),
+            dispatched: 0,
+            suppressed: 0,
+        }
+    }
+
+    #[inline(always)]
+    pub fn window(&self) -> DedupWindow {
+        self.window
+    }
+
+    /// Number of keys currently remembered.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Duplicates suppressed so far.
+    #[inline(always)]
+    pub fn suppressed(&self) -> u64 {
+        self.suppressed
+    }
+
+    /// `Err` with the key of `action` if it duplicates an action seen
+    /// within the window, otherwise its key, if it has one, to be
+    /// passed to [Dedup::remember] if the action gets processed.
+    ///
+    /// `now` is only called for [DedupWindow::Time].
+    pub(crate) fn check(
+        &mut self,
+        action: &Action,
+        now: impl FnOnce() -> Instant,
+    ) -> Result<Option<Seen>, DedupKey> {
+        self.dispatched += 1;
+        let now = match self.window {
+            DedupWindow::Time(_) => Some(now()),
+            DedupWindow::Actions(_) => None,
+        };
+        self.evict(now);
+
+        let key = match (self.key)(action) {
+            Some(key) => key,
+            None => return Ok(None),
+        };
+        if self.keys.contains(&key) {
+            self.suppressed += 1;
+            return Err(key);
+        }
+        Ok(Some(Seen(key, now, self.dispatched)))
+    }
+
+    /// Remember the key of the checked action, once it's processed.
+    pub(crate) fn remember(&mut self, Seen(key, now, dispatched): Seen) {
+        if self.keys.insert(key) {
+            self.seen.push_back((key, now, dispatched));
+        }
+    }
+
+    /// Forget keys which fell out of the window.
+    fn evict(&mut self, now: Option<Instant>) {
+        while let Some(&(key, seen_at, dispatched)) = self.seen.front() {
+            let expired = match (self.window, now, seen_at) {
+                (DedupWindow::Time(window), Some(now), Some(seen_at)) => {
+                    now.duration_since(seen_at) >= window
+                }
+                (DedupWindow::Actions(window), ..) => self.dispatched - dispatched > window,
+                _ => true,
+            };
+            if !expired {
+                return;
+            }
+            self.keys.remove(&key);
+            self.seen.pop_front();
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use crate::ActionId;
+
+/// Runs the reducer a second time, on a snapshot of the state taken
+/// before the action, and compares the results.
+pub(crate) struct DeterminismCheck<State> {
+    /// Check every `every`-th action.
+    every: u32,
+    /// Number of actions since the last check.
+    skipped: u32,
+    clone: fn(&State) -> State,
+    eq: fn(&State, &State) -> bool,
+    #[cfg(feature = "diff")]
+    serialize: Option<fn(&State) -> serde_json::Value>,
+}
+
+impl<State> DeterminismCheck<State> {
+    pub fn new(every: u32) -> Self
+    where
+        State: Clone + PartialEq,
+    {
+        Self {
+            every: every.max(1),
+            skipped: 0,
+            clone: State::clone,
+            eq: State::eq,
+            #[cfg(feature = "diff")]
+            serialize: None,
+        }
+    }
+
+    #[cfg(feature = "diff")]
+    pub fn with_diff(mut self) -> Self
+    where
+        State: serde::Serialize,
+    {
+        self.serialize = Some(crate::diff::to_value::<State>);
+        self
+    }
+
+    /// Snapshot of the state before the action, if the action should
+    /// be checked.
+    pub fn snapshot(&mut self, state: &State) -> Option<State> {
+        if self.skipped + 1 < self.every {
+            self.skipped += 1;
+            return None;
+        }
+        self.skipped = 0;
+        Some((self.clone)(state))
+    }
+
+    /// Panics if `replayed` differs from `state`.
+    pub fn compare(&self, id: ActionId, state: &State, replayed: &State) {
+        if (self.eq)(state, replayed) {
+            return;
+        }
+        #[cfg(feature = "diff")]
+        if let Some(serialize) = self.serialize {
+            let diff = crate::StateDiff::between(&serialize(state), &serialize(replayed));
+            panic!(
+                "reducer is not deterministic: action {} produced different states from the same input:\n{}",
+                u64::from(id),
+                diff
+            );
+        }
+        panic!(
+            "reducer is not deterministic: action {} produced different states from the same input",
+            u64::from(id)
+        );
+    }
+}
+
+impl<State> Clone for DeterminismCheck<State> {
+    fn clone(&self) -> Self {
+        Self {
+            every: self.every,
+            skipped: self.skipped,
+            clone: self.clone,
+            eq: self.eq,
+            #[cfg(feature = "diff")]
+            serialize: self.serialize,
+        }
+    }
+}
@@ -0,0 +1,494 @@
+use std::io;
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{
+    ActionClassifier, ActionId, ActionWithMeta, Capabilities, EnablingCondition, Provenance,
+    Recorder, Store, TimeService,
+};
+
+/// How long the connector thread waits for incoming messages before
+/// checking for outgoing ones.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Transport of text messages to the devtools socket server.
+///
+/// Used by the connector thread, which calls [DevToolsTransport::recv]
+/// in a loop, so it should wait for a short while (like 20ms) before
+/// returning `Ok(None)` when no message is available.
+pub trait DevToolsTransport: Send + 'static {
+    fn send(&mut self, text: String) -> io::Result<()>;
+
+    fn recv(&mut self) -> io::Result<Option<String>>;
+}
+
+/// WebSocket connection to a `remotedev-server` compatible server.
+pub struct WebSocketTransport {
+    socket: tungstenite::WebSocket<TcpStream>,
+}
+
+impl WebSocketTransport {
+    /// Connect to the server, e.g. `ws://localhost:8000/socketcluster/`.
+    pub fn connect(url: &str) -> io::Result<Self> {
+        let uri: tungstenite::http::Uri = url.parse().map_err(other_error)?;
+        let host = uri
+            .host()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "url without host"))?;
+        let stream = TcpStream::connect((host, uri.port_u16().unwrap_or(80)))?;
+        let (socket, _) = tungstenite::client(url, stream).map_err(other_error)?;
+        socket.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+        Ok(Self { socket })
+    }
+}
+
+impl DevToolsTransport for WebSocketTransport {
+    fn send(&mut self, text: String) -> io::Result<()> {
+        self.socket
+            .send(tungstenite::Message::Text(text))
+            .map_err(other_error)
+    }
+
+    fn recv(&mut self) -> io::Result<Option<String>> {
+        match self.socket.read() {
+            Ok(tungstenite::Message::Text(text)) => Ok(Some(text)),
+            Ok(_) => Ok(None),
+            Err(tungstenite::Error::Io(err))
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(other_error(err)),
+        }
+    }
+}
+
+fn other_error<E: ToString>(err: E) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, err.to_string())
+}
+
+/// Actions recorded by [DevToolsRecorder], waiting to be sent.
+type Pending = Arc<Mutex<Vec<(ActionId, Value)>>>;
+
+/// Connection to the Redux DevTools (through a `remotedev-server`).
+///
+/// Talks the socketcluster protocol on its own thread, so dispatch is
+/// never blocked by the network. The store loop has to:
+/// - set [DevToolsConnector::recorder] as the store's recorder, which
+///   queues every processed action;
+/// - call [DevToolsConnector::poll] once before dispatching anything,
+///   which sends the initial state;
+/// - keep calling it regularly, which sends the queued actions along
+///   with the current state (intermediate states between two polls
+///   aren't sent) and handles commands from the devtools.
+///
+/// Supported commands:
+/// - jump to action/state, mapped to [Store::travel_to], so time travel
+///   has to be enabled. Jumping to the latest action returns to live
+///   state.
+/// - dispatch, with the action given as JSON deserializable to `Action`.
+///
+/// Actions are shown as `{ "type": kind, "payload": action }`, kind
+/// given by the classifier.
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, DevToolsConnector, DevToolsTransport, EnablingCondition, Store};
+/// use serde_json::{json, Value};
+/// use std::sync::mpsc::{channel, Receiver, Sender};
+/// use std::time::Duration;
+///
+/// /// Transport to the server simulated by the test.
+/// struct FakeTransport {
+///     to_server: Sender<String>,
+///     from_server: Receiver<String>,
+/// }
+///
+/// impl DevToolsTransport for FakeTransport {
+///     fn send(&mut self, text: String) -> std::io::Result<()> {
+///         let _ = self.to_server.send(text);
+///         Ok(())
+///     }
+///
+///     fn recv(&mut self) -> std::io::Result<Option<String>> {
+///         Ok(self.from_server.recv_timeout(Duration::from_millis(5)).ok())
+///     }
+/// }
+///
+/// #[derive(serde::Serialize, serde::Deserialize, Clone)]
+/// enum Action {
+///     Add(u32),
+/// }
+///
+/// impl EnablingCondition<u32> for Action {}
+///
+/// fn reducer(state: &mut u32, action: &ActionWithMeta<Action>) {
+///     match action.action {
+///         Action::Add(n) => *state += n,
+///     }
+/// }
+///
+/// let (to_server, server_rx) = channel();
+/// let (server_tx, from_server) = channel();
+/// let transport = FakeTransport { to_server, from_server };
+///
+/// let mut devtools = DevToolsConnector::<Action>::with_transport(transport, "counter", |_| "Add");
+/// let mut store = Store::simple(reducer, |_, _| {}, 0);
+/// store.set_recorder(Some(Box::new(devtools.recorder())));
+/// store.enable_time_travel(16);
+/// devtools.poll(&mut store);
+///
+/// let server_recv = || -> Value {
+///     let text = server_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+///     serde_json::from_str(&text).unwrap()
+/// };
+///
+/// // Handshake, login and subscriptions.
+/// assert_eq!(server_recv()["event"], "#handshake");
+/// server_tx.send(json!({ "rid": 1, "data": { "id": "socket1" } }).to_string()).unwrap();
+/// assert_eq!(server_recv()["event"], "login");
+/// server_tx.send(json!({ "rid": 2, "data": "respond" }).to_string()).unwrap();
+/// assert_eq!(server_recv()["data"]["channel"], "respond");
+/// assert_eq!(server_recv()["data"]["channel"], "sc-socket1");
+///
+/// let init = server_recv();
+/// assert_eq!(init["event"], "log");
+/// assert_eq!(init["data"]["type"], "INIT");
+/// assert_eq!(init["data"]["payload"], "0");
+/// assert_eq!(init["data"]["id"], "socket1");
+/// assert_eq!(init["data"]["name"], "counter");
+///
+/// store.dispatch(Action::Add(1));
+/// store.dispatch(Action::Add(2));
+/// devtools.poll(&mut store);
+///
+/// for next_action_id in [2, 3] {
+///     let log = server_recv();
+///     assert_eq!(log["data"]["type"], "ACTION");
+///     assert_eq!(log["data"]["payload"], "3");
+///     assert_eq!(log["data"]["nextActionId"], next_action_id);
+///     let action: Value = serde_json::from_str(log["data"]["action"].as_str().unwrap()).unwrap();
+///     assert_eq!(action["action"]["type"], "Add");
+/// }
+///
+/// let publish = |data: Value| {
+///     let message = json!({ "event": "#publish", "data": { "channel": "respond", "data": data } });
+///     server_tx.send(message.to_string()).unwrap();
+/// };
+/// fn poll_until<F>(devtools: &mut DevToolsConnector<Action>, store: &mut Store<u32, (), Action>, f: F)
+/// where
+///     F: Fn(&Store<u32, (), Action>) -> bool,
+/// {
+///     for _ in 0..500 {
+///         devtools.poll(store);
+///         if f(store) {
+///             return;
+///         }
+///         std::thread::sleep(Duration::from_millis(10));
+///     }
+///     panic!("timed out");
+/// }
+///
+/// // Jump to the first action.
+/// publish(json!({ "type": "DISPATCH", "payload": { "type": "JUMP_TO_ACTION", "actionId": 1 } }));
+/// poll_until(&mut devtools, &mut store, |store| store.is_detached());
+/// assert_eq!(*store.state(), 1);
+///
+/// // Back to the latest one.
+/// publish(json!({ "type": "DISPATCH", "payload": { "type": "JUMP_TO_STATE", "actionId": 2 } }));
+/// poll_until(&mut devtools, &mut store, |store| !store.is_detached());
+/// assert_eq!(*store.state(), 3);
+///
+/// // Dispatch from the devtools.
+/// publish(json!({ "type": "ACTION", "action": r#"{"Add":10}"# }));
+/// poll_until(&mut devtools, &mut store, |store| *store.state() == 13);
+/// ```
+pub struct DevToolsConnector<Action> {
+    name: String,
+    classifier: ActionClassifier<Action>,
+    outgoing: Sender<Value>,
+    incoming: Receiver<Value>,
+    pending: Pending,
+    /// Ids of sent actions. Devtools refer to them by index + 1, as
+    /// index 0 is the initial state.
+    sent: Vec<ActionId>,
+    initialized: bool,
+    connected: bool,
+    errors: u64,
+}
+
+impl<Action> DevToolsConnector<Action> {
+    /// Connect over WebSocket, e.g. to `ws://localhost:8000/socketcluster/`.
+    ///
+    /// `name` is the instance name shown in the devtools.
+    pub fn connect(
+        url: &str,
+        name: &str,
+        classifier: ActionClassifier<Action>,
+    ) -> io::Result<Self> {
+        let transport = WebSocketTransport::connect(url)?;
+        Ok(Self::with_transport(transport, name, classifier))
+    }
+
+    /// Talk to the devtools over the given transport.
+    pub fn with_transport<T: DevToolsTransport>(
+        transport: T,
+        name: &str,
+        classifier: ActionClassifier<Action>,
+    ) -> Self {
+        let (outgoing, outgoing_rx) = mpsc::channel();
+        let (incoming_tx, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = run(transport, outgoing_rx, incoming_tx);
+        });
+
+        Self {
+            name: name.to_owned(),
+            classifier,
+            outgoing,
+            incoming,
+            pending: Default::default(),
+            sent: Vec::new(),
+            initialized: false,
+            connected: true,
+            errors: 0,
+        }
+    }
+
+    /// Recorder which queues processed actions for sending.
+    pub fn recorder(&self) -> DevToolsRecorder<Action> {
+        DevToolsRecorder {
+            pending: self.pending.clone(),
+            classifier: self.classifier,
+        }
+    }
+
+    /// Whether the connector thread is still running.
+    ///
+    /// Once the connection fails, it isn't reestablished.
+    #[inline(always)]
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Number of messages or commands which couldn't be serialized or
+    /// deserialized.
+    #[inline(always)]
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    /// Handle commands from the devtools, then send queued actions.
+    pub fn poll<State, Service>(&mut self, store: &mut Store<State, Service, Action>)
+    where
+        State: Serialize,
+        Service: TimeService,
+        Action: DeserializeOwned + EnablingCondition<State>,
+    {
+        if !self.initialized {
+            self.initialized = true;
+            let state = self.serialize(store.state());
+            self.send(json!({ "type": "INIT", "payload": state }));
+        }
+
+        loop {
+            match self.incoming.try_recv() {
+                Ok(command) => self.handle(store, command),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.connected = false;
+                    break;
+                }
+            }
+        }
+
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        if pending.is_empty() {
+            return;
+        }
+        let state = self.serialize(store.state());
+        for (id, action) in pending {
+            self.sent.push(id);
+            let action = json!({
+                "type": "PERFORM_ACTION",
+                "action": action,
+                "timestamp": u64::from(id) / 1_000_000,
+            });
+            self.send(json!({
+                "type": "ACTION",
+                "action": action.to_string(),
+                "payload": state,
+                "nextActionId": self.sent.len() + 1,
+            }));
+        }
+    }
+
+    fn handle<State, Service>(&mut self, store: &mut Store<State, Service, Action>, command: Value)
+    where
+        Service: TimeService,
+        Action: DeserializeOwned + EnablingCondition<State>,
+    {
+        match command["type"].as_str() {
+            Some("DISPATCH") => {
+                let payload = &command["payload"];
+                if !matches!(
+                    payload["type"].as_str(),
+                    Some("JUMP_TO_ACTION") | Some("JUMP_TO_STATE")
+                ) {
+                    return;
+                }
+                let index = payload["actionId"].as_u64().unwrap_or(0) as usize;
+                if index >= self.sent.len() {
+                    store.travel_to_latest();
+                } else if let Some(id) = index.checked_sub(1).map(|i| self.sent[i]) {
+                    if store.travel_to(id).is_err() {
+                        self.errors += 1;
+                    }
+                }
+            }
+            Some("ACTION") => {
+                let action = command["action"]
+                    .as_str()
+                    .and_then(|action| serde_json::from_str::<Action>(action).ok());
+                match action {
+                    Some(action) => {
+                        store.dispatch_from(action, Provenance::Remote);
+                    }
+                    None => self.errors += 1,
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn serialize<T: Serialize>(&mut self, value: &T) -> String {
+        serde_json::to_string(value).unwrap_or_else(|_| {
+            self.errors += 1;
+            String::from("null")
+        })
+    }
+
+    fn send(&mut self, mut message: Value) {
+        message["name"] = self.name.clone().into();
+        message["instanceId"] = self.name.clone().into();
+        if self.outgoing.send(message).is_err() {
+            self.connected = false;
+        }
+    }
+}
+
+/// Recorder queueing actions to be sent by [DevToolsConnector::poll].
+pub struct DevToolsRecorder<Action> {
+    pending: Pending,
+    classifier: ActionClassifier<Action>,
+}
+
+impl<Action: Serialize> Recorder<Action> for DevToolsRecorder<Action> {
+    fn record(&mut self, action: &ActionWithMeta<Action>) {
+        let payload = serde_json::to_value(&action.action).unwrap_or(Value::Null);
+        let action_json = json!({
+            "type": (self.classifier)(&action.action),
+            "payload": payload,
+        });
+        self.pending.lock().unwrap().push((action.id, action_json));
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::RECORDER | Capabilities::DEVTOOLS
+    }
+}
+
+/// Connector thread: socketcluster handshake, then forwarding messages
+/// both ways until the connector is dropped or connection fails.
+fn run<T: DevToolsTransport>(
+    mut transport: T,
+    outgoing: Receiver<Value>,
+    incoming: Sender<Value>,
+) -> io::Result<()> {
+    let socket_id = handshake(&mut transport)?;
+
+    loop {
+        loop {
+            match outgoing.try_recv() {
+                Ok(mut data) => {
+                    data["id"] = socket_id.clone().into();
+                    transport.send(json!({ "event": "log", "data": data }).to_string())?;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return Ok(()),
+            }
+        }
+
+        let mut message = match recv(&mut transport)? {
+            Some(message) => message,
+            None => continue,
+        };
+        if message["event"] == "#publish" {
+            let command = message
+                .pointer_mut("/data/data")
+                .map(Value::take)
+                .unwrap_or(Value::Null);
+            if incoming.send(command).is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Receive a JSON message, answering socketcluster pings.
+fn recv<T: DevToolsTransport>(transport: &mut T) -> io::Result<Option<Value>> {
+    let text = match transport.recv()? {
+        Some(text) => text,
+        None => return Ok(None),
+    };
+    match text.as_str() {
+        "#1" => transport.send("#2".to_owned()).map(|_| None),
+        "" => transport.send(String::new()).map(|_| None),
+        _ => Ok(serde_json::from_str(&text).ok()),
+    }
+}
+
+/// Perform handshake and login, subscribe to the command channels.
+/// Returns the socket id.
+fn handshake<T: DevToolsTransport>(transport: &mut T) -> io::Result<String> {
+    let deadline = Instant::now() + HANDSHAKE_TIMEOUT;
+    let request = |transport: &mut T, cid: u64, event: &str, data: Value| {
+        transport.send(json!({ "event": event, "data": data, "cid": cid }).to_string())?;
+        while Instant::now() < deadline {
+            if let Some(mut message) = recv(transport)? {
+                if message["rid"] == cid {
+                    return Ok(message["data"].take());
+                }
+            }
+        }
+        Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "devtools handshake timed out",
+        ))
+    };
+
+    let handshake = request(transport, 1, "#handshake", json!({ "authToken": null }))?;
+    let socket_id = handshake["id"].as_str().unwrap_or_default().to_owned();
+    let channel = request(transport, 2, "login", json!("master"))?;
+
+    let channels = [
+        channel.as_str().unwrap_or("respond").to_owned(),
+        format!("sc-{}", socket_id),
+    ];
+    for (cid, channel) in (3..).zip(channels.iter()) {
+        let subscribe =
+            json!({ "event": "#subscribe", "data": { "channel": channel }, "cid": cid });
+        transport.send(subscribe.to_string())?;
+    }
+
+    Ok(socket_id)
+}
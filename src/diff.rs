@@ -0,0 +1,238 @@
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Arrays longer than this are summarized instead of diffed per item.
+pub const ARRAY_DIFF_LIMIT: usize = 16;
+
+/// Single difference between two serialized states.
+///
+/// Paths are JSON pointers (e.g. `/peers/3/address`), empty for the
+/// root.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffEntry {
+    Added {
+        path: String,
+        value: Value,
+    },
+    Removed {
+        path: String,
+        value: Value,
+    },
+    Changed {
+        path: String,
+        old: Value,
+        new: Value,
+    },
+    /// Array longer than [ARRAY_DIFF_LIMIT] changed.
+    ArrayChanged {
+        path: String,
+        old_len: usize,
+        new_len: usize,
+        /// First index at which arrays differ, or the shorter length
+        /// if one is a prefix of the other.
+        first_mismatch: usize,
+    },
+}
+
+/// Structural diff of two states, serialized to JSON value trees.
+///
+/// ```
+/// use redux_rs::{DiffEntry, StateDiff};
+/// use serde_json::json;
+///
+/// let old_big = vec![0; 100];
+/// let mut new_big = vec![0; 101];
+/// new_big[50] = 1;
+///
+/// let old = json!({ "peers": { "a": { "connected": false } }, "log": [1, 2], "big": old_big });
+/// let new = json!({ "peers": { "a": { "connected": true }, "b": {} }, "log": [1, 2, 3], "big": new_big });
+///
+/// let diff = StateDiff::between(&old, &new);
+/// assert_eq!(
+///     diff.entries,
+///     vec![
+///         DiffEntry::ArrayChanged { path: "/big".into(), old_len: 100, new_len: 101, first_mismatch: 50 },
+///         DiffEntry::Added { path: "/log/2".into(), value: json!(3) },
+///         DiffEntry::Changed { path: "/peers/a/connected".into(), old: json!(false), new: json!(true) },
+///         DiffEntry::Added { path: "/peers/b".into(), value: json!({}) },
+///     ]
+/// );
+/// assert_eq!(
+///     diff.to_string(),
+///     "~ /big: 100 -> 101 items, first difference at 50\n\
+///      + /log/2: 3\n\
+///      ~ /peers/a/connected: false -> true\n\
+///      + /peers/b: {}\n"
+/// );
+///
+/// assert!(StateDiff::between(&old, &old).is_empty());
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct StateDiff {
+    pub entries: Vec<DiffEntry>,
+}
+
+impl StateDiff {
+    pub fn between(old: &Value, new: &Value) -> Self {
+        let mut entries = Vec::new();
+        diff_values(&mut String::new(), old, new, &mut entries);
+        Self { entries }
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn diff_values(path: &mut String, old: &Value, new: &Value, out: &mut Vec<DiffEntry>) {
+    match (old, new) {
+        (Value::Object(old_map), Value::Object(new_map)) => {
+            for (key, old_value) in old_map {
+                let len = push_segment(path, key);
+                match new_map.get(key) {
+                    Some(new_value) => diff_values(path, old_value, new_value, out),
+                    None => out.push(DiffEntry::Removed {
+                        path: path.clone(),
+                        value: old_value.clone(),
+                    }),
+                }
+                path.truncate(len);
+            }
+            for (key, new_value) in new_map {
+                if !old_map.contains_key(key) {
+                    let len = push_segment(path, key);
+                    out.push(DiffEntry::Added {
+                        path: path.clone(),
+                        value: new_value.clone(),
+                    });
+                    path.truncate(len);
+                }
+            }
+        }
+        (Value::Array(old_items), Value::Array(new_items))
+            if old_items.len().max(new_items.len()) <= ARRAY_DIFF_LIMIT =>
+        {
+            for (i, old_value) in old_items.iter().enumerate() {
+                let len = push_segment(path, &i.to_string());
+                match new_items.get(i) {
+                    Some(new_value) => diff_values(path, old_value, new_value, out),
+                    None => out.push(DiffEntry::Removed {
+                        path: path.clone(),
+                        value: old_value.clone(),
+                    }),
+                }
+                path.truncate(len);
+            }
+            for (i, new_value) in new_items.iter().enumerate().skip(old_items.len()) {
+                let len = push_segment(path, &i.to_string());
+                out.push(DiffEntry::Added {
+                    path: path.clone(),
+                    value: new_value.clone(),
+                });
+                path.truncate(len);
+            }
+        }
+        (Value::Array(old_items), Value::Array(new_items)) => {
+            if old_items != new_items {
+                let first_mismatch = old_items
+                    .iter()
+                    .zip(new_items)
+                    .position(|(old, new)| old != new)
+                    .unwrap_or_else(|| old_items.len().min(new_items.len()));
+                out.push(DiffEntry::ArrayChanged {
+                    path: path.clone(),
+                    old_len: old_items.len(),
+                    new_len: new_items.len(),
+                    first_mismatch,
+                });
+            }
+        }
+        _ => {
+            if old != new {
+                out.push(DiffEntry::Changed {
+                    path: path.clone(),
+                    old: old.clone(),
+                    new: new.clone(),
+                });
+            }
+        }
+    }
+}
+
+/// Append JSON pointer segment to `path`, returning previous length.
+fn push_segment(path: &mut String, segment: &str) -> usize {
+    let len = path.len();
+    path.push('/');
+    path.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+    len
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            match entry {
+                DiffEntry::Added { path, value } => writeln!(f, "+ {}: {}", path, value)?,
+                DiffEntry::Removed { path, value } => writeln!(f, "- {}: {}", path, value)?,
+                DiffEntry::Changed { path, old, new } => {
+                    writeln!(f, "~ {}: {} -> {}", path, old, new)?
+                }
+                DiffEntry::ArrayChanged {
+                    path,
+                    old_len,
+                    new_len,
+                    first_mismatch,
+                } => writeln!(
+                    f,
+                    "~ {}: {} -> {} items, first difference at {}",
+                    path, old_len, new_len, first_mismatch
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn to_value<State: Serialize>(state: &State) -> Value {
+    serde_json::to_value(state).unwrap_or(Value::Null)
+}
+
+/// Keeps the last serialized state, diffing it with each new one.
+#[derive(Clone)]
+pub(crate) struct StateDiffer<State> {
+    serialize: fn(&State) -> Value,
+    prev: Value,
+    last: StateDiff,
+}
+
+impl<State> StateDiffer<State> {
+    pub fn new(state: &State) -> Self
+    where
+        State: Serialize,
+    {
+        Self {
+            serialize: to_value::<State>,
+            prev: to_value(state),
+            last: StateDiff::default(),
+        }
+    }
+
+    pub fn update(&mut self, state: &State) {
+        let new = (self.serialize)(state);
+        self.last = StateDiff::between(&self.prev, &new);
+        self.prev = new;
+    }
+
+    #[inline(always)]
+    pub fn last(&self) -> &StateDiff {
+        &self.last
+    }
+
+    /// Current state, serialized.
+    #[inline(always)]
+    pub fn current(&self) -> &Value {
+        &self.prev
+    }
+}
@@ -0,0 +1,115 @@
+use core::fmt;
+
+use crate::{ActionId, ActionWithMeta};
+
+/// Recorded action, with the fingerprint of the state it produced, for
+/// `Store::replay_verified`.
+///
+/// Fingerprint is whatever `fn(&State) -> u64` the recording and the
+/// replay agree on, e.g. a hash of the state.
+#[derive(Debug, Clone)]
+pub struct RecordedStep<Action> {
+    pub action: ActionWithMeta<Action>,
+    pub fingerprint: u64,
+    /// State produced by the action, serialized, so that divergence at
+    /// this step can be diffed.
+    #[cfg(feature = "diff")]
+    pub checkpoint: Option<serde_json::Value>,
+}
+
+impl<Action> RecordedStep<Action> {
+    pub fn new(action: ActionWithMeta<Action>, fingerprint: u64) -> Self {
+        Self {
+            action,
+            fingerprint,
+            #[cfg(feature = "diff")]
+            checkpoint: None,
+        }
+    }
+
+    /// Keep the serialized state produced by the action, see
+    /// [DivergenceReport::diff].
+    #[cfg(feature = "diff")]
+    pub fn with_checkpoint(mut self, checkpoint: serde_json::Value) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+}
+
+/// Where replay first diverged from the recording, see
+/// `Store::replay_verified`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DivergenceReport {
+    /// First action after which the fingerprint differed.
+    pub action_id: ActionId,
+    /// Index of that action in the recording.
+    pub index: usize,
+    pub expected: u64,
+    pub actual: u64,
+    /// Actions replayed after the first divergent one.
+    pub replayed_after: usize,
+    /// How many of those produced a differing fingerprint too.
+    pub diverged_after: usize,
+    /// Difference between the recorded and the replayed state at the
+    /// first divergent action, if the step has a checkpoint and diff
+    /// is enabled, see `Store::enable_diff`.
+    #[cfg(feature = "diff")]
+    pub diff: Option<crate::StateDiff>,
+    /// Whether fingerprint of the last replayed action matched.
+    healed: bool,
+}
+
+impl DivergenceReport {
+    pub(crate) fn new(action_id: ActionId, index: usize, expected: u64, actual: u64) -> Self {
+        Self {
+            action_id,
+            index,
+            expected,
+            actual,
+            replayed_after: 0,
+            diverged_after: 0,
+            #[cfg(feature = "diff")]
+            diff: None,
+            healed: false,
+        }
+    }
+
+    pub(crate) fn replayed(&mut self, matched: bool) {
+        self.replayed_after += 1;
+        if !matched {
+            self.diverged_after += 1;
+        }
+        self.healed = matched;
+    }
+
+    /// Whether the replay ended up matching the recording again, i.e.
+    /// the fingerprint of the last action matched.
+    #[inline(always)]
+    pub fn self_healed(&self) -> bool {
+        self.healed
+    }
+}
+
+impl fmt::Display for DivergenceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "replay diverged at action {} (#{}): expected fingerprint {:016x}, got {:016x}; \
+             {} of {} actions after it diverged too",
+            u64::from(self.action_id),
+            self.index,
+            self.expected,
+            self.actual,
+            self.diverged_after,
+            self.replayed_after
+        )?;
+        if self.healed {
+            write!(f, ", self-healed")?;
+        }
+        #[cfg(feature = "diff")]
+        if let Some(diff) = &self.diff {
+            write!(f, "\n{}", diff)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+use crate::ActionId;
+
+/// What dispatching an action would do, see `Store::dry_run`.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct DryRunResult<T> {
+    /// Id the action was given, the one it would get if dispatched
+    /// right away, with no time passing.
+    pub id: ActionId,
+    /// Whether the action is enabled in the current state.
+    pub enabled: bool,
+    /// State the action would produce (or its projection), `None` if it
+    /// isn't enabled.
+    pub state: Option<T>,
+    /// Safety condition the produced state would violate, formatted with
+    /// `Debug`. Only checked if enabled with
+    /// `Store::enable_safety_check`.
+    pub safety_violation: Option<String>,
+}
+
+impl<T> DryRunResult<T> {
+    /// Whether the action would be applied and keep the state safe.
+    #[inline(always)]
+    pub fn is_ok(&self) -> bool {
+        self.enabled && self.safety_violation.is_none()
+    }
+}
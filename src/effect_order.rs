@@ -0,0 +1,33 @@
+use crate::Effects;
+
+/// When effects added with `Store::add_effects` run, relative to the
+/// effects of the store and of its modules.
+///
+/// For every action, effects run by class, in the order of the
+/// variants, and within a class in the order they were added.
+#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone, Copy)]
+pub enum EffectOrder {
+    /// Before the effects of the store, e.g. for gating.
+    Pre,
+    /// Along with the effects of the modules, which run after the
+    /// effects of the store, after the ones of the modules.
+    Module,
+    /// After all the others, so follow-up actions dispatched by them
+    /// have been processed, e.g. for logging.
+    Post,
+}
+
+/// Effects added with `Store::add_effects`.
+pub(crate) struct OrderedEffects<State, Service, Action> {
+    pub name: &'static str,
+    pub order: EffectOrder,
+    pub effects: Effects<State, Service, Action>,
+}
+
+impl<State, Service, Action> Clone for OrderedEffects<State, Service, Action> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<State, Service, Action> Copy for OrderedEffects<State, Service, Action> {}
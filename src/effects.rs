@@ -1,4 +1,59 @@
 use crate::{ActionWithMeta, Store};
+#[cfg(feature = "strict")]
+use crate::{EffectsCtx, StrictStore};
 
 pub type Effects<State, Service, Action> =
     fn(&mut Store<State, Service, Action>, &ActionWithMeta<Action>);
+
+/// Effects which get an [EffectsCtx] and the store without its
+/// `service` field, see [crate::Store::new_strict] and [StrictStore].
+#[cfg(feature = "strict")]
+pub type StrictEffects<State, Service, Action> =
+    fn(&EffectsCtx, &mut StrictStore<'_, State, Service, Action>, &ActionWithMeta<Action>);
+
+/// Effects as kept by the store, of either signature.
+pub(crate) enum AnyEffects<State, Service, Action> {
+    Plain(Effects<State, Service, Action>),
+    #[cfg(feature = "strict")]
+    Strict(StrictEffects<State, Service, Action>),
+}
+
+impl<State, Service, Action> Clone for AnyEffects<State, Service, Action> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<State, Service, Action> Copy for AnyEffects<State, Service, Action> {}
+
+impl<State, Service, Action> From<Effects<State, Service, Action>>
+    for AnyEffects<State, Service, Action>
+{
+    #[inline(always)]
+    fn from(effects: Effects<State, Service, Action>) -> Self {
+        Self::Plain(effects)
+    }
+}
+
+/// Effects which do nothing.
+pub(crate) fn no_effects<State, Service, Action>(
+    _: &mut Store<State, Service, Action>,
+    _: &ActionWithMeta<Action>,
+) {
+}
+
+/// Call `effects`, with an [EffectsCtx] if they take one.
+#[inline(always)]
+pub(crate) fn call<State, Service, Action>(
+    effects: AnyEffects<State, Service, Action>,
+    store: &mut Store<State, Service, Action>,
+    action: &ActionWithMeta<Action>,
+) {
+    match effects {
+        AnyEffects::Plain(effects) => effects(store, action),
+        #[cfg(feature = "strict")]
+        AnyEffects::Strict(effects) => {
+            effects(&EffectsCtx::new(), &mut StrictStore::new(store), action)
+        }
+    }
+}
@@ -0,0 +1,130 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{
+    ActionId, ActionWithMeta, Effects, EnablingCondition, Interception, Reducer, Store, TimeService,
+};
+
+/// Test double for unit-testing effects.
+///
+/// Wraps a store with a user provided (mock) service and the effects
+/// under test. Action passed to [EffectsProbe::feed] goes through the
+/// reducer and effects as usual, but every action dispatched from the
+/// effects is only recorded, without running its reducer or effects.
+///
+/// # Example
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EffectsProbe, EnablingCondition, Store, TimeService};
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// enum Action {
+///     Request,
+///     Failed,
+///     Retry { attempt: u8 },
+/// }
+///
+/// impl EnablingCondition<State> for Action {}
+///
+/// #[derive(Default)]
+/// struct State {
+///     attempts: u8,
+/// }
+///
+/// struct MockService;
+/// impl TimeService for MockService {}
+///
+/// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+///     if let Action::Failed = &action.action {
+///         state.attempts += 1;
+///     }
+/// }
+///
+/// fn effects(store: &mut Store<State, MockService, Action>, action: &ActionWithMeta<Action>) {
+///     if let Action::Failed = &action.action {
+///         let attempt = store.state().attempts;
+///         if attempt < 3 {
+///             store.dispatch(Action::Retry { attempt });
+///         }
+///     }
+/// }
+///
+/// let mut probe = EffectsProbe::new(reducer, effects, MockService, State::default());
+///
+/// probe.feed(Action::Failed);
+/// assert_eq!(probe.follow_ups(), vec![&Action::Retry { attempt: 1 }]);
+///
+/// probe.feed(Action::Failed);
+/// probe.feed(Action::Failed);
+/// assert!(probe.follow_ups().is_empty());
+/// ```
+pub struct EffectsProbe<State, Service, Action> {
+    store: Store<State, Service, Action>,
+}
+
+impl<State, Service, Action> EffectsProbe<State, Service, Action>
+where
+    Service: TimeService,
+{
+    pub fn new(
+        reducer: Reducer<State, Action>,
+        effects: Effects<State, Service, Action>,
+        service: Service,
+        initial_state: State,
+    ) -> Self {
+        let mut store =
+            Store::new_with_initial_id(reducer, effects, service, ActionId::ZERO, initial_state);
+        store.set_dispatch_interceptor(Some(Self::capture_follow_ups));
+
+        Self { store }
+    }
+
+    fn capture_follow_ups(_: &State, action: &ActionWithMeta<Action>) -> Interception {
+        if action.depth == 0 {
+            Interception::Proceed
+        } else {
+            Interception::Capture
+        }
+    }
+
+    /// Dispatch an action through the reducer and the effects under test.
+    ///
+    /// Follow-ups recorded by the previous call are discarded.
+    pub fn feed<T>(&mut self, action: T) -> bool
+    where
+        T: Into<Action> + EnablingCondition<State>,
+    {
+        self.store.take_intercepted();
+        self.store.dispatch(action)
+    }
+
+    /// Actions dispatched by the effects during last [EffectsProbe::feed].
+    pub fn follow_ups(&self) -> Vec<&Action> {
+        self.store
+            .intercepted()
+            .iter()
+            .map(|action| &action.action)
+            .collect()
+    }
+
+    /// Follow-ups with their metadata.
+    #[inline(always)]
+    pub fn follow_ups_with_meta(&self) -> &[ActionWithMeta<Action>] {
+        self.store.intercepted()
+    }
+
+    #[inline(always)]
+    pub fn state(&self) -> &State {
+        self.store.state()
+    }
+
+    #[inline(always)]
+    pub fn service(&mut self) -> &mut Service {
+        &mut self.store.service
+    }
+
+    #[inline(always)]
+    pub fn store(&mut self) -> &mut Store<State, Service, Action> {
+        &mut self.store
+    }
+}
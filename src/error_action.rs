@@ -0,0 +1,166 @@
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+use core::fmt;
+use core::time::Duration;
+
+use crate::store::{DispatchFn, DispatchMapper};
+use crate::{ActionId, DedupKey, SubscriptionId};
+
+/// Failure noticed by the store, which can be turned into an action,
+/// see `Store::set_error_action`.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum StoreError {
+    /// Reducer panicked and the panic was caught, see
+    /// `Store::set_reducer_panic_policy`.
+    ReducerPanic {
+        action_id: ActionId,
+        message: Option<String>,
+    },
+    /// Effects panicked and the panic was caught, see
+    /// `Store::set_catch_effects_panics`.
+    EffectsPanic {
+        action_id: ActionId,
+        message: Option<String>,
+    },
+    /// State after action `action_id` failed the safety condition, see
+    /// `Store::enable_safety_check`. Error is formatted with `Debug`.
+    SafetyViolation { action_id: ActionId, error: String },
+    /// Action was dropped, because the queue of actions dispatched
+    /// while paused was full.
+    QueueOverflow,
+    /// Action was dropped instead of processed, because it stayed
+    /// queued past its deadline, see `Store::dispatch_with_ttl`.
+    Expired {
+        /// How much later than the deadline it would've been processed.
+        late_by: Duration,
+    },
+    /// Action was rejected as a duplicate of one with the same key,
+    /// see `Store::set_dedup`.
+    Duplicate { key: DedupKey },
+    /// Listener panicked at action `action_id` and got unsubscribed,
+    /// see `Store::subscribe`.
+    ListenerPanic {
+        subscription: SubscriptionId,
+        action_id: ActionId,
+        message: Option<String>,
+    },
+    /// Listener returned an error, formatted with `Debug`, see
+    /// `Store::subscribe_fallible`. `unsubscribed` if it was one more
+    /// than the listener may return.
+    ListenerError {
+        subscription: SubscriptionId,
+        action_id: ActionId,
+        error: String,
+        unsubscribed: bool,
+    },
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ReducerPanic { action_id, message } => {
+                write!(f, "reducer panicked at action {}", u64::from(*action_id))?;
+                crate::poison::write_message(f, message)
+            }
+            Self::EffectsPanic { action_id, message } => {
+                write!(f, "effects panicked at action {}", u64::from(*action_id))?;
+                crate::poison::write_message(f, message)
+            }
+            Self::SafetyViolation { action_id, error } => write!(
+                f,
+                "safety condition violated after action {}: {}",
+                u64::from(*action_id),
+                error
+            ),
+            Self::QueueOverflow => write!(f, "paused queue is full, action dropped"),
+            Self::Expired { late_by } => {
+                write!(f, "queued action expired {:?} ago, action dropped", late_by)
+            }
+            Self::Duplicate { key } => {
+                write!(
+                    f,
+                    "duplicate of a recent action with key {}, action dropped",
+                    key
+                )
+            }
+            Self::ListenerPanic {
+                subscription,
+                action_id,
+                message,
+            } => {
+                write!(
+                    f,
+                    "listener {} panicked at action {}",
+                    subscription,
+                    u64::from(*action_id)
+                )?;
+                crate::poison::write_message(f, message)
+            }
+            Self::ListenerError {
+                subscription,
+                action_id,
+                error,
+                unsubscribed,
+            } => {
+                write!(
+                    f,
+                    "listener {} failed at action {}: {}",
+                    subscription,
+                    u64::from(*action_id),
+                    error
+                )?;
+                if *unsubscribed {
+                    write!(f, ", unsubscribed")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Function signature for turning a [StoreError] into an action which
+/// the store dispatches. `None` means no action.
+pub type ErrorActionMapper<Action> = fn(StoreError) -> Option<Action>;
+
+/// Error mapper of a store, see `Store::set_error_action`.
+pub(crate) struct ErrorActions<Store, Action> {
+    pub mapper: Option<DispatchMapper<ErrorActionMapper<Action>, Store, Action>>,
+    /// Whether an error action is being dispatched, so that errors
+    /// while processing it don't produce more error actions.
+    dispatching: bool,
+}
+
+impl<Store, Action> ErrorActions<Store, Action> {
+    pub fn new() -> Self {
+        Self {
+            mapper: None,
+            dispatching: false,
+        }
+    }
+
+    /// Action the error maps to, with the function dispatching it,
+    /// unless already dispatching one. If there is one, [Self::finish]
+    /// must be called once it's dispatched.
+    pub fn start(&mut self, error: StoreError) -> Option<(DispatchFn<Store, Action>, Action)> {
+        let (mapper, dispatch) = match self.mapper {
+            Some(mapper) if !self.dispatching => mapper,
+            _ => return None,
+        };
+        let action = mapper(error)?;
+        self.dispatching = true;
+        Some((dispatch, action))
+    }
+
+    pub fn finish(&mut self) {
+        self.dispatching = false;
+    }
+}
+
+impl<Store, Action> Clone for ErrorActions<Store, Action> {
+    fn clone(&self) -> Self {
+        Self {
+            mapper: self.mapper,
+            dispatching: self.dispatching,
+        }
+    }
+}
@@ -0,0 +1,314 @@
+//! C ABI for driving a store from non-Rust hosts.
+//!
+//! Store itself is generic, so it's created on the Rust side, by the
+//! crate which knows the state and action types, and handed over to
+//! the host as an opaque `redux_store_t` pointer with
+//! [redux_store_t::into_raw]. Actions come from the host as bytes,
+//! decoded by the decoder registered along with the store.
+//!
+//! All functions return one of the `REDUX_*` codes. Panics never cross
+//! the boundary: they are caught, reported as [REDUX_PANIC], and the
+//! store gets poisoned, rejecting every action from then on.
+//!
+//! Functions are exported from any `cdylib` or `staticlib` depending on
+//! the crate with the feature enabled, usually the one creating the
+//! store. The header is generated with
+//! `cbindgen --config cbindgen.toml --crate redux-rs --output redux.h`.
+
+use std::os::raw::{c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use serde::Serialize;
+
+use crate::{DispatchOutcome, EnablingCondition, PoisonReason, Provenance, Store, TimeService};
+
+/// Action was processed (or queued, if the store is paused).
+pub const REDUX_OK: c_int = 0;
+/// Action was rejected by the store.
+pub const REDUX_REJECTED: c_int = 1;
+/// Decoder couldn't decode the action.
+pub const REDUX_DECODE_ERROR: c_int = -1;
+/// Null pointer was passed.
+pub const REDUX_NULL: c_int = -2;
+/// Rust code panicked. Store is poisoned.
+pub const REDUX_PANIC: c_int = -3;
+/// Output buffer is too small, required length was written to `out_len`.
+pub const REDUX_BUFFER_TOO_SMALL: c_int = -4;
+/// State couldn't be serialized.
+pub const REDUX_SERIALIZE_ERROR: c_int = -5;
+
+/// Called after every action dispatched through
+/// [redux_store_dispatch] was processed, with the `user_data` it was
+/// registered with and the id of the action. Not called for actions
+/// which weren't processed (yet), e.g. rejected ones.
+#[allow(non_camel_case_types)]
+pub type redux_subscriber_t = extern "C" fn(user_data: *mut c_void, action_id: u64);
+
+/// Store with its types erased.
+trait ErasedStore {
+    /// `None` if the action couldn't be decoded.
+    fn dispatch(&mut self, action: &[u8]) -> Option<DispatchOutcome>;
+
+    fn state_json(&self, buf: &mut Vec<u8>) -> serde_json::Result<()>;
+
+    fn poison(&mut self, reason: PoisonReason);
+}
+
+struct Typed<State, Service, Action> {
+    store: Store<State, Service, Action>,
+    decode: fn(&[u8]) -> Option<Action>,
+}
+
+impl<State, Service, Action> ErasedStore for Typed<State, Service, Action>
+where
+    State: Serialize,
+    Service: TimeService,
+    Action: EnablingCondition<State>,
+{
+    fn dispatch(&mut self, action: &[u8]) -> Option<DispatchOutcome> {
+        let action = (self.decode)(action)?;
+        Some(self.store.dispatch_from(action, Provenance::Bridge))
+    }
+
+    fn state_json(&self, buf: &mut Vec<u8>) -> serde_json::Result<()> {
+        serde_json::to_writer(buf, self.store.state())
+    }
+
+    fn poison(&mut self, reason: PoisonReason) {
+        self.store.poison(reason);
+    }
+}
+
+/// Opaque store handle for C hosts.
+///
+/// ```
+/// use redux_rs::{
+///     redux_store_dispatch, redux_store_free, redux_store_state_json, redux_store_subscribe,
+///     redux_store_t, ActionWithMeta, EnablingCondition, Store, REDUX_BUFFER_TOO_SMALL,
+///     REDUX_DECODE_ERROR, REDUX_OK, REDUX_PANIC, REDUX_REJECTED,
+/// };
+/// use serde::Deserialize;
+/// use std::os::raw::c_void;
+///
+/// #[derive(Deserialize)]
+/// enum Action {
+///     Add(u32),
+///     Crash,
+/// }
+///
+/// impl EnablingCondition<u32> for Action {
+///     fn is_enabled(&self, state: &u32) -> bool {
+///         !matches!(self, Action::Add(n) if state.checked_add(*n).is_none())
+///     }
+/// }
+///
+/// fn reducer(state: &mut u32, action: &ActionWithMeta<Action>) {
+///     match action.action {
+///         Action::Add(n) => *state += n,
+///         Action::Crash => panic!("crash"),
+///     }
+/// }
+///
+/// fn decode(bytes: &[u8]) -> Option<Action> {
+///     serde_json::from_slice(bytes).ok()
+/// }
+///
+/// extern "C" fn count(user_data: *mut c_void, _action_id: u64) {
+///     unsafe { *(user_data as *mut u32) += 1 };
+/// }
+///
+/// let store = redux_store_t::into_raw(Store::simple(reducer, |_, _| {}, 0), decode);
+/// let mut notified = 0u32;
+/// let mut out = [0u8; 16];
+/// let mut out_len = 0;
+/// unsafe {
+///     let user_data = &mut notified as *mut u32 as *mut c_void;
+///     assert_eq!(redux_store_subscribe(store, count, user_data), REDUX_OK);
+///
+///     let add = br#"{"Add":40}"#;
+///     assert_eq!(redux_store_dispatch(store, add.as_ptr(), add.len()), REDUX_OK);
+///     let overflow = br#"{"Add":4294967295}"#;
+///     assert_eq!(redux_store_dispatch(store, overflow.as_ptr(), overflow.len()), REDUX_REJECTED);
+///     let garbage = b"{";
+///     assert_eq!(redux_store_dispatch(store, garbage.as_ptr(), garbage.len()), REDUX_DECODE_ERROR);
+///
+///     assert_eq!(redux_store_state_json(store, out.as_mut_ptr(), out.len(), &mut out_len), REDUX_OK);
+///     assert_eq!(&out[..out_len], b"40");
+///     assert_eq!(redux_store_state_json(store, out.as_mut_ptr(), 1, &mut out_len), REDUX_BUFFER_TOO_SMALL);
+///     assert_eq!(out_len, 2);
+///
+///     // Panic doesn't unwind into the host, store is poisoned instead.
+///     let crash = br#""Crash""#;
+///     assert_eq!(redux_store_dispatch(store, crash.as_ptr(), crash.len()), REDUX_PANIC);
+///     assert_eq!(redux_store_dispatch(store, add.as_ptr(), add.len()), REDUX_REJECTED);
+///
+///     redux_store_free(store);
+/// }
+/// // Only the processed action got to the subscriber.
+/// assert_eq!(notified, 1);
+/// ```
+#[allow(non_camel_case_types)]
+pub struct redux_store_t {
+    store: Box<dyn ErasedStore>,
+    subscribers: Vec<(redux_subscriber_t, *mut c_void)>,
+    json: Vec<u8>,
+}
+
+impl redux_store_t {
+    /// Wrap the store, with `decode` turning bytes from the host into
+    /// actions (e.g. with bincode), into a pointer for the host, to be
+    /// released with [redux_store_free].
+    pub fn into_raw<State, Service, Action>(
+        store: Store<State, Service, Action>,
+        decode: fn(&[u8]) -> Option<Action>,
+    ) -> *mut redux_store_t
+    where
+        State: Serialize + 'static,
+        Service: TimeService + 'static,
+        Action: EnablingCondition<State> + 'static,
+    {
+        Box::into_raw(Box::new(Self {
+            store: Box::new(Typed { store, decode }),
+            subscribers: Vec::new(),
+            json: Vec::new(),
+        }))
+    }
+}
+
+/// Runs `f` with the store, converting null pointers and panics to
+/// error codes.
+fn with_store(store: *mut redux_store_t, f: impl FnOnce(&mut redux_store_t) -> c_int) -> c_int {
+    if store.is_null() {
+        return REDUX_NULL;
+    }
+    // Safety: pointer comes from `redux_store_t::into_raw` and the host
+    // guarantees it's not used concurrently. Both borrows are derived
+    // from the raw pointer, the second one only once the first is gone.
+    match panic::catch_unwind(AssertUnwindSafe(|| f(unsafe { &mut *store }))) {
+        Ok(code) => code,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&'static str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_default();
+            // Safety: `f` is done with the store.
+            let store = unsafe { &mut *store };
+            let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                store.store.poison(PoisonReason::Manual(format!(
+                    "panicked in redux_store_t call: {}",
+                    message
+                )))
+            }));
+            REDUX_PANIC
+        }
+    }
+}
+
+/// Decode and dispatch the action, then call the subscribers if it was
+/// processed.
+///
+/// # Safety
+///
+/// `store` must come from [redux_store_t::into_raw], `action` must
+/// point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn redux_store_dispatch(
+    store: *mut redux_store_t,
+    action: *const u8,
+    len: usize,
+) -> c_int {
+    if action.is_null() && len > 0 {
+        return REDUX_NULL;
+    }
+    let action = if len == 0 {
+        &[][..]
+    } else {
+        std::slice::from_raw_parts(action, len)
+    };
+    with_store(store, |store| {
+        let action_id = match store.store.dispatch(action) {
+            None => return REDUX_DECODE_ERROR,
+            Some(DispatchOutcome::Processed(id)) => u64::from(id),
+            Some(DispatchOutcome::Rejected) => return REDUX_REJECTED,
+            // Queued or captured, not processed yet.
+            Some(_) => return REDUX_OK,
+        };
+        for (subscriber, user_data) in &store.subscribers {
+            subscriber(*user_data, action_id);
+        }
+        REDUX_OK
+    })
+}
+
+/// Write the state, serialized as JSON (without a trailing 0), into
+/// `out`, which has room for `capacity` bytes, and its length into
+/// `out_len`. If it doesn't fit, only `out_len` is written and
+/// [REDUX_BUFFER_TOO_SMALL] returned.
+///
+/// # Safety
+///
+/// `store` must come from [redux_store_t::into_raw], `out` must point
+/// to `capacity` writable bytes, `out_len` to a writable `size_t`.
+#[no_mangle]
+pub unsafe extern "C" fn redux_store_state_json(
+    store: *mut redux_store_t,
+    out: *mut u8,
+    capacity: usize,
+    out_len: *mut usize,
+) -> c_int {
+    if out_len.is_null() || (out.is_null() && capacity > 0) {
+        return REDUX_NULL;
+    }
+    with_store(store, |store| {
+        let mut json = std::mem::take(&mut store.json);
+        json.clear();
+        let code = match store.store.state_json(&mut json) {
+            Err(_) => REDUX_SERIALIZE_ERROR,
+            Ok(()) => {
+                *out_len = json.len();
+                if json.len() > capacity {
+                    REDUX_BUFFER_TOO_SMALL
+                } else {
+                    ptr::copy_nonoverlapping(json.as_ptr(), out, json.len());
+                    REDUX_OK
+                }
+            }
+        };
+        store.json = json;
+        code
+    })
+}
+
+/// Register `subscriber` to be called with `user_data` after every
+/// action dispatched with [redux_store_dispatch].
+///
+/// # Safety
+///
+/// `store` must come from [redux_store_t::into_raw]. `user_data` must
+/// stay valid as long as the store does.
+#[no_mangle]
+pub unsafe extern "C" fn redux_store_subscribe(
+    store: *mut redux_store_t,
+    subscriber: redux_subscriber_t,
+    user_data: *mut c_void,
+) -> c_int {
+    with_store(store, |store| {
+        store.subscribers.push((subscriber, user_data));
+        REDUX_OK
+    })
+}
+
+/// Release the store. Null is ignored.
+///
+/// # Safety
+///
+/// `store` must come from [redux_store_t::into_raw] and not be used
+/// afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn redux_store_free(store: *mut redux_store_t) {
+    if !store.is_null() {
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(store))));
+    }
+}
@@ -0,0 +1,283 @@
+#[cfg(not(feature = "std"))]
+use alloc::{collections::VecDeque, rc::Rc, vec::Vec};
+use core::cell::RefCell;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::{collections::VecDeque, rc::Rc};
+
+use crate::{EnablingCondition, Instant, Provenance, Store, TimeService};
+
+/// Default capacity of the inbox of a [FrameDriver].
+pub const UI_INBOX_CAPACITY: usize = 256;
+
+struct Inbox<Action> {
+    actions: VecDeque<Action>,
+    capacity: usize,
+    /// Timers requested since the last frame, with their delay.
+    new_timers: Vec<(Duration, Action)>,
+    dropped: u64,
+}
+
+/// Handle for queueing actions from UI callbacks, to be dispatched at
+/// the beginning of the next frame by [FrameDriver::begin_frame].
+///
+/// Cheap to clone, can be kept in widgets or in the service, so that
+/// effects can schedule timers. Not thread safe, UI runs on one thread.
+pub struct UiDispatcher<Action> {
+    inbox: Rc<RefCell<Inbox<Action>>>,
+}
+
+impl<Action> UiDispatcher<Action> {
+    /// Queue the action. Returns `false` and drops the action if the
+    /// inbox is full.
+    pub fn dispatch(&self, action: Action) -> bool {
+        let mut inbox = self.inbox.borrow_mut();
+        if inbox.actions.len() >= inbox.capacity {
+            inbox.dropped += 1;
+            return false;
+        }
+        inbox.actions.push_back(action);
+        true
+    }
+
+    /// Dispatch the action at the beginning of the first frame at least
+    /// `delay` after the beginning of the current frame, or of the next
+    /// one if called between frames.
+    pub fn dispatch_after(&self, delay: Duration, action: Action) {
+        self.inbox.borrow_mut().new_timers.push((delay, action));
+    }
+
+    /// Number of actions waiting for the next frame.
+    pub fn queued(&self) -> usize {
+        self.inbox.borrow().actions.len()
+    }
+}
+
+impl<Action> Clone for UiDispatcher<Action> {
+    fn clone(&self) -> Self {
+        Self {
+            inbox: self.inbox.clone(),
+        }
+    }
+}
+
+/// What happened during a frame, returned by [FrameDriver::end_frame].
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub struct FrameSummary {
+    /// Actions taken from the inbox, enabled or not.
+    pub from_inbox: u32,
+    /// Timers which fired.
+    pub timers_fired: u32,
+    /// Actions dropped because the inbox was full, since the previous frame.
+    pub dropped: u64,
+    /// Actions processed by the store during the frame, including the
+    /// ones dispatched by effects and from UI callbacks directly.
+    pub processed: u64,
+}
+
+/// Drives the store from a GUI frame loop (e.g. egui's `update`).
+///
+/// UI callbacks don't dispatch directly, they queue actions through a
+/// [UiDispatcher]. Every frame starts with [FrameDriver::begin_frame],
+/// which dispatches the queued actions in order, then due timers, and
+/// gives out the state to draw. The frame ends with
+/// [FrameDriver::end_frame]. Everything happens on the UI thread.
+///
+/// Timers fire at the beginning of the first frame at or after their
+/// deadline, in deadline order, ties in the order they were set. Time is
+/// measured with the store's [TimeService].
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EnablingCondition, FrameDriver, FrozenTime, Instant, Store};
+/// use std::time::{Duration, SystemTime};
+///
+/// #[derive(Debug)]
+/// enum Action {
+///     Click(u32),
+///     HideToast,
+/// }
+/// impl EnablingCondition<State> for Action {}
+///
+/// #[derive(Default)]
+/// struct State {
+///     clicks: Vec<u32>,
+///     toast: bool,
+/// }
+///
+/// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+///     match &action.action {
+///         Action::Click(button) => {
+///             state.clicks.push(*button);
+///             state.toast = true;
+///         }
+///         Action::HideToast => state.toast = false,
+///     }
+/// }
+///
+/// let service = FrozenTime::new(Instant::now());
+/// let store = Store::new(reducer, |_, _| {}, service, SystemTime::now(), State::default());
+/// let mut driver = FrameDriver::new(store, 2);
+/// let ui = driver.dispatcher();
+///
+/// // Callbacks of the previous frame; inbox holds only 2 actions.
+/// assert!(ui.dispatch(Action::Click(1)));
+/// assert!(ui.dispatch(Action::Click(2)));
+/// assert!(!ui.dispatch(Action::Click(3)));
+/// ui.dispatch_after(Duration::from_millis(100), Action::HideToast);
+///
+/// let state = driver.begin_frame();
+/// assert_eq!(state.clicks, [1, 2]);
+/// assert!(state.toast);
+/// let summary = driver.end_frame();
+/// assert_eq!((summary.from_inbox, summary.dropped, summary.processed), (2, 1, 2));
+///
+/// // Timer isn't due yet.
+/// driver.store_mut().service.advance(Duration::from_millis(99));
+/// assert!(driver.begin_frame().toast);
+/// assert_eq!(driver.end_frame().timers_fired, 0);
+///
+/// driver.store_mut().service.advance(Duration::from_millis(1));
+/// assert!(!driver.begin_frame().toast);
+/// assert_eq!(driver.end_frame().timers_fired, 1);
+/// ```
+pub struct FrameDriver<State, Service, Action> {
+    store: Store<State, Service, Action>,
+    inbox: Rc<RefCell<Inbox<Action>>>,
+    /// Sorted by deadline, ties in insertion order.
+    timers: Vec<(Instant, Action)>,
+    summary: FrameSummary,
+    processed_before: u64,
+}
+
+impl<State, Service, Action> FrameDriver<State, Service, Action>
+where
+    Service: TimeService,
+    Action: EnablingCondition<State>,
+{
+    /// Inbox holds up to `inbox_capacity` actions between frames, see
+    /// [UI_INBOX_CAPACITY].
+    pub fn new(store: Store<State, Service, Action>, inbox_capacity: usize) -> Self {
+        let inbox = Inbox {
+            actions: VecDeque::with_capacity(inbox_capacity),
+            capacity: inbox_capacity,
+            new_timers: Vec::new(),
+            dropped: 0,
+        };
+        Self {
+            processed_before: store.actions_processed(),
+            store,
+            inbox: Rc::new(RefCell::new(inbox)),
+            timers: Vec::new(),
+            summary: FrameSummary::default(),
+        }
+    }
+
+    pub fn dispatcher(&self) -> UiDispatcher<Action> {
+        UiDispatcher {
+            inbox: self.inbox.clone(),
+        }
+    }
+
+    /// Dispatch actions queued since the last frame, in order, then
+    /// due timers. Returns the state to draw the frame with.
+    ///
+    /// Actions queued by effects while the frame begins wait for the
+    /// next frame:
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, FrameDriver, Store, UiDispatcher};
+    ///
+    /// struct Echo(u32);
+    /// impl EnablingCondition<Vec<u32>> for Echo {}
+    ///
+    /// struct Service(Option<UiDispatcher<Echo>>);
+    /// impl redux_rs::TimeService for Service {}
+    ///
+    /// fn reducer(state: &mut Vec<u32>, action: &ActionWithMeta<Echo>) {
+    ///     state.push(action.action.0);
+    /// }
+    ///
+    /// fn effects(store: &mut Store<Vec<u32>, Service, Echo>, action: &ActionWithMeta<Echo>) {
+    ///     if action.action.0 < 100 {
+    ///         store.service.0.as_ref().unwrap().dispatch(Echo(action.action.0 + 100));
+    ///     }
+    /// }
+    ///
+    /// let store = Store::new(reducer, effects, Service(None), std::time::SystemTime::now(), vec![]);
+    /// let mut driver = FrameDriver::new(store, 8);
+    /// let ui = driver.dispatcher();
+    /// driver.store_mut().service.0 = Some(ui.clone());
+    ///
+    /// ui.dispatch(Echo(1));
+    /// ui.dispatch(Echo(2));
+    /// assert_eq!(driver.begin_frame(), &[1, 2]);
+    /// assert_eq!(ui.queued(), 2);
+    /// driver.end_frame();
+    ///
+    /// ui.dispatch(Echo(3));
+    /// assert_eq!(driver.begin_frame(), &[1, 2, 101, 102, 3]);
+    /// assert_eq!(driver.end_frame().from_inbox, 3);
+    /// ```
+    pub fn begin_frame(&mut self) -> &State {
+        self.summary = FrameSummary::default();
+        self.processed_before = self.store.actions_processed();
+        let now = self.store.service.monotonic_time();
+
+        // Not holding the borrow while dispatching, so that effects can
+        // use a `UiDispatcher` too. Actions they queue wait for the next
+        // frame.
+        let queued = self.inbox.borrow().actions.len();
+        for _ in 0..queued {
+            let action = self.inbox.borrow_mut().actions.pop_front();
+            if let Some(action) = action {
+                self.summary.from_inbox += 1;
+                self.store.dispatch_from(action, Provenance::Bridge);
+            }
+        }
+        self.schedule_new_timers(now);
+
+        while self.timers.first().map_or(false, |(at, _)| *at <= now) {
+            let (_, action) = self.timers.remove(0);
+            self.summary.timers_fired += 1;
+            self.store.dispatch_from(action, Provenance::Bridge);
+        }
+        self.schedule_new_timers(now);
+
+        self.store.state()
+    }
+
+    /// Finish the frame, returning what happened during it.
+    pub fn end_frame(&mut self) -> FrameSummary {
+        self.summary.dropped = core::mem::take(&mut self.inbox.borrow_mut().dropped);
+        self.summary.processed = self.store.actions_processed() - self.processed_before;
+        self.summary
+    }
+
+    fn schedule_new_timers(&mut self, now: Instant) {
+        let new_timers = core::mem::take(&mut self.inbox.borrow_mut().new_timers);
+        for (delay, action) in new_timers {
+            let at = now + delay;
+            let index = self.timers.partition_point(|(other, _)| *other <= at);
+            self.timers.insert(index, (at, action));
+        }
+    }
+
+    /// Number of timers which didn't fire yet.
+    pub fn pending_timers(&self) -> usize {
+        self.timers.len() + self.inbox.borrow().new_timers.len()
+    }
+
+    #[inline(always)]
+    pub fn store(&self) -> &Store<State, Service, Action> {
+        &self.store
+    }
+
+    #[inline(always)]
+    pub fn store_mut(&mut self) -> &mut Store<State, Service, Action> {
+        &mut self.store
+    }
+
+    pub fn into_store(self) -> Store<State, Service, Action> {
+        self.store
+    }
+}
@@ -0,0 +1,242 @@
+use crate::{ActionId, EnablingCondition, HasService, Store, TimeService};
+
+/// Dispatching part of the store, without the service type.
+///
+/// Lets library code, like reusable effects, be written once against
+/// `&mut dyn Dispatcher<Action>` instead of being generic over (and
+/// monomorphized for) every `Store<State, Service, Action>`. Only code
+/// using the trait objects pays for dynamic dispatch.
+pub trait Dispatcher<Action> {
+    /// See [Store::dispatch].
+    fn dispatch(&mut self, action: Action) -> bool;
+
+    /// See [Store::last_action_id].
+    fn last_action_id(&self) -> ActionId;
+}
+
+/// Read access to the state of the store, without the service type.
+pub trait StateReader<State> {
+    fn state(&self) -> &State;
+}
+
+/// Both [Dispatcher] and [StateReader], implemented for everything
+/// implementing them, so that both can be used through a single
+/// `&mut dyn StoreHandle<State, Action>`.
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EnablingCondition, Store, StoreHandle};
+///
+/// enum Action {
+///     Ping,
+///     Pong,
+/// }
+/// impl EnablingCondition<Vec<&'static str>> for Action {}
+///
+/// fn reducer(state: &mut Vec<&'static str>, action: &ActionWithMeta<Action>) {
+///     state.push(match action.action {
+///         Action::Ping => "ping",
+///         Action::Pong => "pong",
+///     });
+/// }
+///
+/// /// Knows nothing about the service.
+/// fn answer_pings(store: &mut dyn StoreHandle<Vec<&'static str>, Action>, action: &Action) {
+///     if let Action::Ping = action {
+///         if store.state().len() < 4 {
+///             store.dispatch(Action::Pong);
+///         }
+///     }
+/// }
+///
+/// fn effects(store: &mut Store<Vec<&'static str>, (), Action>, action: &ActionWithMeta<Action>) {
+///     answer_pings(store, &action.action);
+/// }
+///
+/// let mut store = Store::simple(reducer, effects, vec![]);
+/// store.dispatch(Action::Ping);
+/// store.dispatch(Action::Ping);
+/// store.dispatch(Action::Ping);
+/// assert_eq!(store.state(), &["ping", "pong", "ping", "pong", "ping"]);
+/// ```
+pub trait StoreHandle<State, Action>: Dispatcher<Action> + StateReader<State> {}
+
+impl<T, State, Action> StoreHandle<State, Action> for T where
+    T: Dispatcher<Action> + StateReader<State>
+{
+}
+
+/// Store, or a view of one like [crate::ScopedStore], for code generic
+/// over it instead of naming `Store<State, Service, Action>`.
+///
+/// Unlike [StoreHandle], state and action are associated types, so
+/// generic code names only the bounds it needs. Together with
+/// [ServiceAccess], reusable effects can live in a library which knows
+/// nothing about the application's service bundle:
+///
+/// ```
+/// use redux_rs::{
+///     ActionWithMeta, EnablingCondition, FrozenTime, HasService, Instant, ServiceAccess, Store,
+///     StoreLike,
+/// };
+/// use std::time::{Duration, SystemTime};
+///
+/// /// Library crate, knows only its own state, action and clock.
+/// mod retry {
+///     use redux_rs::{FrozenTime, ServiceAccess, StoreLike};
+///     use std::time::Duration;
+///
+///     #[derive(Default)]
+///     pub struct State {
+///         pub attempt: u32,
+///     }
+///
+///     pub enum Action {
+///         Failed,
+///         Retry { attempt: u32 },
+///     }
+///
+///     pub fn reducer(state: &mut State, action: &Action) {
+///         if let Action::Retry { attempt } = action {
+///             state.attempt = *attempt;
+///         }
+///     }
+///
+///     /// Retries up to 3 times, doubling the delay each time.
+///     pub fn effects<S>(store: &mut S, action: &Action)
+///     where
+///         S: StoreLike<State = State, Action = Action> + ServiceAccess<FrozenTime>,
+///     {
+///         if let Action::Failed = action {
+///             let attempt = store.state().attempt + 1;
+///             if attempt <= 3 {
+///                 let backoff = Duration::from_millis(100 << attempt);
+///                 store.service().advance(backoff);
+///                 store.dispatch(Action::Retry { attempt });
+///             }
+///         }
+///     }
+/// }
+///
+/// struct Services {
+///     time: FrozenTime,
+/// }
+///
+/// impl HasService<FrozenTime> for Services {
+///     fn service(&mut self) -> &mut FrozenTime {
+///         &mut self.time
+///     }
+/// }
+///
+/// redux_rs::forward_service!(TimeService for Services => FrozenTime);
+///
+/// #[derive(Default)]
+/// struct State {
+///     retry: retry::State,
+/// }
+///
+/// enum Action {
+///     Retry(retry::Action),
+/// }
+/// impl EnablingCondition<State> for Action {}
+///
+/// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+///     match &action.action {
+///         Action::Retry(action) => retry::reducer(&mut state.retry, action),
+///     }
+/// }
+///
+/// fn effects(store: &mut Store<State, Services, Action>, action: &ActionWithMeta<Action>) {
+///     match &action.action {
+///         Action::Retry(action) => {
+///             retry::effects(&mut store.scoped(|state| &state.retry, Action::Retry), action)
+///         }
+///     }
+/// }
+///
+/// let start = Instant::now();
+/// let services = Services { time: FrozenTime::new(start) };
+/// let mut store = Store::new(reducer, effects, services, SystemTime::now(), State::default());
+///
+/// for _ in 0..5 {
+///     store.dispatch(Action::Retry(retry::Action::Failed));
+/// }
+/// assert_eq!(store.state().retry.attempt, 3);
+/// assert_eq!(store.service.time.now() - start, Duration::from_millis(200 + 400 + 800));
+/// ```
+pub trait StoreLike {
+    type State;
+    type Action;
+
+    /// See [Store::state].
+    fn state(&self) -> &Self::State;
+
+    /// See [Store::dispatch].
+    fn dispatch(&mut self, action: Self::Action) -> bool;
+
+    /// See [Store::last_action_id].
+    fn last_action_id(&self) -> ActionId;
+}
+
+/// Access to part `S` of the service, see [crate::HasService].
+pub trait ServiceAccess<S> {
+    fn service(&mut self) -> &mut S;
+}
+
+impl<State, Service, Action> StoreLike for Store<State, Service, Action>
+where
+    Service: TimeService,
+    Action: EnablingCondition<State>,
+{
+    type State = State;
+    type Action = Action;
+
+    #[inline(always)]
+    fn state(&self) -> &State {
+        Store::state(self)
+    }
+
+    #[inline(always)]
+    fn dispatch(&mut self, action: Action) -> bool {
+        Store::dispatch(self, action)
+    }
+
+    #[inline(always)]
+    fn last_action_id(&self) -> ActionId {
+        Store::last_action_id(self)
+    }
+}
+
+impl<State, Service, Action, S> ServiceAccess<S> for Store<State, Service, Action>
+where
+    Service: TimeService + HasService<S>,
+{
+    #[inline(always)]
+    fn service(&mut self) -> &mut S {
+        self.service_as()
+    }
+}
+
+impl<T> Dispatcher<T::Action> for T
+where
+    T: StoreLike + ?Sized,
+{
+    #[inline(always)]
+    fn dispatch(&mut self, action: T::Action) -> bool {
+        StoreLike::dispatch(self, action)
+    }
+
+    #[inline(always)]
+    fn last_action_id(&self) -> ActionId {
+        StoreLike::last_action_id(self)
+    }
+}
+
+impl<T> StateReader<T::State> for T
+where
+    T: StoreLike + ?Sized,
+{
+    #[inline(always)]
+    fn state(&self) -> &T::State {
+        StoreLike::state(self)
+    }
+}
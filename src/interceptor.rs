@@ -0,0 +1,75 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{ActionClassifier, ActionWithMeta};
+
+/// Decision made by [DispatchInterceptor] for a given action.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Interception {
+    /// Process action as usual: reducer and then effects.
+    Proceed,
+    /// Drop the action. `Store::dispatch` will return `false`.
+    Reject,
+    /// Don't process the action, but store it inside the store, so it
+    /// can be inspected later using `Store::intercepted`.
+    ///
+    /// `Store::dispatch` will return `true`.
+    Capture,
+}
+
+/// Function signature for a dispatch interceptor.
+///
+/// Called with the current state and an already enabled action,
+/// before reducer is called.
+pub type DispatchInterceptor<State, Action> = fn(&State, &ActionWithMeta<Action>) -> Interception;
+
+/// Dispatch interceptor set on the store.
+pub(crate) struct Interceptor<State, Action> {
+    intercept: DispatchInterceptor<State, Action>,
+    /// Classifier and sorted kinds of the actions it's called for, all
+    /// if `None`.
+    kinds: Option<(ActionClassifier<Action>, Vec<&'static str>)>,
+}
+
+impl<State, Action> Interceptor<State, Action> {
+    pub fn new(intercept: DispatchInterceptor<State, Action>) -> Self {
+        Self {
+            intercept,
+            kinds: None,
+        }
+    }
+
+    pub fn filtered(
+        intercept: DispatchInterceptor<State, Action>,
+        classifier: ActionClassifier<Action>,
+        mut kinds: Vec<&'static str>,
+    ) -> Self {
+        kinds.sort_unstable();
+        kinds.dedup();
+        Self {
+            intercept,
+            kinds: Some((classifier, kinds)),
+        }
+    }
+
+    /// Decision for `action`, [Interception::Proceed] for kinds the
+    /// interceptor isn't called for.
+    #[inline(always)]
+    pub fn intercept(&self, state: &State, action: &ActionWithMeta<Action>) -> Interception {
+        if let Some((classifier, kinds)) = &self.kinds {
+            if kinds.binary_search(&classifier(&action.action)).is_err() {
+                return Interception::Proceed;
+            }
+        }
+        (self.intercept)(state, action)
+    }
+}
+
+impl<State, Action> Clone for Interceptor<State, Action> {
+    fn clone(&self) -> Self {
+        Self {
+            intercept: self.intercept,
+            kinds: self.kinds.clone(),
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use serde::Serialize;
+
+use crate::scratch::ScratchBuffers;
+use crate::{ActionClassifier, ActionWithMeta, Recorder, ScratchStats, SCRATCH_MAX_RETAINED};
+
+/// Configuration of the [JsonLogger].
+#[derive(Debug, Clone)]
+pub struct JsonLogConfig<Action> {
+    /// Provides the `kind` field.
+    pub classifier: ActionClassifier<Action>,
+    /// Whether to include the whole action in the `action` field, or
+    /// just its kind.
+    pub include_payload: bool,
+    /// Only log every n-th action of the given kind. Kinds not listed
+    /// here are always logged.
+    pub sampling: BTreeMap<&'static str, u64>,
+    /// Lines are serialized into a reused buffer, which is dropped
+    /// instead if it grew over this many bytes. Defaults to
+    /// [SCRATCH_MAX_RETAINED].
+    pub max_retained_buffer: usize,
+}
+
+impl<Action> JsonLogConfig<Action> {
+    pub fn new(classifier: ActionClassifier<Action>) -> Self {
+        Self {
+            classifier,
+            include_payload: true,
+            sampling: BTreeMap::new(),
+            max_retained_buffer: SCRATCH_MAX_RETAINED,
+        }
+    }
+
+    pub fn include_payload(mut self, include_payload: bool) -> Self {
+        self.include_payload = include_payload;
+        self
+    }
+
+    /// Log only every `every`-th action of `kind`.
+    pub fn sample(mut self, kind: &'static str, every: u64) -> Self {
+        self.sampling.insert(kind, every.max(1));
+        self
+    }
+
+    pub fn max_retained_buffer(mut self, bytes: usize) -> Self {
+        self.max_retained_buffer = bytes;
+        self
+    }
+}
+
+#[derive(Serialize)]
+struct JsonLogLine<'a, Action> {
+    id: u64,
+    seq: u64,
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    action: Option<&'a Action>,
+    elapsed_ns: u64,
+}
+
+/// Recorder writing one JSON object per line for every processed action.
+///
+/// Fields:
+/// - `id`: [crate::ActionId] as nanoseconds.
+/// - `seq`: sequence number of the action, counting sampled out
+///   actions as well, starting from 0.
+/// - `kind`: as returned by [JsonLogConfig::classifier].
+/// - `action`: the action, only if [JsonLogConfig::include_payload].
+/// - `elapsed_ns`: time passed since the previous action.
+///
+/// Writer errors don't affect dispatching, failed lines are dropped and
+/// counted in [JsonLogger::write_errors].
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EnablingCondition, JsonLogConfig, JsonLogger, Store};
+/// use std::sync::{Arc, Mutex};
+///
+/// #[derive(serde::Serialize)]
+/// enum Action {
+///     Tick,
+///     Add(u32),
+/// }
+///
+/// impl EnablingCondition<()> for Action {}
+///
+/// fn classify(action: &Action) -> &'static str {
+///     match action {
+///         Action::Tick => "Tick",
+///         Action::Add(_) => "Add",
+///     }
+/// }
+///
+/// #[derive(Clone, Default)]
+/// struct Output(Arc<Mutex<Vec<u8>>>);
+///
+/// impl std::io::Write for Output {
+///     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+///         self.0.lock().unwrap().write(buf)
+///     }
+///
+///     fn flush(&mut self) -> std::io::Result<()> {
+///         Ok(())
+///     }
+/// }
+///
+/// let output = Output::default();
+/// let config = JsonLogConfig::new(classify).sample("Tick", 10);
+///
+/// let mut store = Store::simple(|_: &mut (), _: &ActionWithMeta<Action>| {}, |_, _| {}, ());
+/// store.set_recorder(Some(Box::new(JsonLogger::new(output.clone(), config))));
+///
+/// for _ in 0..20 {
+///     store.dispatch(Action::Tick);
+/// }
+/// store.dispatch(Action::Add(5));
+///
+/// let output = output.0.lock().unwrap();
+/// let lines: Vec<serde_json::Value> = std::str::from_utf8(&output)
+///     .unwrap()
+///     .lines()
+///     .map(|line| serde_json::from_str(line).unwrap())
+///     .collect();
+///
+/// assert_eq!(lines.len(), 3);
+/// assert_eq!(lines[0]["seq"], 0);
+/// assert_eq!(lines[1]["seq"], 10);
+/// assert_eq!(lines[2]["seq"], 20);
+/// assert_eq!(lines[2]["kind"], "Add");
+/// assert_eq!(lines[2]["action"], serde_json::json!({ "Add": 5 }));
+/// assert!(lines[2]["id"].as_u64().unwrap() > lines[1]["id"].as_u64().unwrap());
+/// assert!(lines[2]["elapsed_ns"].is_u64());
+/// ```
+pub struct JsonLogger<W, Action> {
+    writer: W,
+    config: JsonLogConfig<Action>,
+    seq: u64,
+    /// Number of seen actions per sampled kind.
+    sampled_seen: BTreeMap<&'static str, u64>,
+    write_errors: u64,
+    scratch: ScratchBuffers,
+}
+
+impl<W, Action> JsonLogger<W, Action> {
+    pub fn new(writer: W, config: JsonLogConfig<Action>) -> Self {
+        Self {
+            writer,
+            scratch: ScratchBuffers::new(1, config.max_retained_buffer),
+            config,
+            seq: 0,
+            sampled_seen: BTreeMap::new(),
+            write_errors: 0,
+        }
+    }
+
+    /// Number of lines dropped because of writer errors.
+    #[inline(always)]
+    pub fn write_errors(&self) -> u64 {
+        self.write_errors
+    }
+
+    /// Counters of the buffer lines are serialized into.
+    ///
+    /// Once the buffer has grown enough, logging doesn't allocate,
+    /// unless serializing the action does:
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, JsonLogConfig, JsonLogger, Recorder, Store};
+    /// use std::io::Sink;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// #[cfg(feature = "memory-counter")]
+    /// #[global_allocator]
+    /// static GLOBAL: redux_rs::CountingAllocator<std::alloc::System> =
+    ///     redux_rs::CountingAllocator::new(std::alloc::System);
+    ///
+    /// #[derive(serde::Serialize)]
+    /// struct Add(u64);
+    /// impl EnablingCondition<u64> for Add {}
+    ///
+    /// fn reducer(state: &mut u64, action: &ActionWithMeta<Add>) {
+    ///     *state = state.wrapping_add(action.action.0);
+    /// }
+    ///
+    /// /// Logger shared with the test, to read its stats.
+    /// struct Shared(Arc<Mutex<JsonLogger<Sink, Add>>>);
+    ///
+    /// impl Recorder<Add> for Shared {
+    ///     fn record(&mut self, action: &ActionWithMeta<Add>) {
+    ///         self.0.lock().unwrap().record(action);
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///     let config = JsonLogConfig::new(|_| "Add");
+    ///     let logger = Arc::new(Mutex::new(JsonLogger::new(std::io::sink(), config)));
+    ///     let mut store = Store::simple(reducer, |_, _| {}, 0);
+    ///     store.set_recorder(Some(Box::new(Shared(logger.clone()))));
+    ///     // Warm up, with the longest line.
+    ///     store.dispatch(Add(u64::MAX));
+    ///
+    ///     #[cfg(feature = "memory-counter")]
+    ///     let before = GLOBAL.allocations();
+    ///     for i in 0..10_000 {
+    ///         store.dispatch(Add(i));
+    ///     }
+    ///     #[cfg(feature = "memory-counter")]
+    ///     assert_eq!(GLOBAL.allocations(), before);
+    ///
+    ///     let stats = logger.lock().unwrap().scratch_stats();
+    ///     assert_eq!(stats.taken, 10_001);
+    ///     assert_eq!(stats.reused, 10_000);
+    ///     assert_eq!(stats.discarded, 0);
+    ///     assert!(stats.retained_bytes > 0);
+    /// }
+    /// ```
+    #[inline(always)]
+    pub fn scratch_stats(&self) -> ScratchStats {
+        self.scratch.stats()
+    }
+
+    pub fn into_writer(self) -> W {
+        self.writer
+    }
+
+    fn is_sampled_out(&mut self, kind: &'static str) -> bool {
+        let every = match self.config.sampling.get(kind) {
+            Some(every) => *every,
+            None => return false,
+        };
+        let seen = self.sampled_seen.entry(kind).or_insert(0);
+        let sampled_out = *seen % every != 0;
+        *seen += 1;
+        sampled_out
+    }
+}
+
+impl<W, Action> Recorder<Action> for JsonLogger<W, Action>
+where
+    W: Write,
+    Action: Serialize,
+{
+    fn record(&mut self, action: &ActionWithMeta<Action>) {
+        let seq = self.seq;
+        self.seq += 1;
+
+        let kind = (self.config.classifier)(&action.action);
+        if self.is_sampled_out(kind) {
+            return;
+        }
+
+        let line = JsonLogLine {
+            id: action.id.into(),
+            seq,
+            kind,
+            action: Some(&action.action).filter(|_| self.config.include_payload),
+            elapsed_ns: action.elapsed_since_prev.as_nanos() as u64,
+        };
+
+        // Serialized upfront, so that failures never leave partial lines.
+        let mut buf = self.scratch.take();
+        let result = serde_json::to_writer(&mut buf, &line)
+            .map_err(std::io::Error::from)
+            .and_then(|()| {
+                buf.push(b'\n');
+                self.writer.write_all(&buf)
+            });
+        self.scratch.put(buf);
+        if let Err(_err) = result {
+            #[cfg(feature = "log")]
+            log::warn!(
+                target: crate::LOG_TARGET_RECORDER,
+                "failed to record action {}: {}",
+                u64::from(action.id),
+                _err
+            );
+            self.write_errors += 1;
+        }
+    }
+
+    fn scratch_stats(&self) -> Option<ScratchStats> {
+        Some(self.scratch.stats())
+    }
+}
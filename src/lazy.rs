@@ -0,0 +1,299 @@
+use std::cell::Cell;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock};
+use std::thread::{self, ThreadId};
+
+enum Init<T> {
+    /// Not evaluated yet.
+    Pending(Box<dyn FnOnce() -> T + Send>),
+    /// Being evaluated by the thread, others wait for it.
+    Running(ThreadId),
+    /// Evaluated, or never will be.
+    Done,
+}
+
+struct Inner<T> {
+    value: OnceLock<T>,
+    /// Taken, under the lock, by whoever evaluates the payload, so
+    /// others wait for it instead of evaluating it again. The closure
+    /// runs without the lock held.
+    init: Mutex<Init<T>>,
+    evaluated: Condvar,
+}
+
+/// Action payload which is expensive to build, built from a closure
+/// the first time it's read, if ever.
+///
+/// Reducers and effects read it with [Lazy::get]. Clones share the
+/// payload, so the closure runs at most once however many times the
+/// action is cloned (e.g. by recorders) or read.
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, BoundedRecorder, EnablingCondition, Lazy, Store};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+///
+/// #[derive(Clone)]
+/// enum Action {
+///     Summary { verbose: bool, text: Lazy<String> },
+/// }
+/// impl EnablingCondition<Vec<String>> for Action {}
+///
+/// fn reducer(state: &mut Vec<String>, action: &ActionWithMeta<Action>) {
+///     match &action.action {
+///         Action::Summary { verbose: true, text } => state.push(text.get().clone()),
+///         Action::Summary { verbose: false, .. } => {}
+///     }
+/// }
+///
+/// let built = Arc::new(AtomicUsize::new(0));
+/// let summary = |verbose| {
+///     let built = built.clone();
+///     Action::Summary {
+///         verbose,
+///         text: Lazy::new(move || {
+///             built.fetch_add(1, Ordering::SeqCst);
+///             "summary".repeat(3)
+///         }),
+///     }
+/// };
+///
+/// let mut store = Store::simple(reducer, |_, _| {}, vec![]);
+/// store.set_recorder(Some(Box::new(BoundedRecorder::new(16))));
+///
+/// // Nothing reads it.
+/// store.dispatch(summary(false));
+/// assert_eq!(built.load(Ordering::SeqCst), 0);
+///
+/// // Read by the reducer, and again through the recorded clone.
+/// store.dispatch(summary(true));
+/// let recorded = store.recent_actions().last().unwrap();
+/// match &recorded.action {
+///     Action::Summary { text, .. } => assert_eq!(text.get(), "summarysummarysummary"),
+/// }
+/// assert_eq!(built.load(Ordering::SeqCst), 1);
+/// ```
+///
+/// With `serde`, serialized as an `Option` of the payload, evaluating
+/// it first, unless the store is told not to with
+/// `Store::set_lazy_recording`.
+pub struct Lazy<T>(Arc<Inner<T>>);
+
+impl<T> Lazy<T> {
+    /// Payload built by `init` once read.
+    pub fn new<F>(init: F) -> Self
+    where
+        F: FnOnce() -> T + Send + 'static,
+    {
+        Self(Arc::new(Inner {
+            value: OnceLock::new(),
+            init: Mutex::new(Init::Pending(Box::new(init))),
+            evaluated: Condvar::new(),
+        }))
+    }
+
+    /// Payload which is already built.
+    pub fn from_value(value: T) -> Self {
+        Self(Arc::new(Inner {
+            value: OnceLock::from(value),
+            init: Mutex::new(Init::Done),
+            evaluated: Condvar::new(),
+        }))
+    }
+
+    /// Payload which wasn't recorded, see [LazyRecording::SkipUnevaluated].
+    #[cfg(feature = "serde")]
+    fn unrecorded() -> Self {
+        Self(Arc::new(Inner {
+            value: OnceLock::new(),
+            init: Mutex::new(Init::Done),
+            evaluated: Condvar::new(),
+        }))
+    }
+
+    /// The payload, built now unless it already was.
+    ///
+    /// Panics if it isn't available, see [Lazy::try_get].
+    #[inline(always)]
+    pub fn get(&self) -> &T {
+        match self.try_get() {
+            Some(value) => value,
+            None => panic!("lazy payload isn't available, see `Lazy::try_get`"),
+        }
+    }
+
+    /// The payload, built now unless it already was. `None` if it was
+    /// skipped when recorded, see [LazyRecording::SkipUnevaluated],
+    /// building it panicked, or it's read while being built by the same
+    /// thread, i.e. from its own closure.
+    ///
+    /// ```
+    /// use redux_rs::Lazy;
+    /// use std::sync::{Arc, OnceLock};
+    ///
+    /// // Closure reading the payload it builds, through a clone.
+    /// let itself: Arc<OnceLock<Lazy<&str>>> = Arc::new(OnceLock::new());
+    /// let lazy = Lazy::new({
+    ///     let itself = itself.clone();
+    ///     move || match itself.get().unwrap().try_get() {
+    ///         Some(_) => "read",
+    ///         None => "not built yet",
+    ///     }
+    /// });
+    /// itself.set(lazy.clone()).ok().unwrap();
+    ///
+    /// assert_eq!(lazy.try_get(), Some(&"not built yet"));
+    /// ```
+    pub fn try_get(&self) -> Option<&T> {
+        if let Some(value) = self.0.value.get() {
+            return Some(value);
+        }
+        let mut guard = lock(&self.0.init);
+        loop {
+            // Might have been built while waiting for the lock.
+            if let Some(value) = self.0.value.get() {
+                return Some(value);
+            }
+            match &*guard {
+                Init::Pending(_) => break,
+                Init::Running(thread) if *thread == thread::current().id() => return None,
+                Init::Running(_) => {
+                    guard = self
+                        .0
+                        .evaluated
+                        .wait(guard)
+                        .unwrap_or_else(|poisoned| poisoned.into_inner());
+                }
+                Init::Done => return None,
+            }
+        }
+        let init = match std::mem::replace(&mut *guard, Init::Running(thread::current().id())) {
+            Init::Pending(init) => init,
+            _ => unreachable!(),
+        };
+        drop(guard);
+
+        /// Wakes up the waiting threads, even if the closure panics.
+        struct Finish<'a, T>(&'a Inner<T>);
+
+        impl<T> Drop for Finish<'_, T> {
+            fn drop(&mut self) {
+                *lock(&self.0.init) = Init::Done;
+                self.0.evaluated.notify_all();
+            }
+        }
+
+        let finish = Finish(&self.0);
+        let _ = self.0.value.set(init());
+        drop(finish);
+        self.0.value.get()
+    }
+
+    /// The payload if it was already built, without building it.
+    #[inline(always)]
+    pub fn get_if_evaluated(&self) -> Option<&T> {
+        self.0.value.get()
+    }
+
+    #[inline(always)]
+    pub fn is_evaluated(&self) -> bool {
+        self.0.value.get().is_some()
+    }
+}
+
+fn lock<T>(init: &Mutex<Init<T>>) -> MutexGuard<'_, Init<T>> {
+    init.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl<T> Clone for Lazy<T> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> From<T> for Lazy<T> {
+    fn from(value: T) -> Self {
+        Self::from_value(value)
+    }
+}
+
+/// Compares the payloads, building them.
+impl<T: PartialEq> PartialEq for Lazy<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0) || self.try_get() == other.try_get()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Lazy<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.get_if_evaluated() {
+            Some(value) => f.debug_tuple("Lazy").field(value).finish(),
+            None => f.write_str("Lazy(<not evaluated>)"),
+        }
+    }
+}
+
+/// How [Lazy] payloads not evaluated by the time an action is recorded
+/// get serialized, see `Store::set_lazy_recording`.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub enum LazyRecording {
+    /// Evaluate them, so the recording replays exactly.
+    #[default]
+    Evaluate,
+    /// Serialize them as missing. They can't be read from actions
+    /// deserialized from the recording, [Lazy::try_get] returns `None`,
+    /// so replaying the recording might not reproduce the state.
+    SkipUnevaluated,
+}
+
+thread_local! {
+    static RECORDING: Cell<LazyRecording> = const { Cell::new(LazyRecording::Evaluate) };
+}
+
+/// Run `record` with `policy` applying to [Lazy]s it serializes.
+pub(crate) fn recording<R>(policy: LazyRecording, record: impl FnOnce() -> R) -> R {
+    struct Restore(LazyRecording);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            RECORDING.with(|recording| recording.set(self.0));
+        }
+    }
+
+    let _restore = Restore(RECORDING.with(|recording| recording.replace(policy)));
+    record()
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for Lazy<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let value = match RECORDING.with(Cell::get) {
+            LazyRecording::Evaluate => self.try_get(),
+            LazyRecording::SkipUnevaluated => self.get_if_evaluated(),
+        };
+        value.serialize(serializer)
+    }
+}
+
+/// ```
+/// use redux_rs::Lazy;
+///
+/// let lazy = Lazy::new(|| vec![1, 2, 3]);
+/// let json = serde_json::to_string(&lazy).unwrap();
+/// assert_eq!(json, "[1,2,3]");
+///
+/// let decoded: Lazy<Vec<u32>> = serde_json::from_str(&json).unwrap();
+/// assert_eq!(decoded.get(), &[1, 2, 3]);
+/// let skipped: Lazy<Vec<u32>> = serde_json::from_str("null").unwrap();
+/// assert_eq!(skipped.try_get(), None);
+/// ```
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Lazy<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match Option::<T>::deserialize(deserializer)? {
+            Some(value) => Self::from_value(value),
+            None => Self::unrecorded(),
+        })
+    }
+}
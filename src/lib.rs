@@ -37,31 +37,347 @@
 //! To actually change the state (read: create a new one), we need what is called a reducer.
 //! It is a simple function which takes in the current state plus the action to perform and returns a new state.
 //!
+//! ```
+//! # #[derive(Default)]
+//! # struct State {
+//! #     counter: i8
+//! # }
+//! # enum Action {
+//! #     Increment,
+//! #     Decrement
+//! # }
+//! use redux_rs::ActionWithMeta;
+//!
+//! fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+//!     match &action.action {
+//!         Action::Increment => state.counter += 1,
+//!         Action::Decrement => state.counter -= 1,
+//!     }
+//! }
+//! ```
 //!
 //! ### Store
 //!
 //! To put it all together, we use a store which keeps track of a state and provides an easy to use API for dispatching actions.
 //! The store takes the reducer and an initial state.
+//!
+//! ```
+//! # #[derive(Default)]
+//! # struct State {
+//! #     counter: i8
+//! # }
+//! # enum Action {
+//! #     Increment,
+//! #     Decrement
+//! # }
+//! # fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+//! #     match &action.action {
+//! #         Action::Increment => state.counter += 1,
+//! #         Action::Decrement => state.counter -= 1,
+//! #     }
+//! # }
+//! use redux_rs::{ActionWithMeta, EnablingCondition, Store};
+//!
+//! impl EnablingCondition<State> for Action {}
+//!
+//! let mut store = Store::simple(reducer, |_, _| {}, State::default());
+//!
+//! store.dispatch(Action::Increment);
+//! store.dispatch(Action::Increment);
+//! store.dispatch(Action::Decrement);
+//!
+//! assert_eq!(store.state().counter, 1);
 //! ```
 
 #![cfg_attr(not(feature = "std"), no_std)]
-#![cfg_attr(not(feature = "std"), feature(alloc))]
-#![cfg_attr(feature = "fuzzing", feature(no_coverage))]
+// Only nightly feature used by the crate, needed by `fuzzcheck` derives.
+#![cfg_attr(feature = "coverage", feature(no_coverage))]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
+mod time;
+pub use time::{
+    ClockAnomaly, ClockAnomalyHook, ClockDiagnostics, Instant, MonotonicTime, TimeGranularity,
+};
 
 mod store;
-pub use store::Store;
+pub use store::{DispatchOutcome, Store};
+
+mod small_vec;
+mod subsystems;
+
+mod capabilities;
+pub use capabilities::Capabilities;
+
+#[cfg(all(feature = "async", feature = "std"))]
+mod condition;
+#[cfg(feature = "async")]
+mod stream;
+#[cfg(feature = "async")]
+pub use stream::{StreamEnd, StreamReport};
+
+mod polled;
+pub use polled::{PollableService, PolledReport};
+
+mod gui;
+pub use gui::{FrameDriver, FrameSummary, UiDispatcher, UI_INBOX_CAPACITY};
+
+mod handle;
+pub use handle::{Dispatcher, ServiceAccess, StateReader, StoreHandle, StoreLike};
+
+mod scoped;
+pub use scoped::ScopedStore;
+
+mod module;
+pub use module::{Embed, Lens, Module, ModuleId, ModuleInfo, ModuleRegistry};
+
+mod builder;
+pub use builder::{BuildError, StoreBuilder};
 
 mod action;
-pub use action::{ActionId, ActionWithMeta, EnablingCondition};
+#[allow(deprecated)]
+pub use action::ActionWithId;
+pub use action::{
+    kind_name, ActionClassifier, ActionId, ActionKind, ActionWithMeta, EnablingCondition,
+};
+#[cfg(feature = "derive")]
+pub use redux_rs_derive::{handle_actions, redux_actions, EnablingCondition, SafetyCondition};
+
+mod provenance;
+pub use provenance::Provenance;
+
+#[cfg(feature = "memory")]
+mod memory;
+#[cfg(feature = "memory-counter")]
+pub use memory::CountingAllocator;
+#[cfg(feature = "memory")]
+pub use memory::{
+    MemoryAlarm, MemoryAlarmHook, MemoryDetector, MemoryReport, MemorySampling, MemoryThresholds,
+};
+
+#[cfg(feature = "stats")]
+mod stats;
+#[cfg(all(feature = "stats", feature = "memory"))]
+pub use stats::{AllocatingAction, TOP_ALLOCATING_LEN};
+#[cfg(feature = "stats")]
+pub use stats::{
+    Cascade, CascadeStats, DispatchStats, KindCascades, KindStats, ProvenanceCounts,
+    CASCADE_SIZE_BUCKETS,
+};
 
 mod safety_condition;
-pub use safety_condition::SafetyCondition;
+pub use safety_condition::{SafetyCondition, SafetyConditionFn, SafetyConditions, SafetyViolation};
+
+mod condition_cache;
+pub use condition_cache::{CacheableCondition, ConditionCacheStats};
 
 mod reducer;
 pub use reducer::Reducer;
+#[cfg(feature = "strict")]
+pub use reducer::StrictReducer;
+
+#[cfg(feature = "strict")]
+mod ctx;
+#[cfg(feature = "strict")]
+pub use ctx::{EffectsCtx, ReducerCtx};
 
 mod effects;
 pub use effects::Effects;
+#[cfg(feature = "strict")]
+pub use effects::StrictEffects;
+
+#[cfg(feature = "strict")]
+mod strict_store;
+#[cfg(feature = "strict")]
+pub use strict_store::StrictStore;
+
+mod effect_order;
+pub use effect_order::EffectOrder;
 
 mod service;
-pub use service::TimeService;
+#[cfg(feature = "testing")]
+pub use service::MockTimeService;
+#[cfg(feature = "wasm")]
+pub use service::PerformanceTimeService;
+pub use service::{
+    FrozenTime, HasService, RandomnessService, ScaledTime, SeededRandomness, TimeService,
+};
+
+mod scratch;
+pub use scratch::{ScratchStats, SCRATCH_MAX_RETAINED};
+
+mod shared;
+pub use shared::{PayloadSize, Shared};
+
+#[cfg(feature = "std")]
+mod lazy;
+#[cfg(feature = "std")]
+pub use lazy::{Lazy, LazyRecording};
+
+mod recorder;
+pub use recorder::{
+    BoundedRecorder, EncodingRecorder, Fork, MetaEvent, RecentActions, Recorder, RecordingHeader,
+    RingRecorder,
+};
+
+mod config;
+pub use config::{config_hash, ConfigReducer, ReplayError};
+
+mod divergence;
+pub use divergence::{DivergenceReport, RecordedStep};
+
+mod crash_recovery;
+pub use crash_recovery::{RecoveryError, RecoveryPolicy, RecoveryReport, StoreSnapshot};
+
+#[cfg(feature = "std")]
+mod service_recording;
+#[cfg(feature = "std")]
+pub use service_recording::{RecordableCall, Recorded, Replayed, ServiceCall, ServiceCallLog};
+
+#[cfg(feature = "prometheus")]
+mod metrics;
+#[cfg(feature = "prometheus")]
+pub use metrics::StoreMetrics;
+
+#[cfg(feature = "persistence")]
+mod persistence;
+#[cfg(feature = "persistence")]
+pub use persistence::{Cadence, FileSink, PersistenceConfig, SnapshotSink};
+
+#[cfg(feature = "remote")]
+mod remote;
+#[cfg(feature = "remote")]
+pub use remote::{RemoteDispatchClient, RemoteDispatchServer, RemoteReply};
+
+#[cfg(feature = "log")]
+mod logging;
+#[cfg(feature = "log")]
+pub use logging::{
+    LOG_TARGET_CLOCK, LOG_TARGET_DISPATCH, LOG_TARGET_PERSISTENCE, LOG_TARGET_RECORDER,
+    LOG_TARGET_SAFETY,
+};
+
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(feature = "ffi")]
+pub use ffi::{
+    redux_store_dispatch, redux_store_free, redux_store_state_json, redux_store_subscribe,
+    redux_store_t, redux_subscriber_t, REDUX_BUFFER_TOO_SMALL, REDUX_DECODE_ERROR, REDUX_NULL,
+    REDUX_OK, REDUX_PANIC, REDUX_REJECTED, REDUX_SERIALIZE_ERROR,
+};
+
+#[cfg(feature = "json_log")]
+mod json_log;
+#[cfg(feature = "json_log")]
+pub use json_log::{JsonLogConfig, JsonLogger};
+
+mod watchdog;
+pub use watchdog::{DispatchTimings, SlowActionHook};
+
+mod state_history;
+pub use state_history::StateHistory;
+
+mod pause;
+pub use pause::PAUSED_QUEUE_CAPACITY;
+
+mod coalesce;
+pub use coalesce::{CoalesceKey, CoalescePolicy, Coalescing};
+
+mod dedup;
+pub use dedup::{Dedup, DedupKey, DedupWindow};
+
+mod timers;
+pub use timers::TimerId;
+#[cfg(feature = "std")]
+pub use timers::{CatchUp, RecurringId};
+
+mod throttle;
+pub use throttle::{Debounce, Throttle};
+
+mod rate_limit;
+pub use rate_limit::{RateLimit, RateLimitActionMapper, RateLimitChange, RateLimiter};
+
+mod sim;
+pub use sim::{
+    Cluster, ClusterEvent, ClusterNet, ClusterSend, Delivery, DeliveryRule, NodeId, Simulation,
+    SimulationReport,
+};
+
+mod error_action;
+pub use error_action::{ErrorActionMapper, StoreError};
+
+mod subscription;
+pub use subscription::SubscriptionId;
+
+mod map_delta;
+pub use map_delta::{DeltaTracking, MapDelta, MapSelector};
+
+#[cfg(feature = "audit")]
+mod audit;
+#[cfg(feature = "audit")]
+pub use audit::{AuditReport, AuditedState, KindAudit, Section};
+
+mod poison;
+pub use poison::PoisonReason;
+
+mod transaction;
+pub use transaction::TransactionError;
+
+mod dry_run;
+pub use dry_run::DryRunResult;
+
+#[cfg(feature = "panic_context")]
+mod panic_context;
+#[cfg(feature = "panic_context")]
+pub use panic_context::{
+    eprint_panic_context, PanicContext, PanicContextSink, PANIC_CONTEXT_RECENT_IDS,
+};
+
+#[cfg(feature = "std")]
+mod recovery;
+#[cfg(feature = "std")]
+pub use recovery::{CaughtPanic, CaughtPanicHook, PanicSource, ReducerPanicPolicy};
+
+mod shutdown;
+pub use shutdown::{PendingActions, ShutdownPolicy, ShutdownReport};
+
+mod breakpoint;
+#[cfg(feature = "breakpoint_dump")]
+pub use breakpoint::BreakpointDump;
+pub use breakpoint::{Breakpoint, BreakpointHook, BreakpointId};
+
+mod time_travel;
+pub use time_travel::TimeTravelError;
+
+#[cfg(feature = "diff")]
+mod diff;
+#[cfg(feature = "diff")]
+pub use diff::{DiffEntry, StateDiff, ARRAY_DIFF_LIMIT};
+
+#[cfg(feature = "determinism-check")]
+mod determinism;
+
+#[cfg(feature = "devtools")]
+mod devtools;
+#[cfg(feature = "devtools")]
+pub use devtools::{DevToolsConnector, DevToolsRecorder, DevToolsTransport, WebSocketTransport};
+
+mod interceptor;
+pub use interceptor::{DispatchInterceptor, Interception};
+
+#[cfg(feature = "testing")]
+mod effects_probe;
+#[cfg(feature = "testing")]
+pub use effects_probe::EffectsProbe;
+
+#[cfg(feature = "testing")]
+mod minimize;
+#[cfg(feature = "testing")]
+pub use minimize::{minimize, MinimizeProgress};
+
+#[cfg(feature = "testing")]
+mod scenario;
+#[cfg(feature = "testing")]
+#[doc(hidden)]
+pub use scenario::{__scenario_advance, __scenario_assert, __scenario_store};
@@ -0,0 +1,207 @@
+//! Targets of the diagnostics emitted with the `log` crate, when the
+//! `log` feature is enabled. Without it, no logging code is compiled.
+
+/// Dispatching: rejected actions (`debug`), actions dropped because
+/// the paused queue was full (`warn`) and every processed action
+/// (`trace`).
+///
+/// Messages carry the id of the action, or of the last processed one
+/// if the action didn't get an id. Logged by a capturing logger:
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EnablingCondition, Store, LOG_TARGET_DISPATCH};
+/// use log::{Level, LevelFilter, Log, Metadata, Record};
+/// use std::sync::Mutex;
+///
+/// struct Capture(Mutex<Vec<(Level, String, String)>>);
+///
+/// impl Log for Capture {
+///     fn enabled(&self, metadata: &Metadata) -> bool {
+///         metadata.target().starts_with("redux::")
+///     }
+///
+///     fn log(&self, record: &Record) {
+///         let line = (record.level(), record.target().to_owned(), record.args().to_string());
+///         self.0.lock().unwrap().push(line);
+///     }
+///
+///     fn flush(&self) {}
+/// }
+///
+/// static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+///
+/// struct Add(u32);
+/// impl EnablingCondition<u32> for Add {
+///     fn is_enabled(&self, _: &u32) -> bool {
+///         self.0 > 0
+///     }
+/// }
+///
+/// fn reducer(state: &mut u32, action: &ActionWithMeta<Add>) {
+///     *state += action.action.0;
+/// }
+///
+/// let mut store = Store::simple(reducer, |_, _| {}, 0);
+/// log::set_logger(&CAPTURE).unwrap();
+/// log::set_max_level(LevelFilter::Debug);
+///
+/// store.dispatch(Add(1));
+/// let id = u64::from(store.last_action_id());
+/// store.dispatch(Add(0));
+///
+/// store.pause_with_capacity(1);
+/// store.dispatch(Add(2));
+/// store.dispatch(Add(3));
+/// store.dispatch(Add(4));
+///
+/// let logged = CAPTURE.0.lock().unwrap();
+/// let kind = std::any::type_name::<Add>();
+/// assert_eq!(
+///     *logged,
+///     [
+///         (
+///             Level::Debug,
+///             LOG_TARGET_DISPATCH.to_owned(),
+///             format!("rejected {} at action {}: not enabled", kind, id),
+///         ),
+///         (
+///             Level::Warn,
+///             LOG_TARGET_DISPATCH.to_owned(),
+///             format!("paused queue full, dropped {} after action {} (1 dropped while paused)", kind, id),
+///         ),
+///         (
+///             Level::Warn,
+///             LOG_TARGET_DISPATCH.to_owned(),
+///             format!("paused queue full, dropped {} after action {} (2 dropped while paused)", kind, id),
+///         ),
+///     ]
+/// );
+/// ```
+pub const LOG_TARGET_DISPATCH: &str = "redux::dispatch";
+
+/// Clock anomalies (`warn`), see [crate::ClockAnomaly].
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EnablingCondition, FrozenTime, Instant, Store, LOG_TARGET_CLOCK};
+/// use log::{LevelFilter, Log, Metadata, Record};
+/// use std::sync::Mutex;
+/// use std::time::{Duration, SystemTime};
+///
+/// struct Capture(Mutex<Vec<String>>);
+///
+/// impl Log for Capture {
+///     fn enabled(&self, metadata: &Metadata) -> bool {
+///         metadata.target() == LOG_TARGET_CLOCK
+///     }
+///
+///     fn log(&self, record: &Record) {
+///         if self.enabled(record.metadata()) {
+///             self.0.lock().unwrap().push(record.args().to_string());
+///         }
+///     }
+///
+///     fn flush(&self) {}
+/// }
+///
+/// static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+///
+/// struct Action;
+/// impl EnablingCondition<()> for Action {}
+///
+/// let reducer = |_: &mut (), _: &ActionWithMeta<Action>| {};
+/// let start = Instant::now() + Duration::from_secs(60);
+/// let mut store = Store::new(reducer, |_, _| {}, FrozenTime::new(start), SystemTime::UNIX_EPOCH, ());
+/// store.set_max_time_step(Some(Duration::from_secs(1)));
+/// log::set_logger(&CAPTURE).unwrap();
+/// log::set_max_level(LevelFilter::Warn);
+///
+/// store.dispatch(Action);
+/// let id = u64::from(store.last_action_id());
+/// store.service = FrozenTime::new(start - Duration::from_secs(10));
+/// store.dispatch(Action);
+/// let id2 = u64::from(store.last_action_id());
+/// store.service = FrozenTime::new(start + Duration::from_secs(5));
+/// store.dispatch(Action);
+///
+/// assert_eq!(
+///     *CAPTURE.0.lock().unwrap(),
+///     [
+///         format!("clock went backwards by 10s after action {}", id),
+///         format!("clock jumped by 5s after action {}, capped to 1s", id2),
+///     ]
+/// );
+/// ```
+pub const LOG_TARGET_CLOCK: &str = "redux::clock";
+
+/// Safety condition violations (`warn`), see
+/// `Store::enable_safety_check`.
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EnablingCondition, SafetyCondition, Store, LOG_TARGET_SAFETY};
+/// use log::{LevelFilter, Log, Metadata, Record};
+/// use std::sync::Mutex;
+///
+/// struct Capture(Mutex<Vec<String>>);
+///
+/// impl Log for Capture {
+///     fn enabled(&self, metadata: &Metadata) -> bool {
+///         metadata.target() == LOG_TARGET_SAFETY
+///     }
+///
+///     fn log(&self, record: &Record) {
+///         if self.enabled(record.metadata()) {
+///             self.0.lock().unwrap().push(record.args().to_string());
+///         }
+///     }
+///
+///     fn flush(&self) {}
+/// }
+///
+/// static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+///
+/// struct Balance(i64);
+///
+/// impl SafetyCondition for Balance {
+///     type Error = i64;
+///
+///     fn check_safety_condition(&self) -> Result<(), i64> {
+///         if self.0 < 0 {
+///             Err(self.0)
+///         } else {
+///             Ok(())
+///         }
+///     }
+/// }
+///
+/// struct Withdraw(i64);
+/// impl EnablingCondition<Balance> for Withdraw {}
+///
+/// fn reducer(state: &mut Balance, action: &ActionWithMeta<Withdraw>) {
+///     state.0 -= action.action.0;
+/// }
+///
+/// let mut store = Store::simple(reducer, |_, _| {}, Balance(10));
+/// store.enable_safety_check();
+/// log::set_logger(&CAPTURE).unwrap();
+/// log::set_max_level(LevelFilter::Warn);
+///
+/// store.dispatch(Withdraw(5));
+/// store.dispatch(Withdraw(7));
+///
+/// assert_eq!(
+///     *CAPTURE.0.lock().unwrap(),
+///     [format!(
+///         "safety condition violated after action {}: -2",
+///         u64::from(store.last_action_id())
+///     )]
+/// );
+/// ```
+pub const LOG_TARGET_SAFETY: &str = "redux::safety";
+
+/// Recorder I/O errors (`warn`), like failed writes of
+/// `JsonLogger`.
+pub const LOG_TARGET_RECORDER: &str = "redux::recorder";
+
+/// Snapshots which failed to be saved (`warn`), see
+/// `Store::enable_persistence`.
+pub const LOG_TARGET_PERSISTENCE: &str = "redux::persistence";
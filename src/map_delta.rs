@@ -0,0 +1,137 @@
+#[cfg(not(feature = "std"))]
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::cmp::Ordering;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+/// Function signature selecting a map out of the state, see
+/// `Store::subscribe_map_delta`.
+pub type MapSelector<State, K, V> = fn(&State) -> &BTreeMap<K, V>;
+
+/// What `Store::subscribe_map_delta` remembers of the map between
+/// actions.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum DeltaTracking {
+    /// Only keys, so only added and removed entries are reported.
+    Keys,
+    /// Keys along with clones of their values, so changed values are
+    /// reported too. Cheap for values which are [crate::Shared].
+    Values,
+}
+
+/// Change of a map in the state caused by an action, see
+/// `Store::subscribe_map_delta`.
+///
+/// Every list is sorted by key.
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MapDelta<K, V> {
+    pub added: Vec<(K, V)>,
+    pub removed: Vec<K>,
+    /// Entries whose value changed, with the new value. Always empty
+    /// with [DeltaTracking::Keys].
+    pub changed: Vec<(K, V)>,
+}
+
+impl<K, V> MapDelta<K, V> {
+    fn new() -> Self {
+        Self {
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Map as seen after the previous action, sorted by key.
+enum Previous<K, V> {
+    Keys(Vec<K>),
+    Values(Vec<(K, V)>),
+}
+
+/// Computes [MapDelta]s of a map between actions, by walking it along
+/// with what's remembered of it.
+pub(crate) struct MapTracker<K, V> {
+    previous: Previous<K, V>,
+}
+
+impl<K: Ord + Clone, V: PartialEq + Clone> MapTracker<K, V> {
+    pub fn new(map: &BTreeMap<K, V>, tracking: DeltaTracking) -> Self {
+        let previous = match tracking {
+            DeltaTracking::Keys => Previous::Keys(map.keys().cloned().collect()),
+            DeltaTracking::Values => Previous::Values(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect(),
+            ),
+        };
+        Self { previous }
+    }
+
+    /// Delta since the last call, `None` if nothing changed.
+    pub fn update(&mut self, map: &BTreeMap<K, V>) -> Option<MapDelta<K, V>> {
+        let delta = match &self.previous {
+            Previous::Keys(keys) => delta(keys, map, |key| key, |_, _| false),
+            Previous::Values(entries) => delta(
+                entries,
+                map,
+                |(key, _)| key,
+                |(_, previous), value| previous != value,
+            ),
+        };
+        if delta.is_empty() {
+            return None;
+        }
+        let tracking = match self.previous {
+            Previous::Keys(_) => DeltaTracking::Keys,
+            Previous::Values(_) => DeltaTracking::Values,
+        };
+        *self = Self::new(map, tracking);
+        Some(delta)
+    }
+}
+
+/// Merge `previous` and `map`, both sorted by key.
+fn delta<K, V, P>(
+    previous: &[P],
+    map: &BTreeMap<K, V>,
+    key: impl Fn(&P) -> &K,
+    changed: impl Fn(&P, &V) -> bool,
+) -> MapDelta<K, V>
+where
+    K: Ord + Clone,
+    V: Clone,
+{
+    let mut delta = MapDelta::new();
+    let mut previous = previous.iter().peekable();
+    let mut current = map.iter().peekable();
+    loop {
+        let order = match (previous.peek(), current.peek()) {
+            (Some(old), Some((new, _))) => key(old).cmp(new),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => return delta,
+        };
+        match order {
+            Ordering::Less => {
+                let old = previous.next().unwrap();
+                delta.removed.push(key(old).clone());
+            }
+            Ordering::Greater => {
+                let (new, value) = current.next().unwrap();
+                delta.added.push((new.clone(), value.clone()));
+            }
+            Ordering::Equal => {
+                let old = previous.next().unwrap();
+                let (new, value) = current.next().unwrap();
+                if changed(old, value) {
+                    delta.changed.push((new.clone(), value.clone()));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,481 @@
+#[cfg(feature = "memory-counter")]
+use core::alloc::{GlobalAlloc, Layout};
+#[cfg(feature = "memory-counter")]
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// Source of allocator statistics.
+trait MemoryStats {
+    /// Total bytes currently allocated by the process.
+    fn total_allocated() -> u64;
+}
+
+/// Reads stats from jemalloc, which must be the global allocator.
+#[cfg(all(feature = "jemallocator", not(feature = "memory-counter")))]
+struct Jemalloc;
+
+#[cfg(all(feature = "jemallocator", not(feature = "memory-counter")))]
+impl MemoryStats for Jemalloc {
+    fn total_allocated() -> u64 {
+        // Stats are cached by jemalloc and only refreshed when epoch advances.
+        let _ = jemalloc_ctl::epoch::advance();
+        jemalloc_ctl::stats::allocated::read()
+            .map(|allocated| allocated as u64)
+            .unwrap_or(0)
+    }
+}
+
+/// Reads stats maintained by [CountingAllocator].
+#[cfg(feature = "memory-counter")]
+struct Counter;
+
+#[cfg(feature = "memory-counter")]
+impl MemoryStats for Counter {
+    fn total_allocated() -> u64 {
+        ALLOCATED.load(Ordering::Relaxed) as u64
+    }
+}
+
+/// No allocator stats source is enabled, so it's always 0.
+#[cfg(not(any(feature = "jemallocator", feature = "memory-counter")))]
+struct NoStats;
+
+#[cfg(not(any(feature = "jemallocator", feature = "memory-counter")))]
+impl MemoryStats for NoStats {
+    fn total_allocated() -> u64 {
+        0
+    }
+}
+
+// Counting allocator, if enabled, takes precedence, as it has to be
+// installed explicitly.
+#[cfg(feature = "memory-counter")]
+type Source = Counter;
+#[cfg(all(feature = "jemallocator", not(feature = "memory-counter")))]
+type Source = Jemalloc;
+#[cfg(not(any(feature = "jemallocator", feature = "memory-counter")))]
+type Source = NoStats;
+
+/// Total bytes currently allocated by the process.
+#[inline(always)]
+pub(crate) fn total_allocated() -> u64 {
+    Source::total_allocated()
+}
+
+/// How often the store reads allocator stats when dispatching.
+///
+/// Reading them has a cost (with jemalloc in the order of
+/// microseconds), which adds up for lots of tiny actions. Actions for
+/// which stats aren't read reuse the last read value, with
+/// `ActionWithMeta::allocated_sampled` set to `false`.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub enum MemorySampling {
+    /// Read for every action (default).
+    #[default]
+    EveryAction,
+    /// Read for every n-th action.
+    EveryN(u32),
+    /// Read once given time passed since the last read, measured with
+    /// the store's [crate::TimeService].
+    Every(core::time::Duration),
+}
+
+/// Reads of allocator stats, as often as [MemorySampling] says.
+#[derive(Clone, Default)]
+pub(crate) struct AllocatedSampler {
+    sampling: MemorySampling,
+    /// Last read total allocated bytes and when they were read.
+    last: Option<(u64, crate::Instant)>,
+    /// Number of actions since allocator stats were last read.
+    skipped: u32,
+}
+
+impl AllocatedSampler {
+    pub fn set_sampling(&mut self, sampling: MemorySampling) {
+        self.sampling = sampling;
+        self.skipped = 0;
+    }
+
+    /// Total allocated bytes and whether they were just read, or reused
+    /// from the last read.
+    pub fn sample(&mut self, now: crate::Instant) -> (u64, bool) {
+        let last = match self.last {
+            Some(last) => last,
+            None => {
+                let allocated = total_allocated();
+                self.last = Some((allocated, now));
+                return (allocated, true);
+            }
+        };
+
+        let sample = match self.sampling {
+            MemorySampling::EveryAction => true,
+            MemorySampling::EveryN(n) => {
+                if self.skipped + 1 >= n {
+                    self.skipped = 0;
+                    true
+                } else {
+                    self.skipped += 1;
+                    false
+                }
+            }
+            MemorySampling::Every(interval) => now.saturating_duration_since(last.1) >= interval,
+        };
+
+        if !sample {
+            return (last.0, false);
+        }
+        let allocated = total_allocated();
+        self.last = Some((allocated, now));
+        (allocated, true)
+    }
+}
+
+#[cfg(feature = "memory-counter")]
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+#[cfg(feature = "memory-counter")]
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Global allocator wrapper counting allocated bytes, for allocation
+/// tracking without jemalloc.
+///
+/// Must be installed as `#[global_allocator]`, otherwise the store sees
+/// no allocations at all.
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, BoundedRecorder, CountingAllocator, EnablingCondition, Store};
+/// use std::alloc::System;
+///
+/// #[global_allocator]
+/// static GLOBAL: CountingAllocator<System> = CountingAllocator::new(System);
+///
+/// #[derive(Clone)]
+/// struct Action;
+/// impl EnablingCondition<Vec<Vec<u8>>> for Action {}
+///
+/// fn reducer(state: &mut Vec<Vec<u8>>, _: &ActionWithMeta<Action>) {
+///     state.push(vec![0; 4096]);
+/// }
+///
+/// fn main() {
+///     let mut store = Store::simple(reducer, |_, _| {}, Vec::with_capacity(2));
+///     store.set_recorder(Some(Box::new(BoundedRecorder::new(2))));
+///
+///     store.dispatch(Action);
+///     store.dispatch(Action);
+///
+///     let allocated: Vec<_> = store.recent_actions().map(|a| a.total_allocated).collect();
+///     assert!(allocated[1] >= allocated[0] + 4096);
+///     assert!(GLOBAL.allocations() > 0);
+/// }
+/// ```
+///
+/// Dispatch itself doesn't allocate when the action produces no
+/// follow-ups, even with breakpoints being hit:
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, Breakpoint, CountingAllocator, EnablingCondition, Store};
+/// use std::alloc::System;
+///
+/// #[global_allocator]
+/// static GLOBAL: CountingAllocator<System> = CountingAllocator::new(System);
+///
+/// struct Add(u64);
+/// impl EnablingCondition<u64> for Add {}
+///
+/// fn reducer(state: &mut u64, action: &ActionWithMeta<Add>) {
+///     *state += action.action.0;
+/// }
+///
+/// fn main() {
+///     let mut store = Store::simple(reducer, |_, _| {}, 0);
+///     for _ in 0..4 {
+///         store.add_breakpoint(Breakpoint::State(|state| *state >= 10));
+///     }
+///     // Warm up.
+///     store.dispatch(Add(1));
+///
+///     let before = GLOBAL.allocations();
+///     store.dispatch(Add(1));
+///     store.dispatch(Add(10));
+///     assert_eq!(GLOBAL.allocations(), before);
+///     assert!(store.is_paused());
+/// }
+/// ```
+///
+/// Enforced for 10 000 actions below, covering the buffers preallocated
+/// with the [crate::StoreBuilder] capacity knobs (see its
+/// "Allocations" section for what voids the guarantee):
+///
+/// ```
+/// use redux_rs::{
+///     ActionWithMeta, Breakpoint, CountingAllocator, EnablingCondition, Interception,
+///     RingRecorder, StoreBuilder,
+/// };
+/// use std::alloc::System;
+///
+/// #[global_allocator]
+/// static GLOBAL: CountingAllocator<System> = CountingAllocator::new(System);
+///
+/// const ACTIONS: u64 = 10_000;
+///
+/// #[derive(Clone)]
+/// struct Add(u64);
+/// impl EnablingCondition<u64> for Add {}
+///
+/// fn reducer(state: &mut u64, action: &ActionWithMeta<Add>) {
+///     *state += action.action.0;
+/// }
+///
+/// fn capture_odd(_: &u64, action: &ActionWithMeta<Add>) -> Interception {
+///     match action.action.0 % 2 {
+///         0 => Interception::Proceed,
+///         _ => Interception::Capture,
+///     }
+/// }
+///
+/// fn main() {
+///     let mut store = StoreBuilder::new()
+///         .reducer(reducer)
+///         .service(())
+///         .initial_state(0)
+///         .recorder(Box::new(RingRecorder::<Add, 16>::new()))
+///         .dispatch_interceptor(capture_odd)
+///         .paused_queue_capacity(ACTIONS as usize)
+///         .intercepted_capacity(ACTIONS as usize)
+///         .breakpoint_capacity(1)
+///         .build()
+///         .unwrap();
+///     store.add_breakpoint(Breakpoint::State(|state| *state == 200));
+///     // Warm up.
+///     store.dispatch(Add(0));
+///
+///     let before = GLOBAL.allocations();
+///     for i in 0..ACTIONS {
+///         store.dispatch(Add(i % 4));
+///     }
+///     assert_eq!(GLOBAL.allocations(), before);
+///
+///     // Breakpoint paused the store half way, the rest got queued.
+///     assert!(store.is_paused());
+///     assert_eq!(*store.state(), 200);
+///     assert_eq!(store.intercepted().len(), 199);
+///     assert_eq!(store.paused_actions() as u64, ACTIONS - 399);
+/// }
+/// ```
+#[cfg(feature = "memory-counter")]
+pub struct CountingAllocator<A> {
+    inner: A,
+}
+
+#[cfg(feature = "memory-counter")]
+impl<A> CountingAllocator<A> {
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+
+    /// Bytes currently allocated.
+    pub fn allocated(&self) -> usize {
+        ALLOCATED.load(Ordering::Relaxed)
+    }
+
+    /// Number of allocations made so far, including reallocations.
+    pub fn allocations(&self) -> usize {
+        ALLOCATIONS.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(feature = "memory-counter")]
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        if !ptr.is_null() {
+            ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.inner.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            if new_size > layout.size() {
+                ALLOCATED.fetch_add(new_size - layout.size(), Ordering::Relaxed);
+            } else {
+                ALLOCATED.fetch_sub(layout.size() - new_size, Ordering::Relaxed);
+            }
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        new_ptr
+    }
+}
+
+/// Thresholds of the [MemoryDetector]. `None` disables the check.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryThresholds {
+    /// Alarm when allocated bytes rise above this value.
+    pub peak: Option<u64>,
+    /// Alarm when allocated bytes haven't decreased for this many
+    /// consecutive actions, while growing overall.
+    pub growth_window: Option<u32>,
+}
+
+/// Threshold crossed by allocated memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryAlarm {
+    /// Allocated bytes rose above [MemoryThresholds::peak].
+    PeakExceeded { threshold: u64 },
+    /// Allocated bytes grew by `grown_by` over last `actions` actions,
+    /// without ever decreasing.
+    MonotonicGrowth { actions: u32, grown_by: u64 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryReport {
+    pub alarm: MemoryAlarm,
+    /// Allocated bytes when the alarm fired.
+    pub allocated: u64,
+    /// Highest allocated bytes seen so far.
+    pub peak: u64,
+}
+
+/// Function signature for a hook called when a [MemoryAlarm] fires.
+///
+/// Gets mutable store so that it can dispatch an action in response,
+/// e.g. to evict caches.
+pub type MemoryAlarmHook<State, Service, Action> =
+    fn(&mut crate::Store<State, Service, Action>, &MemoryReport);
+
+/// Detects peaks and leak-like growth of allocated memory.
+///
+/// Fed with allocated bytes sampled at every dispatched action, when
+/// set with `Store::set_memory_alarm`. Each alarm fires once per
+/// crossing: peak alarm re-arms after allocated bytes drop back to the
+/// threshold, growth alarm after another full window.
+///
+/// ```
+/// use redux_rs::{MemoryAlarm, MemoryDetector, MemoryThresholds};
+///
+/// let mut detector = MemoryDetector::new(MemoryThresholds {
+///     peak: Some(1000),
+///     growth_window: Some(3),
+/// });
+///
+/// let alarms: Vec<_> = [100, 200, 150, 160, 170, 180, 1200, 1300, 900, 1100]
+///     .iter()
+///     .map(|allocated| detector.observe(*allocated).map(|report| report.alarm))
+///     .collect();
+///
+/// assert_eq!(
+///     alarms,
+///     vec![
+///         None,
+///         None,
+///         None,
+///         None,
+///         None,
+///         Some(MemoryAlarm::MonotonicGrowth { actions: 3, grown_by: 30 }),
+///         Some(MemoryAlarm::PeakExceeded { threshold: 1000 }),
+///         None,
+///         None,
+///         Some(MemoryAlarm::PeakExceeded { threshold: 1000 }),
+///     ]
+/// );
+/// assert_eq!(detector.peak(), 1300);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MemoryDetector {
+    thresholds: MemoryThresholds,
+    peak: u64,
+    last: Option<u64>,
+    /// Allocated bytes at the start of the current growth streak.
+    streak_start: u64,
+    /// Number of actions in the current growth streak.
+    streak: u32,
+}
+
+impl MemoryDetector {
+    pub fn new(thresholds: MemoryThresholds) -> Self {
+        Self {
+            thresholds,
+            peak: 0,
+            last: None,
+            streak_start: 0,
+            streak: 0,
+        }
+    }
+
+    #[inline(always)]
+    pub fn thresholds(&self) -> MemoryThresholds {
+        self.thresholds
+    }
+
+    /// Change thresholds, keeping peak and current growth streak.
+    pub fn set_thresholds(&mut self, thresholds: MemoryThresholds) {
+        self.thresholds = thresholds;
+    }
+
+    /// Highest allocated bytes observed so far.
+    #[inline(always)]
+    pub fn peak(&self) -> u64 {
+        self.peak
+    }
+
+    /// Observe allocated bytes, returning a report if an alarm fired.
+    ///
+    /// If both alarms fire at once, peak alarm is returned.
+    pub fn observe(&mut self, allocated: u64) -> Option<MemoryReport> {
+        let last = self.last.replace(allocated);
+        self.peak = self.peak.max(allocated);
+
+        let growth = match last {
+            Some(last) if allocated >= last => {
+                self.streak += 1;
+                self.thresholds
+                    .growth_window
+                    .filter(|window| self.streak >= *window)
+                    .and_then(|window| {
+                        let grown_by = allocated - self.streak_start;
+                        // Fires again only after another full window.
+                        self.streak = 0;
+                        self.streak_start = allocated;
+                        Some(MemoryAlarm::MonotonicGrowth {
+                            actions: window,
+                            grown_by,
+                        })
+                        .filter(|_| grown_by > 0)
+                    })
+            }
+            _ => {
+                self.streak = 0;
+                self.streak_start = allocated;
+                None
+            }
+        };
+
+        let peak = self.thresholds.peak.and_then(|threshold| {
+            let was_above = last.map_or(false, |last| last > threshold);
+            Some(MemoryAlarm::PeakExceeded { threshold })
+                .filter(|_| allocated > threshold && !was_above)
+        });
+
+        peak.or(growth).map(|alarm| MemoryReport {
+            alarm,
+            allocated,
+            peak: self.peak,
+        })
+    }
+}
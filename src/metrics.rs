@@ -0,0 +1,182 @@
+use prometheus::{
+    exponential_buckets, Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts,
+    Registry, TextEncoder,
+};
+
+use crate::DispatchTimings;
+
+/// Prometheus metrics of the store internals.
+///
+/// Attach to the store with `Store::set_metrics`, together with a
+/// classifier which maps actions to the `kind` label. Label cardinality
+/// is bounded by the number of kinds the classifier returns.
+///
+/// Store only updates the metrics, exposing them (e.g. over HTTP) is up
+/// to the user, using [StoreMetrics::gather] or [StoreMetrics::registry].
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EnablingCondition, Store, StoreMetrics};
+///
+/// enum Action {
+///     Small,
+///     Big,
+/// }
+///
+/// impl EnablingCondition<()> for Action {
+///     fn is_enabled(&self, _: &()) -> bool {
+///         matches!(self, Action::Small)
+///     }
+/// }
+///
+/// fn classify(action: &Action) -> &'static str {
+///     match action {
+///         Action::Small => "Small",
+///         Action::Big => "Big",
+///     }
+/// }
+///
+/// let mut store = Store::simple(|_: &mut (), _: &ActionWithMeta<Action>| {}, |_, _| {}, ());
+/// store.set_metrics(StoreMetrics::new().unwrap(), classify);
+///
+/// store.dispatch(Action::Small);
+/// store.dispatch(Action::Small);
+/// store.dispatch(Action::Big);
+///
+/// let text = store.metrics().unwrap().gather();
+/// assert!(text.contains(r#"redux_actions_total{kind="Small"} 2"#));
+/// assert!(text.contains(r#"redux_actions_rejected_total{kind="Big"} 1"#));
+/// ```
+///
+/// Pending work is exported as gauges, updated as actions are queued
+/// and processed:
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EnablingCondition, Store, StoreMetrics};
+///
+/// struct Action(usize);
+/// impl EnablingCondition<()> for Action {}
+///
+/// fn effects(store: &mut Store<(), (), Action>, action: &ActionWithMeta<Action>) {
+///     let text = store.metrics().unwrap().gather();
+///     let waiting = format!("redux_paused_actions {}", 2 - action.action.0);
+///     assert!(text.contains(&waiting));
+/// }
+///
+/// let mut store = Store::simple(|_, _| {}, effects, ());
+/// store.set_metrics(StoreMetrics::new().unwrap(), |_| "Action");
+/// store.pause();
+/// for n in 0..3 {
+///     store.dispatch(Action(n));
+/// }
+/// assert!(store.metrics().unwrap().gather().contains("redux_paused_actions 3"));
+///
+/// store.resume();
+/// let text = store.metrics().unwrap().gather();
+/// assert!(text.contains("redux_paused_actions 0"));
+/// assert!(text.contains("redux_pending_timers 0"));
+/// ```
+#[derive(Clone)]
+pub struct StoreMetrics {
+    registry: Registry,
+    actions: IntCounterVec,
+    rejected: IntCounterVec,
+    reducer_duration: HistogramVec,
+    effects_duration: HistogramVec,
+    paused_actions: IntGauge,
+    pending_timers: IntGauge,
+}
+
+impl StoreMetrics {
+    /// Create metrics registered in a new registry.
+    pub fn new() -> prometheus::Result<Self> {
+        Self::with_registry(Registry::new())
+    }
+
+    /// Create metrics registered in the given registry.
+    pub fn with_registry(registry: Registry) -> prometheus::Result<Self> {
+        let actions = IntCounterVec::new(
+            Opts::new("redux_actions_total", "Number of processed actions."),
+            &["kind"],
+        )?;
+        let rejected = IntCounterVec::new(
+            Opts::new(
+                "redux_actions_rejected_total",
+                "Number of actions rejected by enabling condition or interceptor.",
+            ),
+            &["kind"],
+        )?;
+        // From 1us to ~4s.
+        let buckets = exponential_buckets(1e-6, 4.0, 12)?;
+        let reducer_duration = HistogramVec::new(
+            HistogramOpts::new("redux_reducer_duration_seconds", "Reducer duration.")
+                .buckets(buckets.clone()),
+            &["kind"],
+        )?;
+        let effects_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "redux_effects_duration_seconds",
+                "Effects duration, including processing of actions they dispatched.",
+            )
+            .buckets(buckets),
+            &["kind"],
+        )?;
+        let paused_actions = IntGauge::new(
+            "redux_paused_actions",
+            "Number of actions queued while paused, including ones left to process while resuming.",
+        )?;
+        let pending_timers = IntGauge::new(
+            "redux_pending_timers",
+            "Number of timers which didn't fire yet.",
+        )?;
+
+        registry.register(Box::new(actions.clone()))?;
+        registry.register(Box::new(rejected.clone()))?;
+        registry.register(Box::new(reducer_duration.clone()))?;
+        registry.register(Box::new(effects_duration.clone()))?;
+        registry.register(Box::new(paused_actions.clone()))?;
+        registry.register(Box::new(pending_timers.clone()))?;
+
+        Ok(Self {
+            registry,
+            actions,
+            rejected,
+            reducer_duration,
+            effects_duration,
+            paused_actions,
+            pending_timers,
+        })
+    }
+
+    #[inline(always)]
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Metrics encoded in the prometheus text exposition format.
+    pub fn gather(&self) -> String {
+        let mut buffer = Vec::new();
+        // Encoding into `Vec` can only fail on invalid metric families,
+        // which we never register.
+        let _ = TextEncoder::new().encode(&self.registry.gather(), &mut buffer);
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+
+    pub(crate) fn observe_processed(&self, kind: &str, timings: &DispatchTimings) {
+        self.actions.with_label_values(&[kind]).inc();
+        self.reducer_duration
+            .with_label_values(&[kind])
+            .observe(timings.reducer.as_secs_f64());
+        self.effects_duration
+            .with_label_values(&[kind])
+            .observe(timings.effects.as_secs_f64());
+    }
+
+    pub(crate) fn observe_rejected(&self, kind: &str) {
+        self.rejected.with_label_values(&[kind]).inc();
+    }
+
+    pub(crate) fn set_pending(&self, paused_actions: usize, pending_timers: usize) {
+        self.paused_actions.set(paused_actions as i64);
+        self.pending_timers.set(pending_timers as i64);
+    }
+}
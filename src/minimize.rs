@@ -0,0 +1,185 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::effects::no_effects;
+use crate::{ActionId, EnablingCondition, Reducer, SafetyCondition, Store, TimeService};
+
+/// Progress of the [minimize] run, passed to the progress callback.
+#[derive(Debug, Clone, Copy)]
+pub struct MinimizeProgress {
+    /// Number of candidate sequences executed so far.
+    pub runs: usize,
+    /// Length of the smallest failing sequence found so far.
+    pub current_len: usize,
+    /// Current granularity (number of chunks the sequence is split into).
+    pub granularity: usize,
+}
+
+struct MinimizeService;
+
+/// Clock doesn't influence reducers, so it can be constant.
+impl TimeService for MinimizeService {
+    #[cfg(any(not(feature = "std"), target_arch = "wasm32"))]
+    fn monotonic_time(&mut self) -> crate::Instant {
+        crate::Instant::default()
+    }
+}
+
+/// Runs `actions` on a fresh store and returns `true` if safety
+/// condition got violated after any of the actions.
+///
+/// Actions which aren't enabled are skipped, just like in a real store.
+fn violates<State, Action>(
+    initial_state: &State,
+    reducer: Reducer<State, Action>,
+    actions: &[Action],
+) -> bool
+where
+    State: Clone + SafetyCondition,
+    Action: Clone + EnablingCondition<State>,
+{
+    let mut store = Store::new_with_initial_id(
+        reducer,
+        no_effects,
+        MinimizeService,
+        ActionId::ZERO,
+        initial_state.clone(),
+    );
+
+    actions.iter().any(|action| {
+        store.dispatch(action.clone());
+        store.state().check_safety_condition().is_err()
+    })
+}
+
+/// Find the minimal subsequence of `actions` which still violates
+/// [SafetyCondition], using delta debugging (ddmin).
+///
+/// Returns `None` if `actions` don't violate the safety condition in
+/// the first place.
+///
+/// Every candidate is executed from `initial_state` with the given
+/// reducer (effects aren't executed), so this can be slow for long
+/// sequences. `progress` is called after every executed candidate.
+///
+/// # Example
+///
+/// ```
+/// use redux_rs::{minimize, ActionWithMeta, EnablingCondition, SafetyCondition};
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// enum Action {
+///     Noise(u32),
+///     Lock,
+///     Unlock,
+/// }
+///
+/// impl EnablingCondition<State> for Action {}
+///
+/// #[derive(Debug, Clone, Default)]
+/// struct State {
+///     locked: bool,
+///     unlocked_while_locked: bool,
+/// }
+///
+/// impl SafetyCondition for State {
+///     type Error = ();
+///
+///     fn check_safety_condition(&self) -> Result<(), ()> {
+///         if self.unlocked_while_locked {
+///             Err(())
+///         } else {
+///             Ok(())
+///         }
+///     }
+/// }
+///
+/// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+///     match &action.action {
+///         Action::Noise(_) => {}
+///         Action::Lock => state.locked = true,
+///         Action::Unlock => state.unlocked_while_locked = state.locked,
+///     }
+/// }
+///
+/// let mut actions: Vec<_> = (0..1000).map(Action::Noise).collect();
+/// actions[100] = Action::Lock;
+/// actions[900] = Action::Unlock;
+///
+/// let minimal = minimize(&State::default(), reducer, &actions, |_| {});
+/// assert_eq!(minimal, Some(vec![Action::Lock, Action::Unlock]));
+/// ```
+pub fn minimize<State, Action, F>(
+    initial_state: &State,
+    reducer: Reducer<State, Action>,
+    actions: &[Action],
+    mut progress: F,
+) -> Option<Vec<Action>>
+where
+    State: Clone + SafetyCondition,
+    Action: Clone + EnablingCondition<State>,
+    F: FnMut(MinimizeProgress),
+{
+    let mut runs = 0;
+    let mut test = |candidate: &[Action], current_len: usize, granularity: usize| {
+        let result = violates(initial_state, reducer, candidate);
+        runs += 1;
+        progress(MinimizeProgress {
+            runs,
+            current_len,
+            granularity,
+        });
+        result
+    };
+
+    if !test(actions, actions.len(), 1) {
+        return None;
+    }
+
+    let mut current = actions.to_vec();
+    let mut granularity = 2;
+
+    while current.len() >= 2 {
+        let len = current.len();
+        let chunk_len = len.div_ceil(granularity);
+        let chunks: Vec<_> = (0..len).step_by(chunk_len).collect();
+        let mut reduced = false;
+
+        for &start in &chunks {
+            let end = (start + chunk_len).min(len);
+            let subset = &current[start..end];
+            if test(subset, len, granularity) {
+                current = subset.to_vec();
+                granularity = 2;
+                reduced = true;
+                break;
+            }
+        }
+
+        if !reduced && granularity > 2 {
+            for &start in &chunks {
+                let end = (start + chunk_len).min(len);
+                let complement: Vec<_> = current[..start]
+                    .iter()
+                    .chain(&current[end..])
+                    .cloned()
+                    .collect();
+                if test(&complement, len, granularity) {
+                    current = complement;
+                    granularity = (granularity - 1).max(2);
+                    reduced = true;
+                    break;
+                }
+            }
+        }
+
+        if !reduced {
+            if granularity >= len {
+                break;
+            }
+            granularity = (granularity * 2).min(len);
+        }
+    }
+
+    Some(current)
+}
@@ -0,0 +1,458 @@
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::any::TypeId;
+use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+use crate::{ActionWithMeta, EnablingCondition, ScopedStore, Store, StoreLike, TimeService};
+
+/// Self-contained part of the state machine: its part of the state,
+/// its actions, reducer and effects.
+///
+/// Modules know nothing about the application embedding them, they
+/// are wired into it with [ModuleRegistry::register].
+pub trait Module {
+    type SubState;
+    type SubAction;
+
+    /// Sub-state the module starts with, see [crate::StoreBuilder::modules].
+    fn initial_state() -> Self::SubState;
+
+    /// Called for actions of the module, after the root reducer.
+    fn reducer(state: &mut Self::SubState, action: &ActionWithMeta<&Self::SubAction>);
+
+    /// Called for actions of the module, after the root effects. Does
+    /// nothing by default.
+    fn effects<Parent: StoreLike>(
+        _store: &mut ScopedStore<'_, Parent, Self::SubState, Self::SubAction>,
+        _action: &ActionWithMeta<&Self::SubAction>,
+    ) {
+    }
+}
+
+/// Where the state of a module lives in the application state, see
+/// [crate::lens].
+pub struct Lens<State, SubState> {
+    pub get: fn(&State) -> &SubState,
+    pub get_mut: fn(&mut State) -> &mut SubState,
+}
+
+impl<State, SubState> Clone for Lens<State, SubState> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<State, SubState> Copy for Lens<State, SubState> {}
+
+/// How actions of a module are embedded in the application action, see
+/// [crate::embed].
+pub struct Embed<Action, SubAction> {
+    pub wrap: fn(SubAction) -> Action,
+    /// Action of the module, `None` for actions of other modules.
+    pub project: fn(&Action) -> Option<&SubAction>,
+}
+
+impl<Action, SubAction> Clone for Embed<Action, SubAction> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Action, SubAction> Copy for Embed<Action, SubAction> {}
+
+/// [Lens] to a field of the state, e.g. `lens!(peers)` or
+/// `lens!(network.peers)`.
+#[macro_export]
+macro_rules! lens {
+    ($($field:tt).+) => {
+        $crate::Lens {
+            get: |state| &state.$($field).+,
+            get_mut: |state| &mut state.$($field).+,
+        }
+    };
+}
+
+/// [Embed] of module actions as a single field variant of the
+/// application action, e.g. `embed!(Action::Peer)`.
+#[macro_export]
+macro_rules! embed {
+    ($variant:path) => {
+        $crate::Embed {
+            wrap: $variant,
+            project: |action| match action {
+                $variant(action) => Some(action),
+                #[allow(unreachable_patterns)]
+                _ => None,
+            },
+        }
+    };
+}
+
+/// Id of a registered module, recorded in [ActionWithMeta::module] of
+/// its actions.
+///
+/// Registered modules are numbered from 1 in registration order, so
+/// ids are the same across runs registering the same modules in the
+/// same order. Actions of no module belong to [ModuleId::ROOT].
+#[derive(Debug, Default, Ord, PartialOrd, Eq, PartialEq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModuleId(u16);
+
+impl ModuleId {
+    pub const ROOT: Self = Self(0);
+
+    #[inline(always)]
+    fn from_index(index: usize) -> Self {
+        Self(index as u16 + 1)
+    }
+}
+
+impl From<ModuleId> for u16 {
+    fn from(id: ModuleId) -> Self {
+        id.0
+    }
+}
+
+/// Entry of the table of modules, see [ModuleRegistry::table].
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ModuleInfo {
+    pub id: ModuleId,
+    /// Type name of the module, `"root"` for [ModuleId::ROOT].
+    pub name: &'static str,
+}
+
+/// Module with its [Lens] and [Embed].
+struct Mounted<M: Module, State, Action> {
+    lens: Lens<State, M::SubState>,
+    embed: Embed<Action, M::SubAction>,
+    _module: PhantomData<fn() -> M>,
+}
+
+impl<M: Module, State, Action> Clone for Mounted<M, State, Action> {
+    fn clone(&self) -> Self {
+        Self {
+            lens: self.lens,
+            embed: self.embed,
+            _module: PhantomData,
+        }
+    }
+}
+
+pub(crate) trait ModuleReducer<State, Action> {
+    fn init(&self, state: &mut State);
+
+    fn reduce(&self, state: &mut State, action: &ActionWithMeta<Action>);
+}
+
+impl<M: Module, State, Action> ModuleReducer<State, Action> for Mounted<M, State, Action> {
+    fn init(&self, state: &mut State) {
+        *(self.lens.get_mut)(state) = M::initial_state();
+    }
+
+    fn reduce(&self, state: &mut State, action: &ActionWithMeta<Action>) {
+        if let Some(sub_action) = (self.embed.project)(&action.action) {
+            M::reducer((self.lens.get_mut)(state), &action.with_action(sub_action));
+        }
+    }
+}
+
+pub(crate) trait ModuleEffects<State, Service, Action> {
+    /// Type name of the module, see `Store::effects_order`.
+    fn name(&self) -> &'static str;
+
+    fn type_id(&self) -> TypeId;
+
+    /// Whether the action is one of the module's.
+    fn owns(&self, action: &Action) -> bool;
+
+    fn effects(&self, store: &mut Store<State, Service, Action>, action: &ActionWithMeta<Action>);
+}
+
+impl<M: Module + 'static, State, Service, Action> ModuleEffects<State, Service, Action>
+    for Mounted<M, State, Action>
+where
+    Service: TimeService,
+    Action: EnablingCondition<State>,
+{
+    fn name(&self) -> &'static str {
+        core::any::type_name::<M>()
+    }
+
+    fn type_id(&self) -> TypeId {
+        TypeId::of::<M>()
+    }
+
+    fn owns(&self, action: &Action) -> bool {
+        (self.embed.project)(action).is_some()
+    }
+
+    fn effects(&self, store: &mut Store<State, Service, Action>, action: &ActionWithMeta<Action>) {
+        if let Some(sub_action) = (self.embed.project)(&action.action) {
+            let mut scoped = store.scoped(self.lens.get, self.embed.wrap);
+            M::effects(&mut scoped, &action.with_action(sub_action));
+        }
+    }
+}
+
+pub(crate) type ModuleReducers<State, Action> =
+    Arc<[Box<dyn ModuleReducer<State, Action> + Send + Sync>]>;
+
+type ModuleEffectsList<State, Service, Action> =
+    Arc<[Box<dyn ModuleEffects<State, Service, Action> + Send + Sync>]>;
+
+/// Id of the first of the modules the action belongs to.
+#[inline(always)]
+pub(crate) fn module_of<State, Service, Action>(
+    effects: &[Box<dyn ModuleEffects<State, Service, Action> + Send + Sync>],
+    action: &Action,
+) -> ModuleId {
+    match effects.iter().position(|module| module.owns(action)) {
+        Some(index) => ModuleId::from_index(index),
+        None => ModuleId::ROOT,
+    }
+}
+
+/// [ModuleInfo]s of the modules, root first.
+pub(crate) fn table<State, Service, Action>(
+    effects: &[Box<dyn ModuleEffects<State, Service, Action> + Send + Sync>],
+) -> Vec<ModuleInfo> {
+    let root = ModuleInfo {
+        id: ModuleId::ROOT,
+        name: "root",
+    };
+    let modules = effects
+        .iter()
+        .enumerate()
+        .map(|(index, module)| ModuleInfo {
+            id: ModuleId::from_index(index),
+            name: module.name(),
+        });
+    core::iter::once(root).chain(modules).collect()
+}
+
+/// Id of module `M`, if registered.
+pub(crate) fn id_of<M: 'static, State, Service, Action>(
+    effects: &[Box<dyn ModuleEffects<State, Service, Action> + Send + Sync>],
+) -> Option<ModuleId> {
+    let type_id = TypeId::of::<M>();
+    let index = effects
+        .iter()
+        .position(|module| module.type_id() == type_id)?;
+    Some(ModuleId::from_index(index))
+}
+
+/// Runs reducers of the modules the action belongs to.
+#[inline(always)]
+pub(crate) fn reduce<State, Action>(
+    reducers: Option<&ModuleReducers<State, Action>>,
+    state: &mut State,
+    action: &ActionWithMeta<Action>,
+) {
+    for reducer in reducers.into_iter().flat_map(|reducers| reducers.iter()) {
+        reducer.reduce(state, action);
+    }
+}
+
+/// Modules of the store, composed into its reducer and effects.
+///
+/// Registered modules are passed to [crate::StoreBuilder::modules].
+/// For every action, after the root reducer (and effects), reducers
+/// (and effects) of the modules are called with the part of the
+/// state, and the action, belonging to them, if it does. Effects get a
+/// [ScopedStore], so they can only dispatch actions of their module.
+///
+/// ```
+/// use redux_rs::{
+///     embed, lens, ActionWithMeta, EnablingCondition, Module, ModuleRegistry, ScopedStore,
+///     StoreBuilder, StoreLike,
+/// };
+///
+/// struct Peers;
+///
+/// enum PeerAction {
+///     Connect(u16),
+///     Connected(u16),
+/// }
+///
+/// impl Module for Peers {
+///     type SubState = Vec<(u16, bool)>;
+///     type SubAction = PeerAction;
+///
+///     fn initial_state() -> Self::SubState {
+///         vec![]
+///     }
+///
+///     fn reducer(state: &mut Self::SubState, action: &ActionWithMeta<&PeerAction>) {
+///         match action.action {
+///             PeerAction::Connect(port) => state.push((*port, false)),
+///             PeerAction::Connected(port) => state.iter_mut().filter(|p| p.0 == *port).for_each(|p| p.1 = true),
+///         }
+///     }
+///
+///     fn effects<Parent: StoreLike>(
+///         store: &mut ScopedStore<'_, Parent, Self::SubState, PeerAction>,
+///         action: &ActionWithMeta<&PeerAction>,
+///     ) {
+///         if let PeerAction::Connect(port) = action.action {
+///             store.dispatch(PeerAction::Connected(*port));
+///         }
+///     }
+/// }
+///
+/// /// Has no effects.
+/// struct Counter;
+///
+/// impl Module for Counter {
+///     type SubState = u64;
+///     type SubAction = u64;
+///
+///     fn initial_state() -> u64 {
+///         100
+///     }
+///
+///     fn reducer(state: &mut u64, action: &ActionWithMeta<&u64>) {
+///         *state += *action.action;
+///     }
+/// }
+///
+/// #[derive(Default)]
+/// struct State {
+///     peers: Vec<(u16, bool)>,
+///     counter: u64,
+///     dispatched: u32,
+/// }
+///
+/// enum Action {
+///     Peer(PeerAction),
+///     Count(u64),
+/// }
+/// impl EnablingCondition<State> for Action {}
+///
+/// /// Root reducer only handles what's common to all actions.
+/// fn reducer(state: &mut State, _: &ActionWithMeta<Action>) {
+///     state.dispatched += 1;
+/// }
+///
+/// let mut modules = ModuleRegistry::<State, (), Action>::new();
+/// modules.register::<Peers>(lens!(peers), embed!(Action::Peer));
+/// modules.register::<Counter>(lens!(counter), embed!(Action::Count));
+///
+/// let mut store = StoreBuilder::new()
+///     .reducer(reducer)
+///     .service(())
+///     .initial_state(State::default())
+///     .modules(modules)
+///     .build()
+///     .unwrap();
+/// assert_eq!(store.state().counter, 100);
+///
+/// store.dispatch(Action::Peer(PeerAction::Connect(9732)));
+/// assert_eq!(store.state().peers, [(9732, true)]);
+/// assert_eq!(store.state().counter, 100);
+///
+/// store.dispatch(Action::Count(5));
+/// assert_eq!(store.state().peers, [(9732, true)]);
+/// assert_eq!(store.state().counter, 105);
+/// assert_eq!(store.state().dispatched, 3);
+/// ```
+pub struct ModuleRegistry<State, Service, Action> {
+    reducers: Vec<Box<dyn ModuleReducer<State, Action> + Send + Sync>>,
+    effects: Vec<Box<dyn ModuleEffects<State, Service, Action> + Send + Sync>>,
+}
+
+impl<State, Service, Action> ModuleRegistry<State, Service, Action> {
+    pub fn new() -> Self {
+        Self {
+            reducers: Vec::new(),
+            effects: Vec::new(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.reducers.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.reducers.is_empty()
+    }
+
+    /// Ids and names of the modules, for tooling reading recordings,
+    /// root first. See [ModuleId].
+    pub fn table(&self) -> Vec<ModuleInfo> {
+        table(&self.effects)
+    }
+
+    /// Id of module `M`, if registered.
+    pub fn id_of<M: Module + 'static>(&self) -> Option<ModuleId> {
+        id_of::<M, _, _, _>(&self.effects)
+    }
+}
+
+impl<State, Service, Action> ModuleRegistry<State, Service, Action>
+where
+    Service: TimeService,
+    Action: EnablingCondition<State>,
+{
+    /// Add module `M`, with its state at `lens` and its actions
+    /// embedded with `embed`. Modules are called in registration order,
+    /// and get their [ModuleId]s in it.
+    pub fn register<M>(
+        &mut self,
+        lens: Lens<State, M::SubState>,
+        embed: Embed<Action, M::SubAction>,
+    ) -> &mut Self
+    where
+        M: Module + 'static,
+        State: 'static,
+        Service: 'static,
+        Action: 'static,
+    {
+        let mounted = Mounted::<M, State, Action> {
+            lens,
+            embed,
+            _module: PhantomData,
+        };
+        self.reducers.push(Box::new(mounted.clone()));
+        self.effects.push(Box::new(mounted));
+        self
+    }
+}
+
+impl<State, Service, Action> Default for ModuleRegistry<State, Service, Action> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Registered modules, as kept by the store.
+pub(crate) struct Modules<State, Service, Action> {
+    pub reducers: ModuleReducers<State, Action>,
+    pub effects: ModuleEffectsList<State, Service, Action>,
+}
+
+impl<State, Service, Action> Modules<State, Service, Action> {
+    /// Set initial sub-states of the modules in `state`.
+    pub fn new(registry: ModuleRegistry<State, Service, Action>, state: &mut State) -> Self {
+        for reducer in &registry.reducers {
+            reducer.init(state);
+        }
+        Self {
+            reducers: registry.reducers.into(),
+            effects: registry.effects.into(),
+        }
+    }
+}
+
+impl<State, Service, Action> Clone for Modules<State, Service, Action> {
+    fn clone(&self) -> Self {
+        Self {
+            reducers: self.reducers.clone(),
+            effects: self.effects.clone(),
+        }
+    }
+}
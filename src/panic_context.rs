@@ -0,0 +1,173 @@
+use std::fmt;
+
+use crate::{ActionClassifier, ActionId, ActionWithMeta};
+
+/// Number of ids of previously processed actions in [PanicContext].
+pub const PANIC_CONTEXT_RECENT_IDS: usize = 8;
+
+/// What the store was doing when a reducer or effects panicked.
+///
+/// Reported to the sink set with `Store::set_panic_context_sink` while
+/// the panic unwinds through `Store::dispatch`, once per dispatch call
+/// being unwound, innermost first.
+#[derive(Debug, Clone, Copy)]
+pub struct PanicContext {
+    /// Action being processed.
+    pub action_id: ActionId,
+    /// Kind of the action, if `Store::set_panic_context_classifier` is set.
+    pub kind: Option<&'static str>,
+    pub depth: u32,
+    recent_ids: RecentIds,
+}
+
+impl PanicContext {
+    /// Ids of up to [PANIC_CONTEXT_RECENT_IDS] actions processed before
+    /// this one, oldest first.
+    pub fn recent_ids(&self) -> impl Iterator<Item = ActionId> + '_ {
+        let (tail, head) = self.recent_ids.ids.split_at(self.recent_ids.next);
+        // Unused slots hold `ActionId::ZERO`, which no action can have.
+        head.iter()
+            .chain(tail)
+            .copied()
+            .filter(|id| *id != ActionId::ZERO)
+    }
+}
+
+impl fmt::Display for PanicContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "panic while processing action {}",
+            u64::from(self.action_id)
+        )?;
+        if let Some(kind) = self.kind {
+            write!(f, " ({})", kind)?;
+        }
+        write!(f, " at depth {}, recent actions: [", self.depth)?;
+        for (i, id) in self.recent_ids().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", u64::from(id))?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// Function signature for where [PanicContext] gets reported.
+pub type PanicContextSink = fn(&PanicContext);
+
+/// Default [PanicContextSink], printing the context to stderr.
+pub fn eprint_panic_context(context: &PanicContext) {
+    eprintln!("redux-rs: {}", context);
+}
+
+/// Ids of last processed actions.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RecentIds {
+    ids: [ActionId; PANIC_CONTEXT_RECENT_IDS],
+    /// Index of the slot which will be written next.
+    next: usize,
+}
+
+impl Default for RecentIds {
+    fn default() -> Self {
+        Self {
+            ids: [ActionId::ZERO; PANIC_CONTEXT_RECENT_IDS],
+            next: 0,
+        }
+    }
+}
+
+impl RecentIds {
+    #[inline(always)]
+    pub fn push(&mut self, id: ActionId) {
+        self.ids[self.next] = id;
+        self.next = (self.next + 1) % PANIC_CONTEXT_RECENT_IDS;
+    }
+}
+
+/// Reports the context to the sink if dropped while panicking.
+pub(crate) struct PanicContextGuard {
+    context: PanicContext,
+    sink: PanicContextSink,
+}
+
+impl PanicContextGuard {
+    pub fn new(
+        action_id: ActionId,
+        kind: Option<&'static str>,
+        depth: u32,
+        recent_ids: RecentIds,
+        sink: PanicContextSink,
+    ) -> Self {
+        Self {
+            context: PanicContext {
+                action_id,
+                kind,
+                depth,
+                recent_ids,
+            },
+            sink,
+        }
+    }
+}
+
+impl Drop for PanicContextGuard {
+    #[inline(always)]
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            self.report();
+        }
+    }
+}
+
+impl PanicContextGuard {
+    #[cold]
+    #[inline(never)]
+    fn report(&self) {
+        (self.sink)(&self.context);
+    }
+}
+
+/// Panic context settings of a store and the ids it reports.
+pub(crate) struct PanicContextTracker<Action> {
+    pub sink: Option<PanicContextSink>,
+    pub classifier: Option<ActionClassifier<Action>>,
+    recent_ids: RecentIds,
+}
+
+impl<Action> PanicContextTracker<Action> {
+    pub fn new() -> Self {
+        Self {
+            sink: Some(eprint_panic_context),
+            classifier: None,
+            recent_ids: RecentIds::default(),
+        }
+    }
+
+    /// Guard reporting the context if processing of the action panics.
+    #[inline(always)]
+    pub fn guard(&mut self, action: &ActionWithMeta<Action>) -> Option<PanicContextGuard> {
+        let sink = self.sink?;
+        let guard = PanicContextGuard::new(
+            action.id,
+            self.classifier.map(|classifier| classifier(&action.action)),
+            action.depth,
+            self.recent_ids,
+            sink,
+        );
+        self.recent_ids.push(action.id);
+        Some(guard)
+    }
+}
+
+impl<Action> Clone for PanicContextTracker<Action> {
+    fn clone(&self) -> Self {
+        Self {
+            sink: self.sink,
+            classifier: self.classifier,
+            recent_ids: self.recent_ids,
+        }
+    }
+}
@@ -0,0 +1,284 @@
+#[cfg(not(feature = "std"))]
+use alloc::collections::VecDeque;
+#[cfg(feature = "std")]
+use std::collections::VecDeque;
+
+use crate::{CoalescePolicy, Coalescing, Instant, Provenance};
+
+/// Default capacity of the queue of actions dispatched while paused.
+pub const PAUSED_QUEUE_CAPACITY: usize = 1024;
+
+/// Result of [Paused::enqueue].
+pub(crate) enum Enqueued {
+    /// Action was added at the end of the queue.
+    Queued,
+    /// Action was coalesced with the queued one at the given index.
+    Coalesced(usize),
+    /// Queue was full, action got dropped.
+    Full,
+}
+
+/// Action in the queue of a paused store.
+#[derive(Clone)]
+pub(crate) struct QueuedAction<Action> {
+    pub action: Action,
+    /// Action is dropped instead of processed after this, see
+    /// `Store::dispatch_with_ttl`.
+    pub expires_at: Option<Instant>,
+    pub provenance: Provenance,
+}
+
+/// Actions dispatched while the store is paused.
+#[derive(Clone)]
+pub(crate) struct Paused<Action> {
+    queue: VecDeque<QueuedAction<Action>>,
+    capacity: usize,
+    /// Next dispatch is the action taken from the queue by
+    /// `Store::step_one`, so it has to be processed.
+    step_through: bool,
+    /// Actions dropped because the queue was full.
+    dropped: u64,
+}
+
+impl<Action> Paused<Action> {
+    /// `queue` must be empty, it's passed in so that its allocation
+    /// can be reused.
+    pub fn new(capacity: usize, queue: VecDeque<QueuedAction<Action>>) -> Self {
+        debug_assert!(queue.is_empty());
+        Self {
+            queue,
+            capacity,
+            step_through: false,
+            dropped: 0,
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+    }
+
+    /// Coalesces the action with a queued one if `coalescing` finds one
+    /// with the same key, otherwise queues it, if the queue isn't full.
+    ///
+    /// Merged action expires when the later of the two would, and keeps
+    /// provenance of the queued one.
+    pub fn enqueue(
+        &mut self,
+        action: Action,
+        expires_at: Option<Instant>,
+        provenance: Provenance,
+        coalescing: Option<&Coalescing<Action>>,
+    ) -> Enqueued {
+        let action = QueuedAction {
+            action,
+            expires_at,
+            provenance,
+        };
+        if let Some(coalescing) = coalescing {
+            if let Some(index) = self.find_same_key(&action.action, coalescing) {
+                match coalescing.policy {
+                    CoalescePolicy::KeepLatest => self.queue[index] = action,
+                    CoalescePolicy::KeepFirst => {}
+                    CoalescePolicy::Merge(merge) => {
+                        // Moving the queued action out and back in keeps
+                        // its position, without needing a placeholder.
+                        if let Some(queued) = self.queue.remove(index) {
+                            let expires_at = match (queued.expires_at, action.expires_at) {
+                                (Some(a), Some(b)) => Some(a.max(b)),
+                                _ => None,
+                            };
+                            let merged = QueuedAction {
+                                action: merge(queued.action, action.action),
+                                expires_at,
+                                provenance: queued.provenance,
+                            };
+                            self.queue.insert(index, merged);
+                        }
+                    }
+                }
+                return Enqueued::Coalesced(index);
+            }
+        }
+        if self.is_full() {
+            self.dropped += 1;
+            return Enqueued::Full;
+        }
+        self.queue.push_back(action);
+        Enqueued::Queued
+    }
+
+    fn find_same_key(&self, action: &Action, coalescing: &Coalescing<Action>) -> Option<usize> {
+        let key = (coalescing.key)(action)?;
+        self.queue
+            .iter()
+            .rposition(|queued| (coalescing.key)(&queued.action) == Some(key))
+    }
+
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    pub fn get(&self, index: usize) -> Option<&Action> {
+        self.queue.get(index).map(|queued| &queued.action)
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    #[cfg(feature = "log")]
+    #[inline(always)]
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    #[inline(always)]
+    pub fn is_full(&self) -> bool {
+        self.queue.len() >= self.capacity
+    }
+
+    #[inline(always)]
+    pub fn pop(&mut self) -> Option<QueuedAction<Action>> {
+        self.queue.pop_front()
+    }
+
+    /// Let the next dispatch through, for `Store::step_one`.
+    #[inline(always)]
+    pub fn step_through(&mut self) {
+        self.step_through = true;
+    }
+
+    /// Whether dispatch should process the action instead of queueing.
+    #[inline(always)]
+    pub fn take_step_through(&mut self) -> bool {
+        core::mem::take(&mut self.step_through)
+    }
+
+    pub fn into_queue(self) -> VecDeque<QueuedAction<Action>> {
+        self.queue
+    }
+}
+
+/// Pausing state of the store: queue of a paused store, along with what
+/// is kept between pauses.
+pub(crate) struct PauseQueue<Action> {
+    /// Set while paused.
+    paused: Option<Paused<Action>>,
+    /// Capacity of the queue used by `Store::pause`.
+    capacity: usize,
+    /// Empty queue kept between pauses, so that its allocation is
+    /// reused, see `Store::reserve_paused_queue`.
+    spare: VecDeque<QueuedAction<Action>>,
+    /// Coalescing of actions in the queue.
+    pub coalescing: Option<Coalescing<Action>>,
+    /// Actions left in queues taken by `Store::resume`, not processed yet.
+    pub draining: usize,
+}
+
+impl<Action> PauseQueue<Action> {
+    pub fn new() -> Self {
+        Self {
+            paused: None,
+            capacity: PAUSED_QUEUE_CAPACITY,
+            spare: VecDeque::new(),
+            coalescing: None,
+            draining: 0,
+        }
+    }
+
+    /// Actions queued while paused, or left to process while resuming.
+    #[cfg(any(feature = "stats", feature = "prometheus"))]
+    pub fn pending(&self) -> usize {
+        self.paused.as_ref().map_or(0, |paused| paused.len()) + self.draining
+    }
+
+    #[inline(always)]
+    pub fn paused(&self) -> Option<&Paused<Action>> {
+        self.paused.as_ref()
+    }
+
+    #[inline(always)]
+    pub fn paused_mut(&mut self) -> Option<&mut Paused<Action>> {
+        self.paused.as_mut()
+    }
+
+    #[inline(always)]
+    pub fn default_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Pause with queue of `capacity`, or change the capacity if
+    /// already paused. Returns whether it wasn't paused before.
+    pub fn pause(&mut self, capacity: usize) -> bool {
+        match self.paused.as_mut() {
+            Some(paused) => {
+                paused.set_capacity(capacity);
+                false
+            }
+            None => {
+                let queue = core::mem::take(&mut self.spare);
+                self.paused = Some(Paused::new(capacity, queue));
+                true
+            }
+        }
+    }
+
+    /// Stop pausing, returning the queued actions. Queue should be
+    /// given back with [PauseQueue::recycle] once drained.
+    pub fn unpause(&mut self) -> Option<VecDeque<QueuedAction<Action>>> {
+        self.paused.take().map(Paused::into_queue)
+    }
+
+    /// Keep the drained `queue` for the next pause, unless a later
+    /// pause took a bigger one in the meantime.
+    pub fn recycle(&mut self, queue: VecDeque<QueuedAction<Action>>) {
+        debug_assert!(queue.is_empty());
+        if queue.capacity() > self.spare.capacity() {
+            self.spare = queue;
+        }
+    }
+
+    /// See `Store::reserve_paused_queue`.
+    pub fn reserve(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.spare.reserve(capacity);
+    }
+
+    /// Whether the dispatched action must be queued, instead of
+    /// processed.
+    #[inline(always)]
+    pub fn queues_dispatch(&mut self) -> bool {
+        match self.paused.as_mut() {
+            Some(paused) => !paused.take_step_through(),
+            None => false,
+        }
+    }
+
+    /// [Paused::enqueue], with the configured coalescing. Must only be
+    /// called while paused.
+    pub fn enqueue(
+        &mut self,
+        action: Action,
+        expires_at: Option<Instant>,
+        provenance: Provenance,
+    ) -> Enqueued {
+        match self.paused.as_mut() {
+            Some(paused) => {
+                paused.enqueue(action, expires_at, provenance, self.coalescing.as_ref())
+            }
+            None => Enqueued::Full,
+        }
+    }
+}
+
+impl<Action: Clone> Clone for PauseQueue<Action> {
+    fn clone(&self) -> Self {
+        Self {
+            paused: self.paused.clone(),
+            capacity: self.capacity,
+            spare: VecDeque::with_capacity(self.spare.capacity()),
+            coalescing: self.coalescing,
+            // Queue being drained stays with the original.
+            draining: 0,
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{ActionId, Instant};
+
+/// Storage of the persisted state snapshot, see
+/// `Store::enable_persistence`.
+pub trait SnapshotSink: Send {
+    /// Replace the stored snapshot.
+    fn save(&mut self, snapshot: &[u8]) -> io::Result<()>;
+
+    /// Latest saved snapshot, `None` if nothing was saved yet.
+    fn load(&mut self) -> io::Result<Option<Vec<u8>>>;
+}
+
+/// How often the state is persisted.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Cadence {
+    /// After every n-th processed action.
+    EveryNActions(u64),
+    /// After the first action processed once given time passed since
+    /// the last save, measured with the store's [crate::TimeService].
+    Every(Duration),
+}
+
+pub struct PersistenceConfig {
+    pub every: Cadence,
+    pub sink: Box<dyn SnapshotSink>,
+}
+
+/// [SnapshotSink] keeping the snapshot in a file.
+///
+/// Snapshot is written to a temporary file next to it first, which
+/// then replaces the old one with a rename, so a crash while saving
+/// leaves the previous snapshot intact.
+#[derive(Debug, Clone)]
+pub struct FileSink {
+    path: PathBuf,
+    tmp_path: PathBuf,
+}
+
+impl FileSink {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+        let mut tmp_path = path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        Self {
+            path,
+            tmp_path: tmp_path.into(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Where the snapshot is written before being renamed to
+    /// [FileSink::path], `path` with `.tmp` appended.
+    #[inline(always)]
+    pub fn tmp_path(&self) -> &Path {
+        &self.tmp_path
+    }
+}
+
+impl SnapshotSink for FileSink {
+    fn save(&mut self, snapshot: &[u8]) -> io::Result<()> {
+        let mut file = fs::File::create(&self.tmp_path)?;
+        file.write_all(snapshot)?;
+        file.sync_all()?;
+        fs::rename(&self.tmp_path, &self.path)
+    }
+
+    fn load(&mut self) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(&self.path) {
+            Ok(snapshot) => Ok(Some(snapshot)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Saves snapshots at the configured cadence, with state serialization
+/// type erased.
+pub(crate) struct Persistence<State> {
+    every: Cadence,
+    sink: Box<dyn SnapshotSink>,
+    serialize: fn(&State, &mut Vec<u8>) -> io::Result<()>,
+    buf: Vec<u8>,
+    actions_since_save: u64,
+    last_save: Option<Instant>,
+    errors: u64,
+}
+
+impl<State> Persistence<State> {
+    pub fn new(config: PersistenceConfig) -> Self
+    where
+        State: Serialize,
+    {
+        Self {
+            every: config.every,
+            sink: config.sink,
+            serialize: serialize::<State>,
+            buf: Vec::new(),
+            actions_since_save: 0,
+            last_save: None,
+            errors: 0,
+        }
+    }
+
+    /// Called after the reducer of every action.
+    pub fn after_action(&mut self, state: &State, now: Instant, action_id: ActionId) {
+        self.actions_since_save += 1;
+        let due = match self.every {
+            Cadence::EveryNActions(n) => self.actions_since_save >= n,
+            Cadence::Every(period) => match self.last_save {
+                Some(last_save) => now.saturating_duration_since(last_save) >= period,
+                None => true,
+            },
+        };
+        if due {
+            self.save(state, now, action_id);
+        }
+    }
+
+    /// Save the state after the action `action_id`.
+    pub fn save(
+        &mut self,
+        state: &State,
+        now: Instant,
+        #[cfg_attr(not(feature = "log"), allow(unused_variables))] action_id: ActionId,
+    ) {
+        self.actions_since_save = 0;
+        self.last_save = Some(now);
+        self.buf.clear();
+        let result = (self.serialize)(state, &mut self.buf).and_then(|()| self.sink.save(&self.buf));
+        if let Err(_err) = result {
+            #[cfg(feature = "log")]
+            log::warn!(
+                target: crate::LOG_TARGET_PERSISTENCE,
+                "failed to save snapshot after action {}: {}",
+                u64::from(action_id),
+                _err
+            );
+            self.errors += 1;
+        }
+    }
+
+    #[inline(always)]
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    pub fn sink_mut(&mut self) -> &mut dyn SnapshotSink {
+        &mut *self.sink
+    }
+
+    pub fn into_sink(self) -> Box<dyn SnapshotSink> {
+        self.sink
+    }
+}
+
+fn serialize<State: Serialize>(state: &State, buf: &mut Vec<u8>) -> io::Result<()> {
+    serde_json::to_writer(buf, state)?;
+    Ok(())
+}
+
+/// Loads the latest snapshot from the sink, formatting errors, so that
+/// the builder doesn't need to be generic over them.
+pub(crate) fn load<State: DeserializeOwned>(
+    sink: &mut dyn SnapshotSink,
+) -> Result<Option<State>, String> {
+    let snapshot = match sink.load().map_err(|err| err.to_string())? {
+        Some(snapshot) => snapshot,
+        None => return Ok(None),
+    };
+    serde_json::from_slice(&snapshot)
+        .map(Some)
+        .map_err(|err| err.to_string())
+}
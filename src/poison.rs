@@ -0,0 +1,47 @@
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+use core::fmt;
+
+use crate::ActionId;
+
+/// Why the store got poisoned, see `Store::poison`.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum PoisonReason {
+    /// Poisoned explicitly with `Store::poison`.
+    Manual(String),
+    /// Reducer panicked while processing action `action_id`, see
+    /// `Store::set_poison_on_panic`.
+    ReducerPanic {
+        action_id: ActionId,
+        message: Option<String>,
+    },
+    /// Effects panicked while processing action `action_id`, see
+    /// `Store::set_poison_on_panic`.
+    EffectsPanic {
+        action_id: ActionId,
+        message: Option<String>,
+    },
+}
+
+impl fmt::Display for PoisonReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Manual(reason) => write!(f, "{}", reason),
+            Self::ReducerPanic { action_id, message } => {
+                write!(f, "reducer panicked at action {}", u64::from(*action_id))?;
+                write_message(f, message)
+            }
+            Self::EffectsPanic { action_id, message } => {
+                write!(f, "effects panicked at action {}", u64::from(*action_id))?;
+                write_message(f, message)
+            }
+        }
+    }
+}
+
+pub(crate) fn write_message(f: &mut fmt::Formatter<'_>, message: &Option<String>) -> fmt::Result {
+    match message {
+        Some(message) => write!(f, ": {}", message),
+        None => Ok(()),
+    }
+}
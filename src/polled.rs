@@ -0,0 +1,189 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::{EnablingCondition, Instant, Provenance, Store, TimeService};
+
+/// Service wrapping an event loop (mio, polling, ...), which drives the
+/// store with [Store::run_polled].
+pub trait PollableService: TimeService {
+    type Event;
+
+    /// Wait for events for up to `timeout`, or until there are some if
+    /// it's `None`, appending them to `events`.
+    ///
+    /// Expired timers of the service should be reported as events too.
+    fn poll(&mut self, timeout: Option<Duration>, events: &mut Vec<Self::Event>);
+
+    /// Deadline of the nearest pending timer, which bounds the timeout
+    /// of the next [PollableService::poll].
+    fn next_deadline(&self) -> Option<Instant> {
+        None
+    }
+}
+
+/// Result of [Store::run_polled].
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub struct PolledReport {
+    /// Calls of [PollableService::poll].
+    pub polls: u64,
+    /// Events returned by the service.
+    pub events: u64,
+    /// Events mapped to an action which was rejected by the store.
+    pub rejected: u64,
+    /// Store timers fired, see [Store::schedule_timer].
+    pub timers_fired: u64,
+}
+
+impl<State, Service, Action> Store<State, Service, Action>
+where
+    Service: PollableService,
+    Action: EnablingCondition<State>,
+{
+    /// Alternate between polling the service and dispatching actions
+    /// made from the events with `map_event` (events mapped to `None` are
+    /// skipped), until `stop` returns `true` for the state.
+    ///
+    /// Poll timeout is the time left until [PollableService::next_deadline]
+    /// or [Store::next_timer_deadline], whichever is earlier. Due store
+    /// timers fire after the events of every poll.
+    /// `stop` is checked before every poll, so all the events of a poll
+    /// get dispatched.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Instant, PollableService, Store, TimeService};
+    /// use std::time::Duration;
+    ///
+    /// #[derive(Debug)]
+    /// enum Event {
+    ///     Readable(u32),
+    ///     Timeout,
+    ///     Spurious,
+    /// }
+    ///
+    /// /// Scripted event loop, time passes only while waiting.
+    /// struct FakePoll {
+    ///     now: Instant,
+    ///     /// Events returned by consecutive polls.
+    ///     script: Vec<Vec<Event>>,
+    ///     timer: Option<Instant>,
+    ///     timeouts: Vec<Option<Duration>>,
+    /// }
+    ///
+    /// impl TimeService for FakePoll {
+    ///     fn monotonic_time(&mut self) -> Instant {
+    ///         self.now
+    ///     }
+    /// }
+    ///
+    /// impl PollableService for FakePoll {
+    ///     type Event = Event;
+    ///
+    ///     fn poll(&mut self, timeout: Option<Duration>, events: &mut Vec<Event>) {
+    ///         self.timeouts.push(timeout);
+    ///         match self.timer {
+    ///             Some(at) if self.script.is_empty() => {
+    ///                 self.now = at;
+    ///                 self.timer = None;
+    ///                 events.push(Event::Timeout);
+    ///             }
+    ///             _ => {
+    ///                 self.now += Duration::from_millis(10);
+    ///                 events.extend(self.script.remove(0));
+    ///             }
+    ///         }
+    ///     }
+    ///
+    ///     fn next_deadline(&self) -> Option<Instant> {
+    ///         self.timer
+    ///     }
+    /// }
+    ///
+    /// #[derive(Default)]
+    /// struct State {
+    ///     received: u32,
+    ///     timed_out: bool,
+    /// }
+    ///
+    /// enum Action {
+    ///     Received(u32),
+    ///     TimedOut,
+    /// }
+    ///
+    /// impl EnablingCondition<State> for Action {
+    ///     fn is_enabled(&self, state: &State) -> bool {
+    ///         !matches!(self, Action::Received(0))
+    ///     }
+    /// }
+    ///
+    /// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+    ///     match action.action {
+    ///         Action::Received(n) => state.received += n,
+    ///         Action::TimedOut => state.timed_out = true,
+    ///     }
+    /// }
+    ///
+    /// fn effects(store: &mut Store<State, FakePoll, Action>, action: &ActionWithMeta<Action>) {
+    ///     // Wait for at most 100ms since the first data.
+    ///     if let Action::Received(_) = action.action {
+    ///         let now = store.service.now;
+    ///         store.service.timer.get_or_insert(now + Duration::from_millis(100));
+    ///     }
+    /// }
+    ///
+    /// fn map_event(event: Event) -> Option<Action> {
+    ///     match event {
+    ///         Event::Readable(n) => Some(Action::Received(n)),
+    ///         Event::Timeout => Some(Action::TimedOut),
+    ///         Event::Spurious => None,
+    ///     }
+    /// }
+    ///
+    /// let service = FakePoll {
+    ///     now: Instant::now(),
+    ///     script: vec![vec![Event::Spurious], vec![Event::Readable(1), Event::Readable(0)], vec![Event::Readable(2)]],
+    ///     timer: None,
+    ///     timeouts: vec![],
+    /// };
+    /// let mut store = Store::new(reducer, effects, service, std::time::SystemTime::now(), State::default());
+    ///
+    /// let report = store.run_polled(map_event, |state| state.timed_out);
+    /// assert_eq!(store.state().received, 3);
+    /// assert_eq!((report.polls, report.events, report.rejected), (4, 5, 1));
+    ///
+    /// // No timer until data arrived, then the time left until it expires.
+    /// assert_eq!(
+    ///     store.service.timeouts,
+    ///     [None, None, Some(Duration::from_millis(100)), Some(Duration::from_millis(90))]
+    /// );
+    /// ```
+    pub fn run_polled(
+        &mut self,
+        map_event: fn(Service::Event) -> Option<Action>,
+        stop: fn(&State) -> bool,
+    ) -> PolledReport {
+        let mut report = PolledReport::default();
+        let mut events = Vec::new();
+        while !stop(self.state()) {
+            let deadline = match (self.service.next_deadline(), self.next_timer_deadline()) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+            let service = &mut self.service;
+            let timeout = deadline
+                .map(|deadline| deadline.saturating_duration_since(service.monotonic_time()));
+            self.service.poll(timeout, &mut events);
+            report.polls += 1;
+            for event in events.drain(..) {
+                report.events += 1;
+                if let Some(action) = map_event(event) {
+                    if !self.dispatch_from(action, Provenance::Bridge).is_accepted() {
+                        report.rejected += 1;
+                    }
+                }
+            }
+            report.timers_fired += self.fire_timers() as u64;
+        }
+        report
+    }
+}
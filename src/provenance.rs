@@ -0,0 +1,128 @@
+#[cfg(feature = "std")]
+use crate::RecurringId;
+use crate::{ActionId, TimerId};
+
+/// Where an action came from, see [crate::ActionWithMeta::provenance].
+///
+/// Filled in by the store: an action dispatched while another one is
+/// being processed is [Provenance::Effect] of it, whichever way the
+/// processed one itself came in. Other variants tag actions entering
+/// the store from the outside.
+///
+/// ```
+/// use redux_rs::{
+///     ActionWithMeta, EnablingCondition, FrozenTime, Instant, PendingActions, Provenance, Store,
+/// };
+/// use std::time::{Duration, SystemTime};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Action {
+///     Connect,
+///     Connected,
+///     Timeout,
+///     Retry,
+/// }
+///
+/// type State = Vec<ActionWithMeta<Action>>;
+///
+/// impl EnablingCondition<State> for Action {}
+///
+/// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+///     state.push(action.clone());
+/// }
+///
+/// fn effects(store: &mut Store<State, FrozenTime, Action>, action: &ActionWithMeta<Action>) {
+///     match action.action {
+///         Action::Connect => {
+///             store.dispatch(Action::Connected);
+///         }
+///         Action::Timeout => {
+///             store.dispatch(Action::Retry);
+///         }
+///         _ => {}
+///     }
+/// }
+///
+/// let service = FrozenTime::new(Instant::now());
+/// let mut store = Store::new(reducer, effects, service, SystemTime::UNIX_EPOCH, vec![]);
+///
+/// store.dispatch(Action::Connect);
+/// let timer = store.schedule_timer(Duration::from_secs(1), Action::Timeout);
+/// store.service.advance(Duration::from_secs(1));
+/// store.fire_timers();
+///
+/// let state = store.state();
+/// assert_eq!(state[0].provenance, Provenance::External);
+/// assert_eq!(state[1].provenance, Provenance::Effect { parent: state[0].id });
+/// assert_eq!(state[2].provenance, Provenance::Timer { id: timer });
+/// // Dispatched by effects of the timer's action, so not a timer's.
+/// assert_eq!(state[3].provenance, Provenance::Effect { parent: state[2].id });
+///
+/// // Queued while paused, keeping where they came from.
+/// store.pause();
+/// store.dispatch(Action::Connect);
+/// store.resume();
+/// let state = store.state();
+/// assert_eq!(state[4].provenance, Provenance::External);
+/// assert_eq!(state[5].provenance, Provenance::Effect { parent: state[4].id });
+///
+/// store.restore_pending(PendingActions {
+///     actions: vec![Action::Retry],
+/// });
+/// assert_eq!(store.state()[6].provenance, Provenance::Replay);
+/// ```
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Provenance {
+    /// Dispatched by the caller of the store, e.g. a service event loop.
+    #[default]
+    External,
+    /// Dispatched by effects (or a reducer, error action mapper, ...)
+    /// of the `parent` action.
+    Effect { parent: ActionId },
+    /// Fired by `Store::fire_timers`.
+    Timer { id: TimerId },
+    /// Fired by `Store::fire_timers` for a recurring action.
+    #[cfg(feature = "std")]
+    Recurring { id: RecurringId },
+    /// Fired by `Store::fire_timers` for the idle watchdog, see
+    /// `Store::set_idle_watchdog`.
+    #[cfg(feature = "std")]
+    Watchdog,
+    /// Re-dispatched from another store, see `Store::restore_pending`.
+    Replay,
+    /// Passed in by a driver bridging the store to an event source:
+    /// [crate::FrameDriver], `Store::run_stream`, `Store::run_polled`
+    /// or the C API.
+    Bridge,
+    /// Sent by another process or node: `RemoteDispatchServer`,
+    /// devtools, or another node of a [crate::Simulation].
+    Remote,
+}
+
+impl Provenance {
+    /// Short name of the variant, e.g. for labels.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::External => "external",
+            Self::Effect { .. } => "effect",
+            Self::Timer { .. } => "timer",
+            #[cfg(feature = "std")]
+            Self::Recurring { .. } => "recurring",
+            #[cfg(feature = "std")]
+            Self::Watchdog => "watchdog",
+            Self::Replay => "replay",
+            Self::Bridge => "bridge",
+            Self::Remote => "remote",
+        }
+    }
+
+    /// Action whose processing dispatched this one.
+    #[inline(always)]
+    pub fn parent(&self) -> Option<ActionId> {
+        match self {
+            Self::Effect { parent } => Some(*parent),
+            _ => None,
+        }
+    }
+}
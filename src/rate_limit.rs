@@ -0,0 +1,207 @@
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use crate::store::{DispatchFn, DispatchMapper};
+use crate::{ActionClassifier, Instant};
+
+/// Token bucket of a single action kind: holds up to `capacity` tokens,
+/// gets one back every `refill_every`, and every action of the kind
+/// takes one.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct RateLimit {
+    pub capacity: u32,
+    pub refill_every: Duration,
+}
+
+impl RateLimit {
+    pub const fn new(capacity: u32, refill_every: Duration) -> Self {
+        Self {
+            capacity,
+            refill_every,
+        }
+    }
+}
+
+/// Change of the state of a rate limited kind, see
+/// `Store::set_rate_limit_action`.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum RateLimitChange {
+    /// First action of the kind was dropped since it was last let
+    /// through.
+    Limited,
+    /// First action of the kind was let through again.
+    Recovered,
+}
+
+/// Function signature mapping changes of the state of a rate limited
+/// kind to an action to dispatch, `None` for no action.
+pub type RateLimitActionMapper<Action> = fn(&'static str, RateLimitChange) -> Option<Action>;
+
+#[derive(Debug, Clone)]
+struct Bucket {
+    limit: RateLimit,
+    tokens: u32,
+    /// Time not yet turned into tokens.
+    credit: Duration,
+    last: Option<Instant>,
+    limited: bool,
+    dropped: u64,
+}
+
+impl Bucket {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            tokens: limit.capacity,
+            credit: Duration::ZERO,
+            last: None,
+            limited: false,
+            dropped: 0,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        if let Some(last) = self.last {
+            self.credit += now.saturating_duration_since(last);
+        }
+        self.last = Some(now);
+
+        let refill_every = self.limit.refill_every.as_nanos();
+        let refilled = match refill_every {
+            0 => u128::from(self.limit.capacity),
+            _ => self.credit.as_nanos() / refill_every,
+        };
+        let missing = self.limit.capacity - self.tokens;
+        if refilled >= u128::from(missing) {
+            self.tokens = self.limit.capacity;
+            // Full bucket doesn't save up time.
+            self.credit = Duration::ZERO;
+        } else {
+            self.tokens += refilled as u32;
+            self.credit -= self.limit.refill_every * refilled as u32;
+        }
+    }
+}
+
+/// Per-kind rate limiting of dispatched actions, see
+/// `Store::set_rate_limiter`.
+///
+/// Every kind configured with [RateLimiter::limit] gets a token bucket,
+/// other kinds aren't limited. Time is taken from the service's clock,
+/// sampled as for action ids, so it's deterministic with a mock clock.
+#[derive(Debug, Clone)]
+pub struct RateLimiter<Action> {
+    classifier: ActionClassifier<Action>,
+    buckets: BTreeMap<&'static str, Bucket>,
+}
+
+impl<Action> RateLimiter<Action> {
+    pub fn new(classifier: ActionClassifier<Action>) -> Self {
+        Self {
+            classifier,
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    /// Limit actions of `kind`. Bucket starts full.
+    pub fn limit(mut self, kind: &'static str, limit: RateLimit) -> Self {
+        self.buckets.insert(kind, Bucket::new(limit));
+        self
+    }
+
+    /// Actions of `kind` dropped so far.
+    pub fn dropped(&self, kind: &str) -> u64 {
+        self.buckets.get(kind).map_or(0, |bucket| bucket.dropped)
+    }
+
+    /// Whether the last action of `kind` was dropped.
+    pub fn is_limited(&self, kind: &str) -> bool {
+        matches!(self.buckets.get(kind), Some(bucket) if bucket.limited)
+    }
+
+    /// Take a token for `action` dispatched at `now`, returning its
+    /// kind, whether it may go through and the change of the state of
+    /// the kind, if any.
+    pub(crate) fn check(
+        &mut self,
+        action: &Action,
+        now: Instant,
+    ) -> (&'static str, bool, Option<RateLimitChange>) {
+        let kind = (self.classifier)(action);
+        let bucket = match self.buckets.get_mut(kind) {
+            Some(bucket) => bucket,
+            None => return (kind, true, None),
+        };
+        bucket.refill(now);
+
+        if bucket.tokens > 0 {
+            bucket.tokens -= 1;
+            let change = bucket.limited.then_some(RateLimitChange::Recovered);
+            bucket.limited = false;
+            (kind, true, change)
+        } else {
+            bucket.dropped += 1;
+            let change = (!bucket.limited).then_some(RateLimitChange::Limited);
+            bucket.limited = true;
+            (kind, false, change)
+        }
+    }
+}
+
+/// Rate limiting of the store: the limiter and the mapper of its
+/// changes to actions, set independently.
+pub(crate) struct RateLimiting<Store, Action> {
+    pub limiter: Option<RateLimiter<Action>>,
+    pub change_action: Option<DispatchMapper<RateLimitActionMapper<Action>, Store, Action>>,
+}
+
+impl<Store, Action> RateLimiting<Store, Action> {
+    pub fn new() -> Self {
+        Self {
+            limiter: None,
+            change_action: None,
+        }
+    }
+
+    /// Kind which recovered by letting `action` through, if any, or
+    /// `Err` with its kind and change of the kind's state if it's
+    /// dropped.
+    #[inline(always)]
+    pub fn check(
+        &mut self,
+        action: &Action,
+        now: Instant,
+    ) -> Result<Option<&'static str>, (&'static str, Option<RateLimitChange>)> {
+        let (kind, allowed, change) = match self.limiter.as_mut() {
+            Some(limiter) => limiter.check(action, now),
+            None => return Ok(None),
+        };
+        if allowed {
+            Ok(change.map(|_| kind))
+        } else {
+            Err((kind, change))
+        }
+    }
+
+    /// Action the change maps to, with the function dispatching it.
+    pub fn change_action(
+        &self,
+        kind: &'static str,
+        change: RateLimitChange,
+    ) -> Option<(DispatchFn<Store, Action>, Action)> {
+        let (mapper, dispatch) = self.change_action?;
+        mapper(kind, change).map(|action| (dispatch, action))
+    }
+}
+
+impl<Store, Action: Clone> Clone for RateLimiting<Store, Action> {
+    fn clone(&self) -> Self {
+        Self {
+            limiter: self.limiter.clone(),
+            change_action: self.change_action,
+        }
+    }
+}
@@ -0,0 +1,398 @@
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, vec::Vec};
+use core::slice;
+
+use crate::{ActionId, ActionWithMeta, Capabilities, ModuleId, PayloadSize};
+
+/// Event in the store's life, other than a processed action, which
+/// matters for replaying the recording.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MetaEvent {
+    /// Reducer was replaced with `Store::replace_reducer` after the
+    /// action `after` was dispatched.
+    ReducerReplaced { after: ActionId },
+    /// Effects were replaced with `Store::replace_effects` after the
+    /// action `after` was dispatched.
+    EffectsReplaced { after: ActionId },
+}
+
+/// Information needed to replay a recording, see `Store::replay`.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordingHeader {
+    /// [crate::config_hash] of the store's configuration, if it has any.
+    pub config_hash: Option<u64>,
+    /// Whether payloads of `Lazy`s which weren't evaluated are missing,
+    /// see `Store::set_lazy_recording`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub lazy_payloads_skipped: bool,
+    /// Where the store was forked from, if it's a fork.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fork: Option<Fork>,
+    /// Seed of the store's [crate::SeededRandomness], if it's recorded,
+    /// see `Store::record_rng_seed`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub seed: Option<u64>,
+}
+
+/// Point a store was forked at, see `Store::fork`.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fork {
+    /// Id namespace of the fork, see [ActionWithMeta::namespace].
+    pub namespace: u64,
+    /// Id namespace of the store it was forked from.
+    pub parent_namespace: u64,
+    /// Last action processed before forking. Actions up to it are
+    /// shared with the parent.
+    pub after: ActionId,
+}
+
+/// Records actions processed by the store.
+///
+/// Set with `Store::set_recorder`. [Recorder::record] is called for every
+/// action which is about to be passed to the reducer, in processing order.
+pub trait Recorder<Action> {
+    fn record(&mut self, action: &ActionWithMeta<Action>);
+
+    /// Called once, before any action, when the recorder is set.
+    /// Ignored by default.
+    fn record_header(&mut self, _header: &RecordingHeader) {}
+
+    /// Called for [MetaEvent]s, in order with recorded actions. Ignored
+    /// by default.
+    fn record_meta(&mut self, _event: &MetaEvent) {}
+
+    /// Recently recorded actions kept in memory, oldest first.
+    ///
+    /// Recorders which don't keep actions in memory return nothing.
+    fn recent_actions(&self) -> RecentActions<'_, Action> {
+        RecentActions::empty()
+    }
+
+    /// What `Store::capabilities` reports while the recorder is set,
+    /// [Capabilities::RECORDER] by default.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::RECORDER
+    }
+
+    /// Counters of the scratch buffers used for serialization, for
+    /// recorders which pool them, like [crate::JsonLogger].
+    fn scratch_stats(&self) -> Option<crate::ScratchStats> {
+        None
+    }
+}
+
+/// Iterator over recently recorded actions, oldest first.
+pub struct RecentActions<'a, Action> {
+    head: slice::Iter<'a, Option<ActionWithMeta<Action>>>,
+    tail: slice::Iter<'a, Option<ActionWithMeta<Action>>>,
+}
+
+impl<'a, Action> RecentActions<'a, Action> {
+    pub fn empty() -> Self {
+        Self {
+            head: [].iter(),
+            tail: [].iter(),
+        }
+    }
+}
+
+impl<'a, Action> Iterator for RecentActions<'a, Action> {
+    type Item = &'a ActionWithMeta<Action>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.head
+            .by_ref()
+            .chain(self.tail.by_ref())
+            .find_map(|slot| slot.as_ref())
+    }
+}
+
+/// Fixed capacity ring buffer over slots `S`. Once full, oldest
+/// entry gets overwritten.
+#[derive(Clone)]
+struct Ring<S> {
+    slots: S,
+    /// Index of the slot which will be written next.
+    next: usize,
+}
+
+impl<S> Ring<S> {
+    fn push<T>(&mut self, item: T)
+    where
+        S: AsMut<[Option<T>]>,
+    {
+        let slots = self.slots.as_mut();
+        if slots.is_empty() {
+            return;
+        }
+        slots[self.next] = Some(item);
+        self.next = (self.next + 1) % slots.len();
+    }
+
+    fn iter<Action>(&self) -> RecentActions<'_, Action>
+    where
+        S: AsRef<[Option<ActionWithMeta<Action>>]>,
+    {
+        // Slots after `next` are either oldest entries or still empty.
+        let (tail, head) = self.slots.as_ref().split_at(self.next);
+        RecentActions {
+            head: head.iter(),
+            tail: tail.iter(),
+        }
+    }
+
+    fn payload_bytes<Action: PayloadSize>(&self) -> usize
+    where
+        S: AsRef<[Option<ActionWithMeta<Action>>]>,
+    {
+        self.iter().map(|action| action.action.payload_size()).sum()
+    }
+}
+
+/// Recorder keeping last `N` actions, without any heap allocation.
+///
+/// Usable without `std`.
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EnablingCondition, RingRecorder, Store};
+///
+/// #[derive(Clone)]
+/// struct Action(u8);
+/// impl EnablingCondition<()> for Action {}
+///
+/// let mut store = Store::simple(|_: &mut (), _: &ActionWithMeta<Action>| {}, |_, _| {}, ());
+/// store.set_recorder(Some(Box::new(RingRecorder::<Action, 3>::new())));
+///
+/// for i in 0..5 {
+///     store.dispatch(Action(i));
+/// }
+///
+/// let recent: Vec<_> = store.recent_actions().map(|a| a.action.0).collect();
+/// assert_eq!(recent, vec![2, 3, 4]);
+/// ```
+#[derive(Clone)]
+pub struct RingRecorder<Action, const N: usize> {
+    ring: Ring<[Option<ActionWithMeta<Action>>; N]>,
+}
+
+impl<Action, const N: usize> RingRecorder<Action, N> {
+    pub fn new() -> Self {
+        Self {
+            ring: Ring {
+                slots: core::array::from_fn(|_| None),
+                next: 0,
+            },
+        }
+    }
+
+    /// Sum of [PayloadSize]s of the kept actions. Payloads shared with
+    /// the state or other actions are counted in full.
+    pub fn payload_bytes(&self) -> usize
+    where
+        Action: PayloadSize,
+    {
+        self.ring.payload_bytes()
+    }
+}
+
+impl<Action, const N: usize> Default for RingRecorder<Action, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Action: Clone, const N: usize> Recorder<Action> for RingRecorder<Action, N> {
+    fn record(&mut self, action: &ActionWithMeta<Action>) {
+        self.ring.push(action.clone());
+    }
+
+    fn recent_actions(&self) -> RecentActions<'_, Action> {
+        self.ring.iter()
+    }
+}
+
+/// Recorder keeping last `capacity` actions, capacity chosen at runtime.
+///
+/// Memory for all of the entries is allocated once, on creation.
+#[derive(Clone)]
+pub struct BoundedRecorder<Action> {
+    ring: Ring<Box<[Option<ActionWithMeta<Action>>]>>,
+}
+
+impl<Action> BoundedRecorder<Action> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            ring: Ring {
+                slots: (0..capacity).map(|_| None).collect::<Vec<_>>().into(),
+                next: 0,
+            },
+        }
+    }
+
+    /// Sum of [PayloadSize]s of the kept actions. Payloads shared with
+    /// the state or other actions are counted in full.
+    ///
+    /// Kept actions only clone their payloads if they're [crate::Shared],
+    /// so this is how much data the recorder references, rather than
+    /// how much it allocated.
+    pub fn payload_bytes(&self) -> usize
+    where
+        Action: PayloadSize,
+    {
+        self.ring.payload_bytes()
+    }
+}
+
+impl<Action: Clone> Recorder<Action> for BoundedRecorder<Action> {
+    fn record(&mut self, action: &ActionWithMeta<Action>) {
+        self.ring.push(action.clone());
+    }
+
+    fn recent_actions(&self) -> RecentActions<'_, Action> {
+        self.ring.iter()
+    }
+}
+
+/// Recorder encoding every action into a reusable buffer and handing
+/// the bytes over to `sink`.
+///
+/// Actions are only borrowed, so unlike [RingRecorder] and
+/// [BoundedRecorder] it doesn't need `Action: Clone`. Buffer is
+/// cleared, but not shrunk, between actions, so once it has grown
+/// enough, recording doesn't allocate.
+///
+/// With `serde`, `encode` would usually be something like
+/// `|action, buf| serde_json::to_writer(buf, action).unwrap()`.
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EnablingCondition, EncodingRecorder, Store};
+/// use std::io::Write;
+/// use std::sync::{Arc, Mutex};
+///
+/// // Not `Clone`.
+/// struct Packet(Vec<u8>);
+/// impl EnablingCondition<()> for Packet {}
+///
+/// fn encode(action: &ActionWithMeta<Packet>, buf: &mut Vec<u8>) {
+///     write!(buf, "{}:", u64::from(action.id)).unwrap();
+///     buf.extend_from_slice(&action.action.0);
+/// }
+///
+/// let lines = Arc::new(Mutex::new(Vec::new()));
+/// let sink = {
+///     let lines = lines.clone();
+///     move |bytes: &[u8]| lines.lock().unwrap().push(bytes.to_vec())
+/// };
+///
+/// let mut store = Store::simple(|_: &mut (), _: &ActionWithMeta<Packet>| {}, |_, _| {}, ());
+/// store.set_recorder(Some(Box::new(EncodingRecorder::new(encode, sink))));
+/// store.dispatch(Packet(b"ping".to_vec()));
+///
+/// let expected = format!("{}:ping", u64::from(store.last_action_id()));
+/// assert_eq!(lines.lock().unwrap()[0], expected.as_bytes());
+/// ```
+pub struct EncodingRecorder<Action, S> {
+    encode: fn(&ActionWithMeta<Action>, &mut Vec<u8>),
+    sink: S,
+    buf: Vec<u8>,
+}
+
+impl<Action, S> EncodingRecorder<Action, S>
+where
+    S: FnMut(&[u8]),
+{
+    pub fn new(encode: fn(&ActionWithMeta<Action>, &mut Vec<u8>), sink: S) -> Self {
+        Self {
+            encode,
+            sink,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Current capacity of the reused buffer.
+    #[inline(always)]
+    pub fn buffer_capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+}
+
+impl<Action, S> Recorder<Action> for EncodingRecorder<Action, S>
+where
+    S: FnMut(&[u8]),
+{
+    fn record(&mut self, action: &ActionWithMeta<Action>) {
+        self.buf.clear();
+        (self.encode)(action, &mut self.buf);
+        (self.sink)(&self.buf);
+    }
+}
+
+/// Recorder of a store along with what it records, see
+/// `Store::set_recorder`.
+pub(crate) struct Recording<Action> {
+    pub recorder: Option<Box<dyn Recorder<Action> + Send>>,
+    /// Modules whose actions are recorded, all if `None`, see
+    /// `Store::set_recorded_modules`.
+    pub modules: Option<Vec<ModuleId>>,
+    /// See `Store::set_lazy_recording`.
+    #[cfg(feature = "std")]
+    pub lazy: crate::LazyRecording,
+}
+
+impl<Action> Recording<Action> {
+    pub fn new() -> Self {
+        Self {
+            recorder: None,
+            modules: None,
+            #[cfg(feature = "std")]
+            lazy: crate::LazyRecording::Evaluate,
+        }
+    }
+
+    pub fn lazy_payloads_skipped(&self) -> bool {
+        #[cfg(feature = "std")]
+        return self.lazy == crate::LazyRecording::SkipUnevaluated;
+        #[cfg(not(feature = "std"))]
+        return false;
+    }
+
+    /// Pass the action to the recorder, unless it belongs to a module
+    /// which isn't recorded.
+    #[inline(always)]
+    pub fn record(&mut self, action: &ActionWithMeta<Action>) {
+        let recorder = match self.recorder.as_mut() {
+            Some(recorder) => recorder,
+            None => return,
+        };
+        if let Some(modules) = &self.modules {
+            if !modules.contains(&action.module) {
+                return;
+            }
+        }
+        #[cfg(feature = "std")]
+        crate::lazy::recording(self.lazy, || recorder.record(action));
+        #[cfg(not(feature = "std"))]
+        recorder.record(action);
+    }
+
+    pub fn record_meta(&mut self, event: &MetaEvent) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record_meta(event);
+        }
+    }
+}
+
+impl<Action> Clone for Recording<Action> {
+    fn clone(&self) -> Self {
+        Self {
+            // Recorder can't be cloned, clone starts without one.
+            recorder: None,
+            modules: self.modules.clone(),
+            #[cfg(feature = "std")]
+            lazy: self.lazy,
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::any::Any;
+use std::fmt;
+
+use crate::ActionId;
+
+/// What the store does when the reducer panics, see
+/// `Store::set_reducer_panic_policy`.
+pub enum ReducerPanicPolicy<State> {
+    /// Let the panic unwind through `Store::dispatch`. Default.
+    Propagate,
+    /// Catch the panic and skip the effects of the action. State is
+    /// left as the reducer left it when it panicked, so it might be
+    /// half updated.
+    SkipAction,
+    /// Catch the panic, restore the state from a snapshot taken before
+    /// the reducer ran, made with the given function (usually
+    /// `State::clone`), and skip the effects of the action.
+    ///
+    /// Costs a snapshot per action.
+    RestoreSnapshot(fn(&State) -> State),
+}
+
+impl<State> Clone for ReducerPanicPolicy<State> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<State> Copy for ReducerPanicPolicy<State> {}
+
+/// Where a caught panic came from.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum PanicSource {
+    Reducer,
+    Effects,
+}
+
+/// Panic caught while processing an action.
+pub struct CaughtPanic {
+    /// Action being processed when the panic happened.
+    pub action_id: ActionId,
+    pub source: PanicSource,
+    /// Value the panic was raised with.
+    pub payload: Box<dyn Any + Send>,
+}
+
+impl CaughtPanic {
+    /// Panic message, if the panic was raised with one (like with
+    /// `panic!("...")`).
+    pub fn message(&self) -> Option<&str> {
+        panic_message(&*self.payload)
+    }
+}
+
+/// Message of a panic raised with `panic!("...")`.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> Option<&str> {
+    payload
+        .downcast_ref::<&'static str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+}
+
+impl fmt::Debug for CaughtPanic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CaughtPanic")
+            .field("action_id", &self.action_id)
+            .field("source", &self.source)
+            .field("message", &self.message())
+            .finish()
+    }
+}
+
+/// Function signature for a hook called for every caught panic.
+pub type CaughtPanicHook = fn(&CaughtPanic);
+
+/// How the store handles panics, see `Store::set_reducer_panic_policy`,
+/// `Store::set_catch_effects_panics` and `Store::set_poison_on_panic`.
+pub(crate) struct PanicHandling<State> {
+    pub reducer_policy: ReducerPanicPolicy<State>,
+    pub catch_effects: bool,
+    pub hook: Option<CaughtPanicHook>,
+    pub poison: bool,
+    /// Number of caught panics, from reducer or effects.
+    pub caught: u64,
+}
+
+impl<State> PanicHandling<State> {
+    pub fn new() -> Self {
+        Self {
+            reducer_policy: ReducerPanicPolicy::Propagate,
+            catch_effects: false,
+            hook: None,
+            poison: false,
+            caught: 0,
+        }
+    }
+
+    /// Count the caught panic, returning why the store gets poisoned
+    /// by it, if it does.
+    pub fn count(&mut self, panic: &CaughtPanic) -> Option<crate::PoisonReason> {
+        self.caught += 1;
+        if !self.poison {
+            return None;
+        }
+        let action_id = panic.action_id;
+        let message = panic.message().map(String::from);
+        Some(match panic.source {
+            PanicSource::Reducer => crate::PoisonReason::ReducerPanic { action_id, message },
+            PanicSource::Effects => crate::PoisonReason::EffectsPanic { action_id, message },
+        })
+    }
+}
+
+impl<State> Clone for PanicHandling<State> {
+    fn clone(&self) -> Self {
+        Self {
+            reducer_policy: self.reducer_policy,
+            catch_effects: self.catch_effects,
+            hook: self.hook,
+            poison: self.poison,
+            caught: self.caught,
+        }
+    }
+}
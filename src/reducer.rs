@@ -1,8 +1,54 @@
 use crate::ActionWithMeta;
+#[cfg(feature = "strict")]
+use crate::ReducerCtx;
 
 /// Function signature for a reducer.
 pub type Reducer<State, Action> = fn(&mut State, &ActionWithMeta<Action>);
 
+/// Function signature for a reducer which gets a [ReducerCtx], see
+/// [crate::Store::new_strict].
+#[cfg(feature = "strict")]
+pub type StrictReducer<State, Action> = fn(&ReducerCtx, &mut State, &ActionWithMeta<Action>);
+
+/// Reducer as kept by the store, of either signature.
+pub(crate) enum AnyReducer<State, Action> {
+    Plain(Reducer<State, Action>),
+    #[cfg(feature = "strict")]
+    Strict(StrictReducer<State, Action>),
+}
+
+impl<State, Action> Clone for AnyReducer<State, Action> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<State, Action> Copy for AnyReducer<State, Action> {}
+
+impl<State, Action> From<Reducer<State, Action>> for AnyReducer<State, Action> {
+    #[inline(always)]
+    fn from(reducer: Reducer<State, Action>) -> Self {
+        Self::Plain(reducer)
+    }
+}
+
+/// Reducer which does nothing.
+pub(crate) fn no_reducer<State, Action>(_: &mut State, _: &ActionWithMeta<Action>) {}
+
+/// Call `reducer`, with a [ReducerCtx] if it takes one.
+#[inline(always)]
+pub(crate) fn call<State, Action>(
+    reducer: AnyReducer<State, Action>,
+    state: &mut State,
+    action: &ActionWithMeta<Action>,
+) {
+    match reducer {
+        AnyReducer::Plain(reducer) => reducer(state, action),
+        #[cfg(feature = "strict")]
+        AnyReducer::Strict(reducer) => reducer(&ReducerCtx::new(), state, action),
+    }
+}
+
 #[macro_export]
 /// Combines multiple reducers into a single one.
 ///
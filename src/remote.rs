@@ -0,0 +1,241 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::{ActionId, DispatchOutcome, EnablingCondition, Provenance, Store, TimeService};
+
+/// Reply to a remotely dispatched action, sent as a single JSON line,
+/// like `{"status":"accepted","id":1690000000000000000}`.
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Clone)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RemoteReply {
+    /// Action was dispatched. `id` is `None` if the store was paused
+    /// and the action got queued.
+    Accepted { id: Option<ActionId> },
+    /// Action was rejected by the store, e.g. because it wasn't enabled.
+    Rejected,
+    /// Action was filtered out by the allowlist of the server.
+    NotAllowed,
+    /// Line couldn't be deserialized into an action.
+    Invalid { error: String },
+}
+
+type Request<Action> = (Action, Sender<RemoteReply>);
+
+/// Server accepting actions to dispatch over TCP, for operational
+/// tooling, see [RemoteDispatchClient].
+///
+/// Protocol is line delimited JSON: client sends an action per line,
+/// server replies with a [RemoteReply] per line, in order. Actions are
+/// dispatched by [RemoteDispatchServer::poll], called from the store
+/// loop. Actions not passing the `allow` filter are replied to without
+/// reaching the store.
+///
+/// There's no authentication, so only listen on trusted interfaces.
+///
+/// ```
+/// use redux_rs::{
+///     ActionWithMeta, EnablingCondition, RemoteDispatchClient, RemoteDispatchServer, RemoteReply,
+///     Store,
+/// };
+/// use serde::{Deserialize, Serialize};
+/// use std::net::TcpListener;
+/// use std::time::Duration;
+///
+/// #[derive(Serialize, Deserialize)]
+/// enum Action {
+///     SetLogLevel(u8),
+///     Shutdown,
+/// }
+///
+/// impl EnablingCondition<u8> for Action {
+///     fn is_enabled(&self, _: &u8) -> bool {
+///         match self {
+///             Action::SetLogLevel(level) => *level <= 5,
+///             Action::Shutdown => true,
+///         }
+///     }
+/// }
+///
+/// fn reducer(state: &mut u8, action: &ActionWithMeta<Action>) {
+///     if let Action::SetLogLevel(level) = action.action {
+///         *state = level;
+///     }
+/// }
+///
+/// fn allow(action: &Action) -> bool {
+///     !matches!(action, Action::Shutdown)
+/// }
+///
+/// let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+/// let mut server = RemoteDispatchServer::new(listener, allow).unwrap();
+/// let addr = server.local_addr();
+///
+/// let cli = std::thread::spawn(move || {
+///     let mut client = RemoteDispatchClient::connect(addr).unwrap();
+///     vec![
+///         client.dispatch(&Action::SetLogLevel(3)).unwrap(),
+///         client.dispatch(&Action::SetLogLevel(9)).unwrap(),
+///         client.dispatch(&Action::Shutdown).unwrap(),
+///         client.dispatch_raw("\"Reboot\"").unwrap(),
+///     ]
+/// });
+///
+/// let mut store = Store::simple(reducer, |_, _| {}, 0);
+/// while !cli.is_finished() {
+///     server.poll(&mut store);
+///     std::thread::sleep(Duration::from_millis(1));
+/// }
+/// let replies = cli.join().unwrap();
+///
+/// assert_eq!(*store.state(), 3);
+/// assert_eq!(
+///     replies[0],
+///     RemoteReply::Accepted {
+///         id: Some(store.last_action_id())
+///     }
+/// );
+/// assert_eq!(replies[1], RemoteReply::Rejected);
+/// assert_eq!(replies[2], RemoteReply::NotAllowed);
+/// assert!(matches!(replies[3], RemoteReply::Invalid { .. }));
+/// ```
+pub struct RemoteDispatchServer<Action> {
+    requests: Receiver<Request<Action>>,
+    local_addr: SocketAddr,
+}
+
+impl<Action> RemoteDispatchServer<Action>
+where
+    Action: DeserializeOwned + Send + 'static,
+{
+    /// Start accepting connections on `listener`, on a background
+    /// thread, with a thread per connection.
+    pub fn new(listener: TcpListener, allow: fn(&Action) -> bool) -> io::Result<Self> {
+        let local_addr = listener.local_addr()?;
+        let (requests_tx, requests) = mpsc::channel();
+        thread::spawn(move || accept(listener, allow, requests_tx));
+        Ok(Self {
+            requests,
+            local_addr,
+        })
+    }
+
+    #[inline(always)]
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Dispatch actions received since the last call and reply to them.
+    /// Returns the number of dispatched actions.
+    pub fn poll<State, Service>(&mut self, store: &mut Store<State, Service, Action>) -> usize
+    where
+        Service: TimeService,
+        Action: EnablingCondition<State>,
+    {
+        let mut dispatched = 0;
+        loop {
+            let (action, reply) = match self.requests.try_recv() {
+                Ok(request) => request,
+                Err(_) => break,
+            };
+            dispatched += 1;
+            let reply_message = match store.dispatch_from(action, Provenance::Remote) {
+                DispatchOutcome::Rejected => RemoteReply::Rejected,
+                outcome => RemoteReply::Accepted { id: outcome.id() },
+            };
+            // Client might have disconnected in the meantime.
+            let _ = reply.send(reply_message);
+        }
+        dispatched
+    }
+}
+
+fn accept<Action>(
+    listener: TcpListener,
+    allow: fn(&Action) -> bool,
+    requests: Sender<Request<Action>>,
+) where
+    Action: DeserializeOwned + Send + 'static,
+{
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(_) => continue,
+        };
+        let requests = requests.clone();
+        thread::spawn(move || {
+            let _ = serve(stream, allow, requests);
+        });
+    }
+}
+
+/// Handle requests of a single connection, until it's closed or the
+/// server is dropped.
+fn serve<Action>(
+    stream: TcpStream,
+    allow: fn(&Action) -> bool,
+    requests: Sender<Request<Action>>,
+) -> io::Result<()>
+where
+    Action: DeserializeOwned,
+{
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let reply = match serde_json::from_str::<Action>(&line?) {
+            Err(err) => RemoteReply::Invalid {
+                error: err.to_string(),
+            },
+            Ok(action) if !allow(&action) => RemoteReply::NotAllowed,
+            Ok(action) => {
+                let (reply_tx, reply_rx) = mpsc::channel();
+                if requests.send((action, reply_tx)).is_err() {
+                    return Ok(());
+                }
+                match reply_rx.recv() {
+                    Ok(reply) => reply,
+                    Err(_) => return Ok(()),
+                }
+            }
+        };
+        serde_json::to_writer(&mut writer, &reply)?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Client of [RemoteDispatchServer], e.g. for a CLI.
+pub struct RemoteDispatchClient {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl RemoteDispatchClient {
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let writer = TcpStream::connect(addr)?;
+        Ok(Self {
+            reader: BufReader::new(writer.try_clone()?),
+            writer,
+        })
+    }
+
+    /// Send the action and wait for the reply.
+    pub fn dispatch<Action: Serialize>(&mut self, action: &Action) -> io::Result<RemoteReply> {
+        let line = serde_json::to_string(action)?;
+        self.dispatch_raw(&line)
+    }
+
+    /// Send an already serialized action, which must be a single line.
+    pub fn dispatch_raw(&mut self, action: &str) -> io::Result<RemoteReply> {
+        self.writer.write_all(action.as_bytes())?;
+        self.writer.write_all(b"\n")?;
+        let mut reply = String::new();
+        if self.reader.read_line(&mut reply)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(serde_json::from_str(&reply)?)
+    }
+}
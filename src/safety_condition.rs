@@ -1,3 +1,9 @@
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String, vec::Vec};
+use core::fmt;
+
+/// With the `derive` feature, it can be derived for states made of
+/// parts implementing it, see `redux_rs_derive`.
 pub trait SafetyCondition {
     type Error;
 
@@ -9,3 +15,264 @@ pub trait SafetyCondition {
         Ok(())
     }
 }
+
+/// Safety condition check with the error formatted, see [check_formatted].
+pub(crate) type FormattedCheck<State> = fn(&State) -> Result<(), String>;
+
+/// Checks the safety condition, with the error formatted with `Debug`,
+/// so that the store doesn't need to be generic over the error type.
+pub(crate) fn check_formatted<State>(state: &State) -> Result<(), String>
+where
+    State: SafetyCondition,
+    State::Error: fmt::Debug,
+{
+    state
+        .check_safety_condition()
+        .map_err(|error| format!("{:?}", error))
+}
+
+/// Violation reported by [SafetyConditions::check].
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct SafetyViolation<Error> {
+    /// Index of the violated condition, in order of registration.
+    pub index: usize,
+    pub error: Error,
+}
+
+/// Condition checked by [SafetyConditions].
+pub type SafetyConditionFn<State, Error> = fn(&State) -> Result<(), Error>;
+
+type CheckAll<State, Error> =
+    fn(&[SafetyConditionFn<State, Error>], &State) -> Vec<SafetyViolation<Error>>;
+
+/// Collection of independent safety conditions, checked together,
+/// reporting all the violations instead of just the first one.
+///
+/// Meant to be used as the [SafetyCondition] of a state made of many
+/// independent parts. Conditions are checked one after another, or,
+/// with the `rayon` feature and [SafetyConditions::parallel], in
+/// parallel. Either way, violations are reported in order of
+/// registration.
+///
+/// ```
+/// use redux_rs::{SafetyCondition, SafetyConditions, SafetyViolation};
+///
+/// struct State {
+///     peers: Vec<u32>,
+///     max_peers: usize,
+///     balance: i64,
+/// }
+///
+/// fn peers_limit(state: &State) -> Result<(), String> {
+///     if state.peers.len() > state.max_peers {
+///         return Err(format!("{} peers over the limit", state.peers.len()));
+///     }
+///     Ok(())
+/// }
+///
+/// fn non_negative_balance(state: &State) -> Result<(), String> {
+///     if state.balance < 0 {
+///         return Err("negative balance".into());
+///     }
+///     Ok(())
+/// }
+///
+/// fn unique_peers(state: &State) -> Result<(), String> {
+///     let mut peers = state.peers.clone();
+///     peers.sort_unstable();
+///     peers.dedup();
+///     if peers.len() != state.peers.len() {
+///         return Err("duplicate peer".into());
+///     }
+///     Ok(())
+/// }
+///
+/// impl SafetyCondition for State {
+///     type Error = Vec<SafetyViolation<String>>;
+///
+///     fn check_safety_condition(&self) -> Result<(), Self::Error> {
+///         SafetyConditions::new()
+///             .with(peers_limit)
+///             .with(non_negative_balance)
+///             .with(unique_peers)
+///             .check(self)
+///     }
+/// }
+///
+/// let state = State {
+///     peers: vec![1, 2, 2],
+///     max_peers: 2,
+///     balance: 10,
+/// };
+/// assert_eq!(
+///     state.check_safety_condition(),
+///     Err(vec![
+///         SafetyViolation {
+///             index: 0,
+///             error: "3 peers over the limit".to_string()
+///         },
+///         SafetyViolation {
+///             index: 2,
+///             error: "duplicate peer".to_string()
+///         },
+///     ])
+/// );
+/// ```
+pub struct SafetyConditions<State, Error> {
+    conditions: Vec<SafetyConditionFn<State, Error>>,
+    check_all: CheckAll<State, Error>,
+}
+
+impl<State, Error> SafetyConditions<State, Error> {
+    pub fn new() -> Self {
+        Self {
+            conditions: Vec::new(),
+            check_all: check_sequential::<State, Error>,
+        }
+    }
+
+    /// Register a condition. It gets the next index.
+    pub fn with(mut self, condition: SafetyConditionFn<State, Error>) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    /// Same as [SafetyConditions::with], for a collection which is kept around.
+    pub fn add(&mut self, condition: SafetyConditionFn<State, Error>) -> usize {
+        self.conditions.push(condition);
+        self.conditions.len() - 1
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.conditions.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+
+    /// Check every condition, returning all violations, ordered by index.
+    pub fn check(&self, state: &State) -> Result<(), Vec<SafetyViolation<Error>>> {
+        let violations = (self.check_all)(&self.conditions, state);
+        if violations.is_empty() {
+            return Ok(());
+        }
+        Err(violations)
+    }
+
+    /// Check the conditions one after another, even if
+    /// [SafetyConditions::parallel] was set.
+    pub fn check_sequential(&self, state: &State) -> Result<(), Vec<SafetyViolation<Error>>> {
+        let violations = check_sequential(&self.conditions, state);
+        if violations.is_empty() {
+            return Ok(());
+        }
+        Err(violations)
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<State, Error> SafetyConditions<State, Error>
+where
+    State: Sync,
+    Error: Send,
+{
+    /// Check the conditions in parallel, on the rayon global thread
+    /// pool. Only worth it when conditions are expensive, as spreading
+    /// work over threads costs microseconds.
+    ///
+    /// ```
+    /// use redux_rs::SafetyConditions;
+    ///
+    /// fn digit<const I: usize>(state: &Vec<u8>) -> Result<(), u8> {
+    ///     match state[I] {
+    ///         digit if digit < 10 => Ok(()),
+    ///         other => Err(other),
+    ///     }
+    /// }
+    ///
+    /// let sequential = SafetyConditions::new()
+    ///     .with(digit::<0>)
+    ///     .with(digit::<1>)
+    ///     .with(digit::<2>)
+    ///     .with(digit::<3>)
+    ///     .with(digit::<4>)
+    ///     .with(digit::<5>)
+    ///     .with(digit::<6>)
+    ///     .with(digit::<7>);
+    /// let parallel = sequential.clone().parallel();
+    ///
+    /// let mut state = vec![1, 2, 3, 4, 5, 6, 7, 8];
+    /// assert_eq!(parallel.check(&state), Ok(()));
+    ///
+    /// state[1] = 20;
+    /// state[4] = 50;
+    /// state[7] = 80;
+    /// let violations = parallel.check(&state).unwrap_err();
+    /// let indices: Vec<_> = violations.iter().map(|v| (v.index, v.error)).collect();
+    /// assert_eq!(indices, [(1, 20), (4, 50), (7, 80)]);
+    /// assert_eq!(Err(violations), sequential.check(&state));
+    /// assert_eq!(parallel.check(&state), parallel.check_sequential(&state));
+    /// ```
+    pub fn parallel(mut self) -> Self {
+        self.check_all = check_parallel::<State, Error>;
+        self
+    }
+}
+
+impl<State, Error> Default for SafetyConditions<State, Error> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<State, Error> Clone for SafetyConditions<State, Error> {
+    fn clone(&self) -> Self {
+        Self {
+            conditions: self.conditions.clone(),
+            check_all: self.check_all,
+        }
+    }
+}
+
+fn check_sequential<State, Error>(
+    conditions: &[SafetyConditionFn<State, Error>],
+    state: &State,
+) -> Vec<SafetyViolation<Error>> {
+    conditions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, condition)| {
+            condition(state)
+                .err()
+                .map(|error| SafetyViolation { index, error })
+        })
+        .collect()
+}
+
+#[cfg(feature = "rayon")]
+fn check_parallel<State, Error>(
+    conditions: &[SafetyConditionFn<State, Error>],
+    state: &State,
+) -> Vec<SafetyViolation<Error>>
+where
+    State: Sync,
+    Error: Send,
+{
+    use rayon::prelude::*;
+
+    let mut violations: Vec<_> = conditions
+        .par_iter()
+        .enumerate()
+        .filter_map(|(index, condition)| {
+            condition(state)
+                .err()
+                .map(|error| SafetyViolation { index, error })
+        })
+        .collect();
+    // Not relying on rayon keeping the order of filtered items.
+    violations.sort_unstable_by_key(|violation| violation.index);
+    violations
+}
@@ -0,0 +1,249 @@
+use core::time::Duration;
+
+use crate::effects::no_effects;
+use crate::{ActionId, Effects, Instant, MockTimeService, Reducer, Store};
+
+/// Declarative test scenario: builds a store with [MockTimeService],
+/// then runs steps one after another, in order.
+///
+/// Evaluates to the store, so it can be inspected further afterwards.
+///
+/// Steps, each terminated with `;`:
+/// - `dispatch <action>` - dispatch the action.
+/// - `at +<duration> dispatch <action>` - advance the clock by
+///   `duration`, then dispatch the action.
+/// - `advance <duration>` - advance the clock.
+/// - `assert <closure>` - call the closure with the current state,
+///   panic if it returns `false`.
+///
+/// Durations are written as an integer with unit, one of `ns`, `us`,
+/// `ms` or `s`, e.g. `10ms` or `5s`.
+///
+/// Failing assertion panics with the step number (counted from 1)
+/// and id of the last dispatched action.
+///
+/// ```
+/// use redux_rs::{scenario, ActionWithMeta, EnablingCondition, Store, MockTimeService};
+/// use std::time::Duration;
+///
+/// #[derive(Default)]
+/// struct State {
+///     counter: u32,
+///     last_gap: Duration,
+/// }
+///
+/// enum Action {
+///     Increment,
+/// }
+///
+/// impl EnablingCondition<State> for Action {}
+///
+/// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+///     match &action.action {
+///         Action::Increment => {
+///             state.counter += 1;
+///             state.last_gap = action.elapsed_since_prev();
+///         }
+///     }
+/// }
+///
+/// let store = scenario! {
+///     state: State::default(),
+///     reducer: reducer,
+///     steps: {
+///         dispatch Action::Increment;
+///         assert |s| s.counter == 1;
+///         advance 5s;
+///         at +10ms dispatch Action::Increment;
+///         assert |s| s.counter == 2;
+///         assert |s| s.last_gap == Duration::from_millis(5_010);
+///     }
+/// };
+///
+/// assert_eq!(store.state().counter, 2);
+/// ```
+///
+/// With effects:
+///
+/// ```
+/// use redux_rs::{scenario, ActionWithMeta, EnablingCondition, MockTimeService, Store};
+///
+/// enum Action {
+///     Ping,
+///     Pong,
+/// }
+///
+/// impl EnablingCondition<Vec<&'static str>> for Action {}
+///
+/// fn reducer(log: &mut Vec<&'static str>, action: &ActionWithMeta<Action>) {
+///     log.push(match action.action {
+///         Action::Ping => "ping",
+///         Action::Pong => "pong",
+///     });
+/// }
+///
+/// fn effects(
+///     store: &mut Store<Vec<&'static str>, MockTimeService, Action>,
+///     action: &ActionWithMeta<Action>,
+/// ) {
+///     if let Action::Ping = action.action {
+///         store.dispatch(Action::Pong);
+///     }
+/// }
+///
+/// scenario! {
+///     state: Vec::new(),
+///     reducer: reducer,
+///     effects: effects,
+///     steps: {
+///         at +1ms dispatch Action::Ping;
+///         assert |log| log == &["ping", "pong"];
+///     }
+/// };
+/// ```
+///
+/// Failing assertion:
+///
+/// ```should_panic
+/// # use redux_rs::{scenario, ActionWithMeta, EnablingCondition};
+/// # struct Increment;
+/// # impl EnablingCondition<u32> for Increment {}
+/// # fn reducer(state: &mut u32, _: &ActionWithMeta<Increment>) {
+/// #     *state += 1;
+/// # }
+/// // Panics with "scenario step 2 failed: `|s| *s == 2` doesn't hold, last action id: ...".
+/// scenario! {
+///     state: 0,
+///     reducer: reducer,
+///     steps: {
+///         dispatch Increment;
+///         assert |s| *s == 2;
+///     }
+/// };
+/// ```
+#[macro_export]
+macro_rules! scenario {
+    (
+        state: $state:expr,
+        reducer: $reducer:expr,
+        effects: $effects:expr,
+        steps: { $($steps:tt)* } $(,)?
+    ) => {{
+        let mut store = $crate::__scenario_store($reducer, Some($effects), $state);
+        let mut step = 0usize;
+        $crate::scenario!(@steps store, step; $($steps)*);
+        store
+    }};
+    (
+        state: $state:expr,
+        reducer: $reducer:expr,
+        steps: { $($steps:tt)* } $(,)?
+    ) => {{
+        let mut store = $crate::__scenario_store($reducer, None, $state);
+        let mut step = 0usize;
+        $crate::scenario!(@steps store, step; $($steps)*);
+        store
+    }};
+
+    (@steps $store:ident, $step:ident;) => {
+        let _ = $step;
+    };
+    (@steps $store:ident, $step:ident; at + $by:tt dispatch $action:expr; $($rest:tt)*) => {
+        $step += 1;
+        $crate::__scenario_advance(&mut $store, stringify!($by));
+        $store.dispatch($action);
+        $crate::scenario!(@steps $store, $step; $($rest)*);
+    };
+    (@steps $store:ident, $step:ident; dispatch $action:expr; $($rest:tt)*) => {
+        $step += 1;
+        $store.dispatch($action);
+        $crate::scenario!(@steps $store, $step; $($rest)*);
+    };
+    (@steps $store:ident, $step:ident; advance $by:tt; $($rest:tt)*) => {
+        $step += 1;
+        $crate::__scenario_advance(&mut $store, stringify!($by));
+        $crate::scenario!(@steps $store, $step; $($rest)*);
+    };
+    (@steps $store:ident, $step:ident; assert $check:expr; $($rest:tt)*) => {
+        $step += 1;
+        $crate::__scenario_assert(&$store, $step, stringify!($check), $check);
+        $crate::scenario!(@steps $store, $step; $($rest)*);
+    };
+}
+
+#[doc(hidden)]
+pub fn __scenario_store<State, Action>(
+    reducer: Reducer<State, Action>,
+    effects: Option<Effects<State, MockTimeService, Action>>,
+    initial_state: State,
+) -> Store<State, MockTimeService, Action> {
+    #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+    let start = Instant::now();
+    #[cfg(any(not(feature = "std"), target_arch = "wasm32"))]
+    let start = Instant::default();
+
+    Store::new_with_initial_id(
+        reducer,
+        effects.unwrap_or(no_effects),
+        MockTimeService::new(start),
+        ActionId::ZERO,
+        initial_state,
+    )
+}
+
+/// Advances the clock of the store by a duration like `10ms`, as
+/// written in [scenario] steps.
+#[doc(hidden)]
+pub fn __scenario_advance<State, Action>(
+    store: &mut Store<State, MockTimeService, Action>,
+    by: &str,
+) {
+    store.service.advance(parse_duration(by));
+}
+
+/// Parses durations like `10ms`, as written in [scenario] steps.
+fn parse_duration(s: &str) -> Duration {
+    let split = s
+        .find(|c: char| !c.is_ascii_digit() && c != '_')
+        .unwrap_or(s.len());
+    let (digits, unit) = s.split_at(split);
+    let value = digits
+        .bytes()
+        .filter(|b| *b != b'_')
+        .try_fold(0u64, |value, b| {
+            value.checked_mul(10)?.checked_add(u64::from(b - b'0'))
+        })
+        .filter(|_| !digits.is_empty())
+        .unwrap_or_else(|| panic!("invalid scenario duration: `{}`", s));
+
+    match unit {
+        "ns" => Duration::from_nanos(value),
+        "us" => Duration::from_micros(value),
+        "ms" => Duration::from_millis(value),
+        "s" => Duration::from_secs(value),
+        _ => panic!(
+            "invalid scenario duration unit in `{}`, expected one of: ns, us, ms, s",
+            s
+        ),
+    }
+}
+
+#[doc(hidden)]
+#[track_caller]
+pub fn __scenario_assert<State, Action, F>(
+    store: &Store<State, MockTimeService, Action>,
+    step: usize,
+    check_str: &str,
+    check: F,
+) where
+    F: FnOnce(&State) -> bool,
+{
+    if !check(store.state()) {
+        panic!(
+            "scenario step {} failed: `{}` doesn't hold, last action id: {}",
+            step,
+            check_str,
+            u64::from(store.last_action_id())
+        )
+    }
+}
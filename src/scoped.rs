@@ -0,0 +1,213 @@
+use crate::{ActionId, EnablingCondition, ServiceAccess, Store, StoreLike, TimeService};
+
+/// View of the store limited to the sub-state and actions of a single
+/// module, created with [Store::scoped] or [ScopedStore::scoped].
+///
+/// Effects of a module written against it can't read the state of
+/// other modules nor dispatch their actions, and don't depend on the
+/// application embedding the module:
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EnablingCondition, Store};
+///
+/// mod counter {
+///     use redux_rs::{ScopedStore, StoreLike};
+///
+///     #[derive(Default)]
+///     pub struct State {
+///         pub value: u32,
+///     }
+///
+///     pub enum Action {
+///         Increment,
+///     }
+///
+///     pub fn reducer(state: &mut State, action: &Action) {
+///         match action {
+///             Action::Increment => state.value += 1,
+///         }
+///     }
+///
+///     /// Counts up to 3.
+///     pub fn effects<Parent: StoreLike>(
+///         store: &mut ScopedStore<'_, Parent, State, Action>,
+///         action: &Action,
+///     ) {
+///         if let Action::Increment = action {
+///             if store.state().value < 3 {
+///                 store.dispatch(Action::Increment);
+///             }
+///         }
+///     }
+/// }
+///
+/// mod log {
+///     use redux_rs::StoreHandle;
+///
+///     #[derive(Default)]
+///     pub struct State {
+///         pub lines: Vec<String>,
+///     }
+///
+///     pub enum Action {
+///         Line(String),
+///     }
+///
+///     pub fn reducer(state: &mut State, action: &Action) {
+///         match action {
+///             Action::Line(line) => state.lines.push(line.clone()),
+///         }
+///     }
+///
+///     /// [ScopedStore] implements [StoreHandle] too.
+///     pub fn log(store: &mut dyn StoreHandle<State, Action>, line: String) {
+///         store.dispatch(Action::Line(line));
+///     }
+/// }
+///
+/// #[derive(Default)]
+/// struct State {
+///     counter: counter::State,
+///     log: log::State,
+/// }
+///
+/// enum Action {
+///     Counter(counter::Action),
+///     Log(log::Action),
+/// }
+/// impl EnablingCondition<State> for Action {}
+///
+/// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+///     match &action.action {
+///         Action::Counter(action) => counter::reducer(&mut state.counter, action),
+///         Action::Log(action) => log::reducer(&mut state.log, action),
+///     }
+/// }
+///
+/// fn effects(store: &mut Store<State, (), Action>, action: &ActionWithMeta<Action>) {
+///     if let Action::Counter(action) = &action.action {
+///         let value = store.state().counter.value;
+///         log::log(&mut store.scoped(|state| &state.log, Action::Log), format!("counter: {}", value));
+///         counter::effects(&mut store.scoped(|state| &state.counter, Action::Counter), action);
+///     }
+/// }
+///
+/// let mut store = Store::simple(reducer, effects, State::default());
+/// store.dispatch(Action::Counter(counter::Action::Increment));
+/// assert_eq!(store.state().counter.value, 3);
+/// assert_eq!(store.state().log.lines, ["counter: 1", "counter: 2", "counter: 3"]);
+/// ```
+///
+/// The parent store isn't reachable through the view:
+///
+/// ```compile_fail
+/// use redux_rs::{EnablingCondition, ScopedStore, Store};
+///
+/// struct State {
+///     mine: u32,
+///     theirs: u32,
+/// }
+///
+/// struct Action;
+/// impl EnablingCondition<State> for Action {}
+///
+/// fn peek(store: &ScopedStore<'_, Store<State, (), Action>, u32, Action>) -> u32 {
+///     store.store.state().theirs
+/// }
+/// ```
+pub struct ScopedStore<'a, Parent: StoreLike + ?Sized, SubState, SubAction> {
+    store: &'a mut Parent,
+    state_lens: fn(&Parent::State) -> &SubState,
+    action_wrap: fn(SubAction) -> Parent::Action,
+}
+
+impl<State, Service, Action> Store<State, Service, Action>
+where
+    Service: TimeService,
+    Action: EnablingCondition<State>,
+{
+    /// View of the store which can only read the part of the state
+    /// selected by `state_lens` and dispatch actions wrapped into
+    /// `Action` by `action_wrap`. See [ScopedStore].
+    pub fn scoped<SubState, SubAction>(
+        &mut self,
+        state_lens: fn(&State) -> &SubState,
+        action_wrap: fn(SubAction) -> Action,
+    ) -> ScopedStore<'_, Self, SubState, SubAction> {
+        ScopedStore {
+            store: self,
+            state_lens,
+            action_wrap,
+        }
+    }
+}
+
+impl<'a, Parent, SubState, SubAction> ScopedStore<'a, Parent, SubState, SubAction>
+where
+    Parent: StoreLike + ?Sized,
+{
+    #[inline(always)]
+    pub fn state(&self) -> &SubState {
+        (self.state_lens)(self.store.state())
+    }
+
+    /// Dispatch the action to the parent store, wrapped, see
+    /// [Store::dispatch].
+    #[inline(always)]
+    pub fn dispatch(&mut self, action: SubAction) -> bool {
+        self.store.dispatch((self.action_wrap)(action))
+    }
+
+    #[inline(always)]
+    pub fn last_action_id(&self) -> ActionId {
+        self.store.last_action_id()
+    }
+
+    /// View of this view, further limited to a part of its sub-state
+    /// and actions.
+    pub fn scoped<SubSubState, SubSubAction>(
+        &mut self,
+        state_lens: fn(&SubState) -> &SubSubState,
+        action_wrap: fn(SubSubAction) -> SubAction,
+    ) -> ScopedStore<'_, Self, SubSubState, SubSubAction> {
+        ScopedStore {
+            store: self,
+            state_lens,
+            action_wrap,
+        }
+    }
+}
+
+impl<'a, Parent, SubState, SubAction> StoreLike for ScopedStore<'a, Parent, SubState, SubAction>
+where
+    Parent: StoreLike + ?Sized,
+{
+    type State = SubState;
+    type Action = SubAction;
+
+    #[inline(always)]
+    fn state(&self) -> &SubState {
+        ScopedStore::state(self)
+    }
+
+    #[inline(always)]
+    fn dispatch(&mut self, action: SubAction) -> bool {
+        ScopedStore::dispatch(self, action)
+    }
+
+    #[inline(always)]
+    fn last_action_id(&self) -> ActionId {
+        ScopedStore::last_action_id(self)
+    }
+}
+
+impl<'a, Parent, SubState, SubAction, S> ServiceAccess<S>
+    for ScopedStore<'a, Parent, SubState, SubAction>
+where
+    Parent: StoreLike + ServiceAccess<S> + ?Sized,
+{
+    #[inline(always)]
+    fn service(&mut self) -> &mut S {
+        self.store.service()
+    }
+}
@@ -0,0 +1,72 @@
+/// Default limit of capacity of a buffer kept for reuse, see
+/// [ScratchBuffers::new].
+pub const SCRATCH_MAX_RETAINED: usize = 1024 * 1024;
+
+/// Counters of a pool of scratch buffers.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ScratchStats {
+    /// Buffers taken from the pool.
+    pub taken: u64,
+    /// Taken buffers which were reused rather than newly created.
+    pub reused: u64,
+    /// Buffers dropped instead of being kept, because they grew over
+    /// the retained capacity limit, or the pool was full.
+    pub discarded: u64,
+    /// Capacity of the buffers currently kept, in bytes.
+    pub retained_bytes: usize,
+}
+
+/// Pool of byte buffers for serialization, so that buffers which have
+/// grown get reused instead of allocating new ones for every action.
+///
+/// Buffers grown over `max_retained` bytes are dropped when put back,
+/// so that a single huge action doesn't keep its memory forever.
+#[cfg(feature = "json_log")]
+pub(crate) struct ScratchBuffers {
+    free: Vec<Vec<u8>>,
+    max_buffers: usize,
+    max_retained: usize,
+    stats: ScratchStats,
+}
+
+#[cfg(feature = "json_log")]
+impl ScratchBuffers {
+    pub fn new(max_buffers: usize, max_retained: usize) -> Self {
+        Self {
+            free: Vec::with_capacity(max_buffers),
+            max_buffers,
+            max_retained,
+            stats: ScratchStats::default(),
+        }
+    }
+
+    /// Empty buffer, reused if possible.
+    pub fn take(&mut self) -> Vec<u8> {
+        self.stats.taken += 1;
+        match self.free.pop() {
+            Some(buf) => {
+                self.stats.reused += 1;
+                self.stats.retained_bytes -= buf.capacity();
+                buf
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Give the buffer back for reuse.
+    pub fn put(&mut self, mut buf: Vec<u8>) {
+        if buf.capacity() > self.max_retained || self.free.len() >= self.max_buffers {
+            self.stats.discarded += 1;
+            return;
+        }
+        buf.clear();
+        self.stats.retained_bytes += buf.capacity();
+        self.free.push(buf);
+    }
+
+    #[inline(always)]
+    pub fn stats(&self) -> ScratchStats {
+        self.stats
+    }
+}
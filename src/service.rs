@@ -1,7 +1,624 @@
-use std::time::Instant;
+#[cfg(feature = "std")]
+use std::time::SystemTime;
 
+use crate::Instant;
+
+/// Source of time for the store, reducers and effects.
+///
+/// Reducers and effects must never call std time APIs (`Instant::now()`,
+/// `SystemTime::now()`) directly. Reducers should use time from the
+/// action metadata and effects should go through the service, otherwise
+/// they can't be made deterministic with a mock service.
 pub trait TimeService {
+    #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
     fn monotonic_time(&mut self) -> Instant {
         Instant::now()
     }
+
+    /// Without `std` (or in the browser) there is no clock we could
+    /// default to, so it must be provided by the implementor.
+    #[cfg(any(not(feature = "std"), target_arch = "wasm32"))]
+    fn monotonic_time(&mut self) -> Instant;
+
+    /// Current wall clock time.
+    #[cfg(feature = "std")]
+    fn system_time(&mut self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Service for stores which don't need anything but the default clock.
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+impl TimeService for () {}
+
+/// Closure returning current monotonic time can be used as a service.
+impl<F> TimeService for F
+where
+    F: FnMut() -> Instant,
+{
+    fn monotonic_time(&mut self) -> Instant {
+        self()
+    }
+}
+
+/// Clock which stands still until explicitly advanced.
+///
+/// Meant for simulations, where time should only move when the
+/// simulation says so. Only monotonic time is frozen, wall clock comes
+/// from the default [TimeService::system_time].
+#[derive(Debug, Clone, Copy)]
+pub struct FrozenTime {
+    now: Instant,
+}
+
+impl FrozenTime {
+    pub fn new(now: Instant) -> Self {
+        Self { now }
+    }
+
+    #[inline(always)]
+    pub fn now(&self) -> Instant {
+        self.now
+    }
+
+    pub fn advance(&mut self, by: core::time::Duration) {
+        self.now += by;
+    }
+
+    /// Move clock to `now`. Ignored if it's earlier than current time.
+    pub fn advance_to(&mut self, now: Instant) {
+        self.now = self.now.max(now);
+    }
+}
+
+impl TimeService for FrozenTime {
+    fn monotonic_time(&mut self) -> Instant {
+        self.now
+    }
+}
+
+/// Clock running `factor` times faster than the `inner` clock.
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EnablingCondition, FrozenTime, Instant, ScaledTime, Store};
+/// use std::time::{Duration, SystemTime};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Action {
+///     Start,
+///     Hourly,
+/// }
+///
+/// impl EnablingCondition<Vec<Action>> for Action {}
+///
+/// fn reducer(state: &mut Vec<Action>, action: &ActionWithMeta<Action>) {
+///     state.push(action.action);
+/// }
+///
+/// fn effects(
+///     store: &mut Store<Vec<Action>, ScaledTime<FrozenTime>, Action>,
+///     action: &ActionWithMeta<Action>,
+/// ) {
+///     if let Action::Start = action.action {
+///         store.schedule_timer(Duration::from_secs(3600), Action::Hourly);
+///     }
+/// }
+///
+/// let real_clock = FrozenTime::new(Instant::now());
+/// let service = ScaledTime::new(real_clock, 100_000.0);
+/// let mut store = Store::new(reducer, effects, service, SystemTime::UNIX_EPOCH, vec![]);
+/// store.dispatch(Action::Start);
+///
+/// // An hour of scaled time is 36ms of the real clock.
+/// store.service.inner_mut().advance(Duration::from_millis(35));
+/// assert_eq!(store.fire_timers(), 0);
+/// store.service.inner_mut().advance(Duration::from_millis(1));
+/// assert_eq!(store.fire_timers(), 1);
+///
+/// assert_eq!(store.state(), &[Action::Start, Action::Hourly]);
+/// assert_eq!(store.uptime(), Duration::from_secs(3600));
+/// ```
+///
+/// Factor must be finite and not negative:
+///
+/// ```should_panic
+/// use redux_rs::{FrozenTime, Instant, ScaledTime};
+///
+/// ScaledTime::new(FrozenTime::new(Instant::now()), f64::NAN);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScaledTime<T> {
+    inner: T,
+    base: Instant,
+    factor: f64,
+}
+
+impl<T: TimeService> ScaledTime<T> {
+    /// Scaled time starts at current time of the `inner` clock.
+    ///
+    /// Panics if `factor` is negative, infinite or NaN.
+    pub fn new(mut inner: T, factor: f64) -> Self {
+        assert!(
+            factor.is_finite() && factor >= 0.0,
+            "ScaledTime factor must be finite and not negative, got {}",
+            factor
+        );
+        let base = inner.monotonic_time();
+        Self {
+            inner,
+            base,
+            factor,
+        }
+    }
+
+    #[inline(always)]
+    pub fn factor(&self) -> f64 {
+        self.factor
+    }
+
+    #[inline(always)]
+    pub fn inner_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: TimeService> TimeService for ScaledTime<T> {
+    fn monotonic_time(&mut self) -> Instant {
+        let elapsed = self
+            .inner
+            .monotonic_time()
+            .saturating_duration_since(self.base);
+        self.base + elapsed.mul_f64(self.factor)
+    }
+}
+
+/// Manually advanced clock for tests.
+///
+/// Both monotonic and wall clock time stay still until
+/// [MockTimeService::advance] is called.
+///
+/// ```
+/// use redux_rs::{
+///     ActionWithMeta, BoundedRecorder, EnablingCondition, Instant, MockTimeService, Store,
+///     TimeService,
+/// };
+/// use std::time::{Duration, SystemTime};
+///
+/// #[derive(Clone)]
+/// enum Action {
+///     Ping,
+///     Pong { sent_at: SystemTime },
+/// }
+///
+/// impl EnablingCondition<Option<SystemTime>> for Action {}
+///
+/// fn reducer(state: &mut Option<SystemTime>, action: &ActionWithMeta<Action>) {
+///     if let Action::Pong { sent_at } = &action.action {
+///         *state = Some(*sent_at);
+///     }
+/// }
+///
+/// fn effects(
+///     store: &mut Store<Option<SystemTime>, MockTimeService, Action>,
+///     action: &ActionWithMeta<Action>,
+/// ) {
+///     if let Action::Ping = &action.action {
+///         let sent_at = store.service.system_time();
+///         store.dispatch(Action::Pong { sent_at });
+///     }
+/// }
+///
+/// let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+/// let service = MockTimeService::new(Instant::now()).with_system_time(start);
+/// let mut store = Store::new(reducer, effects, service, start, None);
+/// store.set_recorder(Some(Box::new(BoundedRecorder::new(16))));
+///
+/// store.service.advance(Duration::from_secs(5));
+/// store.dispatch(Action::Ping);
+///
+/// assert_eq!(*store.state(), Some(start + Duration::from_secs(5)));
+///
+/// // Replayed on a clock at another time, the effect's reading of the
+/// // clock comes from the recorded action.
+/// let header = store.recording_header();
+/// let recorded: Vec<_> = store.recent_actions().cloned().collect();
+/// let later = start + Duration::from_secs(3_600);
+/// let service = MockTimeService::new(Instant::now()).with_system_time(later);
+/// let mut replayed = Store::new(reducer, effects, service, later, None);
+/// replayed.replay(&header, &recorded).unwrap();
+/// assert_eq!(replayed.state(), store.state());
+/// ```
+#[cfg(feature = "testing")]
+#[derive(Debug, Clone)]
+pub struct MockTimeService {
+    monotonic: Instant,
+    #[cfg(feature = "std")]
+    system: SystemTime,
+}
+
+#[cfg(feature = "testing")]
+impl MockTimeService {
+    /// Wall clock starts at unix epoch.
+    pub fn new(monotonic: Instant) -> Self {
+        Self {
+            monotonic,
+            #[cfg(feature = "std")]
+            system: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn with_system_time(mut self, system_time: SystemTime) -> Self {
+        self.system = system_time;
+        self
+    }
+
+    /// Advance both monotonic and wall clock.
+    pub fn advance(&mut self, by: core::time::Duration) {
+        self.monotonic += by;
+        #[cfg(feature = "std")]
+        {
+            self.system += by;
+        }
+    }
+}
+
+#[cfg(feature = "testing")]
+impl TimeService for MockTimeService {
+    fn monotonic_time(&mut self) -> Instant {
+        self.monotonic
+    }
+
+    #[cfg(feature = "std")]
+    fn system_time(&mut self) -> SystemTime {
+        self.system
+    }
+}
+
+/// [TimeService] for the browser, backed by `performance.now()`.
+#[cfg(feature = "wasm")]
+#[derive(Debug, Clone)]
+pub struct PerformanceTimeService {
+    performance: web_sys::Performance,
+}
+
+#[cfg(feature = "wasm")]
+impl PerformanceTimeService {
+    /// Returns `None` if there is no `window.performance`, e.g. in web workers.
+    pub fn new() -> Option<Self> {
+        let performance = web_sys::window()?.performance()?;
+        Some(Self { performance })
+    }
+}
+
+#[cfg(feature = "wasm")]
+impl TimeService for PerformanceTimeService {
+    fn monotonic_time(&mut self) -> Instant {
+        // `now()` is in milliseconds, with microsecond precision at best.
+        let nanos = self.performance.now() * 1_000_000.0;
+        crate::MonotonicTime::from_nanos(nanos as u64)
+    }
+}
+
+/// Source of randomness for effects.
+///
+/// Just like time, randomness must go through the service so that
+/// effects stay deterministic when the service is replaced with
+/// a seeded one (tests, replay).
+///
+/// Usually implemented on the same service struct as [TimeService]:
+///
+/// ```
+/// use redux_rs::{RandomnessService, SeededRandomness, TimeService};
+///
+/// struct Service {
+///     rng: SeededRandomness,
+/// }
+///
+/// impl TimeService for Service {}
+///
+/// impl RandomnessService for Service {
+///     fn next_u64(&mut self) -> u64 {
+///         self.rng.next_u64()
+///     }
+/// }
+/// ```
+pub trait RandomnessService {
+    fn next_u64(&mut self) -> u64;
+
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    /// Random number in range `[0, bound)`. Returns 0 if `bound` is 0.
+    fn next_u64_below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            return 0;
+        }
+        self.next_u64() % bound
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+}
+
+/// Deterministic [RandomnessService] (SplitMix64) for tests and replay.
+///
+/// Same seed always produces the same sequence of numbers.
+///
+/// ```
+/// use redux_rs::{
+///     ActionWithMeta, EnablingCondition, RandomnessService, SeededRandomness, Store,
+///     TimeService,
+/// };
+/// use std::time::{Duration, Instant, SystemTime};
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// enum Action {
+///     Failed,
+///     Retry { jitter_ms: u64 },
+/// }
+///
+/// impl EnablingCondition<Vec<Action>> for Action {}
+///
+/// struct Service {
+///     now: Instant,
+///     rng: SeededRandomness,
+/// }
+///
+/// impl TimeService for Service {
+///     fn monotonic_time(&mut self) -> Instant {
+///         self.now += Duration::from_millis(1);
+///         self.now
+///     }
+/// }
+///
+/// impl RandomnessService for Service {
+///     fn next_u64(&mut self) -> u64 {
+///         self.rng.next_u64()
+///     }
+/// }
+///
+/// fn reducer(state: &mut Vec<Action>, action: &ActionWithMeta<Action>) {
+///     state.push(action.action.clone());
+/// }
+///
+/// fn effects(store: &mut Store<Vec<Action>, Service, Action>, action: &ActionWithMeta<Action>) {
+///     if let Action::Failed = &action.action {
+///         let jitter_ms = store.service.next_u64_below(1000);
+///         store.dispatch(Action::Retry { jitter_ms });
+///     }
+/// }
+///
+/// let run = |seed| {
+///     let service = Service {
+///         now: Instant::now(),
+///         rng: SeededRandomness::new(seed),
+///     };
+///     let mut store = Store::new(reducer, effects, service, SystemTime::UNIX_EPOCH, vec![]);
+///     for _ in 0..10 {
+///         store.dispatch(Action::Failed);
+///     }
+///     store.state().clone()
+/// };
+///
+/// assert_eq!(run(42), run(42));
+/// assert_ne!(run(42), run(43));
+/// ```
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeededRandomness {
+    seed: u64,
+    state: u64,
+}
+
+impl SeededRandomness {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// Seed this generator was created with.
+    #[inline(always)]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl RandomnessService for SeededRandomness {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(feature = "rand")]
+impl rand_core::RngCore for SeededRandomness {
+    fn next_u32(&mut self) -> u32 {
+        RandomnessService::next_u32(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RandomnessService::next_u64(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RandomnessService::fill_bytes(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        RandomnessService::fill_bytes(self, dest);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rand")]
+impl rand_core::SeedableRng for SeededRandomness {
+    type Seed = [u8; 8];
+
+    fn from_seed(seed: Self::Seed) -> Self {
+        Self::new(u64::from_le_bytes(seed))
+    }
+}
+
+/// Access to one of the services of a bundle of services.
+///
+/// Store has a single service, so applications needing many (time,
+/// randomness, network, storage) bundle them into a struct, implement
+/// `HasService` for each part, and forward [TimeService] and
+/// [RandomnessService] to the parts implementing them with
+/// [crate::forward_service]. Effects get the part they need with
+/// `Store::service_as`, or, when written for any bundle, by bounding
+/// the service with `HasService`.
+///
+/// Every service trivially has itself.
+///
+/// ```
+/// use redux_rs::{
+///     forward_service, ActionWithMeta, EnablingCondition, FrozenTime, HasService, Instant,
+///     RandomnessService, SeededRandomness, Store, TimeService,
+/// };
+/// use std::time::{Duration, SystemTime};
+///
+/// #[derive(Default)]
+/// struct FakeNetwork {
+///     sent: Vec<(u64, Instant)>,
+/// }
+///
+/// struct Services {
+///     time: FrozenTime,
+///     rng: SeededRandomness,
+///     network: FakeNetwork,
+/// }
+///
+/// impl HasService<FrozenTime> for Services {
+///     fn service(&mut self) -> &mut FrozenTime {
+///         &mut self.time
+///     }
+/// }
+///
+/// impl HasService<SeededRandomness> for Services {
+///     fn service(&mut self) -> &mut SeededRandomness {
+///         &mut self.rng
+///     }
+/// }
+///
+/// impl HasService<FakeNetwork> for Services {
+///     fn service(&mut self) -> &mut FakeNetwork {
+///         &mut self.network
+///     }
+/// }
+///
+/// forward_service!(TimeService for Services => FrozenTime);
+/// forward_service!(RandomnessService for Services => SeededRandomness);
+///
+/// struct Send;
+/// impl EnablingCondition<()> for Send {}
+///
+/// /// Works with any bundle having a clock, randomness and a network.
+/// fn send<S>(service: &mut S)
+/// where
+///     S: TimeService + RandomnessService + HasService<FakeNetwork>,
+/// {
+///     let nonce = service.next_u64();
+///     let now = service.monotonic_time();
+///     HasService::<FakeNetwork>::service(service).sent.push((nonce, now));
+/// }
+///
+/// fn effects(store: &mut Store<(), Services, Send>, _: &ActionWithMeta<Send>) {
+///     send(&mut store.service);
+/// }
+///
+/// let start = Instant::now();
+/// let services = Services {
+///     time: FrozenTime::new(start),
+///     rng: SeededRandomness::new(7),
+///     network: FakeNetwork::default(),
+/// };
+/// let mut store = Store::new(|_, _| {}, effects, services, SystemTime::UNIX_EPOCH, ());
+///
+/// store.service_as::<FrozenTime>().advance(Duration::from_secs(1));
+/// store.dispatch(Send);
+///
+/// let nonce = SeededRandomness::new(7).next_u64();
+/// let now = start + Duration::from_secs(1);
+/// assert_eq!(store.service_as::<FakeNetwork>().sent, [(nonce, now)]);
+/// assert_eq!(store.uptime(), Duration::from_secs(1));
+/// ```
+pub trait HasService<S> {
+    fn service(&mut self) -> &mut S;
+}
+
+impl<S> HasService<S> for S {
+    #[inline(always)]
+    fn service(&mut self) -> &mut S {
+        self
+    }
+}
+
+/// Implements a service trait for a bundle of services by forwarding
+/// it to one of its parts, see [HasService].
+///
+/// Supports [TimeService] and [RandomnessService], e.g.
+/// `forward_service!(TimeService for Services => FrozenTime)`.
+#[macro_export]
+macro_rules! forward_service {
+    (TimeService for $bundle:ty => $service:ty) => {
+        impl $crate::TimeService for $bundle {
+            fn monotonic_time(&mut self) -> $crate::Instant {
+                $crate::TimeService::monotonic_time($crate::HasService::<$service>::service(self))
+            }
+
+            $crate::__forward_system_time!($service);
+        }
+    };
+    (RandomnessService for $bundle:ty => $service:ty) => {
+        impl $crate::RandomnessService for $bundle {
+            fn next_u64(&mut self) -> u64 {
+                $crate::RandomnessService::next_u64($crate::HasService::<$service>::service(self))
+            }
+
+            fn next_u32(&mut self) -> u32 {
+                $crate::RandomnessService::next_u32($crate::HasService::<$service>::service(self))
+            }
+
+            fn next_u64_below(&mut self, bound: u64) -> u64 {
+                $crate::RandomnessService::next_u64_below(
+                    $crate::HasService::<$service>::service(self),
+                    bound,
+                )
+            }
+
+            fn fill_bytes(&mut self, dest: &mut [u8]) {
+                $crate::RandomnessService::fill_bytes($crate::HasService::<$service>::service(self), dest)
+            }
+        }
+    };
+}
+
+/// `TimeService::system_time` only exists with `std`, which has to be
+/// decided by this crate's features, not the ones of the crate using
+/// [forward_service].
+#[cfg(feature = "std")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __forward_system_time {
+    ($service:ty) => {
+        fn system_time(&mut self) -> ::std::time::SystemTime {
+            $crate::TimeService::system_time($crate::HasService::<$service>::service(self))
+        }
+    };
+}
+
+#[cfg(not(feature = "std"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __forward_system_time {
+    ($service:ty) => {};
 }
@@ -0,0 +1,362 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::ActionId;
+
+/// Call of a service which can be recorded by [Recorded] and answered
+/// from the recording by [Replayed].
+///
+/// Implemented by the application, usually for a marker type per
+/// method of its service trait, with hooks encoding the input and the
+/// output, and decoding the output.
+pub trait RecordableCall {
+    /// Identifies the call in the log.
+    const NAME: &'static str;
+
+    type Input: ?Sized;
+    type Output;
+
+    fn encode_input(input: &Self::Input, buf: &mut Vec<u8>);
+
+    fn encode_output(output: &Self::Output, buf: &mut Vec<u8>);
+
+    fn decode_output(bytes: &[u8]) -> Self::Output;
+}
+
+/// Recorded call of a service.
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ServiceCall {
+    /// Action being processed when the call was made, see
+    /// `Store::set_service_call_log`.
+    pub action_id: ActionId,
+    /// [RecordableCall::NAME].
+    pub name: String,
+    pub input: Vec<u8>,
+    pub output: Vec<u8>,
+}
+
+#[derive(Default)]
+struct LogInner {
+    calls: Vec<ServiceCall>,
+    /// Index of the call [Replayed] answers next.
+    next: usize,
+    current_action_id: Option<ActionId>,
+}
+
+/// Log of service calls, shared by [Recorded] or [Replayed] in the
+/// service and the store, which tells it which action is being
+/// processed, see `Store::set_service_call_log`.
+///
+/// Calls are kept in the order they were made, each keyed by the id of
+/// the action being processed, so together with the recorded actions
+/// they form an interleaved recording.
+#[derive(Clone, Default)]
+pub struct ServiceCallLog {
+    inner: Arc<Mutex<LogInner>>,
+}
+
+impl ServiceCallLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Log to replay, see [Replayed].
+    pub fn from_calls(calls: Vec<ServiceCall>) -> Self {
+        let log = Self::default();
+        log.lock().calls = calls;
+        log
+    }
+
+    /// Recorded calls, oldest first.
+    pub fn calls(&self) -> Vec<ServiceCall> {
+        self.lock().calls.clone()
+    }
+
+    /// Number of calls [Replayed] didn't answer yet.
+    pub fn remaining(&self) -> usize {
+        let inner = self.lock();
+        inner.calls.len() - inner.next
+    }
+
+    /// Set the action being processed, returning the previous one.
+    pub(crate) fn enter(&self, action_id: Option<ActionId>) -> Option<ActionId> {
+        core::mem::replace(&mut self.lock().current_action_id, action_id)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, LogInner> {
+        // Poisoned only if a panic unwound while pushing a call, which
+        // leaves the log consistent.
+        self.inner
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl fmt::Debug for ServiceCallLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let inner = self.lock();
+        f.debug_struct("ServiceCallLog")
+            .field("calls", &inner.calls.len())
+            .field("next", &inner.next)
+            .finish()
+    }
+}
+
+/// Service `S` recording its calls into a [ServiceCallLog].
+///
+/// Application implements its service trait for `Recorded<S>`, passing
+/// recordable calls through [Recorded::call], and the same trait for
+/// [Replayed], passing them to [Replayed::call].
+///
+/// ```
+/// use redux_rs::{
+///     ActionWithMeta, EnablingCondition, RecordableCall, Recorded, Replayed, ServiceCallLog,
+///     Store, TimeService,
+/// };
+/// use std::convert::TryInto;
+/// use std::time::SystemTime;
+///
+/// trait Network {
+///     fn fetch(&mut self, url: &str) -> Option<u32>;
+/// }
+///
+/// /// `Network::fetch`, recordable.
+/// struct Fetch;
+///
+/// impl RecordableCall for Fetch {
+///     const NAME: &'static str = "fetch";
+///     type Input = str;
+///     type Output = Option<u32>;
+///
+///     fn encode_input(url: &str, buf: &mut Vec<u8>) {
+///         buf.extend_from_slice(url.as_bytes());
+///     }
+///
+///     fn encode_output(output: &Option<u32>, buf: &mut Vec<u8>) {
+///         if let Some(len) = output {
+///             buf.extend_from_slice(&len.to_le_bytes());
+///         }
+///     }
+///
+///     fn decode_output(bytes: &[u8]) -> Option<u32> {
+///         Some(u32::from_le_bytes(bytes.try_into().ok()?))
+///     }
+/// }
+///
+/// /// Real network, faked. Not needed for the replay.
+/// struct Http;
+///
+/// impl Network for Http {
+///     fn fetch(&mut self, url: &str) -> Option<u32> {
+///         url.strip_prefix("https://").map(|host| host.len() as u32)
+///     }
+/// }
+///
+/// impl Network for Recorded<Http> {
+///     fn fetch(&mut self, url: &str) -> Option<u32> {
+///         self.call::<Fetch>(url, |http, url| http.fetch(url))
+///     }
+/// }
+///
+/// impl Network for Replayed {
+///     fn fetch(&mut self, url: &str) -> Option<u32> {
+///         self.call::<Fetch>(url)
+///     }
+/// }
+///
+/// struct Service<N> {
+///     network: N,
+/// }
+///
+/// impl<N> TimeService for Service<N> {}
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// enum Action {
+///     Fetch(String),
+///     Fetched(Option<u32>),
+/// }
+///
+/// impl EnablingCondition<Vec<Action>> for Action {}
+///
+/// fn reducer(state: &mut Vec<Action>, action: &ActionWithMeta<Action>) {
+///     state.push(action.action.clone());
+/// }
+///
+/// fn effects<N: Network>(
+///     store: &mut Store<Vec<Action>, Service<N>, Action>,
+///     action: &ActionWithMeta<Action>,
+/// ) {
+///     if let Action::Fetch(url) = &action.action {
+///         let fetched = store.service.network.fetch(url);
+///         store.dispatch(Action::Fetched(fetched));
+///     }
+/// }
+///
+/// let inputs = [
+///     Action::Fetch("https://tezedge.com".to_owned()),
+///     Action::Fetch("gopher://old".to_owned()),
+/// ];
+///
+/// // Record.
+/// let log = ServiceCallLog::new();
+/// let network = Recorded::new(Http, log.clone());
+/// let service = Service { network };
+/// let mut store = Store::new(reducer, effects, service, SystemTime::UNIX_EPOCH, vec![]);
+/// store.set_service_call_log(Some(log.clone()));
+/// for input in inputs.clone() {
+///     store.dispatch(input);
+/// }
+/// let calls = log.calls();
+/// assert_eq!(calls.len(), 2);
+/// assert_eq!(calls[0].name, "fetch");
+///
+/// // Replay, without `Http`.
+/// let log = ServiceCallLog::from_calls(calls);
+/// let service = Service { network: Replayed::new(log.clone()) };
+/// let mut replay = Store::new(reducer, effects, service, SystemTime::UNIX_EPOCH, vec![]);
+/// replay.set_service_call_log(Some(log.clone()));
+/// for input in inputs {
+///     replay.dispatch(input);
+/// }
+/// assert_eq!(replay.state(), store.state());
+/// assert_eq!(log.remaining(), 0);
+/// ```
+pub struct Recorded<S> {
+    inner: S,
+    log: ServiceCallLog,
+}
+
+impl<S> Recorded<S> {
+    pub fn new(inner: S, log: ServiceCallLog) -> Self {
+        Self { inner, log }
+    }
+
+    /// Service being recorded, for calls which don't need recording.
+    #[inline(always)]
+    pub fn inner(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    pub fn log(&self) -> &ServiceCallLog {
+        &self.log
+    }
+
+    /// Make the call `C` with `f` and record it.
+    pub fn call<C: RecordableCall>(
+        &mut self,
+        input: &C::Input,
+        f: impl FnOnce(&mut S, &C::Input) -> C::Output,
+    ) -> C::Output {
+        let output = f(&mut self.inner, input);
+        let mut call = ServiceCall {
+            action_id: ActionId::ZERO,
+            name: C::NAME.to_owned(),
+            input: Vec::new(),
+            output: Vec::new(),
+        };
+        C::encode_input(input, &mut call.input);
+        C::encode_output(&output, &mut call.output);
+        let mut inner = self.log.lock();
+        call.action_id = inner.current_action_id.unwrap_or(ActionId::ZERO);
+        inner.calls.push(call);
+        output
+    }
+}
+
+/// Answers calls of service `S` from a [ServiceCallLog] recorded by
+/// [Recorded], see there.
+///
+/// `S` only tells replayed services apart, the service itself isn't
+/// needed.
+///
+/// Replay which makes calls in a different sequence than recorded
+/// fails loudly:
+///
+/// ```should_panic
+/// use redux_rs::{ActionId, RecordableCall, Replayed, ServiceCall, ServiceCallLog};
+///
+/// struct Ping;
+///
+/// impl RecordableCall for Ping {
+///     const NAME: &'static str = "ping";
+///     type Input = u8;
+///     type Output = ();
+///
+///     fn encode_input(input: &u8, buf: &mut Vec<u8>) {
+///         buf.push(*input);
+///     }
+///
+///     fn encode_output(_: &(), _: &mut Vec<u8>) {}
+///
+///     fn decode_output(_: &[u8]) {}
+/// }
+///
+/// let log = ServiceCallLog::from_calls(vec![ServiceCall {
+///     action_id: ActionId::ZERO,
+///     name: "ping".to_owned(),
+///     input: vec![1],
+///     output: vec![],
+/// }]);
+/// let mut replayed = Replayed::<()>::new(log);
+/// // Recorded with 1.
+/// replayed.call::<Ping>(&2);
+/// ```
+pub struct Replayed<S = ()> {
+    log: ServiceCallLog,
+    _service: PhantomData<S>,
+}
+
+impl<S> Replayed<S> {
+    pub fn new(log: ServiceCallLog) -> Self {
+        Self {
+            log,
+            _service: PhantomData,
+        }
+    }
+
+    pub fn log(&self) -> &ServiceCallLog {
+        &self.log
+    }
+
+    /// Output of the next recorded call.
+    ///
+    /// Panics if the next recorded call isn't `C` with the same input,
+    /// or there's none, naming the action being processed and the one
+    /// the recorded call was made by.
+    pub fn call<C: RecordableCall>(&mut self, input: &C::Input) -> C::Output {
+        let mut encoded = Vec::new();
+        C::encode_input(input, &mut encoded);
+
+        let mut inner = self.log.lock();
+        let index = inner.next;
+        let current = inner
+            .current_action_id
+            .map_or_else(|| "none".to_owned(), |id| u64::from(id).to_string());
+        let recorded = match inner.calls.get(index) {
+            Some(recorded) => recorded,
+            None => panic!(
+                "service call `{}` at action {} wasn't recorded, log has {} calls",
+                C::NAME,
+                current,
+                index
+            ),
+        };
+        if recorded.name != C::NAME || recorded.input != encoded {
+            panic!(
+                "service call #{} mismatch at action {}: recorded `{}` {:?} at action {}, got `{}` {:?}",
+                index,
+                current,
+                recorded.name,
+                recorded.input,
+                u64::from(recorded.action_id),
+                C::NAME,
+                encoded
+            );
+        }
+        let output = C::decode_output(&recorded.output);
+        inner.next += 1;
+        output
+    }
+}
@@ -0,0 +1,313 @@
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, string::String, sync::Arc, vec::Vec};
+use core::borrow::Borrow;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+/// Payload shared between actions, the state and recorders, instead of
+/// being copied into each of them.
+///
+/// Cloning only bumps a reference count, so large payloads (blocks,
+/// operation batches) can be put into actions, kept in the state and
+/// recorded, all without a deep copy. Comparison, hashing and
+/// formatting go to the payload itself, so actions holding it can
+/// derive those as if they held the payload.
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, BoundedRecorder, EnablingCondition, Shared, Store};
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// struct Block {
+///     level: u32,
+///     operations: Vec<Vec<u8>>,
+/// }
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// enum Action {
+///     BlockReceived(Shared<Block>),
+/// }
+///
+/// #[derive(Default)]
+/// struct State {
+///     head: Option<Shared<Block>>,
+/// }
+///
+/// impl EnablingCondition<State> for Action {}
+///
+/// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+///     match &action.action {
+///         Action::BlockReceived(block) => state.head = Some(block.clone()),
+///     }
+/// }
+///
+/// let mut store = Store::simple(reducer, |_, _| {}, State::default());
+/// store.set_recorder(Some(Box::new(BoundedRecorder::new(1))));
+///
+/// let block = Shared::new(Block {
+///     level: 1,
+///     operations: vec![vec![0; 1024]; 64],
+/// });
+/// store.dispatch(Action::BlockReceived(block.clone()));
+///
+/// // State and the recorder point at the very same block.
+/// let head = store.state().head.as_ref().unwrap();
+/// assert!(Shared::ptr_eq(head, &block));
+/// let recorded = store.recent_actions().next().unwrap();
+/// match &recorded.action {
+///     Action::BlockReceived(recorded) => assert!(Shared::ptr_eq(recorded, &block)),
+/// }
+/// // `block`, the state and the recorder.
+/// assert_eq!(Shared::strong_count(&block), 3);
+///
+/// // Compared by value.
+/// assert_eq!(recorded.action, Action::BlockReceived(Shared::new((*block).clone())));
+/// ```
+///
+/// With `serde`, serialized as the payload itself, and deserialized into
+/// a fresh, unshared payload, so serde's `rc` feature isn't needed.
+pub struct Shared<T: ?Sized>(Arc<T>);
+
+impl<T> Shared<T> {
+    pub fn new(value: T) -> Self {
+        Self(Arc::new(value))
+    }
+}
+
+impl<T: ?Sized> Shared<T> {
+    /// Whether both point at the same payload.
+    #[inline(always)]
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.0, &other.0)
+    }
+
+    /// Number of [Shared]s and [Arc]s pointing at the payload.
+    #[inline(always)]
+    pub fn strong_count(this: &Self) -> usize {
+        Arc::strong_count(&this.0)
+    }
+
+    #[inline(always)]
+    pub fn as_arc(this: &Self) -> &Arc<T> {
+        &this.0
+    }
+
+    #[inline(always)]
+    pub fn into_arc(this: Self) -> Arc<T> {
+        this.0
+    }
+}
+
+impl<T: ?Sized> Clone for Shared<T> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: ?Sized> Deref for Shared<T> {
+    type Target = T;
+
+    #[inline(always)]
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: ?Sized> AsRef<T> for Shared<T> {
+    #[inline(always)]
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for Shared<T> {
+    #[inline(always)]
+    fn borrow(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> From<T> for Shared<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ?Sized> From<Arc<T>> for Shared<T> {
+    fn from(arc: Arc<T>) -> Self {
+        Self(arc)
+    }
+}
+
+impl<T: Default> Default for Shared<T> {
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq for Shared<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Self::ptr_eq(self, other) || *self.0 == *other.0
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for Shared<T> {}
+
+impl<T: ?Sized + PartialOrd> PartialOrd for Shared<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        (*self.0).partial_cmp(&*other.0)
+    }
+}
+
+impl<T: ?Sized + Ord> Ord for Shared<T> {
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        (*self.0).cmp(&*other.0)
+    }
+}
+
+impl<T: ?Sized + Hash> Hash for Shared<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (*self.0).hash(state)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Shared<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for Shared<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: ?Sized + serde::Serialize> serde::Serialize for Shared<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (*self.0).serialize(serializer)
+    }
+}
+
+/// ```
+/// use redux_rs::Shared;
+///
+/// #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+/// enum Action {
+///     Operations(Shared<Vec<String>>),
+/// }
+///
+/// let action = Action::Operations(Shared::new(vec!["transfer".to_owned()]));
+/// let json = serde_json::to_string(&action).unwrap();
+/// assert_eq!(json, r#"{"Operations":["transfer"]}"#);
+///
+/// let decoded: Action = serde_json::from_str(&json).unwrap();
+/// assert_eq!(decoded, action);
+/// let (Action::Operations(decoded), Action::Operations(original)) = (&decoded, &action);
+/// assert!(!Shared::ptr_eq(decoded, original));
+/// ```
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Shared<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// Size of the data a value carries, in bytes, used for memory
+/// accounting of actions, see `Store::enable_payload_stats` and
+/// [crate::BoundedRecorder::payload_bytes].
+///
+/// Allocator statistics don't see payloads behind a [Shared], as
+/// passing them around doesn't allocate, so the payload reports its
+/// size itself. Data behind pointers is included, and a [Shared]
+/// payload is counted in full wherever it's referenced.
+pub trait PayloadSize {
+    fn payload_size(&self) -> usize;
+}
+
+macro_rules! payload_size_of {
+    ($($ty:ty),*) => {
+        $(
+            impl PayloadSize for $ty {
+                #[inline(always)]
+                fn payload_size(&self) -> usize {
+                    core::mem::size_of::<$ty>()
+                }
+            }
+        )*
+    };
+}
+
+payload_size_of!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64
+);
+
+impl PayloadSize for str {
+    #[inline(always)]
+    fn payload_size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl PayloadSize for String {
+    #[inline(always)]
+    fn payload_size(&self) -> usize {
+        self.len()
+    }
+}
+
+impl<T: PayloadSize> PayloadSize for [T] {
+    fn payload_size(&self) -> usize {
+        self.iter().map(PayloadSize::payload_size).sum()
+    }
+}
+
+impl<T: PayloadSize, const N: usize> PayloadSize for [T; N] {
+    fn payload_size(&self) -> usize {
+        self[..].payload_size()
+    }
+}
+
+impl<T: PayloadSize> PayloadSize for Vec<T> {
+    fn payload_size(&self) -> usize {
+        self[..].payload_size()
+    }
+}
+
+impl<T: PayloadSize> PayloadSize for Option<T> {
+    fn payload_size(&self) -> usize {
+        self.as_ref().map_or(0, PayloadSize::payload_size)
+    }
+}
+
+impl<T: ?Sized + PayloadSize> PayloadSize for Box<T> {
+    fn payload_size(&self) -> usize {
+        (**self).payload_size()
+    }
+}
+
+impl<T: ?Sized + PayloadSize> PayloadSize for Shared<T> {
+    fn payload_size(&self) -> usize {
+        (*self.0).payload_size()
+    }
+}
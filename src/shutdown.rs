@@ -0,0 +1,44 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// What to do with pending work on `Store::shutdown`.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum ShutdownPolicy {
+    /// Process actions queued while paused, including the ones their
+    /// effects dispatch, before shutting down.
+    DrainQueue,
+    /// Don't process queued actions, return them in the report instead.
+    Abandon,
+}
+
+/// Actions which were dispatched but not processed.
+///
+/// With `serde` feature, can be persisted and later handed over to
+/// `Store::restore_pending`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PendingActions<Action> {
+    /// Oldest first.
+    pub actions: Vec<Action>,
+}
+
+impl<Action> PendingActions<Action> {
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+/// Returned by `Store::shutdown`.
+#[derive(Debug, Clone)]
+pub struct ShutdownReport<Action> {
+    /// Number of actions processed while shutting down.
+    pub processed: u64,
+    /// Actions left unprocessed, with [ShutdownPolicy::Abandon].
+    pub abandoned: PendingActions<Action>,
+}
@@ -0,0 +1,690 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use crate::{
+    EnablingCondition, FrozenTime, HasService, Instant, Provenance, SeededRandomness, Store,
+    TimeService,
+};
+
+/// Result of [Simulation::run_until].
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub struct SimulationReport {
+    /// Scheduled inputs dispatched.
+    pub inputs: u64,
+    /// Store timers fired, see [Store::schedule_timer].
+    pub timers_fired: u64,
+}
+
+/// Runs a store over virtual time.
+///
+/// Store's service must have a [FrozenTime] clock, which only the
+/// simulation moves: [Simulation::run_until] jumps it straight to the
+/// next event, a scheduled input or a store timer, handles the event and
+/// repeats, so nothing depends on the wall clock, as long as effects
+/// don't read [TimeService::system_time], which [FrozenTime] doesn't
+/// freeze. Inputs scheduled for
+/// the same time are dispatched in the order they were scheduled, before
+/// timers due at that time.
+///
+/// Actions are processed by the store as usual, so a recorder set on
+/// the store records the simulation like any other run.
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EnablingCondition, FrozenTime, Instant, Simulation, Store};
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::{Hash, Hasher};
+/// use std::time::{Duration, SystemTime};
+///
+/// #[derive(Debug, Clone, Hash)]
+/// enum Action {
+///     Request(u32),
+///     Timeout(u32),
+///     Response(u32),
+/// }
+///
+/// #[derive(Debug, Default, Hash)]
+/// struct State {
+///     pending: Vec<u32>,
+///     timed_out: Vec<u32>,
+///     log: Vec<(u64, Action)>,
+/// }
+///
+/// impl EnablingCondition<State> for Action {
+///     fn is_enabled(&self, state: &State) -> bool {
+///         match self {
+///             Action::Request(_) => true,
+///             Action::Timeout(id) | Action::Response(id) => state.pending.contains(id),
+///         }
+///     }
+/// }
+///
+/// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+///     match action.action {
+///         Action::Request(id) => state.pending.push(id),
+///         Action::Timeout(id) => {
+///             state.pending.retain(|p| *p != id);
+///             state.timed_out.push(id);
+///         }
+///         Action::Response(id) => state.pending.retain(|p| *p != id),
+///     }
+///     state.log.push((action.id.into(), action.action.clone()));
+/// }
+///
+/// fn effects(store: &mut Store<State, FrozenTime, Action>, action: &ActionWithMeta<Action>) {
+///     if let Action::Request(id) = action.action {
+///         store.schedule_timer(Duration::from_secs(30), Action::Timeout(id));
+///     }
+/// }
+///
+/// let run = || {
+///     let clock = FrozenTime::new(Instant::now());
+///     let store = Store::new(reducer, effects, clock, SystemTime::UNIX_EPOCH, State::default());
+///     let mut sim = Simulation::new(store);
+///     sim.schedule_input(Duration::from_secs(1), Action::Request(1));
+///     sim.schedule_input(Duration::from_secs(2), Action::Request(2));
+///     sim.schedule_input(Duration::from_secs(20), Action::Response(1));
+///
+///     let report = sim.run_until(Duration::from_secs(10 * 60));
+///     assert_eq!(report.inputs, 3);
+///     assert_eq!(report.timers_fired, 2);
+///     assert_eq!(sim.elapsed(), Duration::from_secs(10 * 60));
+///
+///     let state = sim.store().state();
+///     assert_eq!(state.timed_out, [2]);
+///     let mut hasher = DefaultHasher::new();
+///     state.hash(&mut hasher);
+///     hasher.finish()
+/// };
+///
+/// // Same script, same result, to the nanosecond of every action id.
+/// assert_eq!(run(), run());
+/// ```
+pub struct Simulation<State, Service, Action> {
+    store: Store<State, Service, Action>,
+    start: Instant,
+    /// Sorted by time, inputs scheduled for the same time keep the
+    /// order in which they were scheduled.
+    inputs: Vec<(Instant, Action)>,
+}
+
+impl<State, Service, Action> Simulation<State, Service, Action>
+where
+    Service: TimeService + HasService<FrozenTime>,
+    Action: EnablingCondition<State>,
+{
+    /// Virtual time starts at the current time of the store's clock.
+    pub fn new(mut store: Store<State, Service, Action>) -> Self {
+        let start = HasService::<FrozenTime>::service(&mut store.service).now();
+        Self {
+            store,
+            start,
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Dispatch `action` when virtual time reaches `at`, counted from the
+    /// start of the simulation.
+    pub fn schedule_input(&mut self, at: Duration, action: Action) {
+        let at = self.start + at;
+        let index = self.inputs.partition_point(|(other, _)| *other <= at);
+        self.inputs.insert(index, (at, action));
+    }
+
+    /// Handle events until virtual time reaches `deadline`, counted from
+    /// the start of the simulation, and move the clock to it.
+    pub fn run_until(&mut self, deadline: Duration) -> SimulationReport {
+        let deadline = self.start + deadline;
+        let mut report = SimulationReport::default();
+        loop {
+            let next_input = self.inputs.first().map(|(at, _)| *at);
+            let next = match (next_input, self.store.next_timer_deadline()) {
+                (Some(a), Some(b)) => a.min(b),
+                (Some(a), None) | (None, Some(a)) => a,
+                (None, None) => break,
+            };
+            if next > deadline {
+                break;
+            }
+            self.clock().advance_to(next);
+
+            let now = self.clock().now();
+            while matches!(self.inputs.first(), Some((at, _)) if *at <= now) {
+                let (_, action) = self.inputs.remove(0);
+                report.inputs += 1;
+                self.store.dispatch(action);
+            }
+            report.timers_fired += self.store.fire_timers() as u64;
+        }
+        self.clock().advance_to(deadline);
+        report
+    }
+
+    /// Virtual time passed since the start of the simulation.
+    pub fn elapsed(&mut self) -> Duration {
+        let start = self.start;
+        self.clock().now().saturating_duration_since(start)
+    }
+
+    /// Number of inputs which weren't dispatched yet.
+    pub fn pending_inputs(&self) -> usize {
+        self.inputs.len()
+    }
+
+    #[inline(always)]
+    pub fn store(&self) -> &Store<State, Service, Action> {
+        &self.store
+    }
+
+    /// Store can be used directly between runs, e.g. to dispatch an
+    /// action at the current virtual time.
+    #[inline(always)]
+    pub fn store_mut(&mut self) -> &mut Store<State, Service, Action> {
+        &mut self.store
+    }
+
+    pub fn into_store(self) -> Store<State, Service, Action> {
+        self.store
+    }
+
+    fn clock(&mut self) -> &mut FrozenTime {
+        HasService::<FrozenTime>::service(&mut self.store.service)
+    }
+}
+
+/// Index of a store in a [Cluster].
+pub type NodeId = usize;
+
+/// Part of the service of a [Cluster] node, through which its effects
+/// send messages to other nodes, see [ClusterSend].
+#[derive(Debug)]
+pub struct ClusterNet<Payload> {
+    node: NodeId,
+    outbox: Vec<(NodeId, Payload)>,
+}
+
+impl<Payload> ClusterNet<Payload> {
+    /// `node` is the index of the store in the cluster.
+    pub fn new(node: NodeId) -> Self {
+        Self {
+            node,
+            outbox: Vec::new(),
+        }
+    }
+
+    /// Id of the node this service belongs to.
+    #[inline(always)]
+    pub fn node(&self) -> NodeId {
+        self.node
+    }
+}
+
+/// Sending messages to other nodes of a [Cluster], implemented for every
+/// service having a [ClusterNet].
+pub trait ClusterSend<Payload> {
+    /// Message is handed over to the cluster once the current event is
+    /// handled, then delivered according to [Cluster::set_delivery_rule].
+    fn cluster_send(&mut self, to: NodeId, payload: Payload);
+}
+
+impl<S, Payload> ClusterSend<Payload> for S
+where
+    S: HasService<ClusterNet<Payload>>,
+{
+    fn cluster_send(&mut self, to: NodeId, payload: Payload) {
+        HasService::<ClusterNet<Payload>>::service(self)
+            .outbox
+            .push((to, payload));
+    }
+}
+
+/// What happens to a message sent within a [Cluster].
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum Delivery {
+    Deliver {
+        delay: Duration,
+    },
+    Drop,
+    /// Delivered twice, after `delay` and after `again_after` more.
+    Duplicate {
+        delay: Duration,
+        again_after: Duration,
+    },
+}
+
+/// Decides what happens to a message from node `from` to node `to`.
+/// Randomness is seeded by the cluster, see [Cluster::new].
+pub type DeliveryRule<Payload> =
+    fn(from: NodeId, to: NodeId, payload: &Payload, rng: &mut SeededRandomness) -> Delivery;
+
+/// Input dispatched or message delivered to a node of a [Cluster], as
+/// recorded by [Cluster::record_events].
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ClusterEvent<Action> {
+    /// Virtual time, counted from the start of the cluster.
+    pub at: Duration,
+    pub node: NodeId,
+    pub action: Action,
+}
+
+/// Events recorded by [Cluster::record_events].
+struct EventLog<Action> {
+    events: Vec<ClusterEvent<Action>>,
+    clone_action: fn(&Action) -> Action,
+}
+
+struct InFlight<Payload> {
+    at: Instant,
+    from: NodeId,
+    to: NodeId,
+    payload: Payload,
+}
+
+/// Several stores running over one virtual clock, exchanging messages
+/// through a simulated network.
+///
+/// Each store's service must have a [FrozenTime] clock and a
+/// [ClusterNet], with which effects send messages using
+/// [ClusterSend::cluster_send]. What happens to a message is decided by
+/// the [DeliveryRule], delivered payloads are turned into actions on the
+/// destination store with `deliver`.
+///
+/// [Cluster::run_until] repeatedly handles the earliest event across all
+/// the nodes: scheduled input, message delivery or store timer. Events
+/// at the same time are handled in a fixed order (inputs, then messages
+/// in the order they were sent, then timers by node), so runs with the
+/// same script and seed are identical. Inputs and deliveries, the only
+/// things coming from outside the stores, can be recorded with
+/// [Cluster::record_events] and replayed with [Cluster::replay].
+///
+/// ```
+/// use redux_rs::{
+///     forward_service, ActionWithMeta, Cluster, ClusterEvent, ClusterNet, ClusterSend, Delivery,
+///     EnablingCondition, FrozenTime, HasService, Instant, NodeId, SeededRandomness, Store,
+/// };
+/// use std::sync::atomic::{AtomicBool, Ordering};
+/// use std::time::{Duration, SystemTime};
+///
+/// struct Services {
+///     time: FrozenTime,
+///     net: ClusterNet<Message>,
+/// }
+///
+/// impl HasService<FrozenTime> for Services {
+///     fn service(&mut self) -> &mut FrozenTime {
+///         &mut self.time
+///     }
+/// }
+///
+/// impl HasService<ClusterNet<Message>> for Services {
+///     fn service(&mut self) -> &mut ClusterNet<Message> {
+///         &mut self.net
+///     }
+/// }
+///
+/// forward_service!(TimeService for Services => FrozenTime);
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Message {
+///     Ping(u32),
+///     Pong(u32),
+/// }
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// enum Action {
+///     SendPing(u32),
+///     PingTimeout(u32),
+///     Received(NodeId, Message),
+/// }
+///
+/// #[derive(Debug, Default, PartialEq)]
+/// struct State {
+///     waiting: Option<u32>,
+///     ponged: Vec<u32>,
+///     pings_sent: u32,
+///     pings_received: u32,
+/// }
+///
+/// impl EnablingCondition<State> for Action {
+///     fn is_enabled(&self, state: &State) -> bool {
+///         match self {
+///             Action::PingTimeout(n) => state.waiting == Some(*n),
+///             _ => true,
+///         }
+///     }
+/// }
+///
+/// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+///     match action.action {
+///         Action::SendPing(n) => {
+///             state.waiting = Some(n);
+///             state.pings_sent += 1;
+///         }
+///         Action::PingTimeout(_) => state.waiting = None,
+///         Action::Received(_, Message::Ping(_)) => state.pings_received += 1,
+///         Action::Received(_, Message::Pong(n)) => {
+///             if state.waiting == Some(n) {
+///                 state.waiting = None;
+///                 state.ponged.push(n);
+///             }
+///         }
+///     }
+/// }
+///
+/// fn effects(store: &mut Store<State, Services, Action>, action: &ActionWithMeta<Action>) {
+///     match action.action {
+///         // Node 0 pings node 1, retrying after a second.
+///         Action::SendPing(n) => {
+///             store.service.cluster_send(1, Message::Ping(n));
+///             store.schedule_timer(Duration::from_secs(1), Action::PingTimeout(n));
+///         }
+///         Action::PingTimeout(n) => {
+///             store.dispatch(Action::SendPing(n));
+///         }
+///         Action::Received(from, Message::Ping(n)) => {
+///             store.service.cluster_send(from, Message::Pong(n));
+///         }
+///         Action::Received(_, Message::Pong(_)) => {}
+///     }
+/// }
+///
+/// fn node(id: NodeId, start: Instant) -> Store<State, Services, Action> {
+///     let services = Services {
+///         time: FrozenTime::new(start),
+///         net: ClusterNet::new(id),
+///     };
+///     Store::new(reducer, effects, services, SystemTime::UNIX_EPOCH, State::default())
+/// }
+///
+/// fn deliver(from: NodeId, message: Message) -> Action {
+///     Action::Received(from, message)
+/// }
+///
+/// /// Every message takes 100ms, first ping is lost.
+/// fn lose_first_ping(_: NodeId, _: NodeId, message: &Message, _: &mut SeededRandomness) -> Delivery {
+///     static LOST: AtomicBool = AtomicBool::new(false);
+///     match message {
+///         Message::Ping(_) if !LOST.swap(true, Ordering::SeqCst) => Delivery::Drop,
+///         _ => Delivery::Deliver { delay: Duration::from_millis(100) },
+///     }
+/// }
+///
+/// let start = Instant::now();
+/// let mut cluster = Cluster::new(vec![node(0, start), node(1, start)], deliver, 42);
+/// cluster.set_delivery_rule(lose_first_ping);
+/// cluster.record_events();
+/// cluster.schedule_input(Duration::ZERO, 0, Action::SendPing(0));
+/// cluster.schedule_input(Duration::from_secs(5), 0, Action::SendPing(1));
+/// cluster.run_until(Duration::from_secs(60));
+///
+/// // Lost ping got retried after the timeout, the second one went through.
+/// let pinger = cluster.store(0).state();
+/// assert_eq!(pinger.ponged, [0, 1]);
+/// assert_eq!(pinger.pings_sent, 3);
+/// assert_eq!(pinger.waiting, None);
+/// assert_eq!(cluster.store(1).state().pings_received, 2);
+///
+/// // Ping-pong of the retry took 200ms.
+/// let events = cluster.recorded_events().unwrap().to_vec();
+/// assert_eq!(
+///     events[..3],
+///     [
+///         ClusterEvent { at: Duration::ZERO, node: 0, action: Action::SendPing(0) },
+///         ClusterEvent {
+///             at: Duration::from_millis(1100),
+///             node: 1,
+///             action: Action::Received(0, Message::Ping(0)),
+///         },
+///         ClusterEvent {
+///             at: Duration::from_millis(1200),
+///             node: 0,
+///             action: Action::Received(1, Message::Pong(0)),
+///         },
+///     ]
+/// );
+///
+/// // Replaying the recording on fresh nodes gives the same states.
+/// let mut replayed = Cluster::new(vec![node(0, start), node(1, start)], deliver, 0);
+/// replayed.replay(&events);
+/// replayed.run_until(Duration::from_secs(60));
+/// for id in 0..2 {
+///     assert_eq!(replayed.store(id).state(), cluster.store(id).state());
+/// }
+/// ```
+pub struct Cluster<State, Service, Action, Payload> {
+    stores: Vec<Store<State, Service, Action>>,
+    start: Instant,
+    now: Instant,
+    /// Sorted by time, then by order of scheduling.
+    inputs: Vec<(Instant, NodeId, Action)>,
+    /// Sorted by delivery time, then by order of sending.
+    in_flight: Vec<InFlight<Payload>>,
+    deliver: fn(NodeId, Payload) -> Action,
+    delivery_rule: DeliveryRule<Payload>,
+    rng: SeededRandomness,
+    /// Messages sent by the stores are dropped, while replaying.
+    replaying: bool,
+    recording: Option<EventLog<Action>>,
+}
+
+/// Delivers every message right away.
+fn deliver_immediately<Payload>(
+    _: NodeId,
+    _: NodeId,
+    _: &Payload,
+    _: &mut SeededRandomness,
+) -> Delivery {
+    Delivery::Deliver {
+        delay: Duration::ZERO,
+    }
+}
+
+impl<State, Service, Action, Payload> Cluster<State, Service, Action, Payload>
+where
+    Service: TimeService + HasService<FrozenTime> + HasService<ClusterNet<Payload>>,
+    Action: EnablingCondition<State>,
+    Payload: Clone,
+{
+    /// Node ids are indices in `stores`. Virtual time starts at the
+    /// latest of the stores' clocks, the rest are moved to it.
+    ///
+    /// Panics if `stores` is empty.
+    ///
+    /// Messages are delivered right away until a rule is set with
+    /// [Cluster::set_delivery_rule], which gets randomness seeded with
+    /// `seed`.
+    pub fn new(
+        mut stores: Vec<Store<State, Service, Action>>,
+        deliver: fn(NodeId, Payload) -> Action,
+        seed: u64,
+    ) -> Self {
+        let start = stores
+            .iter_mut()
+            .map(|store| HasService::<FrozenTime>::service(&mut store.service).now())
+            .max()
+            .expect("cluster needs at least one store");
+        let mut cluster = Self {
+            stores,
+            start,
+            now: start,
+            inputs: Vec::new(),
+            in_flight: Vec::new(),
+            deliver,
+            delivery_rule: deliver_immediately,
+            rng: SeededRandomness::new(seed),
+            replaying: false,
+            recording: None,
+        };
+        cluster.advance_clocks();
+        cluster
+    }
+
+    pub fn set_delivery_rule(&mut self, rule: DeliveryRule<Payload>) {
+        self.delivery_rule = rule;
+    }
+
+    /// Start recording inputs and deliveries, see [Cluster::recorded_events].
+    pub fn record_events(&mut self)
+    where
+        Action: Clone,
+    {
+        self.recording = Some(EventLog {
+            events: Vec::new(),
+            clone_action: Action::clone,
+        });
+    }
+
+    /// Inputs and deliveries handled since [Cluster::record_events], in
+    /// order. `None` if not recording.
+    pub fn recorded_events(&self) -> Option<&[ClusterEvent<Action>]> {
+        self.recording.as_ref().map(|log| &log.events[..])
+    }
+
+    /// Schedule recorded `events` as inputs, and from now on drop
+    /// messages sent by the stores, since their deliveries are part of
+    /// the recording.
+    pub fn replay(&mut self, events: &[ClusterEvent<Action>])
+    where
+        Action: Clone,
+    {
+        self.replaying = true;
+        for event in events {
+            self.schedule_input(event.at, event.node, event.action.clone());
+        }
+    }
+
+    /// Dispatch `action` on node `node` when virtual time reaches `at`,
+    /// counted from the start of the cluster.
+    pub fn schedule_input(&mut self, at: Duration, node: NodeId, action: Action) {
+        let at = self.start + at;
+        let index = self.inputs.partition_point(|(other, ..)| *other <= at);
+        self.inputs.insert(index, (at, node, action));
+    }
+
+    /// Handle events until virtual time reaches `deadline`, counted from
+    /// the start of the cluster, and move the clocks to it.
+    pub fn run_until(&mut self, deadline: Duration) -> SimulationReport {
+        let deadline = self.start + deadline;
+        let mut report = SimulationReport::default();
+        while let Some(next) = self.next_event() {
+            if next > deadline {
+                break;
+            }
+            self.now = self.now.max(next);
+            self.advance_clocks();
+
+            while matches!(self.inputs.first(), Some((at, ..)) if *at <= self.now) {
+                let (_, node, action) = self.inputs.remove(0);
+                report.inputs += 1;
+                self.dispatch(node, action, Provenance::External);
+            }
+            while matches!(self.in_flight.first(), Some(message) if message.at <= self.now) {
+                let message = self.in_flight.remove(0);
+                let action = (self.deliver)(message.from, message.payload);
+                self.dispatch(message.to, action, Provenance::Remote);
+            }
+            for node in 0..self.stores.len() {
+                report.timers_fired += self.stores[node].fire_timers() as u64;
+                self.collect_sent(node);
+            }
+        }
+        self.now = self.now.max(deadline);
+        self.advance_clocks();
+        report
+    }
+
+    /// Virtual time passed since the start of the cluster.
+    pub fn elapsed(&self) -> Duration {
+        self.now.saturating_duration_since(self.start)
+    }
+
+    /// Number of nodes.
+    pub fn len(&self) -> usize {
+        self.stores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stores.is_empty()
+    }
+
+    /// Messages sent, but not delivered yet.
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+
+    /// Panics if there is no such node.
+    pub fn store(&self, node: NodeId) -> &Store<State, Service, Action> {
+        &self.stores[node]
+    }
+
+    /// Panics if there is no such node.
+    pub fn store_mut(&mut self, node: NodeId) -> &mut Store<State, Service, Action> {
+        &mut self.stores[node]
+    }
+
+    pub fn into_stores(self) -> Vec<Store<State, Service, Action>> {
+        self.stores
+    }
+
+    fn next_event(&self) -> Option<Instant> {
+        let inputs = self.inputs.first().map(|(at, ..)| *at);
+        let in_flight = self.in_flight.first().map(|message| message.at);
+        let timers = self
+            .stores
+            .iter()
+            .filter_map(|store| store.next_timer_deadline());
+        inputs.into_iter().chain(in_flight).chain(timers).min()
+    }
+
+    fn advance_clocks(&mut self) {
+        for store in &mut self.stores {
+            HasService::<FrozenTime>::service(&mut store.service).advance_to(self.now);
+        }
+    }
+
+    fn dispatch(&mut self, node: NodeId, action: Action, provenance: Provenance) {
+        if let Some(log) = self.recording.as_mut() {
+            log.events.push(ClusterEvent {
+                at: self.now.saturating_duration_since(self.start),
+                node,
+                action: (log.clone_action)(&action),
+            });
+        }
+        self.stores[node].dispatch_from(action, provenance);
+        self.collect_sent(node);
+    }
+
+    /// Take messages sent by the node and put them in flight according
+    /// to the delivery rule.
+    fn collect_sent(&mut self, from: NodeId) {
+        let net = HasService::<ClusterNet<Payload>>::service(&mut self.stores[from].service);
+        let sent = core::mem::take(&mut net.outbox);
+        if self.replaying {
+            return;
+        }
+        for (to, payload) in sent {
+            match (self.delivery_rule)(from, to, &payload, &mut self.rng) {
+                Delivery::Deliver { delay } => self.send(from, to, payload, delay),
+                Delivery::Drop => {}
+                Delivery::Duplicate { delay, again_after } => {
+                    self.send(from, to, payload.clone(), delay);
+                    self.send(from, to, payload, delay + again_after);
+                }
+            }
+        }
+    }
+
+    fn send(&mut self, from: NodeId, to: NodeId, payload: Payload, delay: Duration) {
+        let at = self.now + delay;
+        let index = self.in_flight.partition_point(|other| other.at <= at);
+        self.in_flight.insert(
+            index,
+            InFlight {
+                at,
+                from,
+                to,
+                payload,
+            },
+        );
+    }
+}
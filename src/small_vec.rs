@@ -0,0 +1,89 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::{self, Vec};
+use core::array;
+use core::iter::Flatten;
+#[cfg(feature = "std")]
+use std::vec;
+
+/// Vec keeping up to `N` items inline, moving them to the heap only
+/// when more are pushed.
+///
+/// For internal lists which are usually empty or tiny, so that building
+/// them doesn't allocate.
+pub(crate) enum SmallVec<T, const N: usize> {
+    /// Slots after the first `len` are `None`.
+    Inline {
+        items: [Option<T>; N],
+        len: usize,
+    },
+    Heap(Vec<T>),
+}
+
+impl<T, const N: usize> SmallVec<T, N> {
+    pub fn new() -> Self {
+        Self::Inline {
+            items: array::from_fn(|_| None),
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, item: T) {
+        match self {
+            Self::Inline { items, len } if *len < N => {
+                items[*len] = Some(item);
+                *len += 1;
+            }
+            Self::Inline { items, .. } => {
+                let mut heap = Vec::with_capacity(N * 2);
+                heap.extend(items.iter_mut().filter_map(Option::take));
+                heap.push(item);
+                *self = Self::Heap(heap);
+            }
+            Self::Heap(heap) => heap.push(item),
+        }
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Inline { len, .. } => *len,
+            Self::Heap(heap) => heap.len(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+pub(crate) enum IntoIter<T, const N: usize> {
+    Inline(Flatten<array::IntoIter<Option<T>, N>>),
+    Heap(vec::IntoIter<T>),
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<T> {
+        match self {
+            Self::Inline(iter) => iter.next(),
+            Self::Heap(iter) => iter.next(),
+        }
+    }
+}
+
+impl<T, const N: usize> IntoIterator for SmallVec<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            Self::Inline { items, .. } => {
+                IntoIter::Inline(IntoIterator::into_iter(items).flatten())
+            }
+            Self::Heap(heap) => IntoIter::Heap(heap.into_iter()),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+#[cfg(not(feature = "std"))]
+use alloc::collections::VecDeque;
+use core::any::Any;
+#[cfg(feature = "std")]
+use std::collections::VecDeque;
+
+use crate::ActionId;
+
+/// Last `capacity` post-reducer states (or their summaries), tagged
+/// with the id of the action which produced them. Oldest are evicted
+/// first.
+///
+/// Takes `capacity` times the size of `Summary` (including its heap
+/// allocations) of memory, so for big states store a projection
+/// rather than the whole state, see `Store::enable_state_history_with`.
+#[derive(Debug, Clone)]
+pub struct StateHistory<Summary> {
+    entries: VecDeque<(ActionId, Summary)>,
+    capacity: usize,
+}
+
+impl<Summary> StateHistory<Summary> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    fn push(&mut self, id: ActionId, summary: Summary) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((id, summary));
+    }
+
+    /// Entries, oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (ActionId, &Summary)> {
+        self.entries.iter().map(|(id, summary)| (*id, summary))
+    }
+
+    /// Entry produced by the action `id`, if it's still kept.
+    pub fn get(&self, id: ActionId) -> Option<&Summary> {
+        self.entries
+            .iter()
+            .find(|(entry_id, _)| *entry_id == id)
+            .map(|(_, summary)| summary)
+    }
+
+    /// Most recent entry.
+    pub fn last(&self) -> Option<(ActionId, &Summary)> {
+        self.entries.back().map(|(id, summary)| (*id, summary))
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+/// [StateHistory] with its projection, with summary type erased so
+/// that the store doesn't need to be generic over it.
+pub(crate) trait StateHistoryRecorder<State> {
+    fn record(&mut self, id: ActionId, state: &State);
+
+    /// The [StateHistory].
+    fn history(&self) -> &dyn Any;
+}
+
+pub(crate) struct ProjectedHistory<State, Summary> {
+    project: fn(&State) -> Summary,
+    history: StateHistory<Summary>,
+}
+
+impl<State, Summary> ProjectedHistory<State, Summary> {
+    pub fn new(capacity: usize, project: fn(&State) -> Summary) -> Self {
+        Self {
+            project,
+            history: StateHistory::new(capacity),
+        }
+    }
+}
+
+impl<State, Summary: 'static> StateHistoryRecorder<State> for ProjectedHistory<State, Summary> {
+    fn record(&mut self, id: ActionId, state: &State) {
+        self.history.push(id, (self.project)(state));
+    }
+
+    fn history(&self) -> &dyn Any {
+        &self.history
+    }
+}
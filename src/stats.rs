@@ -0,0 +1,696 @@
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap;
+#[cfg(all(feature = "memory", not(feature = "std")))]
+use alloc::vec::Vec;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use crate::{
+    ActionClassifier, ActionId, ActionWithMeta, Capabilities, DispatchTimings, Provenance,
+    ScratchStats,
+};
+
+/// Aggregated statistics for a single action kind.
+#[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct KindStats {
+    pub count: u64,
+    pub reducer_total: Duration,
+    pub reducer_max: Duration,
+    /// Effects durations include processing of actions dispatched from them.
+    pub effects_total: Duration,
+    pub effects_max: Duration,
+    #[cfg(feature = "memory")]
+    pub allocated_total: i64,
+    #[cfg(feature = "memory")]
+    pub allocated_max: i64,
+    /// Actions coalesced away while queued, see `Store::set_coalescing`.
+    /// Not included in `count`.
+    pub coalesced: u64,
+    /// Actions dropped because they expired while queued, see
+    /// `Store::dispatch_with_ttl`. Not included in `count`.
+    pub expired: u64,
+    /// Actions dropped by the rate limiter, see
+    /// `Store::set_rate_limiter`. Not included in `count`.
+    pub rate_limited: u64,
+    /// Actions rejected as duplicates, see `Store::set_dedup`. Not
+    /// included in `count`.
+    pub duplicates: u64,
+    /// Sum of [crate::PayloadSize]s of processed actions, 0 unless
+    /// enabled with `Store::enable_payload_stats`.
+    pub payload_total: u64,
+    pub payload_max: u64,
+    /// Breakdown of `count` by where the actions came from.
+    pub provenance: ProvenanceCounts,
+}
+
+impl KindStats {
+    fn record(&mut self, provenance: Provenance, timings: &DispatchTimings) {
+        self.count += 1;
+        self.provenance.count(provenance);
+        self.reducer_total += timings.reducer;
+        self.reducer_max = self.reducer_max.max(timings.reducer);
+        self.effects_total += timings.effects;
+        self.effects_max = self.effects_max.max(timings.effects);
+        #[cfg(feature = "memory")]
+        {
+            self.allocated_total += timings.allocated;
+            self.allocated_max = self.allocated_max.max(timings.allocated);
+        }
+    }
+}
+
+/// Processed actions counted by [Provenance], ignoring the parent or
+/// timer ids.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ProvenanceCounts {
+    pub external: u64,
+    pub effect: u64,
+    pub timer: u64,
+    #[cfg(feature = "std")]
+    pub recurring: u64,
+    #[cfg(feature = "std")]
+    pub watchdog: u64,
+    pub replay: u64,
+    pub bridge: u64,
+    pub remote: u64,
+}
+
+impl ProvenanceCounts {
+    /// Count of actions with the same variant as `provenance`.
+    pub fn get(&self, provenance: Provenance) -> u64 {
+        match provenance {
+            Provenance::External => self.external,
+            Provenance::Effect { .. } => self.effect,
+            Provenance::Timer { .. } => self.timer,
+            #[cfg(feature = "std")]
+            Provenance::Recurring { .. } => self.recurring,
+            #[cfg(feature = "std")]
+            Provenance::Watchdog => self.watchdog,
+            Provenance::Replay => self.replay,
+            Provenance::Bridge => self.bridge,
+            Provenance::Remote => self.remote,
+        }
+    }
+
+    fn count(&mut self, provenance: Provenance) {
+        let count = match provenance {
+            Provenance::External => &mut self.external,
+            Provenance::Effect { .. } => &mut self.effect,
+            Provenance::Timer { .. } => &mut self.timer,
+            #[cfg(feature = "std")]
+            Provenance::Recurring { .. } => &mut self.recurring,
+            #[cfg(feature = "std")]
+            Provenance::Watchdog => &mut self.watchdog,
+            Provenance::Replay => &mut self.replay,
+            Provenance::Bridge => &mut self.bridge,
+            Provenance::Remote => &mut self.remote,
+        };
+        *count += 1;
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.external += other.external;
+        self.effect += other.effect;
+        self.timer += other.timer;
+        #[cfg(feature = "std")]
+        {
+            self.recurring += other.recurring;
+            self.watchdog += other.watchdog;
+        }
+        self.replay += other.replay;
+        self.bridge += other.bridge;
+        self.remote += other.remote;
+    }
+}
+
+/// Number of actions kept by [DispatchStats::top_allocating].
+#[cfg(feature = "memory")]
+pub const TOP_ALLOCATING_LEN: usize = 16;
+
+/// Processed action along with bytes allocated while processing it.
+#[cfg(feature = "memory")]
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AllocatingAction {
+    pub id: ActionId,
+    pub kind: &'static str,
+    /// Change of total allocated bytes by reducer and effects.
+    pub allocated: i64,
+}
+
+/// Number of buckets of [KindCascades::sizes].
+pub const CASCADE_SIZE_BUCKETS: usize = 16;
+
+/// Cascades started by actions of a single kind, i.e. by actions of the
+/// kind dispatched from outside of effects.
+#[derive(Debug, Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct KindCascades {
+    pub cascades: u64,
+    /// Actions dispatched, directly or not, from effects of the roots.
+    pub descendants_total: u64,
+    pub descendants_max: u64,
+    /// Deepest nesting of a descendant, roots being at depth 0.
+    pub depth_max: u32,
+    /// Distribution of descendant counts: `sizes[0]` counts cascades
+    /// without descendants, `sizes[i]` the ones with `2^(i-1)..2^i`
+    /// descendants, the last bucket includes all the bigger ones.
+    pub sizes: [u64; CASCADE_SIZE_BUCKETS],
+}
+
+impl KindCascades {
+    fn record(&mut self, cascade: &Cascade) {
+        self.cascades += 1;
+        self.descendants_total += cascade.descendants;
+        self.descendants_max = self.descendants_max.max(cascade.descendants);
+        self.depth_max = self.depth_max.max(cascade.depth);
+        let bucket = (u64::BITS - cascade.descendants.leading_zeros()) as usize;
+        self.sizes[bucket.min(CASCADE_SIZE_BUCKETS - 1)] += 1;
+    }
+}
+
+/// Cascade of actions, the root one and its descendants.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Cascade {
+    pub root: ActionId,
+    pub kind: &'static str,
+    pub descendants: u64,
+    pub depth: u32,
+}
+
+/// Sizes and depths of cascades of actions, aggregated per kind of the
+/// root action, see `Store::cascade_stats`.
+///
+/// Only the cascade being processed is kept, it's folded into the
+/// aggregates once the root action is done.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CascadeStats {
+    kinds: BTreeMap<&'static str, KindCascades>,
+    /// Cascade with the most descendants, the earliest one on ties.
+    worst: Option<Cascade>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    current: Option<Cascade>,
+}
+
+impl CascadeStats {
+    /// Cascades started by actions of `kind`, if any completed.
+    pub fn kind(&self, kind: &str) -> Option<&KindCascades> {
+        self.kinds.get(kind)
+    }
+
+    /// Cascades of all kinds, sorted by kind.
+    pub fn kinds(&self) -> impl Iterator<Item = (&'static str, &KindCascades)> {
+        self.kinds.iter().map(|(kind, cascades)| (*kind, cascades))
+    }
+
+    /// Completed cascade with the most descendants.
+    #[inline(always)]
+    pub fn worst(&self) -> Option<&Cascade> {
+        self.worst.as_ref()
+    }
+
+    /// Count a processed action at `depth`, starting a new cascade if
+    /// it's a root one.
+    fn record_action(&mut self, id: ActionId, kind: &'static str, depth: u32) {
+        match self.current.as_mut() {
+            Some(current) if depth > 0 => {
+                current.descendants += 1;
+                current.depth = current.depth.max(depth);
+            }
+            _ => {
+                self.current = Some(Cascade {
+                    root: id,
+                    kind,
+                    descendants: 0,
+                    depth: 0,
+                });
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        let cascade = match self.current.take() {
+            Some(cascade) => cascade,
+            None => return,
+        };
+        self.kinds.entry(cascade.kind).or_default().record(&cascade);
+        if !matches!(self.worst, Some(worst) if worst.descendants >= cascade.descendants) {
+            self.worst = Some(cascade);
+        }
+    }
+}
+
+/// Per action kind runtime statistics, collected by the store when
+/// enabled with `Store::enable_stats`.
+///
+/// Durations are measured with the store's [crate::TimeService], which
+/// means the clock is read two more times per action.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DispatchStats {
+    kinds: BTreeMap<&'static str, KindStats>,
+    /// Same as `kinds`, by module, see [crate::ModuleId].
+    modules: BTreeMap<&'static str, KindStats>,
+    /// Sorted by `allocated`, descending.
+    #[cfg(feature = "memory")]
+    top_allocating: Vec<AllocatingAction>,
+    scratch: Option<ScratchStats>,
+    cascades: CascadeStats,
+    capabilities: Capabilities,
+    paused_actions: usize,
+    pending_timers: usize,
+}
+
+impl DispatchStats {
+    /// Statistics of a given kind, if any action of that kind was
+    /// processed, coalesced or expired.
+    pub fn kind(&self, kind: &str) -> Option<&KindStats> {
+        self.kinds.get(kind)
+    }
+
+    /// Statistics of all kinds, sorted by kind.
+    pub fn kinds(&self) -> impl Iterator<Item = (&'static str, &KindStats)> {
+        self.kinds.iter().map(|(kind, stats)| (*kind, stats))
+    }
+
+    /// Statistics of actions of a registered module, by its type name,
+    /// or of actions of no module, by `"root"`. Only counts, durations
+    /// and allocations of processed actions are aggregated by module.
+    pub fn module(&self, name: &str) -> Option<&KindStats> {
+        self.modules.get(name)
+    }
+
+    /// Statistics of all modules which processed an action, sorted by
+    /// name.
+    ///
+    /// ```
+    /// use redux_rs::{
+    ///     embed, lens, ActionWithMeta, EnablingCondition, Module, ModuleRegistry, StoreBuilder,
+    /// };
+    ///
+    /// struct Peers;
+    ///
+    /// impl Module for Peers {
+    ///     type SubState = u32;
+    ///     type SubAction = ();
+    ///
+    ///     fn initial_state() -> u32 {
+    ///         0
+    ///     }
+    ///
+    ///     fn reducer(state: &mut u32, _: &ActionWithMeta<&()>) {
+    ///         *state += 1;
+    ///     }
+    /// }
+    ///
+    /// struct Blocks;
+    ///
+    /// impl Module for Blocks {
+    ///     type SubState = u32;
+    ///     type SubAction = u32;
+    ///
+    ///     fn initial_state() -> u32 {
+    ///         0
+    ///     }
+    ///
+    ///     fn reducer(state: &mut u32, action: &ActionWithMeta<&u32>) {
+    ///         *state = *action.action;
+    ///     }
+    /// }
+    ///
+    /// #[derive(Default)]
+    /// struct State {
+    ///     peers: u32,
+    ///     level: u32,
+    /// }
+    ///
+    /// enum Action {
+    ///     Peer(()),
+    ///     Block(u32),
+    ///     Tick,
+    /// }
+    /// impl EnablingCondition<State> for Action {}
+    ///
+    /// let mut modules = ModuleRegistry::<State, (), Action>::new();
+    /// modules.register::<Peers>(lens!(peers), embed!(Action::Peer));
+    /// modules.register::<Blocks>(lens!(level), embed!(Action::Block));
+    ///
+    /// let mut store = StoreBuilder::new()
+    ///     .service(())
+    ///     .initial_state(State::default())
+    ///     .modules(modules)
+    ///     .build()
+    ///     .unwrap();
+    /// store.enable_stats(|action| match action {
+    ///     Action::Peer(_) => "Peer",
+    ///     Action::Block(_) => "Block",
+    ///     Action::Tick => "Tick",
+    /// });
+    /// store.dispatch(Action::Peer(()));
+    /// store.dispatch(Action::Peer(()));
+    /// store.dispatch(Action::Block(7));
+    /// store.dispatch(Action::Tick);
+    ///
+    /// let stats = store.stats().unwrap();
+    /// let mut counts: Vec<_> = stats
+    ///     .modules()
+    ///     .map(|(name, stats)| (name.rsplit("::").next().unwrap(), stats.count))
+    ///     .collect();
+    /// counts.sort();
+    /// assert_eq!(counts, [("Blocks", 1), ("Peers", 2), ("root", 1)]);
+    ///
+    /// let peers = store.module_name(store.module_id::<Peers>().unwrap()).unwrap();
+    /// assert_eq!(stats.module(peers).unwrap().count, 2);
+    /// ```
+    pub fn modules(&self) -> impl Iterator<Item = (&'static str, &KindStats)> {
+        self.modules.iter().map(|(module, stats)| (*module, stats))
+    }
+
+    /// Total number of recorded actions.
+    pub fn total_count(&self) -> u64 {
+        self.kinds.values().map(|stats| stats.count).sum()
+    }
+
+    /// Recorded actions of all kinds counted by where they came from.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Provenance, Store};
+    ///
+    /// enum Action {
+    ///     Request,
+    ///     Response,
+    /// }
+    ///
+    /// impl EnablingCondition<()> for Action {}
+    ///
+    /// fn effects(store: &mut Store<(), (), Action>, action: &ActionWithMeta<Action>) {
+    ///     if let Action::Request = action.action {
+    ///         store.dispatch(Action::Response);
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(|_, _| {}, effects, ());
+    /// store.enable_stats(|action| match action {
+    ///     Action::Request => "Request",
+    ///     Action::Response => "Response",
+    /// });
+    /// store.dispatch(Action::Request);
+    /// store.dispatch(Action::Request);
+    ///
+    /// let stats = store.stats().unwrap();
+    /// let response = stats.kind("Response").unwrap().provenance;
+    /// assert_eq!(response.effect, 2);
+    /// assert_eq!(response.external, 0);
+    /// assert_eq!(stats.provenance().get(Provenance::External), 2);
+    /// ```
+    pub fn provenance(&self) -> ProvenanceCounts {
+        let mut total = ProvenanceCounts::default();
+        for stats in self.kinds.values() {
+            total.add(&stats.provenance);
+        }
+        total
+    }
+
+    /// Up to [TOP_ALLOCATING_LEN] actions which allocated the most,
+    /// biggest first.
+    #[cfg(feature = "memory")]
+    pub fn top_allocating(&self) -> &[AllocatingAction] {
+        &self.top_allocating
+    }
+
+    /// Scratch buffer counters of the recorder, as of the last recorded
+    /// action, if the recorder pools buffers, see
+    /// [crate::Recorder::scratch_stats].
+    #[inline(always)]
+    pub fn scratch(&self) -> Option<ScratchStats> {
+        self.scratch
+    }
+
+    #[inline(always)]
+    pub fn cascades(&self) -> &CascadeStats {
+        &self.cascades
+    }
+
+    /// Capabilities of the store as of the last change, see
+    /// `Store::capabilities`.
+    #[inline(always)]
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    pub(crate) fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.capabilities = capabilities;
+    }
+
+    /// Actions queued while paused, including the ones left to process
+    /// while resuming, see `Store::resume`.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Store};
+    /// use std::time::Duration;
+    ///
+    /// struct Action(usize);
+    /// impl EnablingCondition<()> for Action {}
+    ///
+    /// fn effects(store: &mut Store<(), (), Action>, action: &ActionWithMeta<Action>) {
+    ///     let stats = store.stats().unwrap();
+    ///     // Actions queued after this one are still waiting.
+    ///     assert_eq!(stats.paused_actions(), 2 - action.action.0);
+    ///     assert_eq!(stats.pending_timers(), 1);
+    /// }
+    ///
+    /// let mut store = Store::simple(|_, _| {}, effects, ());
+    /// store.enable_stats(|_| "Action");
+    /// store.schedule_timer(Duration::from_secs(60), Action(0));
+    /// store.pause();
+    /// for n in 0..3 {
+    ///     store.dispatch(Action(n));
+    /// }
+    /// assert_eq!(store.stats().unwrap().paused_actions(), 3);
+    ///
+    /// store.resume();
+    /// assert_eq!(store.stats().unwrap().paused_actions(), 0);
+    /// assert_eq!(store.stats().unwrap().pending_timers(), 1);
+    /// ```
+    #[inline(always)]
+    pub fn paused_actions(&self) -> usize {
+        self.paused_actions
+    }
+
+    /// Timers which didn't fire yet, see `Store::pending_timers`.
+    #[inline(always)]
+    pub fn pending_timers(&self) -> usize {
+        self.pending_timers
+    }
+
+    pub(crate) fn set_pending(&mut self, paused_actions: usize, pending_timers: usize) {
+        self.paused_actions = paused_actions;
+        self.pending_timers = pending_timers;
+    }
+
+    /// Count a processed action into the current cascade.
+    pub(crate) fn record_cascade_action(&mut self, id: ActionId, kind: &'static str, depth: u32) {
+        self.cascades.record_action(id, kind, depth);
+    }
+
+    /// Fold the current cascade into the aggregates, once its root
+    /// action is done.
+    pub(crate) fn finish_cascade(&mut self) {
+        self.cascades.finish();
+    }
+
+    pub(crate) fn set_scratch(&mut self, scratch: Option<ScratchStats>) {
+        self.scratch = scratch;
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        kind: &'static str,
+        module: &'static str,
+        provenance: Provenance,
+        timings: &DispatchTimings,
+    ) {
+        self.kinds
+            .entry(kind)
+            .or_default()
+            .record(provenance, timings);
+        self.modules
+            .entry(module)
+            .or_default()
+            .record(provenance, timings);
+    }
+
+    pub(crate) fn record_coalesced(&mut self, kind: &'static str) {
+        self.kinds.entry(kind).or_default().coalesced += 1;
+    }
+
+    pub(crate) fn record_expired(&mut self, kind: &'static str) {
+        self.kinds.entry(kind).or_default().expired += 1;
+    }
+
+    pub(crate) fn record_rate_limited(&mut self, kind: &'static str) {
+        self.kinds.entry(kind).or_default().rate_limited += 1;
+    }
+
+    pub(crate) fn record_duplicate(&mut self, kind: &'static str) {
+        self.kinds.entry(kind).or_default().duplicates += 1;
+    }
+
+    pub(crate) fn record_payload(&mut self, kind: &'static str, size: usize) {
+        let stats = self.kinds.entry(kind).or_default();
+        stats.payload_total += size as u64;
+        stats.payload_max = stats.payload_max.max(size as u64);
+    }
+
+    #[cfg(feature = "memory")]
+    pub(crate) fn record_allocation(&mut self, id: ActionId, kind: &'static str, allocated: i64) {
+        let top = &mut self.top_allocating;
+        if top.len() == TOP_ALLOCATING_LEN
+            && top.last().map_or(false, |last| last.allocated >= allocated)
+        {
+            return;
+        }
+        let index = top.partition_point(|other| other.allocated >= allocated);
+        top.insert(
+            index,
+            AllocatingAction {
+                id,
+                kind,
+                allocated,
+            },
+        );
+        top.truncate(TOP_ALLOCATING_LEN);
+    }
+}
+
+/// Statistics of a store along with how they're collected, see
+/// `Store::enable_stats`.
+pub(crate) struct StatsCollector<Action> {
+    classifier: Option<ActionClassifier<Action>>,
+    /// Set by `Store::enable_payload_stats`.
+    payload_size: Option<fn(&Action) -> usize>,
+    stats: DispatchStats,
+}
+
+impl<Action> StatsCollector<Action> {
+    pub fn new() -> Self {
+        Self {
+            classifier: None,
+            payload_size: None,
+            stats: Default::default(),
+        }
+    }
+
+    pub fn set_classifier(&mut self, classifier: Option<ActionClassifier<Action>>) {
+        self.classifier = classifier;
+    }
+
+    pub fn set_payload_size(&mut self, payload_size: fn(&Action) -> usize) {
+        self.payload_size = Some(payload_size);
+    }
+
+    #[inline(always)]
+    pub fn is_enabled(&self) -> bool {
+        self.classifier.is_some()
+    }
+
+    /// Collected statistics, `None` if collection isn't enabled.
+    pub fn get(&self) -> Option<&DispatchStats> {
+        self.classifier.map(|_| &self.stats)
+    }
+
+    pub fn reset(&mut self) {
+        self.stats = Default::default();
+    }
+
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.stats.set_capabilities(capabilities);
+    }
+
+    pub fn set_pending(&mut self, paused_actions: usize, pending_timers: usize) {
+        self.stats.set_pending(paused_actions, pending_timers);
+    }
+
+    /// Kind of the action, `None` if collection isn't enabled.
+    #[inline(always)]
+    pub fn kind(&self, action: &Action) -> Option<&'static str> {
+        self.classifier.map(|classifier| classifier(action))
+    }
+
+    /// Count the action about to be processed into the current cascade.
+    #[inline(always)]
+    pub fn start_action(&mut self, action: &ActionWithMeta<Action>) {
+        if let Some(kind) = self.kind(&action.action) {
+            self.stats
+                .record_cascade_action(action.id, kind, action.depth);
+        }
+    }
+
+    /// Finish the cascade if the processed action was its root.
+    #[inline(always)]
+    pub fn finish_action(&mut self, action: &ActionWithMeta<Action>) {
+        if action.depth == 0 && self.is_enabled() {
+            self.stats.finish_cascade();
+        }
+    }
+
+    /// Record the processed action of the `kind`, which must be the
+    /// one returned by [Self::kind].
+    pub fn record(
+        &mut self,
+        kind: &'static str,
+        module: &'static str,
+        action: &ActionWithMeta<Action>,
+        timings: &DispatchTimings,
+        scratch: Option<ScratchStats>,
+    ) {
+        self.stats.record(kind, module, action.provenance, timings);
+        if let Some(payload_size) = self.payload_size {
+            self.stats
+                .record_payload(kind, payload_size(&action.action));
+        }
+        #[cfg(feature = "memory")]
+        self.stats
+            .record_allocation(action.id, kind, timings.allocated);
+        self.stats.set_scratch(scratch);
+    }
+
+    pub fn record_coalesced(&mut self, action: &Action) {
+        if let Some(kind) = self.kind(action) {
+            self.stats.record_coalesced(kind);
+        }
+    }
+
+    pub fn record_expired(&mut self, action: &Action) {
+        if let Some(kind) = self.kind(action) {
+            self.stats.record_expired(kind);
+        }
+    }
+
+    pub fn record_duplicate(&mut self, action: &Action) {
+        if let Some(kind) = self.kind(action) {
+            self.stats.record_duplicate(kind);
+        }
+    }
+
+    pub fn record_rate_limited(&mut self, kind: &'static str) {
+        if self.is_enabled() {
+            self.stats.record_rate_limited(kind);
+        }
+    }
+}
+
+impl<Action> Clone for StatsCollector<Action> {
+    fn clone(&self) -> Self {
+        Self {
+            classifier: self.classifier,
+            payload_size: self.payload_size,
+            stats: self.stats.clone(),
+        }
+    }
+}
@@ -1,6 +1,31 @@
-use std::time::{Instant, SystemTime};
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::fmt;
+use core::ops::Deref;
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+#[cfg(feature = "std")]
+use std::time::SystemTime;
 
-use crate::{ActionId, ActionWithMeta, Effects, EnablingCondition, Reducer, TimeService};
+use crate::breakpoint::Breakpoints;
+use crate::effects::AnyEffects;
+use crate::pause::{Enqueued, PauseQueue, QueuedAction};
+use crate::rate_limit::RateLimiting;
+use crate::reducer::AnyReducer;
+use crate::state_history::{ProjectedHistory, StateHistoryRecorder};
+use crate::subscription::Subscriptions;
+use crate::subsystems::Subsystems;
+use crate::time::SampledClock;
+use crate::time_travel::TimeTravel;
+use crate::timers::{Fired, Timers};
+use crate::watchdog::SlowActionWatchdog;
+use crate::{
+    ActionId, ActionWithMeta, Breakpoint, BreakpointHook, BreakpointId, Capabilities,
+    ClockAnomalyHook, ClockDiagnostics, DispatchInterceptor, DispatchTimings, EffectOrder, Effects,
+    EnablingCondition, Instant, Interception, MetaEvent, Provenance, RecentActions, Recorder,
+    Reducer, SlowActionHook, TimeGranularity, TimeService, TimeTravelError,
+};
 
 /// Wraps around State and allows only immutable borrow,
 /// Through `StateWrapper::get` method.
@@ -27,6 +52,21 @@ impl<State> StateWrapper<State> {
     }
 }
 
+impl<State> Deref for StateWrapper<State> {
+    type Target = State;
+
+    #[inline(always)]
+    fn deref(&self) -> &State {
+        &self.inner
+    }
+}
+
+impl<State: fmt::Debug> fmt::Debug for StateWrapper<State> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
 impl<T: Clone> Clone for StateWrapper<T> {
     fn clone(&self) -> Self {
         Self {
@@ -35,12 +75,52 @@ impl<T: Clone> Clone for StateWrapper<T> {
     }
 }
 
+/// Mapper to actions, along with `Store::dispatch` for the action type,
+/// so that the mapped actions can be dispatched without requiring
+/// `Action: EnablingCondition<State>` everywhere.
+pub(crate) type DispatchMapper<Mapper, Store, Action> = (Mapper, DispatchFn<Store, Action>);
+
+/// `Store::dispatch` for the action type.
+pub(crate) type DispatchFn<Store, Action> = fn(&mut Store, Action) -> bool;
+
+/// What happened to a dispatched action, see [Store::dispatch_with_outcome].
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum DispatchOutcome {
+    /// Action wasn't enabled, or the store isn't accepting actions.
+    Rejected,
+    /// Store is paused, action was queued without getting an id.
+    Queued,
+    /// Action was captured by the dispatch interceptor.
+    Captured(ActionId),
+    /// Action went through the reducer and effects.
+    Processed(ActionId),
+}
+
+impl DispatchOutcome {
+    /// Whether [Store::dispatch] would return `true`.
+    #[inline(always)]
+    pub fn is_accepted(&self) -> bool {
+        !matches!(self, Self::Rejected)
+    }
+
+    /// Id given to the action, if it got one.
+    #[inline(always)]
+    pub fn id(&self) -> Option<ActionId> {
+        match self {
+            Self::Captured(id) | Self::Processed(id) => Some(*id),
+            Self::Rejected | Self::Queued => None,
+        }
+    }
+}
+
 /// A container holding a state and providing the possibility to dispatch actions.
 ///
 /// A store is defined by the state is holds and the actions it can dispatch.
+///
+/// Use [crate::StoreBuilder] to create one.
 pub struct Store<State, Service, Action> {
-    reducer: Reducer<State, Action>,
-    effects: Effects<State, Service, Action>,
+    reducer: AnyReducer<State, Action>,
+    effects: AnyEffects<State, Service, Action>,
 
     /// Current State.
     ///
@@ -49,14 +129,118 @@ pub struct Store<State, Service, Action> {
     pub state: StateWrapper<State>,
     pub service: Service,
 
-    initial_time: SystemTime,
+    /// Time in nanoseconds from epoch, when store was created.
+    initial_time_nanos: u64,
     initial_monotonic_time: Instant,
-    monotonic_time: Instant,
+    clock: SampledClock,
 
     /// Current recursion depth of dispatch.
     recursion_depth: u32,
+    /// Id of the action whose reducer/effects are running.
+    current_action_id: Option<ActionId>,
 
     last_action_id: ActionId,
+    /// Number of actions which went through the reducer.
+    actions_processed: u64,
+
+    /// Active optional subsystems, see [Store::update_subsystems].
+    subsystems: Subsystems,
+    /// Same as `subsystems`, as seen by users, see [Store::capabilities].
+    capabilities: Capabilities,
+
+    /// Hook called for every enabled action before the reducer.
+    dispatch_interceptor: Option<crate::interceptor::Interceptor<State, Action>>,
+    /// Actions captured by the interceptor, see [Interception::Capture].
+    intercepted: Vec<ActionWithMeta<Action>>,
+
+    recording: crate::recorder::Recording<Action>,
+    /// Set in forks, see [Store::fork].
+    fork: Option<crate::Fork>,
+    /// See [Store::record_rng_seed].
+    rng_seed: Option<u64>,
+    state_history: Option<Box<dyn StateHistoryRecorder<State> + Send>>,
+    subscriptions: Subscriptions<State, Action>,
+    #[cfg(feature = "audit")]
+    audit: Option<crate::audit::Audit<State, Action>>,
+    /// Futures waiting for the state, see [Store::notify_when].
+    #[cfg(all(feature = "async", feature = "std"))]
+    conditions: Option<crate::condition::Conditions<State>>,
+    /// Told which action is being processed, see
+    /// [Store::set_service_call_log].
+    #[cfg(feature = "std")]
+    service_call_log: Option<crate::ServiceCallLog>,
+
+    #[cfg(feature = "stats")]
+    stats: crate::stats::StatsCollector<Action>,
+
+    #[cfg(feature = "prometheus")]
+    metrics: Option<(crate::StoreMetrics, crate::ActionClassifier<Action>)>,
+
+    slow_action_watchdog: Option<SlowActionWatchdog<State, Service, Action>>,
+    #[cfg(feature = "std")]
+    idle_watchdog: Option<crate::watchdog::IdleWatchdog<Action>>,
+
+    time_travel: Option<TimeTravel<State, Action>>,
+
+    /// Queue of actions dispatched while paused, see [Store::pause].
+    pause: PauseQueue<Action>,
+
+    /// Actions scheduled with [Store::schedule_timer] and
+    /// [Store::schedule_recurring].
+    timers: Timers<Action>,
+
+    /// Per-kind rate limiting of dispatched actions.
+    rate_limiting: RateLimiting<Self, Action>,
+    /// Suppression of duplicate actions.
+    dedup: Option<crate::Dedup<Action>>,
+
+    /// Once set, every dispatched action is rejected.
+    poisoned: Option<crate::PoisonReason>,
+    /// Set by `Store::shutdown`, every dispatched action is rejected.
+    shut_down: bool,
+    /// Whether [Store::dispatch_transaction] is in progress.
+    in_transaction: bool,
+    /// Whether effects of the action about to be processed are deferred
+    /// by the transaction.
+    defer_effects: bool,
+
+    /// Safety condition check run after every reducer call.
+    safety_check: Option<crate::safety_condition::FormattedCheck<State>>,
+    /// See [Store::enable_condition_cache].
+    condition_cache: Option<crate::condition_cache::ConditionCache<State, Action>>,
+    error_actions: crate::error_action::ErrorActions<Self, Action>,
+
+    #[cfg(feature = "std")]
+    panics: crate::recovery::PanicHandling<State>,
+
+    #[cfg(feature = "panic_context")]
+    panic_context: crate::panic_context::PanicContextTracker<Action>,
+
+    breakpoints: Breakpoints<State, Service, Action>,
+
+    #[cfg(feature = "diff")]
+    differ: Option<crate::diff::StateDiffer<State>>,
+
+    #[cfg(feature = "determinism-check")]
+    determinism_check: Option<crate::determinism::DeterminismCheck<State>>,
+
+    #[cfg(feature = "persistence")]
+    persistence: Option<crate::persistence::Persistence<State>>,
+
+    /// Modules composed into the reducer and effects, see [crate::ModuleRegistry].
+    modules: Option<crate::module::Modules<State, Service, Action>>,
+    /// Sorted by order, see [Store::add_effects].
+    ordered_effects: Option<Arc<[crate::effect_order::OrderedEffects<State, Service, Action>]>>,
+    /// Read-only configuration, see [Store::new_with_config].
+    config: Option<crate::config::StoreConfig>,
+
+    #[cfg(feature = "memory")]
+    allocated: crate::memory::AllocatedSampler,
+    #[cfg(feature = "memory")]
+    memory_alarm: Option<(
+        crate::MemoryDetector,
+        crate::MemoryAlarmHook<State, Service, Action>,
+    )>,
 }
 
 impl<State, Service, Action> Store<State, Service, Action>
@@ -64,36 +248,387 @@ where
     Service: TimeService,
 {
     /// Creates a new store.
+    #[cfg(feature = "std")]
     pub fn new(
         reducer: Reducer<State, Action>,
         effects: Effects<State, Service, Action>,
-        mut service: Service,
+        service: Service,
+        initial_time: SystemTime,
+        initial_state: State,
+    ) -> Self {
+        let initial_id = ActionId::new_unchecked(crate::time::system_time_to_nanos(initial_time));
+        Self::new_with_initial_id(reducer, effects, service, initial_id, initial_state)
+    }
+
+    /// Creates a new store.
+    ///
+    /// `initial_time_nanos` is time in nanoseconds from unix epoch.
+    #[cfg(not(feature = "std"))]
+    pub fn new(
+        reducer: Reducer<State, Action>,
+        effects: Effects<State, Service, Action>,
+        service: Service,
+        initial_time_nanos: u64,
+        initial_state: State,
+    ) -> Self {
+        let initial_id = ActionId::new_unchecked(initial_time_nanos);
+        Self::new_with_initial_id(reducer, effects, service, initial_id, initial_state)
+    }
+
+    /// Creates a new store whose reducer gets a [crate::ReducerCtx] and
+    /// effects an [crate::EffectsCtx] along with the store as a
+    /// [crate::StrictStore], see [crate::ReducerCtx].
+    #[cfg(all(feature = "strict", feature = "std"))]
+    pub fn new_strict(
+        reducer: crate::StrictReducer<State, Action>,
+        effects: crate::StrictEffects<State, Service, Action>,
+        service: Service,
         initial_time: SystemTime,
         initial_state: State,
+    ) -> Self {
+        let mut store = Self::new(
+            crate::reducer::no_reducer,
+            crate::effects::no_effects,
+            service,
+            initial_time,
+            initial_state,
+        );
+        store.reducer = AnyReducer::Strict(reducer);
+        store.effects = AnyEffects::Strict(effects);
+        store
+    }
+
+    /// Creates a new store whose reducer gets a [crate::ReducerCtx] and
+    /// effects an [crate::EffectsCtx] along with the store as a
+    /// [crate::StrictStore], see [crate::ReducerCtx].
+    ///
+    /// `initial_time_nanos` is time in nanoseconds from unix epoch.
+    #[cfg(all(feature = "strict", not(feature = "std")))]
+    pub fn new_strict(
+        reducer: crate::StrictReducer<State, Action>,
+        effects: crate::StrictEffects<State, Service, Action>,
+        service: Service,
+        initial_time_nanos: u64,
+        initial_state: State,
+    ) -> Self {
+        let mut store = Self::new(
+            crate::reducer::no_reducer,
+            crate::effects::no_effects,
+            service,
+            initial_time_nanos,
+            initial_state,
+        );
+        store.reducer = AnyReducer::Strict(reducer);
+        store.effects = AnyEffects::Strict(effects);
+        store
+    }
+
+    /// Creates a new store with explicitly given starting id.
+    ///
+    /// Unlike [Store::new], wall clock is never consulted, so two stores
+    /// created with the same `initial_id` and the same (mock) service
+    /// produce identical action ids for the same inputs.
+    ///
+    /// ```
+    /// use redux_rs::{
+    ///     ActionId, ActionWithMeta, BoundedRecorder, EnablingCondition, Instant, Store,
+    ///     TimeService,
+    /// };
+    /// use std::time::Duration;
+    ///
+    /// #[derive(Clone)]
+    /// struct Action;
+    /// impl EnablingCondition<()> for Action {}
+    ///
+    /// struct MockClock(Instant);
+    /// impl TimeService for MockClock {
+    ///     fn monotonic_time(&mut self) -> Instant {
+    ///         self.0 += Duration::from_millis(3);
+    ///         self.0
+    ///     }
+    /// }
+    ///
+    /// let run = |start| {
+    ///     let mut store = Store::new_with_initial_id(
+    ///         |_: &mut (), _: &ActionWithMeta<Action>| {},
+    ///         |_, _| {},
+    ///         MockClock(start),
+    ///         ActionId::ZERO,
+    ///         (),
+    ///     );
+    ///     store.set_recorder(Some(Box::new(BoundedRecorder::new(16))));
+    ///     (0..10).for_each(|_| {
+    ///         store.dispatch(Action);
+    ///     });
+    ///     store.recent_actions().map(|a| a.id).collect::<Vec<_>>()
+    /// };
+    ///
+    /// assert_eq!(run(Instant::now()), run(Instant::now()));
+    /// ```
+    pub fn new_with_initial_id(
+        reducer: Reducer<State, Action>,
+        effects: Effects<State, Service, Action>,
+        mut service: Service,
+        initial_id: ActionId,
+        initial_state: State,
     ) -> Self {
         let initial_monotonic_time = service.monotonic_time();
-        let initial_time_nanos = initial_time
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .map(|x| x.as_nanos())
-            .unwrap_or(0);
+        let initial_time_nanos = initial_id.into();
 
         Self {
-            reducer,
-            effects,
+            reducer: reducer.into(),
+            effects: effects.into(),
             service,
             state: StateWrapper {
                 inner: initial_state,
             },
 
-            initial_time,
+            initial_time_nanos,
             initial_monotonic_time,
-            monotonic_time: initial_monotonic_time,
+            clock: SampledClock::new(initial_monotonic_time),
 
             recursion_depth: 0,
-            last_action_id: ActionId::new_unchecked(initial_time_nanos as u64),
+            current_action_id: None,
+            last_action_id: initial_id,
+            actions_processed: 0,
+
+            subsystems: Subsystems::NONE,
+            capabilities: Capabilities::NONE,
+
+            dispatch_interceptor: None,
+            intercepted: Vec::new(),
+
+            recording: crate::recorder::Recording::new(),
+            fork: None,
+            rng_seed: None,
+            state_history: None,
+            subscriptions: Subscriptions::new(),
+            #[cfg(feature = "audit")]
+            audit: None,
+            #[cfg(all(feature = "async", feature = "std"))]
+            conditions: None,
+            #[cfg(feature = "std")]
+            service_call_log: None,
+
+            #[cfg(feature = "stats")]
+            stats: crate::stats::StatsCollector::new(),
+
+            #[cfg(feature = "prometheus")]
+            metrics: None,
+
+            slow_action_watchdog: None,
+            #[cfg(feature = "std")]
+            idle_watchdog: None,
+
+            time_travel: None,
+
+            pause: PauseQueue::new(),
+
+            timers: Timers::new(),
+
+            rate_limiting: RateLimiting::new(),
+            dedup: None,
+
+            poisoned: None,
+            shut_down: false,
+            in_transaction: false,
+            defer_effects: false,
+
+            safety_check: None,
+            condition_cache: None,
+            error_actions: crate::error_action::ErrorActions::new(),
+
+            #[cfg(feature = "std")]
+            panics: crate::recovery::PanicHandling::new(),
+
+            #[cfg(feature = "panic_context")]
+            panic_context: crate::panic_context::PanicContextTracker::new(),
+
+            breakpoints: Breakpoints::new(),
+
+            #[cfg(feature = "diff")]
+            differ: None,
+
+            #[cfg(feature = "determinism-check")]
+            determinism_check: None,
+
+            #[cfg(feature = "persistence")]
+            persistence: None,
+
+            modules: None,
+            ordered_effects: None,
+            config: None,
+
+            #[cfg(feature = "memory")]
+            allocated: Default::default(),
+            #[cfg(feature = "memory")]
+            memory_alarm: None,
         }
     }
 
+    /// Creates a new store with read-only configuration (chain
+    /// parameters, limits, ...), which the reducer gets next to the
+    /// state.
+    ///
+    /// Configuration isn't part of the state, so it's not in snapshots,
+    /// and only its hash is in recordings, see [Store::recording_header].
+    /// Effects can read it with [Store::config].
+    ///
+    /// ```
+    /// use redux_rs::{
+    ///     ActionId, ActionWithMeta, BoundedRecorder, EnablingCondition, ReplayError, Store,
+    /// };
+    ///
+    /// #[derive(Hash)]
+    /// struct Limits {
+    ///     max_peers: usize,
+    /// }
+    ///
+    /// #[derive(Clone)]
+    /// struct Connect(u16);
+    /// impl EnablingCondition<Vec<u16>> for Connect {}
+    ///
+    /// fn reducer(limits: &Limits, peers: &mut Vec<u16>, action: &ActionWithMeta<Connect>) {
+    ///     if peers.len() < limits.max_peers {
+    ///         peers.push(action.action.0);
+    ///     }
+    /// }
+    ///
+    /// fn effects(store: &mut Store<Vec<u16>, (), Connect>, _: &ActionWithMeta<Connect>) {
+    ///     assert_eq!(store.config::<Limits>().unwrap().max_peers, 2);
+    /// }
+    ///
+    /// let new_store = |max_peers| {
+    ///     let limits = Limits { max_peers };
+    ///     Store::new_with_config(limits, reducer, effects, (), ActionId::ZERO, vec![])
+    /// };
+    ///
+    /// let mut store = new_store(2);
+    /// store.set_recorder(Some(Box::new(BoundedRecorder::new(16))));
+    /// for port in [9732, 9733, 9734] {
+    ///     store.dispatch(Connect(port));
+    /// }
+    /// assert_eq!(store.state(), &[9732, 9733]);
+    ///
+    /// let header = store.recording_header();
+    /// let recorded: Vec<_> = store.recent_actions().cloned().collect();
+    ///
+    /// // Same config replays to the same state.
+    /// let mut replayed = new_store(2);
+    /// assert_eq!(replayed.replay(&header, &recorded), Ok(()));
+    /// assert_eq!(replayed.state(), store.state());
+    ///
+    /// // Different config is refused before anything is replayed.
+    /// let mut replayed = new_store(3);
+    /// let error = replayed.replay(&header, &recorded).unwrap_err();
+    /// assert!(matches!(error, ReplayError::ConfigMismatch { .. }));
+    /// assert!(error.to_string().starts_with("recording was made with config"));
+    /// assert!(replayed.state().is_empty());
+    /// ```
+    pub fn new_with_config<Config>(
+        config: Config,
+        reducer: crate::ConfigReducer<Config, State, Action>,
+        effects: Effects<State, Service, Action>,
+        service: Service,
+        initial_id: ActionId,
+        initial_state: State,
+    ) -> Self
+    where
+        Config: core::hash::Hash + Send + Sync + 'static,
+        State: 'static,
+        Action: 'static,
+    {
+        let hash = crate::config_hash(&config);
+        let config = Arc::new(config);
+        let configured = crate::config::Configured {
+            config: config.clone(),
+            reducer,
+        };
+        let mut store = Self::new_with_initial_id(
+            crate::reducer::no_reducer,
+            effects,
+            service,
+            initial_id,
+            initial_state,
+        );
+        store.config = Some(crate::config::StoreConfig {
+            value: config,
+            hash,
+        });
+        store.modules = Some(crate::module::Modules {
+            reducers: Arc::new([Box::new(configured) as Box<_>]),
+            effects: Arc::new([]),
+        });
+        store
+    }
+
+    /// Rebuild the store from parts returned by [Store::into_parts].
+    ///
+    /// Ids of newly dispatched actions continue from `last_action_id`.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Instant, Store};
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// struct Increment;
+    /// impl EnablingCondition<u32> for Increment {}
+    ///
+    /// fn reducer(state: &mut u32, _: &ActionWithMeta<Increment>) {
+    ///     *state += 1;
+    /// }
+    ///
+    /// fn clock() -> impl FnMut() -> Instant {
+    ///     let mut now = Instant::now();
+    ///     move || {
+    ///         now += Duration::from_millis(1);
+    ///         now
+    ///     }
+    /// }
+    ///
+    /// let mut whole = Store::new(reducer, |_, _| {}, clock(), SystemTime::UNIX_EPOCH, 0);
+    /// let mut parts = Store::new(reducer, |_, _| {}, clock(), SystemTime::UNIX_EPOCH, 0);
+    ///
+    /// whole.dispatch(Increment);
+    /// parts.dispatch(Increment);
+    ///
+    /// let (state, service, last_action_id) = parts.into_parts();
+    /// let mut parts = Store::from_parts(reducer, |_, _| {}, state, service, last_action_id);
+    ///
+    /// whole.dispatch(Increment);
+    /// parts.dispatch(Increment);
+    ///
+    /// let (whole_state, _, whole_id) = whole.into_parts();
+    /// let (parts_state, _, parts_id) = parts.into_parts();
+    /// assert_eq!(whole_state, parts_state);
+    /// assert_eq!(whole_id, parts_id);
+    /// ```
+    pub fn from_parts(
+        reducer: Reducer<State, Action>,
+        effects: Effects<State, Service, Action>,
+        state: State,
+        service: Service,
+        last_action_id: ActionId,
+    ) -> Self {
+        Self::new_with_initial_id(reducer, effects, service, last_action_id, state)
+    }
+
+    /// Dismantle the store into state, service and id of the last action.
+    ///
+    /// Store can be rebuilt with [Store::from_parts]. Recorder, dispatch
+    /// interceptor and intercepted actions are dropped.
+    ///
+    /// So is all pending work, without ever being processed:
+    /// - actions queued while paused, coalesced ones included, see
+    ///   [Store::paused_actions]. Call [Store::resume] first to process
+    ///   them, or [Store::shutdown] with [crate::ShutdownPolicy::Abandon]
+    ///   to take them,
+    /// - timers, see [Store::pending_timers],
+    /// - recurring actions, see `Store::schedule_recurring`,
+    /// - the idle watchdog, see `Store::set_idle_watchdog`.
+    pub fn into_parts(self) -> (State, Service, ActionId) {
+        (self.state.inner, self.service, self.last_action_id)
+    }
+
     /// Returns the current state.
     #[inline(always)]
     pub fn state(&self) -> &State {
@@ -105,69 +640,5986 @@ where
         &mut self.service
     }
 
-    /// Convert monotonic time to system clock in nanoseconds from epoch.
-    pub fn monotonic_to_time(&self, monotonic_time: Instant) -> u64 {
-        let time_passed = monotonic_time.duration_since(self.initial_monotonic_time);
-        self.initial_time
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .map(|x| x + time_passed)
-            .map(|x| x.as_nanos())
-            .unwrap_or(0) as u64
+    /// Configuration the store was created with, see
+    /// [Store::new_with_config]. `None` if there's none, or it's not a
+    /// `Config`.
+    #[inline(always)]
+    pub fn config<Config: 'static>(&self) -> Option<&Config> {
+        self.config.as_ref()?.value.downcast_ref()
     }
 
-    /// Dispatch an Action.
+    /// Header for recordings of the actions processed by the store,
+    /// passed to the recorder by [Store::set_recorder].
+    pub fn recording_header(&self) -> crate::RecordingHeader {
+        crate::RecordingHeader {
+            config_hash: self.config.as_ref().map(|config| config.hash),
+            lazy_payloads_skipped: self.recording.lazy_payloads_skipped(),
+            fork: self.fork,
+            seed: self.rng_seed,
+        }
+    }
+
+    /// Id namespace of the store, see [Store::fork].
+    #[inline(always)]
+    pub fn namespace(&self) -> u64 {
+        match self.fork {
+            Some(fork) => fork.namespace,
+            None => 0,
+        }
+    }
+
+    /// Copy of the store continuing as a separate timeline, e.g. to
+    /// simulate alternative scenarios from the same point.
     ///
-    /// Returns `true` if the action was enabled, hence if it was dispatched
-    /// to reducer and then effects.
+    /// Ids of both continue from the same point, so they repeat across
+    /// forks. Actions of the fork are tagged with `id_namespace` (see
+    /// [ActionWithMeta::namespace]), which must differ from those of
+    /// other forks compared with it, so that `(namespace, id)` stays
+    /// unique. Recordings of the fork say where it was forked, in
+    /// [crate::RecordingHeader::fork].
     ///
-    /// If action is not enabled, we return false and do nothing.
-    pub fn dispatch<T>(&mut self, action: T) -> bool
+    /// Fork starts without statistics, and like a [Clone] of the store,
+    /// without a recorder, metrics, state history or listeners.
+    ///
+    /// ```
+    /// use redux_rs::{
+    ///     ActionWithMeta, BoundedRecorder, EnablingCondition, Fork, FrozenTime, Instant, Store,
+    /// };
+    /// use std::collections::BTreeSet;
+    /// use std::time::SystemTime;
+    ///
+    /// #[derive(Debug, Clone, PartialEq)]
+    /// enum Action {
+    ///     Deposit(u64),
+    ///     Withdraw(u64),
+    /// }
+    /// impl EnablingCondition<u64> for Action {}
+    ///
+    /// fn reducer(balance: &mut u64, action: &ActionWithMeta<Action>) {
+    ///     match action.action {
+    ///         Action::Deposit(amount) => *balance += amount,
+    ///         Action::Withdraw(amount) => *balance = balance.saturating_sub(amount),
+    ///     }
+    /// }
+    ///
+    /// // Simulated time, which doesn't pass on its own.
+    /// let service = FrozenTime::new(Instant::now());
+    /// let mut store = Store::new(reducer, |_, _| {}, service, SystemTime::UNIX_EPOCH, 0);
+    /// store.dispatch(Action::Deposit(100));
+    /// let forked_at = store.last_action_id();
+    ///
+    /// let mut saver = store.fork(1);
+    /// let mut spender = store.fork(2);
+    /// assert_eq!(
+    ///     spender.recording_header().fork,
+    ///     Some(Fork { namespace: 2, parent_namespace: 0, after: forked_at })
+    /// );
+    ///
+    /// let mut recordings = vec![];
+    /// for (fork, actions) in [
+    ///     (&mut saver, [Action::Deposit(50), Action::Deposit(10)]),
+    ///     (&mut spender, [Action::Withdraw(70), Action::Withdraw(70)]),
+    /// ] {
+    ///     fork.set_recorder(Some(Box::new(BoundedRecorder::new(16))));
+    ///     for action in actions {
+    ///         fork.dispatch(action);
+    ///     }
+    ///     recordings.extend(fork.recent_actions().cloned());
+    /// }
+    /// assert_eq!(*saver.state(), 160);
+    /// assert_eq!(*spender.state(), 0);
+    /// assert_eq!(*store.state(), 100);
+    ///
+    /// // Both forks reuse the same ids, namespaces tell them apart.
+    /// let ids: BTreeSet<_> = recordings.iter().map(|action| action.id).collect();
+    /// assert_eq!(ids.len(), 2);
+    /// let merged: BTreeSet<_> = recordings.iter().map(|action| (action.namespace, action.id)).collect();
+    /// assert_eq!(merged.len(), 4);
+    /// ```
+    pub fn fork(&self, id_namespace: u64) -> Self
     where
-        T: Into<Action> + EnablingCondition<State>,
+        State: Clone,
+        Service: Clone,
+        Action: Clone,
     {
-        if !action.is_enabled(self.state()) {
-            return false;
+        let mut fork = self.clone();
+        fork.fork = Some(crate::Fork {
+            namespace: id_namespace,
+            parent_namespace: self.namespace(),
+            after: self.last_action_id,
+        });
+        #[cfg(feature = "stats")]
+        {
+            fork.stats.reset();
         }
+        fork.update_subsystems();
+        fork
+    }
 
-        let monotonic_time = self.service.monotonic_time();
-        let time_passed = monotonic_time
-            .duration_since(self.monotonic_time)
-            .as_nanos();
+    /// Process recorded actions, passing them through the reducer,
+    /// but not effects, whose outcome is already recorded as actions.
+    ///
+    /// If the recording was made with configuration, the store must have
+    /// the same one, see [Store::new_with_config].
+    pub fn replay<'a, I>(
+        &mut self,
+        header: &crate::RecordingHeader,
+        actions: I,
+    ) -> Result<(), crate::ReplayError>
+    where
+        I: IntoIterator<Item = &'a ActionWithMeta<Action>>,
+        Action: 'a,
+    {
+        self.check_recording_header(header)?;
+        for action in actions {
+            self.dispatch_reducer(action);
+            self.last_action_id = action.id;
+        }
+        Ok(())
+    }
 
-        self.monotonic_time = monotonic_time;
-        self.last_action_id = self.last_action_id.next(time_passed as u64);
+    /// Same as [Store::replay], but checks the state after every action
+    /// against the fingerprint in the recording, reporting where it
+    /// first differed, `None` if it never did.
+    ///
+    /// Replay goes on after the divergence, so the report also tells
+    /// whether it compounded or healed by the end. With diff enabled
+    /// (see [Store::enable_diff]) and a checkpoint in the divergent
+    /// step, the report has the diff of the recorded and replayed states.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, BoundedRecorder, EnablingCondition, RecordedStep, Store};
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::{Hash, Hasher};
+    ///
+    /// #[derive(Clone)]
+    /// enum Action {
+    ///     Add(u32),
+    ///     Bonus,
+    ///     Reset,
+    /// }
+    ///
+    /// impl EnablingCondition<u32> for Action {}
+    ///
+    /// fn reducer(state: &mut u32, action: &ActionWithMeta<Action>) {
+    ///     match action.action {
+    ///         Action::Add(n) => *state += n,
+    ///         Action::Bonus => *state += 10,
+    ///         Action::Reset => *state = 0,
+    ///     }
+    /// }
+    ///
+    /// /// Same, but with the bonus changed.
+    /// fn changed_reducer(state: &mut u32, action: &ActionWithMeta<Action>) {
+    ///     match action.action {
+    ///         Action::Bonus => *state += 11,
+    ///         _ => reducer(state, action),
+    ///     }
+    /// }
+    ///
+    /// fn fingerprint(state: &u32) -> u64 {
+    ///     let mut hasher = DefaultHasher::new();
+    ///     state.hash(&mut hasher);
+    ///     hasher.finish()
+    /// }
+    ///
+    /// // Record actions, and fingerprints of the states they produced.
+    /// let mut store = Store::simple(reducer, |_, _| {}, 0);
+    /// store.set_recorder(Some(Box::new(BoundedRecorder::new(16))));
+    /// store.enable_state_history_with(16, fingerprint);
+    /// for action in [Action::Add(1), Action::Bonus, Action::Add(2), Action::Reset, Action::Add(3)] {
+    ///     store.dispatch(action);
+    /// }
+    /// let header = store.recording_header();
+    /// let fingerprints = store.state_history::<u64>().unwrap();
+    /// let recording: Vec<_> = store
+    ///     .recent_actions()
+    ///     .map(|action| RecordedStep::new(action.clone(), *fingerprints.get(action.id).unwrap()))
+    ///     .collect();
+    ///
+    /// let mut replay = Store::simple(reducer, |_, _| {}, 0);
+    /// assert_eq!(replay.replay_verified(&header, &recording, fingerprint), Ok(None));
+    ///
+    /// let mut replay = Store::simple(changed_reducer, |_, _| {}, 0);
+    /// let report = replay
+    ///     .replay_verified(&header, &recording, fingerprint)
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(report.index, 1);
+    /// assert_eq!(report.action_id, recording[1].action.id);
+    /// assert_eq!((report.expected, report.actual), (fingerprint(&11), fingerprint(&12)));
+    /// // Diverged further with the next addition, healed by the reset.
+    /// assert_eq!((report.diverged_after, report.replayed_after), (1, 3));
+    /// assert!(report.self_healed());
+    /// ```
+    pub fn replay_verified<'a, I>(
+        &mut self,
+        header: &crate::RecordingHeader,
+        steps: I,
+        fingerprint: fn(&State) -> u64,
+    ) -> Result<Option<crate::DivergenceReport>, crate::ReplayError>
+    where
+        I: IntoIterator<Item = &'a crate::RecordedStep<Action>>,
+        Action: 'a,
+    {
+        self.check_recording_header(header)?;
+        let mut report: Option<crate::DivergenceReport> = None;
+        for (index, step) in steps.into_iter().enumerate() {
+            self.dispatch_reducer(&step.action);
+            self.last_action_id = step.action.id;
+            let actual = fingerprint(self.state.get());
+            match report.as_mut() {
+                Some(report) => report.replayed(actual == step.fingerprint),
+                None if actual != step.fingerprint => {
+                    report = Some(crate::DivergenceReport::new(
+                        step.action.id,
+                        index,
+                        step.fingerprint,
+                        actual,
+                    ));
+                    #[cfg(feature = "diff")]
+                    if let (Some(report), Some(differ), Some(checkpoint)) =
+                        (report.as_mut(), &self.differ, &step.checkpoint)
+                    {
+                        report.diff = Some(crate::StateDiff::between(checkpoint, differ.current()));
+                    }
+                }
+                None => {}
+            }
+        }
+        Ok(report)
+    }
 
-        let action_with_meta = ActionWithMeta {
-            id: self.last_action_id,
-            depth: self.recursion_depth,
+    /// Same as [Store::replay], up to and including action `until`, but
+    /// without running optional subsystems after each action, e.g. to
+    /// catch up with a long recording before debugging from a point in
+    /// it. Returns how many actions were applied.
+    ///
+    /// Time travel, state history, diff, safety check, persistence,
+    /// breakpoints, subscribers, state conditions and audit see only the
+    /// last applied action, once, as if it was the only one, so that they
+    /// resynchronize with the state. Determinism check is skipped. Action
+    /// following `until`, if any, is taken from `actions` and dropped.
+    /// See the `catch_up_100k` benchmark for the speedup.
+    ///
+    /// Once caught up, the clock is moved to the time of `until`, so that
+    /// the next action's id and `elapsed_since_prev` are measured from
+    /// `until`, not from the time before the catch-up.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, BoundedRecorder, EnablingCondition, Store};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// #[derive(Clone)]
+    /// struct Add(u64);
+    /// impl EnablingCondition<u64> for Add {}
+    ///
+    /// fn reducer(state: &mut u64, action: &ActionWithMeta<Add>) {
+    ///     *state += action.action.0;
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, 0);
+    /// store.set_recorder(Some(Box::new(BoundedRecorder::new(1000))));
+    /// for n in 1..=1000 {
+    ///     store.dispatch(Add(n));
+    /// }
+    /// let header = store.recording_header();
+    /// let recording: Vec<_> = store.recent_actions().cloned().collect();
+    /// let until = recording[499].id;
+    ///
+    /// let mut replayed = Store::simple(reducer, |_, _| {}, 0);
+    /// replayed.replay(&header, &recording[..500]).unwrap();
+    ///
+    /// let mut fast = Store::simple(reducer, |_, _| {}, 0);
+    /// let notified = Arc::new(AtomicUsize::new(0));
+    /// let counter = notified.clone();
+    /// fast.subscribe(move |_, _| {
+    ///     counter.fetch_add(1, Ordering::SeqCst);
+    /// });
+    ///
+    /// assert_eq!(fast.fast_forward(recording.iter().cloned(), until), 500);
+    /// assert_eq!(notified.load(Ordering::SeqCst), 1);
+    /// assert_eq!(fast.state(), replayed.state());
+    /// assert_eq!(fast.last_action_id(), until);
+    ///
+    /// // Subsystems are back on.
+    /// fast.dispatch(Add(1));
+    /// assert_eq!(notified.load(Ordering::SeqCst), 2);
+    /// assert!(fast.last_action_id() > until);
+    /// ```
+    pub fn fast_forward<I>(&mut self, actions: I, until: ActionId) -> usize
+    where
+        I: IntoIterator<Item = ActionWithMeta<Action>>,
+    {
+        // Only the reducer runs, so subsystems, including the ones run
+        // before it, stay as they are even if it panics.
+        let mut applied = 0;
+        let mut last = None;
+        for action in actions {
+            if action.id > until {
+                break;
+            }
+            self.apply_reducer(&action);
+            self.last_action_id = action.id;
+            applied += 1;
+            last = Some(action);
+        }
 
-            action: action.into(),
+        let last = match last {
+            Some(last) => last,
+            None => return 0,
         };
-        self.recursion_depth += 1;
+        let since_initial = u64::from(until).saturating_sub(self.initial_time_nanos);
+        let until_time = self.initial_monotonic_time + Duration::from_nanos(since_initial);
+        self.clock.now = self.clock.now.max(until_time);
+        // Writes of the whole catch-up aren't attributed to the last action.
+        #[cfg(feature = "audit")]
+        if let Some(audit) = self.audit.as_ref() {
+            audit.reset(self.state.get_mut());
+        }
+        if self.subsystems.contains(Subsystems::AFTER_REDUCER) {
+            self.after_reducer(&last);
+        }
+        applied
+    }
 
-        self.dispatch_reducer(&action_with_meta);
-        self.dispatch_effects(&action_with_meta);
+    /// State along with the id of the last action it includes, to
+    /// [Store::recover] from later.
+    pub fn snapshot(&self) -> crate::StoreSnapshot<State>
+    where
+        State: Clone,
+    {
+        crate::StoreSnapshot {
+            state: self.state.get().clone(),
+            last_action_id: self.last_action_id,
+        }
+    }
 
-        self.recursion_depth -= 1;
+    /// Restart from a snapshot and the tail of the recording made after
+    /// it was taken, e.g. after a crash.
+    ///
+    /// Tail must start with the action right after the last one the
+    /// snapshot includes, as told by action ids and
+    /// [ActionWithMeta::elapsed_since_prev], otherwise the store refuses
+    /// to recover and is left untouched, unless `policy` says otherwise.
+    /// Tail missing actions inside it is recovered up to them, and
+    /// reported as truncated.
+    ///
+    /// Actions are passed through the reducer, but not effects, like
+    /// with [Store::replay]. Ids of newly dispatched actions continue
+    /// from the last recovered one.
+    ///
+    /// ```
+    /// use redux_rs::{
+    ///     ActionWithMeta, BoundedRecorder, EnablingCondition, RecoveryError, RecoveryPolicy, Store,
+    /// };
+    ///
+    /// #[derive(Clone)]
+    /// struct Add(u32);
+    /// impl EnablingCondition<u32> for Add {}
+    ///
+    /// fn reducer(state: &mut u32, action: &ActionWithMeta<Add>) {
+    ///     *state += action.action.0;
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, 0);
+    /// store.set_recorder(Some(Box::new(BoundedRecorder::new(16))));
+    /// store.dispatch(Add(1));
+    /// store.dispatch(Add(2));
+    /// let snapshot = store.snapshot();
+    /// store.dispatch(Add(3));
+    /// store.dispatch(Add(4));
+    /// store.dispatch(Add(5));
+    /// let header = store.recording_header();
+    /// let recording: Vec<_> = store.recent_actions().cloned().collect();
+    /// let tail = &recording[2..];
+    ///
+    /// // Clean.
+    /// let mut recovered = Store::simple(reducer, |_, _| {}, 0);
+    /// let report = recovered
+    ///     .recover(snapshot.clone(), &header, tail, RecoveryPolicy::default())
+    ///     .unwrap();
+    /// assert_eq!(*recovered.state(), 15);
+    /// assert_eq!((report.applied, report.skipped, report.truncated), (3, 0, false));
+    /// assert_eq!(report.last_action_id, store.last_action_id());
+    /// assert_eq!(recovered.last_action_id(), store.last_action_id());
+    ///
+    /// // Overlapping, refused, or skipped.
+    /// let overlapping = &recording[1..];
+    /// let mut recovered = Store::simple(reducer, |_, _| {}, 0);
+    /// let error = recovered
+    ///     .recover(snapshot.clone(), &header, overlapping, RecoveryPolicy::default())
+    ///     .unwrap_err();
+    /// assert_eq!(
+    ///     error,
+    ///     RecoveryError::Overlap {
+    ///         snapshot: snapshot.last_action_id,
+    ///         action_id: recording[1].id,
+    ///     }
+    /// );
+    /// assert_eq!(*recovered.state(), 0);
+    /// let policy = RecoveryPolicy {
+    ///     skip_overlap: true,
+    ///     ..Default::default()
+    /// };
+    /// let report = recovered
+    ///     .recover(snapshot.clone(), &header, overlapping, policy)
+    ///     .unwrap();
+    /// assert_eq!((report.skipped, report.applied), (1, 3));
+    /// assert_eq!(*recovered.state(), 15);
+    ///
+    /// // Gapped, refused, or gone over.
+    /// let gapped = &recording[3..];
+    /// let mut recovered = Store::simple(reducer, |_, _| {}, 0);
+    /// let error = recovered
+    ///     .recover(snapshot.clone(), &header, gapped, RecoveryPolicy::default())
+    ///     .unwrap_err();
+    /// assert_eq!(
+    ///     error,
+    ///     RecoveryError::Gap {
+    ///         snapshot: snapshot.last_action_id,
+    ///         action_id: recording[3].id,
+    ///         follows: recording[2].id,
+    ///     }
+    /// );
+    /// let policy = RecoveryPolicy {
+    ///     allow_gaps: true,
+    ///     ..Default::default()
+    /// };
+    /// let report = recovered.recover(snapshot.clone(), &header, gapped, policy).unwrap();
+    /// assert_eq!((report.applied, report.gaps), (2, 1));
+    /// assert_eq!(*recovered.state(), 12);
+    ///
+    /// // Missing an action inside.
+    /// let truncated = [recording[2].clone(), recording[4].clone()];
+    /// let mut recovered = Store::simple(reducer, |_, _| {}, 0);
+    /// let report = recovered
+    ///     .recover(snapshot, &header, &truncated, RecoveryPolicy::default())
+    ///     .unwrap();
+    /// assert_eq!((report.applied, report.truncated), (1, true));
+    /// assert_eq!(report.last_action_id, recording[2].id);
+    /// assert_eq!(*recovered.state(), 6);
+    /// ```
+    pub fn recover<'a, I>(
+        &mut self,
+        snapshot: crate::StoreSnapshot<State>,
+        header: &crate::RecordingHeader,
+        tail: I,
+        policy: crate::RecoveryPolicy,
+    ) -> Result<crate::RecoveryReport, crate::RecoveryError>
+    where
+        I: IntoIterator<Item = &'a ActionWithMeta<Action>>,
+        Action: 'a,
+    {
+        self.recover_steps(
+            snapshot,
+            header,
+            tail,
+            |action| action,
+            policy,
+            |_, _, _| {},
+        )
+    }
 
-        true
+    /// Same as [Store::recover], but checks the state after every
+    /// action against the fingerprint in the recording, like
+    /// [Store::replay_verified], reporting where it first differed in
+    /// [crate::RecoveryReport::divergence].
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, BoundedRecorder, EnablingCondition, RecordedStep, Store};
+    ///
+    /// #[derive(Clone)]
+    /// struct Add(u32);
+    /// impl EnablingCondition<u32> for Add {}
+    ///
+    /// fn reducer(state: &mut u32, action: &ActionWithMeta<Add>) {
+    ///     *state += action.action.0;
+    /// }
+    ///
+    /// fn doubling_reducer(state: &mut u32, action: &ActionWithMeta<Add>) {
+    ///     *state += action.action.0 * 2;
+    /// }
+    ///
+    /// fn fingerprint(state: &u32) -> u64 {
+    ///     u64::from(*state)
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, 0);
+    /// store.dispatch(Add(1));
+    /// let snapshot = store.snapshot();
+    /// store.set_recorder(Some(Box::new(BoundedRecorder::new(16))));
+    /// store.enable_state_history_with(16, fingerprint);
+    /// store.dispatch(Add(2));
+    /// store.dispatch(Add(3));
+    /// let header = store.recording_header();
+    /// let fingerprints = store.state_history::<u64>().unwrap();
+    /// let tail: Vec<_> = store
+    ///     .recent_actions()
+    ///     .map(|action| RecordedStep::new(action.clone(), *fingerprints.get(action.id).unwrap()))
+    ///     .collect();
+    ///
+    /// let mut recovered = Store::simple(reducer, |_, _| {}, 0);
+    /// let report = recovered
+    ///     .recover_verified(snapshot.clone(), &header, &tail, fingerprint, Default::default())
+    ///     .unwrap();
+    /// assert_eq!((report.applied, report.divergence), (2, None));
+    ///
+    /// // Code changed since.
+    /// let mut recovered = Store::simple(doubling_reducer, |_, _| {}, 0);
+    /// let report = recovered
+    ///     .recover_verified(snapshot, &header, &tail, fingerprint, Default::default())
+    ///     .unwrap();
+    /// let divergence = report.divergence.unwrap();
+    /// assert_eq!((divergence.index, divergence.expected, divergence.actual), (0, 3, 5));
+    /// ```
+    pub fn recover_verified<'a, I>(
+        &mut self,
+        snapshot: crate::StoreSnapshot<State>,
+        header: &crate::RecordingHeader,
+        tail: I,
+        fingerprint: fn(&State) -> u64,
+        policy: crate::RecoveryPolicy,
+    ) -> Result<crate::RecoveryReport, crate::RecoveryError>
+    where
+        I: IntoIterator<Item = &'a crate::RecordedStep<Action>>,
+        Action: 'a,
+    {
+        let mut divergence: Option<crate::DivergenceReport> = None;
+        let verify = |index, step: &crate::RecordedStep<Action>, state: &State| {
+            let actual = fingerprint(state);
+            match divergence.as_mut() {
+                Some(report) => report.replayed(actual == step.fingerprint),
+                None if actual != step.fingerprint => {
+                    divergence = Some(crate::DivergenceReport::new(
+                        step.action.id,
+                        index,
+                        step.fingerprint,
+                        actual,
+                    ));
+                }
+                None => {}
+            }
+        };
+        let mut report =
+            self.recover_steps(snapshot, header, tail, |step| &step.action, policy, verify)?;
+        report.divergence = divergence;
+        Ok(report)
     }
 
-    /// Runs the reducer.
-    #[inline(always)]
-    fn dispatch_reducer(&mut self, action_with_id: &ActionWithMeta<Action>) {
-        (&self.reducer)(self.state.get_mut(), action_with_id);
+    fn recover_steps<'a, T, I>(
+        &mut self,
+        snapshot: crate::StoreSnapshot<State>,
+        header: &crate::RecordingHeader,
+        tail: I,
+        action: fn(&T) -> &ActionWithMeta<Action>,
+        policy: crate::RecoveryPolicy,
+        mut verify: impl FnMut(usize, &T, &State),
+    ) -> Result<crate::RecoveryReport, crate::RecoveryError>
+    where
+        I: IntoIterator<Item = &'a T>,
+        T: 'a,
+    {
+        use crate::crash_recovery::follows;
+
+        self.check_recording_header(header)?;
+        let mut report = crate::RecoveryReport {
+            snapshot: snapshot.last_action_id,
+            skipped: 0,
+            applied: 0,
+            gaps: 0,
+            truncated: false,
+            last_action_id: snapshot.last_action_id,
+            divergence: None,
+        };
+
+        // Check the tail lines up before touching the state.
+        let mut tail = tail.into_iter().peekable();
+        while let Some(step) = tail.peek() {
+            let first = action(step);
+            if first.id > snapshot.last_action_id {
+                if follows(first) != snapshot.last_action_id && !policy.allow_gaps {
+                    return Err(crate::RecoveryError::Gap {
+                        snapshot: snapshot.last_action_id,
+                        action_id: first.id,
+                        follows: follows(first),
+                    });
+                }
+                break;
+            }
+            if !policy.skip_overlap {
+                return Err(crate::RecoveryError::Overlap {
+                    snapshot: snapshot.last_action_id,
+                    action_id: first.id,
+                });
+            }
+            tail.next();
+            report.skipped += 1;
+        }
+
+        *self.state.get_mut() = snapshot.state;
+        self.last_action_id = snapshot.last_action_id;
+        for (index, step) in tail.enumerate() {
+            let action = action(step);
+            if follows(action) != self.last_action_id {
+                if !policy.allow_gaps {
+                    report.truncated = true;
+                    break;
+                }
+                report.gaps += 1;
+            }
+            self.dispatch_reducer(action);
+            self.last_action_id = action.id;
+            report.applied += 1;
+            verify(report.skipped + index, step, self.state.get());
+        }
+        report.last_action_id = self.last_action_id;
+        Ok(report)
     }
 
-    /// Runs the effects.
+    fn check_recording_header(
+        &self,
+        header: &crate::RecordingHeader,
+    ) -> Result<(), crate::ReplayError> {
+        if let (Some(recorded), Some(supplied)) = (header.seed, self.rng_seed) {
+            if recorded != supplied {
+                return Err(crate::ReplayError::SeedMismatch { recorded, supplied });
+            }
+        }
+        let recorded = match header.config_hash {
+            Some(recorded) => recorded,
+            None => return Ok(()),
+        };
+        match self.config.as_ref() {
+            None => Err(crate::ReplayError::MissingConfig { recorded }),
+            Some(config) if config.hash != recorded => Err(crate::ReplayError::ConfigMismatch {
+                recorded,
+                supplied: config.hash,
+            }),
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// Part `S` of the service bundle, see [crate::HasService].
     #[inline(always)]
-    fn dispatch_effects(&mut self, action_with_id: &ActionWithMeta<Action>) {
-        (&self.effects)(self, action_with_id);
+    pub fn service_as<S>(&mut self) -> &mut S
+    where
+        Service: crate::HasService<S>,
+    {
+        self.service.service()
     }
-}
 
-impl<State, Service, Action> Clone for Store<State, Service, Action>
-where
+    /// Start collecting per action kind statistics, classifying actions
+    /// with `classifier`. Previously collected statistics are kept.
+    ///
+    /// Reads the clock three more times per action, see
+    /// [crate::DispatchTimings]. With a clock advancing per read, e.g. a
+    /// mock one, that changes the times and so the ids of actions, so a
+    /// recording made without statistics won't replay to the same ids
+    /// with them, and vice versa.
+    ///
+    /// ```
+    /// use redux_rs::{kind_name, ActionKind, ActionWithMeta, EnablingCondition, Instant, Store};
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    /// enum Kind {
+    ///     Small,
+    ///     Big,
+    /// }
+    ///
+    /// impl From<Kind> for &'static str {
+    ///     fn from(kind: Kind) -> Self {
+    ///         match kind {
+    ///             Kind::Small => "Small",
+    ///             Kind::Big => "Big",
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// enum Action {
+    ///     Small,
+    ///     Big,
+    /// }
+    ///
+    /// impl ActionKind for Action {
+    ///     type Kind = Kind;
+    ///
+    ///     fn kind(&self) -> Kind {
+    ///         match self {
+    ///             Action::Small => Kind::Small,
+    ///             Action::Big => Kind::Big,
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// impl EnablingCondition<()> for Action {}
+    ///
+    /// // Every clock read advances time by 1ms.
+    /// let mut now = Instant::now();
+    /// let clock = move || {
+    ///     now += Duration::from_millis(1);
+    ///     now
+    /// };
+    ///
+    /// let reducer = |_: &mut (), _: &ActionWithMeta<Action>| {};
+    /// let mut store = Store::new(reducer, |_, _| {}, clock, SystemTime::UNIX_EPOCH, ());
+    /// store.enable_stats(kind_name);
+    ///
+    /// store.dispatch(Action::Small);
+    /// store.dispatch(Action::Small);
+    /// store.dispatch(Action::Big);
+    ///
+    /// let stats = store.stats().unwrap();
+    /// assert_eq!(stats.kind("Small").unwrap().count, 2);
+    /// assert_eq!(stats.kind("Small").unwrap().reducer_total, Duration::from_millis(2));
+    /// assert_eq!(stats.kind("Big").unwrap().effects_max, Duration::from_millis(1));
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn enable_stats(&mut self, classifier: crate::ActionClassifier<Action>) {
+        self.stats.set_classifier(Some(classifier));
+        self.update_subsystems();
+        self.update_pending();
+    }
+
+    /// Stop collecting statistics. Collected statistics are kept.
+    #[cfg(feature = "stats")]
+    pub fn disable_stats(&mut self) {
+        self.stats.set_classifier(None);
+        self.update_subsystems();
+    }
+
+    /// Also record [crate::PayloadSize]s of processed actions in
+    /// [crate::KindStats::payload_total] and
+    /// [crate::KindStats::payload_max], once statistics are enabled.
+    ///
+    /// Passing a [crate::Shared] payload around doesn't allocate, so
+    /// this is how actions sharing their payloads are accounted for.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, PayloadSize, Shared, Store};
+    ///
+    /// #[derive(Clone)]
+    /// enum Action {
+    ///     Ping,
+    ///     Block(Shared<Vec<u8>>),
+    /// }
+    ///
+    /// impl EnablingCondition<()> for Action {}
+    ///
+    /// impl PayloadSize for Action {
+    ///     fn payload_size(&self) -> usize {
+    ///         match self {
+    ///             Action::Ping => 0,
+    ///             Action::Block(bytes) => bytes.payload_size(),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// fn kind(action: &Action) -> &'static str {
+    ///     match action {
+    ///         Action::Ping => "Ping",
+    ///         Action::Block(_) => "Block",
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(|_: &mut (), _: &ActionWithMeta<Action>| {}, |_, _| {}, ());
+    /// store.enable_stats(kind);
+    /// store.enable_payload_stats();
+    ///
+    /// let block = Shared::new(vec![0; 1000]);
+    /// store.dispatch(Action::Block(block.clone()));
+    /// store.dispatch(Action::Block(block));
+    /// store.dispatch(Action::Block(Shared::new(vec![0; 10])));
+    /// store.dispatch(Action::Ping);
+    ///
+    /// let stats = store.stats().unwrap();
+    /// let blocks = stats.kind("Block").unwrap();
+    /// assert_eq!((blocks.payload_total, blocks.payload_max), (2010, 1000));
+    /// assert_eq!(stats.kind("Ping").unwrap().payload_total, 0);
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn enable_payload_stats(&mut self)
+    where
+        Action: crate::PayloadSize,
+    {
+        self.stats.set_payload_size(Action::payload_size);
+    }
+
+    /// Collected statistics, `None` if collection isn't enabled.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> Option<&crate::DispatchStats> {
+        self.stats.get()
+    }
+
+    /// Sizes and depths of cascades of actions dispatched from effects,
+    /// per kind of the action which started them, `None` if statistics
+    /// aren't enabled, see [Store::enable_stats].
+    ///
+    /// Cascade completes once its root action, dispatched from outside
+    /// of effects, is done.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Store};
+    ///
+    /// enum Action {
+    ///     /// Fans out into `n` `Relay`s, each into a `Deliver`.
+    ///     Broadcast(u32),
+    ///     Relay,
+    ///     Deliver,
+    ///     /// Chain of `n` more `Ping`s, each dispatched by the previous.
+    ///     Ping(u32),
+    /// }
+    ///
+    /// impl EnablingCondition<()> for Action {}
+    ///
+    /// fn kind(action: &Action) -> &'static str {
+    ///     match action {
+    ///         Action::Broadcast(_) => "Broadcast",
+    ///         Action::Relay => "Relay",
+    ///         Action::Deliver => "Deliver",
+    ///         Action::Ping(_) => "Ping",
+    ///     }
+    /// }
+    ///
+    /// fn effects(store: &mut Store<(), (), Action>, action: &ActionWithMeta<Action>) {
+    ///     match action.action {
+    ///         Action::Broadcast(n) => (0..n).for_each(|_| {
+    ///             store.dispatch(Action::Relay);
+    ///         }),
+    ///         Action::Relay => {
+    ///             store.dispatch(Action::Deliver);
+    ///         }
+    ///         Action::Ping(n) if n > 0 => {
+    ///             store.dispatch(Action::Ping(n - 1));
+    ///         }
+    ///         _ => {}
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(|_, _| {}, effects, ());
+    /// store.enable_stats(kind);
+    ///
+    /// store.dispatch(Action::Broadcast(3));
+    /// store.dispatch(Action::Broadcast(100));
+    /// store.dispatch(Action::Ping(5));
+    /// store.dispatch(Action::Deliver);
+    ///
+    /// let cascades = store.cascade_stats().unwrap();
+    /// let broadcast = cascades.kind("Broadcast").unwrap();
+    /// assert_eq!(broadcast.cascades, 2);
+    /// assert_eq!(broadcast.descendants_total, 6 + 200);
+    /// assert_eq!(broadcast.descendants_max, 200);
+    /// assert_eq!(broadcast.depth_max, 2);
+    /// // 6 descendants in 4..8, 200 in 128..256.
+    /// assert_eq!(broadcast.sizes[3], 1);
+    /// assert_eq!(broadcast.sizes[8], 1);
+    ///
+    /// let ping = cascades.kind("Ping").unwrap();
+    /// assert_eq!((ping.descendants_max, ping.depth_max), (5, 5));
+    /// assert_eq!(cascades.kind("Deliver").unwrap().sizes[0], 1);
+    /// // Only roots start cascades.
+    /// assert!(cascades.kind("Relay").is_none());
+    ///
+    /// let worst = cascades.worst().unwrap();
+    /// assert_eq!((worst.kind, worst.descendants), ("Broadcast", 200));
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn cascade_stats(&self) -> Option<&crate::CascadeStats> {
+        self.stats().map(|stats| stats.cascades())
+    }
+
+    /// Start recording which sections of the state the reducer writes
+    /// for each kind of action, as told by `classifier`, restarting if
+    /// already recording.
+    ///
+    /// Section counts as written by an action if the reducer accessed
+    /// it mutably, whether or not it changed it. Writes made outside of
+    /// the reducer aren't attributed to any action.
+    ///
+    /// ```
+    /// use redux_rs::{audited_state, ActionWithMeta, EnablingCondition, Section, Store};
+    ///
+    /// #[derive(Default)]
+    /// struct State {
+    ///     peers: Section<Vec<u64>>,
+    ///     blocks: Section<Vec<u32>>,
+    ///     mempool: Section<Vec<u32>>,
+    /// }
+    ///
+    /// audited_state!(State { peers, blocks, mempool });
+    ///
+    /// enum Action {
+    ///     PeerConnected(u64),
+    ///     OperationReceived(u32),
+    ///     BlockApplied(u32),
+    ///     StatusRequested,
+    /// }
+    ///
+    /// impl EnablingCondition<State> for Action {}
+    ///
+    /// fn kind(action: &Action) -> &'static str {
+    ///     match action {
+    ///         Action::PeerConnected(_) => "PeerConnected",
+    ///         Action::OperationReceived(_) => "OperationReceived",
+    ///         Action::BlockApplied(_) => "BlockApplied",
+    ///         Action::StatusRequested => "StatusRequested",
+    ///     }
+    /// }
+    ///
+    /// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+    ///     match action.action {
+    ///         Action::PeerConnected(peer) => state.peers.push(peer),
+    ///         Action::OperationReceived(op) => {
+    ///             // Only writes if it's new.
+    ///             if !state.mempool.contains(&op) {
+    ///                 state.mempool.push(op);
+    ///             }
+    ///         }
+    ///         Action::BlockApplied(level) => {
+    ///             state.blocks.push(level);
+    ///             state.mempool.clear();
+    ///         }
+    ///         Action::StatusRequested => {
+    ///             let _ = (state.peers.len(), state.blocks.last());
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, State::default());
+    /// store.enable_audit(kind);
+    ///
+    /// store.dispatch(Action::PeerConnected(1));
+    /// store.dispatch(Action::OperationReceived(7));
+    /// store.dispatch(Action::OperationReceived(7));
+    /// store.dispatch(Action::BlockApplied(1));
+    /// store.dispatch(Action::StatusRequested);
+    /// store.dispatch(Action::PeerConnected(2));
+    ///
+    /// let report = store.audit_report().unwrap();
+    /// assert_eq!(report.sections(), ["peers", "blocks", "mempool"]);
+    /// assert_eq!(
+    ///     report.matrix(),
+    ///     [
+    ///         ("BlockApplied", vec![0, 1, 1]),
+    ///         ("OperationReceived", vec![0, 0, 1]),
+    ///         ("PeerConnected", vec![2, 0, 0]),
+    ///         ("StatusRequested", vec![0, 0, 0]),
+    ///     ]
+    /// );
+    /// assert_eq!(report.kind("OperationReceived").unwrap().actions, 2);
+    /// assert_eq!(
+    ///     report.to_string(),
+    ///     "kind\tpeers\tblocks\tmempool\n\
+    ///      BlockApplied\t0\t1\t1\n\
+    ///      OperationReceived\t0\t0\t1\n\
+    ///      PeerConnected\t2\t0\t0\n\
+    ///      StatusRequested\t0\t0\t0"
+    /// );
+    /// ```
+    #[cfg(feature = "audit")]
+    pub fn enable_audit(&mut self, classifier: crate::ActionClassifier<Action>)
+    where
+        State: crate::AuditedState,
+    {
+        self.audit = Some(crate::audit::Audit::new(classifier));
+        self.update_subsystems();
+    }
+
+    /// Stop recording writes to the state, dropping the report.
+    #[cfg(feature = "audit")]
+    pub fn disable_audit(&mut self) {
+        self.audit = None;
+        self.update_subsystems();
+    }
+
+    /// Sections written by each kind of action, `None` if audit isn't
+    /// enabled, see [Store::enable_audit].
+    #[cfg(feature = "audit")]
+    pub fn audit_report(&self) -> Option<&crate::AuditReport> {
+        self.audit.as_ref().map(|audit| audit.report())
+    }
+
+    #[cfg(feature = "stats")]
+    pub fn reset_stats(&mut self) {
+        self.stats.reset();
+        self.update_pending();
+    }
+
+    /// Start updating prometheus `metrics`, with actions labeled by
+    /// `classifier`. See [crate::StoreMetrics].
+    #[cfg(feature = "prometheus")]
+    pub fn set_metrics(
+        &mut self,
+        metrics: crate::StoreMetrics,
+        classifier: crate::ActionClassifier<Action>,
+    ) {
+        self.metrics = Some((metrics, classifier));
+        self.update_subsystems();
+        self.update_pending();
+    }
+
+    /// Stop updating metrics, returning them.
+    #[cfg(feature = "prometheus")]
+    pub fn clear_metrics(&mut self) -> Option<crate::StoreMetrics> {
+        let metrics = self.metrics.take().map(|(metrics, _)| metrics);
+        self.update_subsystems();
+        metrics
+    }
+
+    #[cfg(feature = "prometheus")]
+    pub fn metrics(&self) -> Option<&crate::StoreMetrics> {
+        self.metrics.as_ref().map(|(metrics, _)| metrics)
+    }
+
+    /// Call `hook` after every action whose reducer and effects together
+    /// took longer than `threshold`.
+    ///
+    /// Like [Store::enable_stats], reads the clock three more times per
+    /// action, which changes action ids under a clock advancing per read.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, DispatchTimings, EnablingCondition, Instant, Store, TimeService};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// enum Action {
+    ///     Fast,
+    ///     Slow,
+    /// }
+    /// impl EnablingCondition<()> for Action {}
+    ///
+    /// /// Clock advancing 1ms per read, or 100ms if `slow` is set.
+    /// struct Service {
+    ///     now: Instant,
+    ///     slow: bool,
+    /// }
+    ///
+    /// impl TimeService for Service {
+    ///     fn monotonic_time(&mut self) -> Instant {
+    ///         let step = if std::mem::take(&mut self.slow) { 100 } else { 1 };
+    ///         self.now += Duration::from_millis(step);
+    ///         self.now
+    ///     }
+    /// }
+    ///
+    /// fn effects(store: &mut Store<(), Service, Action>, action: &ActionWithMeta<Action>) {
+    ///     if let Action::Slow = action.action {
+    ///         store.service.slow = true;
+    ///     }
+    /// }
+    ///
+    /// static SLOW_ACTIONS: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// fn on_slow_action(_: &Store<(), Service, Action>, _: &ActionWithMeta<Action>, timings: &DispatchTimings) {
+    ///     assert_eq!(timings.effects, Duration::from_millis(100));
+    ///     SLOW_ACTIONS.fetch_add(1, Ordering::SeqCst);
+    /// }
+    ///
+    /// let service = Service { now: Instant::now(), slow: false };
+    /// let reducer = |_: &mut (), _: &ActionWithMeta<Action>| {};
+    /// let mut store = Store::new(reducer, effects, service, SystemTime::UNIX_EPOCH, ());
+    /// store.set_slow_action_watchdog(Duration::from_millis(50), on_slow_action);
+    ///
+    /// store.dispatch(Action::Fast);
+    /// store.dispatch(Action::Slow);
+    /// store.dispatch(Action::Fast);
+    ///
+    /// assert_eq!(SLOW_ACTIONS.load(Ordering::SeqCst), 1);
+    /// ```
+    pub fn set_slow_action_watchdog(
+        &mut self,
+        threshold: Duration,
+        hook: SlowActionHook<State, Service, Action>,
+    ) {
+        self.slow_action_watchdog = Some(SlowActionWatchdog { threshold, hook });
+        self.update_subsystems();
+    }
+
+    /// Change threshold of the slow action watchdog, if it is set.
+    ///
+    /// Can be called from effects, e.g. to lower it while diagnosing.
+    pub fn set_slow_action_threshold(&mut self, threshold: Duration) {
+        if let Some(watchdog) = self.slow_action_watchdog.as_mut() {
+            watchdog.threshold = threshold;
+        }
+    }
+
+    pub fn clear_slow_action_watchdog(&mut self) {
+        self.slow_action_watchdog = None;
+        self.update_subsystems();
+    }
+
+    /// Dispatch `make_action(now)` whenever no action was processed for
+    /// `threshold`, e.g. to notice the node got stuck.
+    ///
+    /// Checked by [Store::fire_timers], which also makes
+    /// [Store::next_timer_deadline] (and so [Store::run_polled]) wake up
+    /// in time for it. Time is measured with the service's clock. Any
+    /// processed action resets the watchdog, including its own, so while
+    /// the store stays idle it fires once per `threshold`. It never fires
+    /// while the store is paused, poisoned or shut down, in which case
+    /// the time is counted again from the check. Fired action has
+    /// [Provenance::Watchdog].
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Instant, Provenance, Store, TimeService};
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum Action {
+    ///     Tick,
+    ///     Idle(SystemTime),
+    /// }
+    ///
+    /// impl EnablingCondition<Vec<SystemTime>> for Action {}
+    ///
+    /// fn reducer(idle: &mut Vec<SystemTime>, action: &ActionWithMeta<Action>) {
+    ///     if let Action::Idle(at) = action.action {
+    ///         assert_eq!(action.provenance, Provenance::Watchdog);
+    ///         idle.push(at);
+    ///     }
+    /// }
+    ///
+    /// /// Both clocks stand still until advanced.
+    /// struct Clock {
+    ///     started: Instant,
+    ///     elapsed: Duration,
+    /// }
+    ///
+    /// impl Clock {
+    ///     fn advance(&mut self, by: Duration) {
+    ///         self.elapsed += by;
+    ///     }
+    /// }
+    ///
+    /// impl TimeService for Clock {
+    ///     fn monotonic_time(&mut self) -> Instant {
+    ///         self.started + self.elapsed
+    ///     }
+    ///
+    ///     fn system_time(&mut self) -> SystemTime {
+    ///         SystemTime::UNIX_EPOCH + self.elapsed
+    ///     }
+    /// }
+    ///
+    /// let start = SystemTime::UNIX_EPOCH;
+    /// let service = Clock { started: Instant::now(), elapsed: Duration::ZERO };
+    /// let mut store = Store::new(reducer, |_, _| {}, service, start, vec![]);
+    /// store.set_idle_watchdog(Duration::from_secs(10), Action::Idle);
+    ///
+    /// // Continuous activity never fires.
+    /// for _ in 0..100 {
+    ///     store.service.advance(Duration::from_secs(5));
+    ///     store.dispatch(Action::Tick);
+    ///     assert_eq!(store.fire_timers(), 0);
+    /// }
+    ///
+    /// // Silence past the threshold fires exactly one action.
+    /// let last_tick = start + Duration::from_secs(500);
+    /// store.service.advance(Duration::from_secs(9));
+    /// assert_eq!(store.fire_timers(), 0);
+    /// store.service.advance(Duration::from_secs(6));
+    /// assert_eq!(store.fire_timers(), 1);
+    /// assert_eq!(store.fire_timers(), 0);
+    /// assert_eq!(store.state(), &[last_tick + Duration::from_secs(15)]);
+    ///
+    /// // Not while paused.
+    /// store.pause();
+    /// store.service.advance(Duration::from_secs(60));
+    /// assert_eq!(store.fire_timers(), 0);
+    /// assert_eq!(store.state().len(), 1);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn set_idle_watchdog(
+        &mut self,
+        threshold: Duration,
+        make_action: fn(SystemTime) -> Action,
+    ) {
+        let now = self.service.monotonic_time();
+        self.idle_watchdog = Some(crate::watchdog::IdleWatchdog::new(
+            threshold,
+            make_action,
+            self.actions_processed,
+            now,
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    pub fn clear_idle_watchdog(&mut self) {
+        self.idle_watchdog = None;
+    }
+
+    /// Deadline of the idle watchdog, if set.
+    #[cfg(feature = "std")]
+    fn idle_deadline(&self) -> Option<Instant> {
+        let watchdog = self.idle_watchdog.as_ref()?;
+        Some(watchdog.deadline(self.actions_processed, self.clock.now))
+    }
+
+    #[cfg(not(feature = "std"))]
+    #[inline(always)]
+    fn idle_deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    /// Fire the idle watchdog if it's due. Returns whether it fired.
+    #[cfg(feature = "std")]
+    fn check_idle_watchdog(&mut self, now: Instant) -> bool
+    where
+        Action: EnablingCondition<State>,
+    {
+        let processed = self.actions_processed;
+        let last_action_time = self.clock.now;
+        let unavailable = self.is_paused() || self.is_poisoned() || self.shut_down;
+        let watchdog = match self.idle_watchdog.as_mut() {
+            Some(watchdog) => watchdog,
+            None => return false,
+        };
+        if !watchdog.is_due(now, processed, last_action_time, unavailable) {
+            return false;
+        }
+
+        let action = (watchdog.make_action)(self.service.system_time());
+        self.dispatch_from(action, Provenance::Watchdog);
+        let processed = self.actions_processed;
+        if let Some(watchdog) = self.idle_watchdog.as_mut() {
+            watchdog.reset(processed, now);
+        }
+        true
+    }
+
+    /// Set how often allocator stats are read. Defaults to
+    /// [crate::MemorySampling::EveryAction].
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, BoundedRecorder, EnablingCondition, MemorySampling, Store};
+    ///
+    /// #[derive(Clone)]
+    /// struct Action;
+    /// impl EnablingCondition<()> for Action {}
+    ///
+    /// let mut store = Store::simple(|_: &mut (), _: &ActionWithMeta<Action>| {}, |_, _| {}, ());
+    /// store.set_recorder(Some(Box::new(BoundedRecorder::new(8))));
+    /// store.set_memory_sampling(MemorySampling::EveryN(3));
+    ///
+    /// for _ in 0..7 {
+    ///     store.dispatch(Action);
+    /// }
+    ///
+    /// let sampled: Vec<_> = store.recent_actions().map(|a| a.allocated_sampled).collect();
+    /// assert_eq!(sampled, [true, false, false, true, false, false, true]);
+    /// ```
+    #[cfg(feature = "memory")]
+    pub fn set_memory_sampling(&mut self, sampling: crate::MemorySampling) {
+        self.allocated.set_sampling(sampling);
+    }
+
+    /// Watch allocated memory, calling `hook` when any of the
+    /// `thresholds` is crossed. See [crate::MemoryDetector].
+    ///
+    /// Memory is sampled when an action is dispatched, according to
+    /// [crate::MemorySampling], and checked once it has been processed.
+    #[cfg(feature = "memory")]
+    pub fn set_memory_alarm(
+        &mut self,
+        thresholds: crate::MemoryThresholds,
+        hook: crate::MemoryAlarmHook<State, Service, Action>,
+    ) {
+        self.memory_alarm = Some((crate::MemoryDetector::new(thresholds), hook));
+    }
+
+    /// Change memory alarm thresholds, if the alarm is set.
+    #[cfg(feature = "memory")]
+    pub fn set_memory_thresholds(&mut self, thresholds: crate::MemoryThresholds) {
+        if let Some((detector, _)) = self.memory_alarm.as_mut() {
+            detector.set_thresholds(thresholds);
+        }
+    }
+
+    #[cfg(feature = "memory")]
+    pub fn clear_memory_alarm(&mut self) {
+        self.memory_alarm = None;
+    }
+
+    #[cfg(feature = "memory")]
+    pub fn memory_detector(&self) -> Option<&crate::MemoryDetector> {
+        self.memory_alarm.as_ref().map(|(detector, _)| detector)
+    }
+
+    /// Start logging processed actions, with a state checkpoint every
+    /// `checkpoint_interval` actions, so that the store can later
+    /// [Store::travel_to] any of them.
+    ///
+    /// The log isn't bounded, so this is meant for debugging.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Store};
+    ///
+    /// #[derive(Clone)]
+    /// struct Add(u32);
+    /// impl EnablingCondition<u32> for Add {}
+    ///
+    /// fn reducer(state: &mut u32, action: &ActionWithMeta<Add>) {
+    ///     *state += action.action.0;
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, 0);
+    /// store.enable_time_travel(4);
+    ///
+    /// let mut ids = vec![];
+    /// for i in 1..=10 {
+    ///     store.dispatch(Add(i));
+    ///     ids.push(store.last_action_id());
+    /// }
+    ///
+    /// // Across checkpoint boundaries, backwards and forwards.
+    /// for i in [9, 2, 3, 7, 0, 4] {
+    ///     store.travel_to(ids[i]).unwrap();
+    ///     assert_eq!(*store.state(), (1..=i as u32 + 1).sum::<u32>());
+    /// }
+    ///
+    /// assert!(store.is_detached());
+    /// assert!(!store.dispatch(Add(100)));
+    ///
+    /// store.travel_to_latest();
+    /// assert_eq!(*store.state(), 55);
+    /// assert!(store.dispatch(Add(100)));
+    /// ```
+    pub fn enable_time_travel(&mut self, checkpoint_interval: usize)
+    where
+        State: Clone,
+        Action: Clone,
+    {
+        self.travel_to_latest();
+        self.time_travel = Some(TimeTravel::new(
+            checkpoint_interval,
+            self.reducer,
+            self.modules.as_ref().map(|modules| modules.reducers.clone()),
+            self.state.get(),
+        ));
+        self.update_subsystems();
+    }
+
+    /// Stop logging actions for time travel and drop the log, returning
+    /// to live state if detached.
+    pub fn disable_time_travel(&mut self) {
+        self.travel_to_latest();
+        self.time_travel = None;
+        self.update_subsystems();
+    }
+
+    /// Replace state with the state right after action `id` was
+    /// processed, by replaying logged actions from the nearest
+    /// checkpoint. Effects aren't called.
+    ///
+    /// Store becomes detached: [Store::dispatch] rejects all actions
+    /// until [Store::travel_to_latest] is called.
+    pub fn travel_to(&mut self, id: ActionId) -> Result<(), TimeTravelError> {
+        let time_travel = self.time_travel.as_mut().ok_or(TimeTravelError::Disabled)?;
+        let result = time_travel.travel_to(id, self.state.get_mut());
+        self.update_subsystems();
+        result
+    }
+
+    /// Return to live state after [Store::travel_to].
+    pub fn travel_to_latest(&mut self) {
+        if let Some(time_travel) = self.time_travel.as_mut() {
+            time_travel.return_to_latest(self.state.get_mut());
+        }
+        self.update_subsystems();
+    }
+
+    /// Whether store shows a past state after [Store::travel_to].
+    #[inline(always)]
+    pub fn is_detached(&self) -> bool {
+        self.detached_at().is_some()
+    }
+
+    /// Id of the action whose state is shown, while detached.
+    pub fn detached_at(&self) -> Option<ActionId> {
+        self.time_travel
+            .as_ref()
+            .and_then(|time_travel| time_travel.detached_at())
+    }
+
+    /// Start computing what each action changed in the state, see
+    /// [Store::diff_last_action].
+    ///
+    /// Costs serializing the whole state after every action and keeping
+    /// the last serialized state around.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, DiffEntry, EnablingCondition, Store};
+    /// use serde_json::json;
+    /// use std::collections::BTreeMap;
+    ///
+    /// enum Action {
+    ///     Set(&'static str, u32),
+    ///     Noop,
+    /// }
+    /// impl EnablingCondition<BTreeMap<&'static str, u32>> for Action {}
+    ///
+    /// fn reducer(state: &mut BTreeMap<&'static str, u32>, action: &ActionWithMeta<Action>) {
+    ///     if let Action::Set(key, value) = action.action {
+    ///         state.insert(key, value);
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, BTreeMap::new());
+    /// store.enable_diff();
+    ///
+    /// store.dispatch(Action::Set("a", 1));
+    /// assert_eq!(
+    ///     store.diff_last_action().unwrap().entries,
+    ///     vec![DiffEntry::Added { path: "/a".into(), value: json!(1) }]
+    /// );
+    ///
+    /// store.dispatch(Action::Noop);
+    /// assert!(store.diff_last_action().unwrap().is_empty());
+    /// ```
+    #[cfg(feature = "diff")]
+    pub fn enable_diff(&mut self)
+    where
+        State: serde::Serialize,
+    {
+        self.differ = Some(crate::diff::StateDiffer::new(self.state.get()));
+        self.update_subsystems();
+    }
+
+    #[cfg(feature = "diff")]
+    pub fn disable_diff(&mut self) {
+        self.differ = None;
+        self.update_subsystems();
+    }
+
+    /// Save a snapshot of the state to `config.sink` at the given
+    /// cadence, serialized as JSON. Save errors don't affect
+    /// dispatching, they are counted in [Store::persistence_errors].
+    ///
+    /// Use [crate::StoreBuilder::with_persistence] to load the snapshot
+    /// when the store is created.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, Cadence, EnablingCondition, PersistenceConfig, SnapshotSink, Store};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// #[derive(Clone, Default)]
+    /// struct MemorySink(Arc<Mutex<Vec<Vec<u8>>>>);
+    ///
+    /// impl SnapshotSink for MemorySink {
+    ///     fn save(&mut self, snapshot: &[u8]) -> std::io::Result<()> {
+    ///         self.0.lock().unwrap().push(snapshot.to_vec());
+    ///         Ok(())
+    ///     }
+    ///
+    ///     fn load(&mut self) -> std::io::Result<Option<Vec<u8>>> {
+    ///         Ok(self.0.lock().unwrap().last().cloned())
+    ///     }
+    /// }
+    ///
+    /// struct Add(u32);
+    /// impl EnablingCondition<u32> for Add {}
+    ///
+    /// fn reducer(state: &mut u32, action: &ActionWithMeta<Add>) {
+    ///     *state += action.action.0;
+    /// }
+    ///
+    /// let sink = MemorySink::default();
+    /// let mut store = Store::simple(reducer, |_, _| {}, 0);
+    /// store.enable_persistence(PersistenceConfig {
+    ///     every: Cadence::EveryNActions(2),
+    ///     sink: Box::new(sink.clone()),
+    /// });
+    ///
+    /// for i in 1..=5 {
+    ///     store.dispatch(Add(i));
+    /// }
+    /// assert_eq!(*sink.0.lock().unwrap(), [b"3".to_vec(), b"10".to_vec()]);
+    ///
+    /// store.persist_now();
+    /// assert_eq!(sink.0.lock().unwrap().last().unwrap(), b"15");
+    /// assert_eq!(store.persistence_errors(), 0);
+    /// ```
+    #[cfg(feature = "persistence")]
+    pub fn enable_persistence(&mut self, config: crate::PersistenceConfig)
+    where
+        State: serde::Serialize,
+    {
+        self.set_persistence(crate::persistence::Persistence::new(config));
+    }
+
+    #[cfg(feature = "persistence")]
+    pub(crate) fn set_persistence(&mut self, persistence: crate::persistence::Persistence<State>) {
+        self.persistence = Some(persistence);
+        self.update_subsystems();
+    }
+
+    /// Stop persisting the state, returning the sink.
+    #[cfg(feature = "persistence")]
+    pub fn disable_persistence(&mut self) -> Option<Box<dyn crate::SnapshotSink>> {
+        let persistence = self.persistence.take();
+        self.update_subsystems();
+        persistence.map(|persistence| persistence.into_sink())
+    }
+
+    /// Save a snapshot right away, if persistence is enabled, e.g. on
+    /// shutdown. Restarts the cadence.
+    #[cfg(feature = "persistence")]
+    pub fn persist_now(&mut self) {
+        let (now, action_id) = (self.clock.now, self.last_action_id);
+        if let Some(persistence) = self.persistence.as_mut() {
+            persistence.save(self.state.get(), now, action_id);
+        }
+    }
+
+    /// Number of snapshots which failed to be serialized or saved.
+    #[cfg(feature = "persistence")]
+    pub fn persistence_errors(&self) -> u64 {
+        self.persistence
+            .as_ref()
+            .map_or(0, |persistence| persistence.errors())
+    }
+
+    /// Changes made to the state by the reducer of the last processed
+    /// action, if diffing is enabled.
+    #[cfg(feature = "diff")]
+    pub fn diff_last_action(&self) -> Option<&crate::StateDiff> {
+        self.differ.as_ref().map(|differ| differ.last())
+    }
+
+    /// Check that the reducer is deterministic: for every `every`-th
+    /// action (1 for all of them), run the reducer a second time on a
+    /// clone of the state from before the action and panic, naming the
+    /// action, if the results differ.
+    ///
+    /// Meant for tests. Costs a state clone, a second reducer run and
+    /// a state comparison per checked action.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Store};
+    /// use std::panic::{catch_unwind, AssertUnwindSafe};
+    /// use std::time::SystemTime;
+    ///
+    /// enum Action {
+    ///     Add(u128),
+    ///     Stamp,
+    /// }
+    /// impl EnablingCondition<u128> for Action {}
+    ///
+    /// fn reducer(state: &mut u128, action: &ActionWithMeta<Action>) {
+    ///     match action.action {
+    ///         Action::Add(n) => *state += n,
+    ///         // Should use `action.time()` instead.
+    ///         Action::Stamp => {
+    ///             *state = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_nanos()
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, 0);
+    /// store.enable_determinism_check(1);
+    /// store.dispatch(Action::Add(1));
+    ///
+    /// let result = catch_unwind(AssertUnwindSafe(|| store.dispatch(Action::Stamp)));
+    /// let message = *result.unwrap_err().downcast::<String>().unwrap();
+    /// assert!(message.starts_with("reducer is not deterministic"));
+    /// ```
+    #[cfg(feature = "determinism-check")]
+    pub fn enable_determinism_check(&mut self, every: u32)
+    where
+        State: Clone + PartialEq,
+    {
+        self.determinism_check = Some(crate::determinism::DeterminismCheck::new(every));
+    }
+
+    /// Same as [Store::enable_determinism_check], but the panic message
+    /// also shows how the two resulting states differ.
+    #[cfg(all(feature = "determinism-check", feature = "diff"))]
+    pub fn enable_determinism_check_with_diff(&mut self, every: u32)
+    where
+        State: Clone + PartialEq + serde::Serialize,
+    {
+        self.determinism_check = Some(crate::determinism::DeterminismCheck::new(every).with_diff());
+    }
+
+    #[cfg(feature = "determinism-check")]
+    pub fn disable_determinism_check(&mut self) {
+        self.determinism_check = None;
+    }
+
+    /// Keep clones of the last `capacity` post-reducer states, see
+    /// [Store::state_history].
+    ///
+    /// Costs a state clone per action and `capacity` states worth of
+    /// memory. For big states, consider
+    /// [Store::enable_state_history_with].
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, StateHistory, Store};
+    ///
+    /// struct Push(u32);
+    /// impl EnablingCondition<Vec<u32>> for Push {}
+    ///
+    /// fn reducer(state: &mut Vec<u32>, action: &ActionWithMeta<Push>) {
+    ///     state.push(action.action.0);
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, vec![]);
+    /// store.enable_state_history(3);
+    ///
+    /// let mut ids = vec![];
+    /// for i in 0..5 {
+    ///     store.dispatch(Push(i));
+    ///     ids.push(store.last_action_id());
+    /// }
+    ///
+    /// let history: &StateHistory<Vec<u32>> = store.state_history().unwrap();
+    /// assert_eq!(history.len(), 3);
+    /// assert_eq!(history.capacity(), 3);
+    /// // Oldest evicted first.
+    /// let kept: Vec<_> = history.iter().map(|(id, state)| (id, state.len())).collect();
+    /// assert_eq!(kept, vec![(ids[2], 3), (ids[3], 4), (ids[4], 5)]);
+    /// assert_eq!(history.get(ids[3]), Some(&vec![0, 1, 2, 3]));
+    /// assert_eq!(history.get(ids[1]), None);
+    ///
+    /// // Keep only a summary: the length and the last item.
+    /// fn summary(state: &Vec<u32>) -> (usize, Option<u32>) {
+    ///     (state.len(), state.last().copied())
+    /// }
+    /// store.enable_state_history_with(2, summary);
+    /// store.dispatch(Push(10));
+    ///
+    /// let history = store.state_history::<(usize, Option<u32>)>().unwrap();
+    /// assert_eq!(history.last(), Some((store.last_action_id(), &(6, Some(10)))));
+    /// // Asking for the wrong type gives nothing.
+    /// assert!(store.state_history::<Vec<u32>>().is_none());
+    /// ```
+    pub fn enable_state_history(&mut self, capacity: usize)
+    where
+        State: Clone + Send + 'static,
+    {
+        self.enable_state_history_with(capacity, State::clone);
+    }
+
+    /// Keep summaries of the last `capacity` post-reducer states, made
+    /// by `project`. Replaces the previous history, if any.
+    pub fn enable_state_history_with<Summary>(
+        &mut self,
+        capacity: usize,
+        project: fn(&State) -> Summary,
+    ) where
+        State: 'static,
+        Summary: Send + 'static,
+    {
+        self.state_history = Some(Box::new(ProjectedHistory::new(capacity, project)));
+        self.update_subsystems();
+    }
+
+    pub fn disable_state_history(&mut self) {
+        self.state_history = None;
+        self.update_subsystems();
+    }
+
+    /// Recent states, or their summaries if enabled with
+    /// [Store::enable_state_history_with]. `None` if not enabled, or
+    /// if `Summary` isn't the type being kept.
+    pub fn state_history<Summary: 'static>(&self) -> Option<&crate::StateHistory<Summary>> {
+        self.state_history.as_ref()?.history().downcast_ref()
+    }
+
+    pub(crate) fn set_modules(&mut self, modules: crate::module::Modules<State, Service, Action>) {
+        self.modules = Some(modules);
+    }
+
+    /// Replace the reducer, returning the previous one. New reducer
+    /// processes actions starting with the next dispatched one.
+    ///
+    /// Recorder is notified with [MetaEvent::ReducerReplaced], and time
+    /// travel replays each action with the reducer which processed it.
+    ///
+    /// Panics if the store was created with [Store::new_strict].
+    ///
+    /// Reducer can't reach the store, so it can't be replaced from
+    /// within the reducer. When called from effects, actions they
+    /// dispatch afterwards are already processed by the new reducer.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, MetaEvent, Recorder, Store};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// struct Tick;
+    /// impl EnablingCondition<u32> for Tick {}
+    ///
+    /// fn add_one(state: &mut u32, _: &ActionWithMeta<Tick>) {
+    ///     *state += 1;
+    /// }
+    ///
+    /// fn add_ten(state: &mut u32, _: &ActionWithMeta<Tick>) {
+    ///     *state += 10;
+    /// }
+    ///
+    /// #[derive(Default, Clone)]
+    /// struct Markers(Arc<Mutex<Vec<MetaEvent>>>);
+    ///
+    /// impl Recorder<Tick> for Markers {
+    ///     fn record(&mut self, _: &ActionWithMeta<Tick>) {}
+    ///
+    ///     fn record_meta(&mut self, event: &MetaEvent) {
+    ///         self.0.lock().unwrap().push(*event);
+    ///     }
+    /// }
+    ///
+    /// let markers = Markers::default();
+    /// let mut store = Store::simple(add_one, |_, _| {}, 0);
+    /// store.set_recorder(Some(Box::new(markers.clone())));
+    ///
+    /// store.dispatch(Tick);
+    /// let swapped_after = store.last_action_id();
+    /// store.replace_reducer(add_ten);
+    /// store.dispatch(Tick);
+    ///
+    /// assert_eq!(*store.state(), 11);
+    /// assert_eq!(
+    ///     *markers.0.lock().unwrap(),
+    ///     [MetaEvent::ReducerReplaced { after: swapped_after }]
+    /// );
+    /// ```
+    pub fn replace_reducer(&mut self, reducer: Reducer<State, Action>) -> Reducer<State, Action> {
+        match self.swap_reducer(reducer.into()) {
+            AnyReducer::Plain(previous) => previous,
+            #[cfg(feature = "strict")]
+            AnyReducer::Strict(_) => unreachable!(),
+        }
+    }
+
+    /// Same as [Store::replace_reducer], for a store created with
+    /// [Store::new_strict].
+    ///
+    /// Panics if the store wasn't created with [Store::new_strict].
+    #[cfg(feature = "strict")]
+    pub fn replace_strict_reducer(
+        &mut self,
+        reducer: crate::StrictReducer<State, Action>,
+    ) -> crate::StrictReducer<State, Action> {
+        match self.swap_reducer(AnyReducer::Strict(reducer)) {
+            AnyReducer::Strict(previous) => previous,
+            AnyReducer::Plain(_) => unreachable!(),
+        }
+    }
+
+    /// Replace the reducer with one of the same signature.
+    fn swap_reducer(&mut self, reducer: AnyReducer<State, Action>) -> AnyReducer<State, Action> {
+        #[cfg(feature = "strict")]
+        assert_eq!(
+            matches!(reducer, AnyReducer::Strict(_)),
+            matches!(self.reducer, AnyReducer::Strict(_)),
+            "reducer of a store created with `Store::new_strict` is replaced with `Store::replace_strict_reducer`, others with `Store::replace_reducer`"
+        );
+        if let Some(time_travel) = self.time_travel.as_mut() {
+            time_travel.reducer_replaced(reducer);
+        }
+        self.record_meta(MetaEvent::ReducerReplaced {
+            after: self.last_action_id,
+        });
+        core::mem::replace(&mut self.reducer, reducer)
+    }
+
+    /// Replace the effects, returning the previous ones. New effects
+    /// run starting with the next dispatched action.
+    ///
+    /// Recorder is notified with [MetaEvent::EffectsReplaced].
+    ///
+    /// Panics if the store was created with [Store::new_strict].
+    pub fn replace_effects(
+        &mut self,
+        effects: Effects<State, Service, Action>,
+    ) -> Effects<State, Service, Action> {
+        match self.swap_effects(effects.into()) {
+            AnyEffects::Plain(previous) => previous,
+            #[cfg(feature = "strict")]
+            AnyEffects::Strict(_) => unreachable!(),
+        }
+    }
+
+    /// Same as [Store::replace_effects], for a store created with
+    /// [Store::new_strict].
+    ///
+    /// Panics if the store wasn't created with [Store::new_strict].
+    #[cfg(feature = "strict")]
+    pub fn replace_strict_effects(
+        &mut self,
+        effects: crate::StrictEffects<State, Service, Action>,
+    ) -> crate::StrictEffects<State, Service, Action> {
+        match self.swap_effects(AnyEffects::Strict(effects)) {
+            AnyEffects::Strict(previous) => previous,
+            AnyEffects::Plain(_) => unreachable!(),
+        }
+    }
+
+    /// Replace the effects with ones of the same signature.
+    fn swap_effects(
+        &mut self,
+        effects: AnyEffects<State, Service, Action>,
+    ) -> AnyEffects<State, Service, Action> {
+        #[cfg(feature = "strict")]
+        assert_eq!(
+            matches!(effects, AnyEffects::Strict(_)),
+            matches!(self.effects, AnyEffects::Strict(_)),
+            "effects of a store created with `Store::new_strict` are replaced with `Store::replace_strict_effects`, others with `Store::replace_effects`"
+        );
+        self.record_meta(MetaEvent::EffectsReplaced {
+            after: self.last_action_id,
+        });
+        core::mem::replace(&mut self.effects, effects)
+    }
+
+    /// Add effects called for every action along with the effects of
+    /// the store and of its modules, at the point given by `order`, see
+    /// [crate::EffectOrder]. `name` identifies them in
+    /// [Store::effects_order].
+    ///
+    /// ```
+    /// use redux_rs::{
+    ///     embed, lens, ActionWithMeta, EffectOrder, EnablingCondition, Module, ModuleRegistry,
+    ///     ScopedStore, Store, StoreBuilder, StoreLike,
+    /// };
+    /// use std::sync::Mutex;
+    ///
+    /// static CALLS: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+    ///
+    /// struct Peers;
+    ///
+    /// impl Module for Peers {
+    ///     type SubState = ();
+    ///     type SubAction = ();
+    ///
+    ///     fn initial_state() {}
+    ///
+    ///     fn reducer(_: &mut (), _: &ActionWithMeta<&()>) {}
+    ///
+    ///     fn effects<Parent: StoreLike>(
+    ///         _: &mut ScopedStore<'_, Parent, (), ()>,
+    ///         _: &ActionWithMeta<&()>,
+    ///     ) {
+    ///         CALLS.lock().unwrap().push("module");
+    ///     }
+    /// }
+    ///
+    /// #[derive(Default)]
+    /// struct State {
+    ///     peers: (),
+    /// }
+    ///
+    /// enum Action {
+    ///     Peer(()),
+    /// }
+    ///
+    /// impl EnablingCondition<State> for Action {}
+    ///
+    /// type S = Store<State, (), Action>;
+    ///
+    /// fn effects(_: &mut S, _: &ActionWithMeta<Action>) {
+    ///     CALLS.lock().unwrap().push("root");
+    /// }
+    ///
+    /// fn logging(_: &mut S, _: &ActionWithMeta<Action>) {
+    ///     CALLS.lock().unwrap().push("logging");
+    /// }
+    ///
+    /// fn gating(_: &mut S, _: &ActionWithMeta<Action>) {
+    ///     CALLS.lock().unwrap().push("gating");
+    /// }
+    ///
+    /// fn metrics(_: &mut S, _: &ActionWithMeta<Action>) {
+    ///     CALLS.lock().unwrap().push("metrics");
+    /// }
+    ///
+    /// let mut modules = ModuleRegistry::<State, (), Action>::new();
+    /// modules.register::<Peers>(lens!(peers), embed!(Action::Peer));
+    /// let mut store = StoreBuilder::new()
+    ///     .reducer(|_, _| {})
+    ///     .effects(effects)
+    ///     .service(())
+    ///     .initial_state(State::default())
+    ///     .modules(modules)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // Registration order only matters within a class.
+    /// store.add_effects("logging", EffectOrder::Post, logging);
+    /// store.add_effects("metrics", EffectOrder::Module, metrics);
+    /// store.add_effects("gating", EffectOrder::Pre, gating);
+    ///
+    /// store.dispatch(Action::Peer(()));
+    /// assert_eq!(
+    ///     *CALLS.lock().unwrap(),
+    ///     ["gating", "root", "module", "metrics", "logging"]
+    /// );
+    ///
+    /// let order: Vec<_> = store.effects_order().into_iter().map(|(_, name)| name).collect();
+    /// assert_eq!(order.len(), 5);
+    /// assert_eq!((order[0], order[1], order[3], order[4]), ("gating", "effects", "metrics", "logging"));
+    /// assert!(order[2].ends_with("Peers"));
+    /// ```
+    pub fn add_effects(
+        &mut self,
+        name: &'static str,
+        order: EffectOrder,
+        effects: Effects<State, Service, Action>,
+    ) {
+        let mut ordered: Vec<_> = self
+            .ordered_effects
+            .iter()
+            .flat_map(|ordered| ordered.iter().copied())
+            .collect();
+        let index = ordered.partition_point(|other| other.order <= order);
+        ordered.insert(
+            index,
+            crate::effect_order::OrderedEffects {
+                name,
+                order,
+                effects,
+            },
+        );
+        self.ordered_effects = Some(ordered.into());
+    }
+
+    /// Names of effects called for every action, in the order they're
+    /// called, along with their class, see [Store::add_effects]. Effects
+    /// of the store are named `effects`, effects of modules after the
+    /// type of the module.
+    pub fn effects_order(&self) -> Vec<(EffectOrder, &'static str)> {
+        let ordered = self.ordered_effects.as_deref().unwrap_or(&[]);
+        let module_start = ordered.partition_point(|effects| effects.order == EffectOrder::Pre);
+        let added = |effects: &crate::effect_order::OrderedEffects<State, Service, Action>| {
+            (effects.order, effects.name)
+        };
+        let modules = self
+            .modules
+            .iter()
+            .flat_map(|modules| modules.effects.iter());
+        ordered[..module_start]
+            .iter()
+            .map(added)
+            .chain(Some((EffectOrder::Module, "effects")))
+            .chain(modules.map(|module| (EffectOrder::Module, module.name())))
+            .chain(ordered[module_start..].iter().map(added))
+            .collect()
+    }
+
+    /// Ids and names of the registered modules, root first, see
+    /// [crate::ModuleRegistry::table].
+    pub fn module_table(&self) -> Vec<crate::ModuleInfo> {
+        let effects = self
+            .modules
+            .as_ref()
+            .map_or(&[][..], |modules| &modules.effects[..]);
+        crate::module::table(effects)
+    }
+
+    /// Id of module `M`, if registered.
+    pub fn module_id<M: crate::Module + 'static>(&self) -> Option<crate::ModuleId> {
+        let effects = self
+            .modules
+            .as_ref()
+            .map_or(&[][..], |modules| &modules.effects[..]);
+        crate::module::id_of::<M, _, _, _>(effects)
+    }
+
+    /// Type name of the module with the id, `"root"` for
+    /// [crate::ModuleId::ROOT].
+    pub fn module_name(&self, id: crate::ModuleId) -> Option<&'static str> {
+        let index = match u16::from(id).checked_sub(1) {
+            Some(index) => index as usize,
+            None => return Some("root"),
+        };
+        let module = self.modules.as_ref()?.effects.get(index)?;
+        Some(module.name())
+    }
+
+    fn record_meta(&mut self, event: MetaEvent) {
+        self.recording.record_meta(&event);
+    }
+
+    /// Stop processing actions: from now on, dispatched actions are
+    /// queued (up to [crate::PAUSED_QUEUE_CAPACITY], or as set with
+    /// [Store::reserve_paused_queue]) until
+    /// [Store::resume] or [Store::step_one]. State can still be read.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Store};
+    ///
+    /// struct Add(u32);
+    /// impl EnablingCondition<u32> for Add {}
+    ///
+    /// fn reducer(state: &mut u32, action: &ActionWithMeta<Add>) {
+    ///     *state += action.action.0;
+    /// }
+    ///
+    /// fn effects(store: &mut Store<u32, (), Add>, action: &ActionWithMeta<Add>) {
+    ///     if action.action.0 == 1 {
+    ///         store.dispatch(Add(100));
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, effects, 0);
+    /// store.pause();
+    ///
+    /// store.dispatch(Add(1));
+    /// store.dispatch(Add(2));
+    /// store.dispatch(Add(3));
+    /// assert_eq!(*store.state(), 0);
+    /// assert_eq!(store.paused_actions(), 3);
+    ///
+    /// // Action dispatched by effects is queued as well.
+    /// assert!(store.step_one());
+    /// assert_eq!(*store.state(), 1);
+    /// assert_eq!(store.paused_actions(), 3);
+    /// assert_eq!(store.actions_processed(), 1);
+    ///
+    /// store.resume();
+    /// assert_eq!(*store.state(), 106);
+    /// assert!(!store.is_paused());
+    /// ```
+    pub fn pause(&mut self) {
+        self.pause_with_capacity(self.pause.default_capacity());
+    }
+
+    /// Same as [Store::pause], with custom capacity of the queue.
+    /// Actions dispatched when the queue is full are dropped.
+    ///
+    /// Changes capacity if already paused, keeping queued actions.
+    pub fn pause_with_capacity(&mut self, capacity: usize) {
+        if self.pause.pause(capacity) {
+            self.update_subsystems();
+        }
+    }
+
+    /// Set (or remove with `None`) coalescing of actions queued while
+    /// paused.
+    ///
+    /// When an action is queued while another one with the same
+    /// [crate::CoalesceKey] is already in the queue, only one of them,
+    /// chosen by [crate::CoalescePolicy], stays queued. Actions which are
+    /// already being processed are never affected. Coalesced actions are
+    /// counted in [crate::KindStats::coalesced], by kind of the action
+    /// which stays queued.
+    ///
+    /// ```
+    /// use redux_rs::{
+    ///     ActionWithMeta, CoalescePolicy, Coalescing, EnablingCondition, Store,
+    /// };
+    ///
+    /// #[derive(Debug)]
+    /// enum Action {
+    ///     MessageReceived { peer: u64, seq: u32 },
+    /// }
+    ///
+    /// impl EnablingCondition<Vec<(u64, u32)>> for Action {}
+    ///
+    /// fn reducer(state: &mut Vec<(u64, u32)>, action: &ActionWithMeta<Action>) {
+    ///     let Action::MessageReceived { peer, seq } = action.action;
+    ///     state.push((peer, seq));
+    /// }
+    ///
+    /// fn key(action: &Action) -> Option<u64> {
+    ///     let Action::MessageReceived { peer, .. } = action;
+    ///     Some(*peer)
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, vec![]);
+    /// #[cfg(feature = "stats")]
+    /// store.enable_stats(|_| "MessageReceived");
+    /// store.set_coalescing(Some(Coalescing::new(key, CoalescePolicy::KeepLatest)));
+    ///
+    /// store.pause();
+    /// for seq in 0..100 {
+    ///     store.dispatch(Action::MessageReceived { peer: u64::from(seq % 3), seq });
+    /// }
+    /// assert_eq!(store.paused_actions(), 3);
+    /// store.resume();
+    ///
+    /// // Exactly 3 reducer calls, in order of the first queued action of
+    /// // each peer, with the latest payloads.
+    /// assert_eq!(store.state(), &[(0, 99), (1, 97), (2, 98)]);
+    /// #[cfg(feature = "stats")]
+    /// {
+    ///     let stats = store.stats().unwrap().kind("MessageReceived").unwrap();
+    ///     assert_eq!(stats.count, 3);
+    ///     assert_eq!(stats.coalesced, 97);
+    /// }
+    ///
+    /// // Merging.
+    /// fn merge(first: Action, latest: Action) -> Action {
+    ///     let Action::MessageReceived { peer, seq: a } = first;
+    ///     let Action::MessageReceived { seq: b, .. } = latest;
+    ///     Action::MessageReceived { peer, seq: a + b }
+    /// }
+    ///
+    /// store.set_coalescing(Some(Coalescing::new(key, CoalescePolicy::Merge(merge))));
+    /// store.pause();
+    /// store.dispatch(Action::MessageReceived { peer: 7, seq: 1 });
+    /// store.dispatch(Action::MessageReceived { peer: 8, seq: 10 });
+    /// store.dispatch(Action::MessageReceived { peer: 7, seq: 2 });
+    /// store.resume();
+    /// assert_eq!(store.state()[3..], [(7, 3), (8, 10)]);
+    /// ```
+    pub fn set_coalescing(&mut self, coalescing: Option<crate::Coalescing<Action>>) {
+        self.pause.coalescing = coalescing;
+    }
+
+    /// Set (or remove with `None`) per-kind rate limiting of dispatched
+    /// actions.
+    ///
+    /// Enabled actions of a kind which ran out of tokens are dropped
+    /// before they're given an id: [Store::dispatch] returns `false` and
+    /// neither ids nor time of later actions are affected. Drops are
+    /// counted in [crate::KindStats::rate_limited] under the limiter's
+    /// kind, if stats are enabled. Actions dispatched while paused are
+    /// limited when processed, not when queued.
+    ///
+    /// ```
+    /// use redux_rs::{
+    ///     ActionWithMeta, EnablingCondition, FrozenTime, Instant, RateLimit, RateLimitChange,
+    ///     RateLimiter, Store,
+    /// };
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// #[derive(Debug, Clone, PartialEq)]
+    /// enum Action {
+    ///     Ping,
+    ///     Tick,
+    ///     Overload(&'static str, RateLimitChange),
+    /// }
+    ///
+    /// impl EnablingCondition<Vec<Action>> for Action {}
+    ///
+    /// fn kind(action: &Action) -> &'static str {
+    ///     match action {
+    ///         Action::Ping => "Ping",
+    ///         Action::Tick => "Tick",
+    ///         Action::Overload(..) => "Overload",
+    ///     }
+    /// }
+    ///
+    /// fn reducer(state: &mut Vec<Action>, action: &ActionWithMeta<Action>) {
+    ///     state.push(action.action.clone());
+    /// }
+    ///
+    /// fn overload(kind: &'static str, change: RateLimitChange) -> Option<Action> {
+    ///     Some(Action::Overload(kind, change))
+    /// }
+    ///
+    /// let clock = FrozenTime::new(Instant::now());
+    /// let mut store = Store::new(reducer, |_, _| {}, clock, SystemTime::UNIX_EPOCH, vec![]);
+    /// #[cfg(feature = "stats")]
+    /// store.enable_stats(kind);
+    /// // Bursts of 2 pings, then one per 100ms.
+    /// let ping = RateLimit::new(2, Duration::from_millis(100));
+    /// store.set_rate_limiter(Some(RateLimiter::new(kind).limit("Ping", ping)));
+    /// store.set_rate_limit_action(Some(overload));
+    ///
+    /// let mut accepted = vec![];
+    /// let mut ping = |store: &mut Store<_, FrozenTime, _>, advance_ms| {
+    ///     store.service.advance(Duration::from_millis(advance_ms));
+    ///     accepted.push(store.dispatch(Action::Ping));
+    /// };
+    /// ping(&mut store, 0);
+    /// ping(&mut store, 0);
+    /// ping(&mut store, 0);
+    /// ping(&mut store, 50);
+    /// // 100ms since the bucket was emptied, one token.
+    /// ping(&mut store, 50);
+    /// ping(&mut store, 0);
+    /// // Full again, idle time beyond that isn't saved up.
+    /// ping(&mut store, 1000);
+    /// ping(&mut store, 0);
+    /// ping(&mut store, 0);
+    /// ping(&mut store, 99);
+    /// ping(&mut store, 1);
+    /// assert_eq!(
+    ///     accepted,
+    ///     [true, true, false, false, true, false, true, true, false, false, true]
+    /// );
+    ///
+    /// // Unconfigured kinds aren't limited.
+    /// assert!((0..10).all(|_| store.dispatch(Action::Tick)));
+    ///
+    /// let changes: Vec<_> = store
+    ///     .state()
+    ///     .iter()
+    ///     .filter_map(|action| match action {
+    ///         Action::Overload(_, change) => Some(*change),
+    ///         _ => None,
+    ///     })
+    ///     .collect();
+    /// use RateLimitChange::{Limited, Recovered};
+    /// assert_eq!(changes, [Limited, Recovered, Limited, Recovered, Limited, Recovered]);
+    /// // Recovery is processed after the action let through.
+    /// assert_eq!(store.state()[3..5], [Action::Ping, Action::Overload("Ping", Recovered)]);
+    ///
+    /// assert_eq!(store.rate_limiter().unwrap().dropped("Ping"), 5);
+    /// #[cfg(feature = "stats")]
+    /// assert_eq!(store.stats().unwrap().kind("Ping").unwrap().rate_limited, 5);
+    ///
+    /// // Dropped actions don't get an id.
+    /// assert!(!store.dispatch(Action::Ping));
+    /// let last = store.last_action_id();
+    /// assert!(!store.dispatch(Action::Ping));
+    /// assert_eq!(store.last_action_id(), last);
+    /// ```
+    pub fn set_rate_limiter(&mut self, limiter: Option<crate::RateLimiter<Action>>) {
+        self.rate_limiting.limiter = limiter;
+    }
+
+    #[inline(always)]
+    pub fn rate_limiter(&self) -> Option<&crate::RateLimiter<Action>> {
+        self.rate_limiting.limiter.as_ref()
+    }
+
+    /// Set (or remove with `None`) the mapper of changes of the state
+    /// of rate limited kinds to actions, see [Store::set_rate_limiter].
+    ///
+    /// Action for a kind getting limited is dispatched when its first
+    /// action is dropped, the one for a kind recovering after its first
+    /// action let through again is processed.
+    pub fn set_rate_limit_action(&mut self, mapper: Option<crate::RateLimitActionMapper<Action>>)
+    where
+        Action: EnablingCondition<State>,
+    {
+        self.rate_limiting.change_action =
+            mapper.map(|mapper| (mapper, Self::dispatch::<Action> as _));
+    }
+
+    /// Set (or remove with `None`) suppression of duplicate actions.
+    ///
+    /// Action with the same [crate::DedupKey] as an action processed
+    /// within the [crate::DedupWindow] before it is rejected: it's
+    /// counted in [crate::KindStats::duplicates] and reported as
+    /// [crate::StoreError::Duplicate], see [Store::set_error_action].
+    /// Duplicates are checked before the enabling condition (and its
+    /// cache, see [Store::enable_condition_cache]), so a duplicate is
+    /// rejected as one even if it's not enabled. Only processed actions
+    /// have their keys remembered, so an action rejected for any reason
+    /// can be retried. Actions dispatched while paused are checked when
+    /// processed, not when queued.
+    ///
+    /// ```
+    /// use redux_rs::{
+    ///     ActionWithMeta, Dedup, DedupWindow, EnablingCondition, FrozenTime, Instant, Store,
+    /// };
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// #[derive(Debug, Clone, PartialEq)]
+    /// enum Action {
+    ///     Deposit { event: u64, amount: u64 },
+    ///     Withdraw { event: u64, amount: u64 },
+    /// }
+    ///
+    /// impl EnablingCondition<u64> for Action {
+    ///     fn is_enabled(&self, balance: &u64) -> bool {
+    ///         match self {
+    ///             Action::Deposit { .. } => true,
+    ///             Action::Withdraw { amount, .. } => amount <= balance,
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// fn reducer(balance: &mut u64, action: &ActionWithMeta<Action>) {
+    ///     match action.action {
+    ///         Action::Deposit { amount, .. } => *balance += amount,
+    ///         Action::Withdraw { amount, .. } => *balance -= amount,
+    ///     }
+    /// }
+    ///
+    /// fn event(action: &Action) -> Option<u64> {
+    ///     match action {
+    ///         Action::Deposit { event, .. } | Action::Withdraw { event, .. } => Some(*event),
+    ///     }
+    /// }
+    ///
+    /// let clock = FrozenTime::new(Instant::now());
+    /// let mut store = Store::new(reducer, |_, _| {}, clock, SystemTime::UNIX_EPOCH, 0);
+    /// #[cfg(feature = "stats")]
+    /// store.enable_stats(|_| "Transfer");
+    /// let window = DedupWindow::Time(Duration::from_millis(100));
+    /// store.set_dedup(Some(Dedup::new(event, window)));
+    ///
+    /// // Delivered twice within the window.
+    /// assert!(store.dispatch(Action::Deposit { event: 1, amount: 10 }));
+    /// store.service.advance(Duration::from_millis(5));
+    /// assert!(!store.dispatch(Action::Deposit { event: 1, amount: 10 }));
+    /// assert_eq!(*store.state(), 10);
+    ///
+    /// // Rejected as a duplicate, though it's not enabled either.
+    /// assert!(store.dispatch(Action::Withdraw { event: 2, amount: 10 }));
+    /// assert!(!store.dispatch(Action::Withdraw { event: 2, amount: 10 }));
+    /// #[cfg(feature = "stats")]
+    /// assert_eq!(store.stats().unwrap().kind("Transfer").unwrap().duplicates, 2);
+    ///
+    /// // Not enabled, so the key isn't remembered and it can be retried
+    /// // once it is.
+    /// assert!(!store.dispatch(Action::Withdraw { event: 5, amount: 5 }));
+    /// assert!(store.dispatch(Action::Deposit { event: 6, amount: 5 }));
+    /// assert!(store.dispatch(Action::Withdraw { event: 5, amount: 5 }));
+    /// assert_eq!(*store.state(), 0);
+    ///
+    /// // Not a duplicate once the window passes.
+    /// store.service.advance(Duration::from_millis(100));
+    /// assert!(store.dispatch(Action::Deposit { event: 1, amount: 10 }));
+    /// assert_eq!(*store.state(), 10);
+    /// // Keys out of the window are forgotten.
+    /// assert_eq!(store.dedup().unwrap().len(), 1);
+    ///
+    /// // Window of the next 2 actions.
+    /// store.set_dedup(Some(Dedup::new(event, DedupWindow::Actions(2))));
+    /// assert!(store.dispatch(Action::Deposit { event: 3, amount: 1 }));
+    /// assert!(store.dispatch(Action::Deposit { event: 4, amount: 1 }));
+    /// assert!(!store.dispatch(Action::Deposit { event: 3, amount: 1 }));
+    /// assert!(store.dispatch(Action::Deposit { event: 3, amount: 1 }));
+    /// assert_eq!(store.dedup().unwrap().suppressed(), 1);
+    /// ```
+    pub fn set_dedup(&mut self, dedup: Option<crate::Dedup<Action>>) {
+        self.dedup = dedup;
+    }
+
+    #[inline(always)]
+    pub fn dedup(&self) -> Option<&crate::Dedup<Action>> {
+        self.dedup.as_ref()
+    }
+
+    /// Preallocate the queue used while paused for `capacity` actions
+    /// and make it the capacity used by [Store::pause], so that neither
+    /// pausing (e.g. by a breakpoint) nor queueing up to `capacity`
+    /// actions allocates. Queue is reused by later pauses.
+    ///
+    /// Only takes effect for the next pause if already paused.
+    pub fn reserve_paused_queue(&mut self, capacity: usize) {
+        self.pause.reserve(capacity);
+    }
+
+    /// Preallocate space for `additional` more actions captured by the
+    /// dispatch interceptor, see [Store::intercepted].
+    pub fn reserve_intercepted(&mut self, additional: usize) {
+        self.intercepted.reserve(additional);
+    }
+
+    /// Preallocate space for `additional` more breakpoints.
+    pub fn reserve_breakpoints(&mut self, additional: usize) {
+        self.breakpoints.reserve(additional);
+    }
+
+    /// Resume processing actions, first processing all the actions
+    /// queued while paused, in order.
+    pub fn resume(&mut self)
+    where
+        Action: EnablingCondition<State>,
+    {
+        let mut queue = match self.pause.unpause() {
+            Some(queue) => queue,
+            None => return,
+        };
+        self.update_subsystems();
+        // Effects might resume again while this one drains.
+        let outer = self.pause.draining;
+        while let Some(queued) = queue.pop_front() {
+            self.pause.draining = outer + queue.len();
+            if let Some((action, provenance)) = self.unexpired(queued) {
+                self.dispatch_from(action, provenance);
+            }
+        }
+        self.pause.draining = outer;
+        self.update_pending();
+        // Effects might have paused again, taking the spare queue.
+        self.pause.recycle(queue);
+    }
+
+    /// Process the oldest action queued while paused. Actions it
+    /// dispatches from effects get queued.
+    ///
+    /// Returns `false` if there was no queued action. Action is taken
+    /// from the queue even if it's not enabled, or expired.
+    pub fn step_one(&mut self) -> bool
+    where
+        Action: EnablingCondition<State>,
+    {
+        let queued = match self.pause.paused_mut().and_then(|paused| paused.pop()) {
+            Some(queued) => queued,
+            None => return false,
+        };
+        if let Some((action, provenance)) = self.unexpired(queued) {
+            if let Some(paused) = self.pause.paused_mut() {
+                paused.step_through();
+            }
+            self.dispatch_from(action, provenance);
+        }
+        self.update_pending();
+        true
+    }
+
+    #[inline(always)]
+    pub fn is_paused(&self) -> bool {
+        self.pause.paused().is_some()
+    }
+
+    /// Number of actions queued while paused.
+    pub fn paused_actions(&self) -> usize {
+        self.pause.paused().map_or(0, |paused| paused.len())
+    }
+
+    /// Whether the store is paused and its queue is full, so dispatched
+    /// actions would be dropped.
+    pub fn is_paused_queue_full(&self) -> bool {
+        self.pause.paused().map_or(false, |paused| paused.is_full())
+    }
+
+    /// Schedule `action` to be dispatched by [Store::fire_timers] once
+    /// `delay` passes, measured with the service's monotonic clock.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, FrozenTime, Instant, Store};
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// #[derive(Debug, Clone, Copy, PartialEq)]
+    /// enum Action {
+    ///     Connect,
+    ///     ConnectTimeout,
+    ///     Connected,
+    /// }
+    ///
+    /// impl EnablingCondition<Vec<Action>> for Action {}
+    ///
+    /// fn reducer(state: &mut Vec<Action>, action: &ActionWithMeta<Action>) {
+    ///     state.push(action.action);
+    /// }
+    ///
+    /// fn effects(
+    ///     store: &mut Store<Vec<Action>, FrozenTime, Action>,
+    ///     action: &ActionWithMeta<Action>,
+    /// ) {
+    ///     if let Action::Connect = action.action {
+    ///         store.schedule_timer(Duration::from_secs(5), Action::ConnectTimeout);
+    ///     }
+    /// }
+    ///
+    /// let service = FrozenTime::new(Instant::now());
+    /// let mut store = Store::new(reducer, effects, service, SystemTime::UNIX_EPOCH, vec![]);
+    ///
+    /// store.dispatch(Action::Connect);
+    /// assert_eq!(store.pending_timers(), 1);
+    ///
+    /// store.service.advance(Duration::from_secs(4));
+    /// assert_eq!(store.fire_timers(), 0);
+    ///
+    /// store.service.advance(Duration::from_secs(1));
+    /// assert_eq!(store.fire_timers(), 1);
+    /// assert_eq!(store.state(), &[Action::Connect, Action::ConnectTimeout]);
+    ///
+    /// // Cancelled timer never fires.
+    /// store.dispatch(Action::Connect);
+    /// let id = store.schedule_timer(Duration::ZERO, Action::Connected);
+    /// assert_eq!(store.cancel_timer(id), Some(Action::Connected));
+    /// store.service.advance(Duration::from_secs(10));
+    /// assert_eq!(store.fire_timers(), 1);
+    /// assert_eq!(store.state()[2..], [Action::Connect, Action::ConnectTimeout]);
+    /// ```
+    pub fn schedule_timer(&mut self, delay: Duration, action: Action) -> crate::TimerId {
+        let deadline = self.service.monotonic_time() + delay;
+        let id = self.timers.once.schedule(deadline, action);
+        self.update_pending();
+        id
+    }
+
+    /// Cancel a timer which didn't fire yet, returning its action.
+    pub fn cancel_timer(&mut self, id: crate::TimerId) -> Option<Action> {
+        let action = self.timers.once.cancel(id);
+        self.update_pending();
+        action
+    }
+
+    /// Number of timers which didn't fire yet.
+    pub fn pending_timers(&self) -> usize {
+        self.timers.once.len()
+    }
+
+    /// Dispatch `make_action(time)` every `interval`, measured with the
+    /// service's monotonic clock, starting `interval` from now, with
+    /// [crate::CatchUp::FireOnceForAll] for missed ticks.
+    ///
+    /// Fired by [Store::fire_timers], `time` is the scheduled time of the
+    /// tick on the system clock of the store, see
+    /// [Store::monotonic_to_time]. Each tick is scheduled a whole number
+    /// of intervals after the first one, not after the time the previous
+    /// one actually fired, so the schedule doesn't drift. Fired actions
+    /// are dispatched like any other, so they are recorded as usual.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, FrozenTime, Instant, Store};
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum Action {
+    ///     PruneCaches(SystemTime),
+    /// }
+    ///
+    /// impl EnablingCondition<Vec<SystemTime>> for Action {}
+    ///
+    /// fn reducer(pruned: &mut Vec<SystemTime>, action: &ActionWithMeta<Action>) {
+    ///     let Action::PruneCaches(at) = action.action;
+    ///     pruned.push(at);
+    /// }
+    ///
+    /// let start = SystemTime::UNIX_EPOCH;
+    /// let service = FrozenTime::new(Instant::now());
+    /// let mut store = Store::new(reducer, |_, _| {}, service, start, vec![]);
+    /// let id = store.schedule_recurring(Duration::from_secs(60), Action::PruneCaches);
+    ///
+    /// // An hour, polled every 7s, so never exactly on a tick.
+    /// for _ in 0..(3600 / 7) {
+    ///     store.service.advance(Duration::from_secs(7));
+    ///     store.fire_timers();
+    /// }
+    /// store.service.advance(Duration::from_secs(3600 % 7));
+    /// store.fire_timers();
+    ///
+    /// let expected: Vec<_> = (1..=60).map(|minute| start + Duration::from_secs(60 * minute)).collect();
+    /// assert_eq!(store.state(), &expected);
+    ///
+    /// assert!(store.cancel_recurring(id));
+    /// store.service.advance(Duration::from_secs(600));
+    /// assert_eq!(store.fire_timers(), 0);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn schedule_recurring(
+        &mut self,
+        interval: Duration,
+        make_action: fn(SystemTime) -> Action,
+    ) -> crate::RecurringId {
+        self.schedule_recurring_with(interval, crate::CatchUp::FireOnceForAll, make_action)
+    }
+
+    /// Same as [Store::schedule_recurring], with `catch_up` for ticks
+    /// missed because [Store::fire_timers] wasn't called for more than
+    /// an interval.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, CatchUp, EnablingCondition, FrozenTime, Instant, Store};
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum Action {
+    ///     RotateStats(SystemTime),
+    /// }
+    ///
+    /// impl EnablingCondition<Vec<SystemTime>> for Action {}
+    ///
+    /// fn reducer(rotated: &mut Vec<SystemTime>, action: &ActionWithMeta<Action>) {
+    ///     let Action::RotateStats(at) = action.action;
+    ///     rotated.push(at);
+    /// }
+    ///
+    /// let start = SystemTime::UNIX_EPOCH;
+    /// let minutes = |m: u64| start + Duration::from_secs(60 * m);
+    ///
+    /// for catch_up in [CatchUp::Skip, CatchUp::FireOnceForAll] {
+    ///     let service = FrozenTime::new(Instant::now());
+    ///     let mut store = Store::new(reducer, |_, _| {}, service, start, vec![]);
+    ///     store.schedule_recurring_with(Duration::from_secs(600), catch_up, Action::RotateStats);
+    ///
+    ///     store.service.advance(Duration::from_secs(601));
+    ///     assert_eq!(store.fire_timers(), 1);
+    ///
+    ///     // Stalled for 3 ticks.
+    ///     store.service.advance(Duration::from_secs(1800));
+    ///     let fired = store.fire_timers();
+    ///
+    ///     // Back on schedule.
+    ///     store.service.advance(Duration::from_secs(599));
+    ///     assert_eq!(store.fire_timers(), 1);
+    ///
+    ///     match catch_up {
+    ///         CatchUp::Skip => {
+    ///             assert_eq!(fired, 0);
+    ///             assert_eq!(store.state(), &[minutes(10), minutes(50)]);
+    ///         }
+    ///         CatchUp::FireOnceForAll => {
+    ///             assert_eq!(fired, 1);
+    ///             assert_eq!(store.state(), &[minutes(10), minutes(40), minutes(50)]);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn schedule_recurring_with(
+        &mut self,
+        interval: Duration,
+        catch_up: crate::CatchUp,
+        make_action: fn(SystemTime) -> Action,
+    ) -> crate::RecurringId {
+        let now = self.service.monotonic_time();
+        self.timers
+            .recurring
+            .schedule(now, interval, catch_up, make_action)
+    }
+
+    /// Stop a recurring action. Returns `false` if it isn't scheduled.
+    #[cfg(feature = "std")]
+    pub fn cancel_recurring(&mut self, id: crate::RecurringId) -> bool {
+        self.timers.recurring.cancel(id)
+    }
+
+    /// Number of scheduled recurring actions.
+    #[cfg(feature = "std")]
+    pub fn recurring_actions(&self) -> usize {
+        self.timers.recurring.len()
+    }
+
+    /// Deadline of the earliest pending timer, recurring action or the
+    /// idle watchdog, see [Store::set_idle_watchdog].
+    pub fn next_timer_deadline(&self) -> Option<Instant> {
+        self.timers
+            .next_deadline()
+            .into_iter()
+            .chain(self.idle_deadline())
+            .min()
+    }
+
+    /// Dispatch actions of all the timers which are due, earliest first,
+    /// then of the recurring actions which are due, then of the idle
+    /// watchdog if it's due, returning how many fired. Timers scheduled
+    /// meanwhile, by effects of those actions, wait for the next call
+    /// even if already due.
+    ///
+    /// [Store::run_polled] calls it after every poll, otherwise it's up
+    /// to the caller to call it, e.g. once [Store::next_timer_deadline]
+    /// passes.
+    pub fn fire_timers(&mut self) -> usize
+    where
+        Action: EnablingCondition<State>,
+    {
+        let now = self.service.monotonic_time();
+        let before = self.timers.once.next_id();
+        let mut fired = 0;
+        while let Some(due) = self.timers.pop_due(now, before) {
+            fired += 1;
+            match due {
+                Fired::Timer(id, action) => {
+                    self.dispatch_from(action, Provenance::Timer { id });
+                }
+                #[cfg(feature = "std")]
+                Fired::Recurring(id, at, make_action) => {
+                    let time =
+                        SystemTime::UNIX_EPOCH + Duration::from_nanos(self.monotonic_to_time(at));
+                    self.dispatch_from(make_action(time), Provenance::Recurring { id });
+                }
+            }
+        }
+        self.update_pending();
+        #[cfg(feature = "std")]
+        if self.check_idle_watchdog(now) {
+            fired += 1;
+        }
+        fired
+    }
+
+    /// Set what happens when the reducer panics. Defaults to
+    /// [crate::ReducerPanicPolicy::Propagate].
+    ///
+    /// Caught panics are reported to the hook set with
+    /// [Store::set_caught_panic_hook]. Default panic hook of the
+    /// process still runs (and prints the panic) before that.
+    ///
+    /// Reducer is assumed unwind safe. With
+    /// [crate::ReducerPanicPolicy::SkipAction] it's up to the caller to
+    /// decide whether the state left behind is usable.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, CaughtPanic, EnablingCondition, ReducerPanicPolicy, Store};
+    /// use std::panic::{catch_unwind, AssertUnwindSafe};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// struct Push(u32);
+    /// impl EnablingCondition<Vec<u32>> for Push {}
+    ///
+    /// fn reducer(state: &mut Vec<u32>, action: &ActionWithMeta<Push>) {
+    ///     state.push(action.action.0);
+    ///     if action.action.0 == 13 {
+    ///         panic!("unlucky number");
+    ///     }
+    /// }
+    ///
+    /// static EFFECTS: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// fn effects(_: &mut Store<Vec<u32>, (), Push>, _: &ActionWithMeta<Push>) {
+    ///     EFFECTS.fetch_add(1, Ordering::SeqCst);
+    /// }
+    ///
+    /// fn on_panic(panic: &CaughtPanic) {
+    ///     assert_eq!(panic.message(), Some("unlucky number"));
+    /// }
+    ///
+    /// // Propagate.
+    /// let mut store = Store::simple(reducer, effects, vec![]);
+    /// let result = catch_unwind(AssertUnwindSafe(|| store.dispatch(Push(13))));
+    /// assert!(result.is_err());
+    ///
+    /// // SkipAction: state is left as is, effects don't run.
+    /// let mut store = Store::simple(reducer, effects, vec![]);
+    /// store.set_reducer_panic_policy(ReducerPanicPolicy::SkipAction);
+    /// store.set_caught_panic_hook(Some(on_panic));
+    /// store.dispatch(Push(1));
+    /// assert!(store.dispatch(Push(13)));
+    /// store.dispatch(Push(2));
+    /// assert_eq!(store.state(), &[1, 13, 2]);
+    /// assert_eq!(EFFECTS.load(Ordering::SeqCst), 2);
+    /// assert_eq!(store.caught_panics(), 1);
+    /// assert_eq!(store.actions_processed(), 2);
+    ///
+    /// // RestoreSnapshot: state is as before the action.
+    /// let mut store = Store::simple(reducer, effects, vec![]);
+    /// store.set_reducer_panic_policy(ReducerPanicPolicy::RestoreSnapshot(Vec::clone));
+    /// store.dispatch(Push(1));
+    /// store.dispatch(Push(13));
+    /// store.dispatch(Push(2));
+    /// assert_eq!(store.state(), &[1, 2]);
+    /// assert_eq!(store.caught_panics(), 1);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn set_reducer_panic_policy(&mut self, policy: crate::ReducerPanicPolicy<State>) {
+        self.panics.reducer_policy = policy;
+    }
+
+    /// Set (or clear) hook called for every caught panic.
+    #[cfg(feature = "std")]
+    pub fn set_caught_panic_hook(&mut self, hook: Option<crate::CaughtPanicHook>) {
+        self.panics.hook = hook;
+    }
+
+    /// Whether to catch panics in effects. Disabled by default.
+    ///
+    /// Caught panics are reported to the hook set with
+    /// [Store::set_caught_panic_hook]. State change made by the reducer
+    /// stands, and so do actions the effects dispatched before
+    /// panicking. Effects are assumed unwind safe.
+    ///
+    /// Panics of the actions dispatched from effects are caught by
+    /// their own effects, or, if they come from the reducer and the
+    /// [crate::ReducerPanicPolicy] doesn't catch them, by the effects
+    /// which dispatched the action.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, CaughtPanic, EnablingCondition, PanicSource, Store};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// enum Action {
+    ///     Request,
+    ///     Response,
+    /// }
+    /// impl EnablingCondition<Vec<&'static str>> for Action {}
+    ///
+    /// fn reducer(state: &mut Vec<&'static str>, action: &ActionWithMeta<Action>) {
+    ///     state.push(match action.action {
+    ///         Action::Request => "request",
+    ///         Action::Response => "response",
+    ///     });
+    /// }
+    ///
+    /// fn effects(store: &mut Store<Vec<&'static str>, (), Action>, action: &ActionWithMeta<Action>) {
+    ///     if let Action::Request = action.action {
+    ///         store.dispatch(Action::Response);
+    ///         panic!("connection reset");
+    ///     }
+    /// }
+    ///
+    /// static CAUGHT: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// fn on_panic(panic: &CaughtPanic) {
+    ///     assert_eq!(panic.source, PanicSource::Effects);
+    ///     assert_eq!(panic.message(), Some("connection reset"));
+    ///     CAUGHT.fetch_add(1, Ordering::SeqCst);
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, effects, vec![]);
+    /// store.set_catch_effects_panics(true);
+    /// store.set_caught_panic_hook(Some(on_panic));
+    ///
+    /// assert!(store.dispatch(Action::Request));
+    /// assert_eq!(store.state(), &["request", "response"]);
+    /// assert_eq!(CAUGHT.load(Ordering::SeqCst), 1);
+    /// assert_eq!(store.dispatch_depth(), 0);
+    ///
+    /// // Store keeps working.
+    /// store.dispatch(Action::Response);
+    /// assert_eq!(store.state().len(), 3);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn set_catch_effects_panics(&mut self, enabled: bool) {
+        self.panics.catch_effects = enabled;
+    }
+
+    /// Number of panics caught so far, see
+    /// [Store::set_reducer_panic_policy] and
+    /// [Store::set_catch_effects_panics].
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    pub fn caught_panics(&self) -> u64 {
+        self.panics.caught
+    }
+
+    /// Set (or disable with `None`) where the [crate::PanicContext] is
+    /// reported when the reducer or effects panic. Defaults to
+    /// [crate::eprint_panic_context].
+    ///
+    /// ```
+    /// use redux_rs::{kind_name, ActionKind, ActionWithMeta, EnablingCondition, PanicContext, Store};
+    /// use std::panic::{catch_unwind, AssertUnwindSafe};
+    /// use std::sync::Mutex;
+    ///
+    /// #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    /// struct Kind;
+    ///
+    /// impl From<Kind> for &'static str {
+    ///     fn from(_: Kind) -> Self {
+    ///         "Divide"
+    ///     }
+    /// }
+    ///
+    /// struct Divide(u32);
+    /// impl EnablingCondition<u32> for Divide {}
+    ///
+    /// impl ActionKind for Divide {
+    ///     type Kind = Kind;
+    ///
+    ///     fn kind(&self) -> Kind {
+    ///         Kind
+    ///     }
+    /// }
+    ///
+    /// fn reducer(state: &mut u32, action: &ActionWithMeta<Divide>) {
+    ///     *state /= action.action.0;
+    /// }
+    ///
+    /// static REPORTED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    ///
+    /// fn sink(context: &PanicContext) {
+    ///     REPORTED.lock().unwrap().push(context.to_string());
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, 1000);
+    /// store.set_panic_context_sink(Some(sink));
+    /// store.set_panic_context_classifier(Some(kind_name));
+    ///
+    /// store.dispatch(Divide(10));
+    /// let prev_id = store.last_action_id();
+    /// let result = catch_unwind(AssertUnwindSafe(|| store.dispatch(Divide(0))));
+    /// assert!(result.is_err());
+    ///
+    /// let reported = REPORTED.lock().unwrap();
+    /// assert_eq!(reported.len(), 1);
+    /// assert!(reported[0].contains("(Divide) at depth 0"));
+    /// assert!(reported[0].ends_with(&format!("recent actions: [{}]", u64::from(prev_id))));
+    /// ```
+    #[cfg(feature = "panic_context")]
+    pub fn set_panic_context_sink(&mut self, sink: Option<crate::PanicContextSink>) {
+        self.panic_context.sink = sink;
+    }
+
+    /// Set (or clear) classifier used to tell the kind of the action in
+    /// [crate::PanicContext].
+    #[cfg(feature = "panic_context")]
+    pub fn set_panic_context_classifier(
+        &mut self,
+        classifier: Option<crate::ActionClassifier<Action>>,
+    ) {
+        self.panic_context.classifier = classifier;
+    }
+
+    /// Whether to [Store::poison] the store when a panic is caught.
+    /// Disabled by default.
+    #[cfg(feature = "std")]
+    pub fn set_poison_on_panic(&mut self, enabled: bool) {
+        self.panics.poison = enabled;
+    }
+
+    /// Check the safety condition of the state after every reducer call.
+    /// Violations are reported as [crate::StoreError::SafetyViolation],
+    /// see [Store::set_error_action].
+    pub fn enable_safety_check(&mut self)
+    where
+        State: crate::SafetyCondition,
+        State::Error: fmt::Debug,
+    {
+        self.safety_check = Some(crate::safety_condition::check_formatted::<State>);
+        self.update_subsystems();
+    }
+
+    pub fn disable_safety_check(&mut self) {
+        self.safety_check = None;
+        self.update_subsystems();
+    }
+
+    /// Cache results of enabling conditions, the last one per kind
+    /// given by `classifier`, and reuse it as long as the fingerprint
+    /// of what the condition depends on stays the same, see
+    /// [crate::CacheableCondition].
+    ///
+    /// Only actions dispatched as `Action` itself are cached. Other
+    /// types converted into it may have stricter conditions, so theirs
+    /// is always evaluated.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, CacheableCondition, EnablingCondition, Store};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// static EVALUATED: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// #[derive(Default)]
+    /// struct State {
+    ///     bootstrapped: bool,
+    ///     blocks: u32,
+    /// }
+    ///
+    /// enum Action {
+    ///     Bootstrapped(bool),
+    ///     Block,
+    /// }
+    ///
+    /// impl EnablingCondition<State> for Action {
+    ///     fn is_enabled(&self, state: &State) -> bool {
+    ///         match self {
+    ///             Action::Bootstrapped(_) => true,
+    ///             Action::Block => {
+    ///                 EVALUATED.fetch_add(1, Ordering::SeqCst);
+    ///                 state.bootstrapped
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// /// Block allowed only up to a limit.
+    /// struct LimitedBlock(u32);
+    ///
+    /// impl EnablingCondition<State> for LimitedBlock {
+    ///     fn is_enabled(&self, state: &State) -> bool {
+    ///         state.bootstrapped && state.blocks < self.0
+    ///     }
+    /// }
+    ///
+    /// impl From<LimitedBlock> for Action {
+    ///     fn from(_: LimitedBlock) -> Self {
+    ///         Action::Block
+    ///     }
+    /// }
+    ///
+    /// impl CacheableCondition<State> for Action {
+    ///     fn dep_fingerprint(&self, state: &State) -> u64 {
+    ///         state.bootstrapped as u64
+    ///     }
+    ///
+    ///     fn is_cacheable(&self) -> bool {
+    ///         matches!(self, Action::Block)
+    ///     }
+    /// }
+    ///
+    /// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+    ///     match action.action {
+    ///         Action::Bootstrapped(bootstrapped) => state.bootstrapped = bootstrapped,
+    ///         Action::Block => state.blocks += 1,
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, State::default());
+    /// store.enable_condition_cache(|action| match action {
+    ///     Action::Bootstrapped(_) => "Bootstrapped",
+    ///     Action::Block => "Block",
+    /// });
+    ///
+    /// assert!(!store.dispatch(Action::Block));
+    /// assert!(!store.dispatch(Action::Block));
+    /// assert_eq!(EVALUATED.load(Ordering::SeqCst), 1);
+    ///
+    /// // Changed dependency is never served the stale result.
+    /// store.dispatch(Action::Bootstrapped(true));
+    /// for _ in 0..100 {
+    ///     assert!(store.dispatch(Action::Block));
+    /// }
+    /// assert_eq!(store.state().blocks, 100);
+    /// assert_eq!(EVALUATED.load(Ordering::SeqCst), 2);
+    ///
+    /// // Cached `Action::Block` result doesn't stand in for the stricter one.
+    /// assert!(!store.dispatch(LimitedBlock(100)));
+    /// assert!(store.dispatch(LimitedBlock(101)));
+    /// assert_eq!(store.state().blocks, 101);
+    ///
+    /// store.dispatch(Action::Bootstrapped(false));
+    /// assert!(!store.dispatch(Action::Block));
+    /// assert_eq!(EVALUATED.load(Ordering::SeqCst), 3);
+    ///
+    /// let stats = store.condition_cache_stats().unwrap();
+    /// assert_eq!((stats.hits, stats.misses, stats.uncacheable), (100, 3, 2));
+    /// ```
+    pub fn enable_condition_cache(&mut self, classifier: crate::ActionClassifier<Action>)
+    where
+        Action: crate::CacheableCondition<State>,
+    {
+        self.condition_cache = Some(crate::condition_cache::ConditionCache::new(classifier));
+    }
+
+    pub fn disable_condition_cache(&mut self) {
+        self.condition_cache = None;
+    }
+
+    /// Counters of the enabling condition cache, `None` if it isn't
+    /// enabled, see [Store::enable_condition_cache].
+    pub fn condition_cache_stats(&self) -> Option<crate::ConditionCacheStats> {
+        self.condition_cache.as_ref().map(|cache| cache.stats())
+    }
+
+    /// Turn failures noticed by the store into actions: for every
+    /// [crate::StoreError], `mapper` is called and the action it
+    /// returns is dispatched as usual.
+    ///
+    /// Errors happening while an error action is being processed,
+    /// including by actions its effects dispatch, don't produce more
+    /// error actions.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, SafetyCondition, Store, StoreError};
+    ///
+    /// #[derive(Default)]
+    /// struct State {
+    ///     balance: i64,
+    ///     tripped: u32,
+    /// }
+    ///
+    /// impl SafetyCondition for State {
+    ///     type Error = i64;
+    ///
+    ///     fn check_safety_condition(&self) -> Result<(), i64> {
+    ///         if self.balance < 0 {
+    ///             return Err(self.balance);
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// enum Action {
+    ///     Withdraw(i64),
+    ///     SafetyTripped(String),
+    /// }
+    /// impl EnablingCondition<State> for Action {}
+    ///
+    /// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+    ///     match &action.action {
+    ///         Action::Withdraw(amount) => state.balance -= amount,
+    ///         Action::SafetyTripped(error) => {
+    ///             assert_eq!(error, "-5");
+    ///             state.tripped += 1;
+    ///             // Still violated, but no error action for that.
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// fn error_action(error: StoreError) -> Option<Action> {
+    ///     match error {
+    ///         StoreError::SafetyViolation { error, .. } => Some(Action::SafetyTripped(error)),
+    ///         _ => None,
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, State::default());
+    /// store.enable_safety_check();
+    /// store.set_error_action(Some(error_action));
+    ///
+    /// store.dispatch(Action::Withdraw(5));
+    /// assert_eq!(store.state().tripped, 1);
+    /// assert_eq!(store.actions_processed(), 2);
+    /// ```
+    pub fn set_error_action(&mut self, mapper: Option<crate::ErrorActionMapper<Action>>)
+    where
+        Action: EnablingCondition<State>,
+    {
+        self.error_actions.mapper = mapper.map(|mapper| (mapper, Self::dispatch::<Action> as _));
+    }
+
+    /// Make the store refuse any further work: from now on,
+    /// [Store::dispatch] rejects every action. State can still be read.
+    ///
+    /// Meant for when state can't be trusted anymore. If already
+    /// poisoned, the first reason is kept.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, PoisonReason, ReducerPanicPolicy, Store};
+    ///
+    /// struct Add(u32);
+    /// impl EnablingCondition<u32> for Add {}
+    ///
+    /// fn reducer(state: &mut u32, action: &ActionWithMeta<Add>) {
+    ///     *state = state.checked_add(action.action.0).expect("overflow");
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, 0);
+    /// assert!(store.dispatch(Add(1)));
+    /// store.poison(PoisonReason::Manual("disk full".into()));
+    /// store.poison(PoisonReason::Manual("ignored".into()));
+    /// assert!(!store.dispatch(Add(1)));
+    /// assert_eq!(*store.state(), 1);
+    /// assert_eq!(store.poison_reason().unwrap().to_string(), "disk full");
+    ///
+    /// // Poisoned automatically by a caught reducer panic.
+    /// let mut store = Store::simple(reducer, |_, _| {}, u32::MAX);
+    /// store.set_reducer_panic_policy(ReducerPanicPolicy::SkipAction);
+    /// store.set_poison_on_panic(true);
+    /// assert!(store.dispatch(Add(1)));
+    /// assert!(store.is_poisoned());
+    /// assert!(!store.dispatch(Add(0)));
+    /// match store.poison_reason() {
+    ///     Some(PoisonReason::ReducerPanic { message, .. }) => {
+    ///         assert_eq!(message.as_deref(), Some("overflow"));
+    ///     }
+    ///     reason => panic!("unexpected reason: {:?}", reason),
+    /// }
+    /// ```
+    pub fn poison(&mut self, reason: crate::PoisonReason) {
+        if self.poisoned.is_none() {
+            self.poisoned = Some(reason);
+            self.update_subsystems();
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.is_some()
+    }
+
+    /// Why the store got poisoned, `None` if it isn't.
+    pub fn poison_reason(&self) -> Option<&crate::PoisonReason> {
+        self.poisoned.as_ref()
+    }
+
+    /// Which optional subsystems are currently enabled or attached.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, BoundedRecorder, Capabilities, EnablingCondition, Store};
+    ///
+    /// #[derive(Clone)]
+    /// enum Action {
+    ///     Process,
+    ///     Summary(String),
+    /// }
+    /// impl EnablingCondition<Vec<String>> for Action {}
+    ///
+    /// fn reducer(state: &mut Vec<String>, action: &ActionWithMeta<Action>) {
+    ///     match &action.action {
+    ///         Action::Process => state.push("processed".to_owned()),
+    ///         Action::Summary(summary) => state.push(summary.clone()),
+    ///     }
+    /// }
+    ///
+    /// /// Summaries are only worth building if someone can see them.
+    /// fn effects(store: &mut Store<Vec<String>, (), Action>, action: &ActionWithMeta<Action>) {
+    ///     let observed = Capabilities::RECORDER | Capabilities::DEVTOOLS;
+    ///     if let Action::Process = action.action {
+    ///         if store.capabilities().intersects(observed) {
+    ///             let summary = format!("{} processed", store.state().len());
+    ///             store.dispatch(Action::Summary(summary));
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, effects, vec![]);
+    /// assert_eq!(store.capabilities(), Capabilities::NONE);
+    /// store.dispatch(Action::Process);
+    /// assert_eq!(store.state(), &["processed"]);
+    ///
+    /// store.set_recorder(Some(Box::new(BoundedRecorder::new(16))));
+    /// store.enable_state_history(8);
+    /// let capabilities = store.capabilities();
+    /// assert!(capabilities.contains(Capabilities::RECORDER | Capabilities::STATE_HISTORY));
+    /// assert!(!capabilities.contains(Capabilities::DEVTOOLS));
+    /// store.dispatch(Action::Process);
+    /// assert_eq!(store.state(), &["processed", "processed", "2 processed"]);
+    ///
+    /// store.pause();
+    /// assert!(store.capabilities().contains(Capabilities::PAUSED));
+    /// store.resume();
+    /// store.set_recorder(None);
+    /// store.disable_state_history();
+    /// assert_eq!(store.capabilities(), Capabilities::NONE);
+    ///
+    /// store.poison(redux_rs::PoisonReason::Manual("maintenance".to_owned()));
+    /// assert!(format!("{:?}", store).contains("capabilities: POISONED"));
+    /// ```
+    #[inline(always)]
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    /// Pause the store (see [Store::pause]) as soon as `breakpoint` is
+    /// hit, calling the hook set with [Store::set_breakpoint_hook].
+    ///
+    /// Breakpoints are checked right after the reducer, so the state
+    /// already reflects the action which hit it. Effects of that action
+    /// still run, but actions they dispatch get queued.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, Breakpoint, BreakpointId, EnablingCondition, Store};
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// struct Add(u32);
+    /// impl EnablingCondition<u32> for Add {}
+    ///
+    /// fn reducer(state: &mut u32, action: &ActionWithMeta<Add>) {
+    ///     *state += action.action.0;
+    /// }
+    ///
+    /// static HITS: AtomicUsize = AtomicUsize::new(0);
+    ///
+    /// fn on_breakpoint(store: &Store<u32, (), Add>, _: BreakpointId, _: &ActionWithMeta<Add>) {
+    ///     assert!(store.is_paused());
+    ///     HITS.fetch_add(1, Ordering::SeqCst);
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, 0);
+    /// store.set_breakpoint_hook(Some(on_breakpoint));
+    ///
+    /// // Action predicate.
+    /// let big_add = store.add_breakpoint(Breakpoint::Action(|a| a.action.0 >= 100));
+    /// store.dispatch(Add(1));
+    /// store.dispatch(Add(100));
+    /// store.dispatch(Add(2));
+    /// assert_eq!(*store.state(), 101);
+    /// assert_eq!(store.paused_actions(), 1);
+    /// assert_eq!(HITS.load(Ordering::SeqCst), 1);
+    ///
+    /// assert!(store.remove_breakpoint(big_add));
+    /// assert!(!store.remove_breakpoint(big_add));
+    /// store.resume();
+    /// assert_eq!(*store.state(), 103);
+    ///
+    /// // One-shot state predicate.
+    /// store.add_breakpoint_once(Breakpoint::State(|state| *state > 110));
+    /// for _ in 0..10 {
+    ///     store.dispatch(Add(5));
+    /// }
+    /// assert_eq!(*store.state(), 113);
+    /// assert_eq!(store.paused_actions(), 8);
+    /// store.resume();
+    /// assert_eq!(*store.state(), 153);
+    /// assert!(!store.is_paused());
+    /// assert_eq!(HITS.load(Ordering::SeqCst), 2);
+    /// ```
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint<State, Action>) -> BreakpointId {
+        let id = self.breakpoints.add(breakpoint, false);
+        self.update_subsystems();
+        id
+    }
+
+    /// Same as [Store::add_breakpoint], but the breakpoint gets
+    /// removed once hit.
+    pub fn add_breakpoint_once(&mut self, breakpoint: Breakpoint<State, Action>) -> BreakpointId {
+        let id = self.breakpoints.add(breakpoint, true);
+        self.update_subsystems();
+        id
+    }
+
+    /// Returns `false` if there was no such breakpoint, e.g. because
+    /// it was one-shot and already got hit.
+    pub fn remove_breakpoint(&mut self, id: BreakpointId) -> bool {
+        let removed = self.breakpoints.remove(id);
+        self.update_subsystems();
+        removed
+    }
+
+    /// Set (or clear) hook called every time a breakpoint is hit.
+    pub fn set_breakpoint_hook(&mut self, hook: Option<BreakpointHook<State, Service, Action>>) {
+        self.breakpoints.hook = hook;
+    }
+
+    /// Set (or clear) where to write a [crate::BreakpointDump] as a
+    /// line of JSON, every time a breakpoint is hit. Written before
+    /// the breakpoint hook is called.
+    ///
+    /// Recent actions are only included if there is a recorder which
+    /// keeps them, see [Store::set_recorder].
+    #[cfg(feature = "breakpoint_dump")]
+    pub fn set_breakpoint_dump(&mut self, sink: Option<Box<dyn std::io::Write + Send>>)
+    where
+        State: serde::Serialize,
+        Action: serde::Serialize,
+    {
+        self.breakpoints.dump = sink.map(crate::breakpoint::DumpWriter::new);
+    }
+
+    /// Stop accepting actions, handling actions queued while paused
+    /// (see [Store::pause]) according to `policy`. From now on,
+    /// [Store::dispatch] rejects every action.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, ShutdownPolicy, Store};
+    ///
+    /// #[derive(Debug, Clone, PartialEq)]
+    /// struct Add(u32);
+    /// impl EnablingCondition<u32> for Add {}
+    ///
+    /// fn reducer(state: &mut u32, action: &ActionWithMeta<Add>) {
+    ///     *state += action.action.0;
+    /// }
+    ///
+    /// fn effects(store: &mut Store<u32, (), Add>, action: &ActionWithMeta<Add>) {
+    ///     if action.action.0 == 1 {
+    ///         store.dispatch(Add(10));
+    ///     }
+    /// }
+    ///
+    /// // Drain.
+    /// let mut store = Store::simple(reducer, effects, 0);
+    /// store.pause();
+    /// store.dispatch(Add(1));
+    /// store.dispatch(Add(2));
+    /// let report = store.shutdown(ShutdownPolicy::DrainQueue);
+    /// assert_eq!(report.processed, 3);
+    /// assert!(report.abandoned.is_empty());
+    /// assert_eq!(*store.state(), 13);
+    /// assert!(!store.dispatch(Add(1)));
+    ///
+    /// // Abandon, and hand the actions over to a new store.
+    /// let mut store = Store::simple(reducer, effects, 0);
+    /// store.pause();
+    /// store.dispatch(Add(1));
+    /// store.dispatch(Add(2));
+    /// let report = store.shutdown(ShutdownPolicy::Abandon);
+    /// assert_eq!(report.processed, 0);
+    /// assert_eq!(report.abandoned.actions, [Add(1), Add(2)]);
+    /// assert_eq!(*store.state(), 0);
+    ///
+    /// let mut store = Store::simple(reducer, effects, 0);
+    /// store.restore_pending(report.abandoned);
+    /// assert_eq!(*store.state(), 13);
+    /// ```
+    pub fn shutdown(&mut self, policy: crate::ShutdownPolicy) -> crate::ShutdownReport<Action>
+    where
+        Action: EnablingCondition<State>,
+    {
+        let processed_before = self.actions_processed;
+        let mut abandoned = Vec::new();
+        match policy {
+            crate::ShutdownPolicy::DrainQueue => self.resume(),
+            crate::ShutdownPolicy::Abandon => {
+                if let Some(queue) = self.pause.unpause() {
+                    self.update_subsystems();
+                    self.update_pending();
+                    abandoned = queue.into_iter().map(|queued| queued.action).collect();
+                }
+            }
+        }
+        self.shut_down = true;
+
+        crate::ShutdownReport {
+            processed: self.actions_processed - processed_before,
+            abandoned: crate::PendingActions { actions: abandoned },
+        }
+    }
+
+    /// Dispatch actions left unprocessed by another store, see
+    /// [Store::shutdown].
+    pub fn restore_pending(&mut self, pending: crate::PendingActions<Action>)
+    where
+        Action: EnablingCondition<State>,
+    {
+        for action in pending.actions {
+            self.dispatch_from(action, Provenance::Replay);
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_shut_down(&self) -> bool {
+        self.shut_down
+    }
+
+    /// Id of the last dispatched action.
+    ///
+    /// Inside effects, it's the id of the action being handled or of
+    /// the last action it dispatched.
+    #[inline(always)]
+    pub fn last_action_id(&self) -> ActionId {
+        self.last_action_id
+    }
+
+    /// Number of actions currently being processed, i.e. how deeply
+    /// nested the current `dispatch` call is.
+    ///
+    /// Effects may use it for load shedding, e.g. to skip bookkeeping
+    /// actions while deep in a dispatch chain. Other pending work, actions
+    /// queued while paused and timers, is counted in
+    /// [crate::DispatchStats::paused_actions] and
+    /// [crate::DispatchStats::pending_timers], and exported as gauges by
+    /// `StoreMetrics`.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Store};
+    ///
+    /// struct Action(u32);
+    /// impl EnablingCondition<()> for Action {}
+    ///
+    /// fn reducer(_: &mut (), _: &ActionWithMeta<Action>) {}
+    ///
+    /// fn effects(store: &mut Store<(), (), Action>, action: &ActionWithMeta<Action>) {
+    ///     assert_eq!(store.dispatch_depth(), action.action.0 + 1);
+    ///     if action.action.0 < 3 {
+    ///         store.dispatch(Action(action.action.0 + 1));
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, effects, ());
+    /// assert_eq!(store.dispatch_depth(), 0);
+    /// store.dispatch(Action(0));
+    /// assert_eq!(store.dispatch_depth(), 0);
+    /// ```
+    #[inline(always)]
+    pub fn dispatch_depth(&self) -> u32 {
+        self.recursion_depth
+    }
+
+    /// Number of actions which went through the reducer.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Store};
+    ///
+    /// struct Action;
+    /// impl EnablingCondition<()> for Action {}
+    ///
+    /// let mut store = Store::simple(|_: &mut (), _: &ActionWithMeta<Action>| {}, |_, _| {}, ());
+    ///
+    /// let mut last_id = store.last_action_id();
+    /// for _ in 0..3 {
+    ///     store.dispatch(Action);
+    ///     assert!(store.last_action_id() > last_id);
+    ///     last_id = store.last_action_id();
+    /// }
+    /// assert_eq!(store.actions_processed(), 3);
+    /// ```
+    #[inline(always)]
+    pub fn actions_processed(&self) -> u64 {
+        self.actions_processed
+    }
+
+    /// Monotonic time passed since store creation, read from the
+    /// service's clock now.
+    ///
+    /// Clocks which advance on every read (e.g. closures stepping time)
+    /// advance here too, use [Store::last_action_uptime] to leave them
+    /// alone.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, FrozenTime, Instant, Store};
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// struct Action;
+    /// impl EnablingCondition<()> for Action {}
+    ///
+    /// let reducer = |_: &mut (), _: &ActionWithMeta<Action>| {};
+    /// let clock = FrozenTime::new(Instant::now());
+    /// let mut store = Store::new(reducer, |_, _| {}, clock, SystemTime::UNIX_EPOCH, ());
+    ///
+    /// store.service.advance(Duration::from_secs(1));
+    /// store.dispatch(Action);
+    /// store.service.advance(Duration::from_secs(2));
+    /// assert_eq!(store.uptime(), Duration::from_secs(3));
+    /// assert_eq!(store.last_action_uptime(), Duration::from_secs(1));
+    /// ```
+    pub fn uptime(&mut self) -> Duration {
+        let now = self.service.monotonic_time();
+        now.saturating_duration_since(self.initial_monotonic_time)
+    }
+
+    /// Monotonic time passed between store creation and the last
+    /// dispatched action.
+    ///
+    /// Doesn't read the clock, so it doesn't advance between dispatches.
+    #[inline(always)]
+    pub fn last_action_uptime(&self) -> Duration {
+        self.clock.now.duration_since(self.initial_monotonic_time)
+    }
+
+    /// Cap time which may pass between two consecutive actions, as far
+    /// as action ids are concerned. `None` (default) means no cap.
+    ///
+    /// Useful so that e.g. a suspended machine doesn't make the next
+    /// action id jump by days.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, ClockDiagnostics, EnablingCondition, Instant, Store};
+    /// use std::cell::Cell;
+    /// use std::rc::Rc;
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// struct Action;
+    /// impl EnablingCondition<()> for Action {}
+    ///
+    /// let reducer = |_: &mut (), _: &ActionWithMeta<Action>| {};
+    /// let start = Instant::now() + Duration::from_secs(60);
+    /// let now = Rc::new(Cell::new(start));
+    /// let clock = {
+    ///     let now = now.clone();
+    ///     move || now.get()
+    /// };
+    /// let mut store = Store::new(reducer, |_, _| {}, clock, SystemTime::UNIX_EPOCH, ());
+    /// store.set_max_time_step(Some(Duration::from_secs(1)));
+    ///
+    /// now.set(start - Duration::from_secs(10));
+    /// store.dispatch(Action);
+    /// let before_jump = store.last_action_id();
+    ///
+    /// now.set(start + Duration::from_secs(3 * 24 * 3600));
+    /// store.dispatch(Action);
+    /// assert_eq!(store.last_action_id().duration_since(before_jump), Duration::from_secs(1));
+    ///
+    /// assert_eq!(
+    ///     store.clock_diagnostics(),
+    ///     ClockDiagnostics { backwards: 1, capped: 1 }
+    /// );
+    /// ```
+    pub fn set_max_time_step(&mut self, max_time_step: Option<Duration>) {
+        self.clock.max_time_step = max_time_step;
+    }
+
+    /// Set how often the clock is sampled. Defaults to
+    /// [TimeGranularity::PerAction].
+    ///
+    /// Reading the clock has a cost, which matters when processing lots
+    /// of tiny actions.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Store, TimeGranularity};
+    ///
+    /// struct Action;
+    /// impl EnablingCondition<()> for Action {}
+    ///
+    /// let mut store = Store::simple(|_: &mut (), _: &ActionWithMeta<Action>| {}, |_, _| {}, ());
+    /// store.set_time_granularity(TimeGranularity::Every(16));
+    ///
+    /// let mut last_id = store.last_action_id();
+    /// for _ in 0..100 {
+    ///     store.dispatch(Action);
+    ///     assert!(store.last_action_id() > last_id);
+    ///     last_id = store.last_action_id();
+    /// }
+    /// ```
+    pub fn set_time_granularity(&mut self, granularity: TimeGranularity) {
+        self.clock.set_granularity(granularity);
+    }
+
+    /// Set (or clear) hook called on every [crate::ClockAnomaly].
+    pub fn set_clock_anomaly_hook(&mut self, hook: Option<ClockAnomalyHook>) {
+        self.clock.anomaly_hook = hook;
+    }
+
+    /// Counters of clock anomalies noticed so far.
+    #[inline(always)]
+    pub fn clock_diagnostics(&self) -> ClockDiagnostics {
+        self.clock.diagnostics
+    }
+
+    /// Monotonic time of the service's clock, in nanoseconds since the
+    /// store was created.
+    ///
+    /// Depends only on the clock, so it's the same for two stores on the
+    /// same (mock) clocks, whichever was created first.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, FrozenTime, Instant, Store};
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// struct Action;
+    /// impl EnablingCondition<()> for Action {}
+    ///
+    /// let reducer = |_: &mut (), _: &ActionWithMeta<Action>| {};
+    /// let new_store = |start| {
+    ///     Store::new(reducer, |_, _| {}, FrozenTime::new(start), SystemTime::UNIX_EPOCH, ())
+    /// };
+    ///
+    /// let now = Instant::now();
+    /// let mut late = new_store(now + Duration::from_secs(60));
+    /// let mut early = new_store(now);
+    /// late.service.advance(Duration::from_millis(5));
+    /// early.service.advance(Duration::from_millis(5));
+    /// assert_eq!(late.monotonic_nanos(), 5_000_000);
+    /// assert_eq!(early.monotonic_nanos(), 5_000_000);
+    /// ```
+    pub fn monotonic_nanos(&mut self) -> u64 {
+        let now = self.service.monotonic_time();
+        now.saturating_duration_since(self.initial_monotonic_time)
+            .as_nanos() as u64
+    }
+
+    /// Convert monotonic time to system clock in nanoseconds from epoch.
+    pub fn monotonic_to_time(&self, monotonic_time: Instant) -> u64 {
+        let time_passed = monotonic_time.saturating_duration_since(self.initial_monotonic_time);
+        self.initial_time_nanos + time_passed.as_nanos() as u64
+    }
+
+    /// Set (or clear with `None`) the dispatch interceptor.
+    ///
+    /// Interceptor is called for every enabled action, after it has been
+    /// assigned an id and before it reaches the reducer. It decides
+    /// whether action is processed, rejected or captured.
+    pub fn set_dispatch_interceptor(
+        &mut self,
+        interceptor: Option<DispatchInterceptor<State, Action>>,
+    ) {
+        self.dispatch_interceptor = interceptor.map(crate::interceptor::Interceptor::new);
+    }
+
+    /// Same as [Store::set_dispatch_interceptor], for an interceptor
+    /// called only for actions of the given `kinds`. Others proceed as
+    /// if there was no interceptor. Cleared with
+    /// `set_dispatch_interceptor(None)`.
+    ///
+    /// ```
+    /// use redux_rs::{ActionKind, ActionWithMeta, EnablingCondition, Interception, Store};
+    ///
+    /// #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    /// enum Kind {
+    ///     Peer,
+    ///     Block,
+    /// }
+    ///
+    /// impl From<Kind> for &'static str {
+    ///     fn from(kind: Kind) -> Self {
+    ///         match kind {
+    ///             Kind::Peer => "Peer",
+    ///             Kind::Block => "Block",
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// enum Action {
+    ///     PeerConnected,
+    ///     BlockApplied,
+    /// }
+    ///
+    /// impl ActionKind for Action {
+    ///     type Kind = Kind;
+    ///
+    ///     fn kind(&self) -> Kind {
+    ///         match self {
+    ///             Action::PeerConnected => Kind::Peer,
+    ///             Action::BlockApplied => Kind::Block,
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// impl EnablingCondition<u32> for Action {}
+    ///
+    /// fn reducer(state: &mut u32, _: &ActionWithMeta<Action>) {
+    ///     *state += 1;
+    /// }
+    ///
+    /// fn reject(_: &u32, _: &ActionWithMeta<Action>) -> Interception {
+    ///     Interception::Reject
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, 0);
+    /// store.set_dispatch_interceptor_filtered(&[Kind::Block], reject);
+    ///
+    /// assert!(!store.dispatch(Action::BlockApplied));
+    /// assert!(store.dispatch(Action::PeerConnected));
+    /// assert_eq!(*store.state(), 1);
+    /// ```
+    pub fn set_dispatch_interceptor_filtered(
+        &mut self,
+        kinds: &[Action::Kind],
+        interceptor: DispatchInterceptor<State, Action>,
+    ) where
+        Action: crate::ActionKind,
+        Action::Kind: Into<&'static str>,
+    {
+        let kinds = kinds.iter().map(|kind| (*kind).into()).collect();
+        self.dispatch_interceptor = Some(crate::interceptor::Interceptor::filtered(
+            interceptor,
+            crate::kind_name::<Action>,
+            kinds,
+        ));
+    }
+
+    /// Actions captured by the dispatch interceptor, oldest first.
+    #[inline(always)]
+    pub fn intercepted(&self) -> &[ActionWithMeta<Action>] {
+        &self.intercepted
+    }
+
+    /// Take actions captured by the dispatch interceptor, leaving
+    /// the buffer empty, without the space reserved with
+    /// [Store::reserve_intercepted].
+    pub fn take_intercepted(&mut self) -> Vec<ActionWithMeta<Action>> {
+        core::mem::take(&mut self.intercepted)
+    }
+
+    /// Set (or remove with `None`) the recorder.
+    ///
+    /// Recorder sees every action passed to the reducer, in processing order.
+    pub fn set_recorder(&mut self, mut recorder: Option<Box<dyn Recorder<Action> + Send>>) {
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.record_header(&self.recording_header());
+        }
+        self.recording.recorder = recorder;
+        self.update_subsystems();
+    }
+
+    /// Record only actions of the given modules, or of all actions with
+    /// `None`, the default. Actions of no module are recorded if
+    /// [crate::ModuleId::ROOT] is given. Recordings limited to some
+    /// modules are for analysis, they can't be replayed.
+    ///
+    /// ```
+    /// use redux_rs::{
+    ///     embed, lens, ActionWithMeta, BoundedRecorder, EnablingCondition, Module, ModuleId,
+    ///     ModuleRegistry, StoreBuilder,
+    /// };
+    ///
+    /// struct Peers;
+    ///
+    /// impl Module for Peers {
+    ///     type SubState = Vec<u16>;
+    ///     type SubAction = u16;
+    ///
+    ///     fn initial_state() -> Vec<u16> {
+    ///         vec![]
+    ///     }
+    ///
+    ///     fn reducer(state: &mut Vec<u16>, action: &ActionWithMeta<&u16>) {
+    ///         state.push(*action.action);
+    ///     }
+    /// }
+    ///
+    /// struct Blocks;
+    ///
+    /// impl Module for Blocks {
+    ///     type SubState = u32;
+    ///     type SubAction = u32;
+    ///
+    ///     fn initial_state() -> u32 {
+    ///         0
+    ///     }
+    ///
+    ///     fn reducer(state: &mut u32, action: &ActionWithMeta<&u32>) {
+    ///         *state = *action.action;
+    ///     }
+    /// }
+    ///
+    /// #[derive(Default)]
+    /// struct State {
+    ///     peers: Vec<u16>,
+    ///     level: u32,
+    /// }
+    ///
+    /// #[derive(Debug, Clone, PartialEq)]
+    /// enum Action {
+    ///     Peer(u16),
+    ///     Block(u32),
+    ///     Tick,
+    /// }
+    /// impl EnablingCondition<State> for Action {}
+    ///
+    /// let mut modules = ModuleRegistry::<State, (), Action>::new();
+    /// modules.register::<Peers>(lens!(peers), embed!(Action::Peer));
+    /// modules.register::<Blocks>(lens!(level), embed!(Action::Block));
+    ///
+    /// let mut store = StoreBuilder::new()
+    ///     .service(())
+    ///     .initial_state(State::default())
+    ///     .modules(modules)
+    ///     .recorder(Box::new(BoundedRecorder::new(16)))
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // Ids follow registration order.
+    /// let peers = store.module_id::<Peers>().unwrap();
+    /// let blocks = store.module_id::<Blocks>().unwrap();
+    /// let table: Vec<_> = store.module_table().iter().map(|module| module.id).collect();
+    /// assert_eq!(table, [ModuleId::ROOT, peers, blocks]);
+    /// assert_eq!(u16::from(blocks), 2);
+    /// assert_eq!(store.module_name(ModuleId::ROOT), Some("root"));
+    ///
+    /// store.dispatch(Action::Peer(9732));
+    /// store.dispatch(Action::Block(1));
+    /// store.dispatch(Action::Tick);
+    /// let modules: Vec<_> = store.recent_actions().map(|action| action.module).collect();
+    /// assert_eq!(modules, [peers, blocks, ModuleId::ROOT]);
+    ///
+    /// store.set_recorded_modules(Some(vec![blocks]));
+    /// store.dispatch(Action::Peer(9733));
+    /// store.dispatch(Action::Block(2));
+    /// store.dispatch(Action::Tick);
+    /// let recorded: Vec<_> = store.recent_actions().skip(3).map(|action| &action.action).collect();
+    /// assert_eq!(recorded, [&Action::Block(2)]);
+    /// assert_eq!(store.state().peers, [9732, 9733]);
+    /// ```
+    pub fn set_recorded_modules(&mut self, modules: Option<Vec<crate::ModuleId>>) {
+        self.recording.modules = modules;
+    }
+
+    /// How [crate::Lazy] payloads not evaluated by the time their action
+    /// is recorded are serialized, see [crate::LazyRecording]. Set it
+    /// before the recorder, so that [crate::RecordingHeader] flags the
+    /// recording if payloads are skipped.
+    #[cfg(feature = "std")]
+    pub fn set_lazy_recording(&mut self, policy: crate::LazyRecording) {
+        self.recording.lazy = policy;
+    }
+
+    /// Record the seed of the service's [crate::SeededRandomness] in
+    /// [crate::RecordingHeader::seed], so that replays can reconstruct
+    /// it. Call it before setting the recorder.
+    ///
+    /// Replaying checks the recorded seed against this store's, if both
+    /// have one, see [crate::ReplayError::SeedMismatch].
+    ///
+    /// ```
+    /// use redux_rs::{
+    ///     ActionWithMeta, BoundedRecorder, EnablingCondition, FrozenTime, HasService, Instant,
+    ///     RandomnessService, ReplayError, SeededRandomness, Store,
+    /// };
+    /// use std::time::SystemTime;
+    ///
+    /// struct Services {
+    ///     time: FrozenTime,
+    ///     rng: SeededRandomness,
+    /// }
+    ///
+    /// impl HasService<FrozenTime> for Services {
+    ///     fn service(&mut self) -> &mut FrozenTime {
+    ///         &mut self.time
+    ///     }
+    /// }
+    ///
+    /// impl HasService<SeededRandomness> for Services {
+    ///     fn service(&mut self) -> &mut SeededRandomness {
+    ///         &mut self.rng
+    ///     }
+    /// }
+    ///
+    /// redux_rs::forward_service!(TimeService for Services => FrozenTime);
+    ///
+    /// #[derive(Clone)]
+    /// struct Jitter(u64);
+    /// impl EnablingCondition<Vec<u64>> for Jitter {}
+    ///
+    /// fn reducer(state: &mut Vec<u64>, action: &ActionWithMeta<Jitter>) {
+    ///     state.push(action.action.0);
+    /// }
+    ///
+    /// let new_store = |seed| {
+    ///     let services = Services {
+    ///         time: FrozenTime::new(Instant::now()),
+    ///         rng: SeededRandomness::new(seed),
+    ///     };
+    ///     let mut store = Store::new(reducer, |_, _| {}, services, SystemTime::UNIX_EPOCH, vec![]);
+    ///     store.record_rng_seed();
+    ///     store
+    /// };
+    ///
+    /// let mut store = new_store(7);
+    /// store.set_recorder(Some(Box::new(BoundedRecorder::new(16))));
+    /// let jitter = store.service_as::<SeededRandomness>().next_u64_below(100);
+    /// store.dispatch(Jitter(jitter));
+    ///
+    /// let header = store.recording_header();
+    /// assert_eq!(header.seed, Some(7));
+    /// let recorded: Vec<_> = store.recent_actions().cloned().collect();
+    ///
+    /// // The seed is reconstructed from the header.
+    /// let mut replayed = new_store(header.seed.unwrap());
+    /// assert_eq!(replayed.replay(&header, &recorded), Ok(()));
+    /// assert_eq!(replayed.state(), store.state());
+    /// assert_eq!(replayed.service_as::<SeededRandomness>().next_u64_below(100), jitter);
+    ///
+    /// let mut other = new_store(8);
+    /// let error = other.replay(&header, &recorded).unwrap_err();
+    /// assert_eq!(error, ReplayError::SeedMismatch { recorded: 7, supplied: 8 });
+    /// ```
+    pub fn record_rng_seed(&mut self)
+    where
+        Service: crate::HasService<crate::SeededRandomness>,
+    {
+        self.rng_seed = Some(self.service_as::<crate::SeededRandomness>().seed());
+    }
+
+    /// Set (or remove with `None`) the log of service calls, which gets
+    /// told the id of the action being processed, so that calls made
+    /// by [crate::Recorded] services are keyed by it and mismatches
+    /// found by [crate::Replayed] services name it.
+    ///
+    /// Log is shared with the service, see [crate::Recorded].
+    #[cfg(feature = "std")]
+    pub fn set_service_call_log(&mut self, log: Option<crate::ServiceCallLog>) {
+        self.service_call_log = log;
+    }
+
+    /// Recently processed actions kept by the recorder, oldest first.
+    ///
+    /// Empty if there is no recorder.
+    pub fn recent_actions(&self) -> RecentActions<'_, Action> {
+        self.recording
+            .recorder
+            .as_ref()
+            .map(|recorder| recorder.recent_actions())
+            .unwrap_or_else(RecentActions::empty)
+    }
+
+    /// Call `listener` with the state and the action after every action
+    /// passes through the reducer, before effects.
+    ///
+    /// Listeners are called in the order they subscribed, a listener
+    /// which got unsubscribed doesn't change the order of the rest. A
+    /// listener panicking doesn't stop the dispatch nor the listeners
+    /// after it: with `std`, panic is caught, the listener unsubscribed
+    /// and reported as [crate::StoreError::ListenerPanic], see
+    /// [Store::set_error_action].
+    ///
+    /// Listeners aren't cloned with the store.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Store, StoreError};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum Action {
+    ///     Add(u32),
+    ///     ListenerPanicked(u64),
+    /// }
+    ///
+    /// impl EnablingCondition<u32> for Action {}
+    ///
+    /// fn reducer(state: &mut u32, action: &ActionWithMeta<Action>) {
+    ///     if let Action::Add(n) = action.action {
+    ///         *state += n;
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, 0);
+    /// store.set_error_action(Some(|error| match error {
+    ///     StoreError::ListenerPanic { subscription, .. } => {
+    ///         Some(Action::ListenerPanicked(subscription.into()))
+    ///     }
+    ///     _ => None,
+    /// }));
+    ///
+    /// let seen = Arc::new(Mutex::new(vec![]));
+    /// for name in ["first", "middle", "last"] {
+    ///     let seen = seen.clone();
+    ///     let mut notified = 0;
+    ///     store.subscribe(move |state: &u32, _: &_| {
+    ///         notified += 1;
+    ///         if name == "middle" && notified == 2 {
+    ///             panic!("middle listener failed");
+    ///         }
+    ///         seen.lock().unwrap().push((name, *state));
+    ///     });
+    /// }
+    ///
+    /// store.dispatch(Action::Add(1));
+    /// store.dispatch(Action::Add(2));
+    /// store.dispatch(Action::Add(3));
+    ///
+    /// assert_eq!(
+    ///     *seen.lock().unwrap(),
+    ///     [
+    ///         ("first", 1),
+    ///         ("middle", 1),
+    ///         ("last", 1),
+    ///         // Middle one panicked, the rest were still notified.
+    ///         ("first", 3),
+    ///         ("last", 3),
+    ///         // Of the error action too.
+    ///         ("first", 3),
+    ///         ("last", 3),
+    ///         ("first", 6),
+    ///         ("last", 6),
+    ///     ]
+    /// );
+    /// assert_eq!(store.subscriptions(), 2);
+    /// assert_eq!(*store.state(), 6);
+    /// ```
+    pub fn subscribe<F>(&mut self, listener: F) -> crate::SubscriptionId
+    where
+        F: FnMut(&State, &ActionWithMeta<Action>) + Send + 'static,
+    {
+        let id = self.subscriptions.subscribe(listener);
+        self.update_subsystems();
+        id
+    }
+
+    /// Future completing once `predicate` holds for the state.
+    ///
+    /// Predicates of all the pending futures are evaluated together
+    /// after every action passes through the reducer, i.e. whenever the
+    /// state may have changed, and never otherwise. Satisfied futures
+    /// are woken and forgotten. Future completes right away if the
+    /// predicate already holds, a dropped one deregisters.
+    ///
+    /// Store isn't borrowed by the future, so the task driving the store
+    /// keeps dispatching while others wait.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Store};
+    /// use std::future::Future;
+    /// use std::pin::Pin;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    /// use std::task::{Context, Poll, Wake, Waker};
+    ///
+    /// struct PeerReady(usize);
+    /// impl EnablingCondition<Vec<bool>> for PeerReady {}
+    ///
+    /// fn reducer(ready: &mut Vec<bool>, action: &ActionWithMeta<PeerReady>) {
+    ///     ready[action.action.0] = true;
+    /// }
+    ///
+    /// struct CountWakes(AtomicUsize);
+    ///
+    /// impl Wake for CountWakes {
+    ///     fn wake(self: Arc<Self>) {
+    ///         self.0.fetch_add(1, Ordering::Relaxed);
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, vec![false; 100]);
+    /// store.dispatch(PeerReady(0));
+    ///
+    /// let wakes = Arc::new(CountWakes(AtomicUsize::new(0)));
+    /// let waker = Waker::from(wakes.clone());
+    /// let mut cx = Context::from_waker(&waker);
+    ///
+    /// let mut waiters: Vec<Pin<Box<dyn Future<Output = ()>>>> = (0..100)
+    ///     .map(|peer| Box::pin(store.notify_when(move |ready: &Vec<bool>| ready[peer])) as _)
+    ///     .collect();
+    /// let mut poll = |waiters: &mut Vec<Pin<Box<dyn Future<Output = ()>>>>| -> Vec<usize> {
+    ///     (0..waiters.len())
+    ///         .filter(|&peer| waiters[peer].as_mut().poll(&mut cx).is_ready())
+    ///         .collect()
+    /// };
+    ///
+    /// // Peer 0 was ready already.
+    /// assert_eq!(poll(&mut waiters), [0]);
+    /// assert_eq!(store.pending_conditions(), 99);
+    ///
+    /// for peer in [7, 42, 99] {
+    ///     store.dispatch(PeerReady(peer));
+    /// }
+    /// assert_eq!(wakes.0.load(Ordering::Relaxed), 3);
+    /// assert_eq!(poll(&mut waiters), [0, 7, 42, 99]);
+    /// assert_eq!(store.pending_conditions(), 96);
+    ///
+    /// // Dropping a waiter deregisters it.
+    /// waiters.truncate(50);
+    /// assert_eq!(store.pending_conditions(), 47);
+    /// store.dispatch(PeerReady(60));
+    /// assert_eq!(wakes.0.load(Ordering::Relaxed), 3);
+    /// ```
+    #[cfg(all(feature = "async", feature = "std"))]
+    pub fn notify_when<P>(&mut self, predicate: P) -> impl core::future::Future<Output = ()> + Send
+    where
+        P: Fn(&State) -> bool + Send + 'static,
+    {
+        if predicate(self.state.get()) {
+            return crate::condition::StateCondition::ready();
+        }
+        let conditions = self
+            .conditions
+            .get_or_insert_with(crate::condition::Conditions::new);
+        let condition = conditions.register(Box::new(predicate));
+        self.update_subsystems();
+        condition
+    }
+
+    /// Number of futures returned by [Store::notify_when] waiting for
+    /// their predicate.
+    #[cfg(all(feature = "async", feature = "std"))]
+    pub fn pending_conditions(&self) -> usize {
+        self.conditions
+            .as_ref()
+            .map_or(0, |conditions| conditions.len())
+    }
+
+    /// Same as [Store::subscribe], for a listener notified only of
+    /// actions of the given `kinds`.
+    ///
+    /// Filtered listeners are indexed by kind, so an action only walks
+    /// the listeners of its kind and the unfiltered ones, which keeps
+    /// many narrow listeners cheap. Order of notification is still the
+    /// order of subscription. The dispatch interceptor is filtered the
+    /// same way with [Store::set_dispatch_interceptor_filtered].
+    ///
+    /// ```
+    /// use redux_rs::{ActionKind, ActionWithMeta, EnablingCondition, Store};
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+    /// enum Kind {
+    ///     Peer,
+    ///     Block,
+    ///     Mempool,
+    /// }
+    ///
+    /// impl From<Kind> for &'static str {
+    ///     fn from(kind: Kind) -> Self {
+    ///         match kind {
+    ///             Kind::Peer => "Peer",
+    ///             Kind::Block => "Block",
+    ///             Kind::Mempool => "Mempool",
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// #[derive(Debug)]
+    /// enum Action {
+    ///     PeerConnected,
+    ///     BlockApplied,
+    ///     OperationAdded,
+    /// }
+    ///
+    /// impl ActionKind for Action {
+    ///     type Kind = Kind;
+    ///
+    ///     fn kind(&self) -> Kind {
+    ///         match self {
+    ///             Action::PeerConnected => Kind::Peer,
+    ///             Action::BlockApplied => Kind::Block,
+    ///             Action::OperationAdded => Kind::Mempool,
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// impl EnablingCondition<()> for Action {}
+    ///
+    /// let mut store = Store::simple(|_, _| {}, |_, _| {}, ());
+    ///
+    /// let seen = Arc::new(Mutex::new(vec![]));
+    /// let listener = |name: &'static str| {
+    ///     let seen = seen.clone();
+    ///     move |_: &(), _: &ActionWithMeta<Action>| seen.lock().unwrap().push(name)
+    /// };
+    /// store.subscribe_filtered(&[Kind::Block], listener("blocks"));
+    /// store.subscribe(listener("all"));
+    /// store.subscribe_filtered(&[Kind::Block, Kind::Mempool], listener("chain"));
+    /// let peers = store.subscribe_filtered(&[Kind::Peer], listener("peers"));
+    ///
+    /// store.dispatch(Action::BlockApplied);
+    /// store.dispatch(Action::OperationAdded);
+    /// store.unsubscribe(peers);
+    /// store.dispatch(Action::PeerConnected);
+    ///
+    /// assert_eq!(
+    ///     *seen.lock().unwrap(),
+    ///     ["blocks", "all", "chain", "all", "chain", "all"]
+    /// );
+    /// ```
+    pub fn subscribe_filtered<F>(
+        &mut self,
+        kinds: &[Action::Kind],
+        listener: F,
+    ) -> crate::SubscriptionId
+    where
+        Action: crate::ActionKind,
+        Action::Kind: Into<&'static str>,
+        F: FnMut(&State, &ActionWithMeta<Action>) + Send + 'static,
+    {
+        let kinds = kinds.iter().map(|kind| (*kind).into()).collect();
+        let id = self
+            .subscriptions
+            .subscribe_filtered(crate::kind_name::<Action>, kinds, listener);
+        self.update_subsystems();
+        id
+    }
+
+    /// Subscribe a listener to changes of a map selected out of the
+    /// state, notified with a [crate::MapDelta] after every action
+    /// which changed it.
+    ///
+    /// Map is compared with how it was after the previous action (or at
+    /// subscription), by walking both in key order, so listeners don't
+    /// have to diff it themselves. With [crate::DeltaTracking::Keys]
+    /// only the keys are remembered, and only added and removed entries
+    /// are reported.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, DeltaTracking, EnablingCondition, MapDelta, Store};
+    /// use std::collections::BTreeMap;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// #[derive(Default)]
+    /// struct State {
+    ///     peers: BTreeMap<u32, &'static str>,
+    /// }
+    ///
+    /// enum Action {
+    ///     Connected(u32, &'static str),
+    ///     Disconnected(u32),
+    ///     Ping,
+    /// }
+    ///
+    /// impl EnablingCondition<State> for Action {}
+    ///
+    /// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+    ///     match action.action {
+    ///         Action::Connected(id, version) => {
+    ///             state.peers.insert(id, version);
+    ///         }
+    ///         Action::Disconnected(id) => {
+    ///             state.peers.remove(&id);
+    ///         }
+    ///         Action::Ping => {}
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, State::default());
+    /// store.dispatch(Action::Connected(1, "v1"));
+    ///
+    /// let mut deltas = vec![];
+    /// for tracking in [DeltaTracking::Values, DeltaTracking::Keys] {
+    ///     let seen = Arc::new(Mutex::new(vec![]));
+    ///     let listener = {
+    ///         let seen = seen.clone();
+    ///         move |delta: &MapDelta<u32, &'static str>, _: &_| {
+    ///             seen.lock().unwrap().push(delta.clone())
+    ///         }
+    ///     };
+    ///     store.subscribe_map_delta(|state| &state.peers, tracking, listener);
+    ///     deltas.push(seen);
+    /// }
+    ///
+    /// store.dispatch(Action::Connected(3, "v1"));
+    /// store.dispatch(Action::Connected(2, "v1"));
+    /// store.dispatch(Action::Ping);
+    /// // Update.
+    /// store.dispatch(Action::Connected(1, "v2"));
+    /// // Same value.
+    /// store.dispatch(Action::Connected(1, "v2"));
+    /// store.dispatch(Action::Disconnected(3));
+    /// store.dispatch(Action::Disconnected(3));
+    ///
+    /// let delta = |added: &[(u32, &'static str)], removed: &[u32], changed: &[(u32, &'static str)]| {
+    ///     MapDelta {
+    ///         added: added.to_vec(),
+    ///         removed: removed.to_vec(),
+    ///         changed: changed.to_vec(),
+    ///     }
+    /// };
+    /// assert_eq!(
+    ///     *deltas[0].lock().unwrap(),
+    ///     [
+    ///         delta(&[(3, "v1")], &[], &[]),
+    ///         delta(&[(2, "v1")], &[], &[]),
+    ///         delta(&[], &[], &[(1, "v2")]),
+    ///         delta(&[], &[3], &[]),
+    ///     ]
+    /// );
+    /// // Update isn't seen without values.
+    /// assert_eq!(
+    ///     *deltas[1].lock().unwrap(),
+    ///     [
+    ///         delta(&[(3, "v1")], &[], &[]),
+    ///         delta(&[(2, "v1")], &[], &[]),
+    ///         delta(&[], &[3], &[]),
+    ///     ]
+    /// );
+    /// ```
+    pub fn subscribe_map_delta<K, V, F>(
+        &mut self,
+        selector: crate::MapSelector<State, K, V>,
+        tracking: crate::DeltaTracking,
+        mut listener: F,
+    ) -> crate::SubscriptionId
+    where
+        State: 'static,
+        K: Ord + Clone + Send + 'static,
+        V: PartialEq + Clone + Send + 'static,
+        F: FnMut(&crate::MapDelta<K, V>, &ActionWithMeta<Action>) + Send + 'static,
+    {
+        let mut tracker = crate::map_delta::MapTracker::new(selector(self.state()), tracking);
+        self.subscribe(move |state, action| {
+            if let Some(delta) = tracker.update(selector(state)) {
+                listener(&delta, action);
+            }
+        })
+    }
+
+    /// Same as [Store::subscribe], for a listener which can fail.
+    ///
+    /// Every error is reported as [crate::StoreError::ListenerError].
+    /// The listener stays subscribed until it returns more than
+    /// `max_errors` errors in total.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Store, StoreError};
+    ///
+    /// #[derive(Default)]
+    /// struct State {
+    ///     sum: u32,
+    ///     failures: Vec<(String, bool)>,
+    /// }
+    ///
+    /// enum Action {
+    ///     Add(u32),
+    ///     ListenerFailed { error: String, unsubscribed: bool },
+    /// }
+    ///
+    /// impl EnablingCondition<State> for Action {}
+    ///
+    /// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+    ///     match &action.action {
+    ///         Action::Add(n) => state.sum += n,
+    ///         Action::ListenerFailed { error, unsubscribed } => {
+    ///             state.failures.push((error.clone(), *unsubscribed));
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, State::default());
+    /// store.set_error_action(Some(|error| match error {
+    ///     StoreError::ListenerError { error, unsubscribed, .. } => {
+    ///         Some(Action::ListenerFailed { error, unsubscribed })
+    ///     }
+    ///     _ => None,
+    /// }));
+    ///
+    /// // Fails after additions making the sum odd, tolerating one error.
+    /// store.subscribe_fallible(1, |state: &State, action: &ActionWithMeta<Action>| {
+    ///     match (&action.action, state.sum % 2) {
+    ///         (Action::Add(_), 1) => Err(state.sum),
+    ///         _ => Ok(()),
+    ///     }
+    /// });
+    ///
+    /// store.dispatch(Action::Add(1));
+    /// store.dispatch(Action::Add(1));
+    /// assert_eq!(store.subscriptions(), 1);
+    /// store.dispatch(Action::Add(1));
+    /// assert_eq!(store.subscriptions(), 0);
+    /// assert_eq!(
+    ///     store.state().failures,
+    ///     [("1".to_owned(), false), ("3".to_owned(), true)]
+    /// );
+    /// ```
+    pub fn subscribe_fallible<F, E>(
+        &mut self,
+        max_errors: u32,
+        listener: F,
+    ) -> crate::SubscriptionId
+    where
+        F: FnMut(&State, &ActionWithMeta<Action>) -> Result<(), E> + Send + 'static,
+        E: fmt::Debug,
+    {
+        let id = self.subscriptions.subscribe_fallible(max_errors, listener);
+        self.update_subsystems();
+        id
+    }
+
+    /// Returns `false` if the listener isn't subscribed, e.g. because
+    /// it was unsubscribed after a panic.
+    pub fn unsubscribe(&mut self, id: crate::SubscriptionId) -> bool {
+        let unsubscribed = self.subscriptions.unsubscribe(id);
+        self.update_subsystems();
+        unsubscribed
+    }
+
+    /// Number of subscribed listeners.
+    #[inline(always)]
+    pub fn subscriptions(&self) -> usize {
+        self.subscriptions.len()
+    }
+
+    /// Dispatch an Action.
+    ///
+    /// Returns `true` if the action was enabled, hence if it was dispatched
+    /// to reducer and then effects.
+    ///
+    /// If action is not enabled, we return false and do nothing.
+    ///
+    /// If dispatch interceptor is set, action might also be rejected
+    /// (returns `false`) or captured (returns `true`) by it, in which
+    /// case neither reducer nor effects are called.
+    ///
+    /// While detached by [Store::travel_to], poisoned by
+    /// [Store::poison] or after [Store::shutdown], every action is
+    /// rejected.
+    ///
+    /// While paused by [Store::pause], action is only queued, returning
+    /// `false` if the queue is full.
+    ///
+    /// Optional subsystems which aren't enabled cost a single flag check
+    /// per group, see `benches/dispatch.rs`.
+    pub fn dispatch<T>(&mut self, action: T) -> bool
+    where
+        T: Into<Action> + EnablingCondition<State>,
+    {
+        self.dispatch_with_outcome(action).is_accepted()
+    }
+
+    /// Same as [Store::dispatch], telling what happened to the action,
+    /// including the id it was given.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, DispatchOutcome, EnablingCondition, Store};
+    ///
+    /// struct Add(u32);
+    /// impl EnablingCondition<u32> for Add {
+    ///     fn is_enabled(&self, _: &u32) -> bool {
+    ///         self.0 > 0
+    ///     }
+    /// }
+    ///
+    /// fn reducer(state: &mut u32, action: &ActionWithMeta<Add>) {
+    ///     *state += action.action.0;
+    /// }
+    ///
+    /// fn effects(store: &mut Store<u32, (), Add>, action: &ActionWithMeta<Add>) {
+    ///     if action.action.0 == 1 {
+    ///         store.dispatch(Add(10));
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, effects, 0);
+    /// let id = store.dispatch_with_outcome(Add(1)).id().unwrap();
+    /// // Follow-up action got a later id.
+    /// assert!(store.last_action_id() > id);
+    ///
+    /// assert_eq!(store.dispatch_with_outcome(Add(0)), DispatchOutcome::Rejected);
+    /// store.pause();
+    /// assert_eq!(store.dispatch_with_outcome(Add(2)), DispatchOutcome::Queued);
+    /// ```
+    pub fn dispatch_with_outcome<T>(&mut self, action: T) -> DispatchOutcome
+    where
+        T: Into<Action> + EnablingCondition<State>,
+    {
+        self.dispatch_expiring(action, None, None)
+    }
+
+    /// Same as [Store::dispatch_with_outcome], tagging the action with
+    /// `provenance`, for the entry points which know where it came from.
+    pub(crate) fn dispatch_from<T>(&mut self, action: T, provenance: Provenance) -> DispatchOutcome
+    where
+        T: Into<Action> + EnablingCondition<State>,
+    {
+        self.dispatch_expiring(action, None, Some(provenance))
+    }
+
+    /// Same as [Store::dispatch], but if the action gets queued, it's
+    /// dropped instead of processed once `ttl` passes, e.g. so that a
+    /// stale user input isn't acted on after a long pause.
+    ///
+    /// Expired action is counted in [crate::KindStats::expired] and
+    /// reported as [crate::StoreError::Expired], see
+    /// [Store::set_error_action]. Enabling condition of the action is
+    /// checked only if it didn't expire.
+    ///
+    /// ```
+    /// use redux_rs::{
+    ///     ActionWithMeta, EnablingCondition, FrozenTime, Instant, Store, StoreError,
+    /// };
+    /// use std::time::{Duration, SystemTime};
+    ///
+    /// #[derive(Debug, Clone, PartialEq)]
+    /// enum Action {
+    ///     Slow,
+    ///     Click(u32),
+    ///     Expired(Duration),
+    /// }
+    ///
+    /// impl EnablingCondition<Vec<Action>> for Action {}
+    ///
+    /// fn reducer(state: &mut Vec<Action>, action: &ActionWithMeta<Action>) {
+    ///     state.push(action.action.clone());
+    /// }
+    ///
+    /// fn effects(
+    ///     store: &mut Store<Vec<Action>, FrozenTime, Action>,
+    ///     action: &ActionWithMeta<Action>,
+    /// ) {
+    ///     if let Action::Slow = action.action {
+    ///         store.service.advance(Duration::from_secs(10));
+    ///     }
+    /// }
+    ///
+    /// let clock = FrozenTime::new(Instant::now());
+    /// let mut store = Store::new(reducer, effects, clock, SystemTime::UNIX_EPOCH, vec![]);
+    /// store.set_error_action(Some(|error| match error {
+    ///     StoreError::Expired { late_by } => Some(Action::Expired(late_by)),
+    ///     _ => None,
+    /// }));
+    ///
+    /// store.pause();
+    /// store.dispatch(Action::Slow);
+    /// store.dispatch_with_ttl(Action::Click(1), Duration::from_secs(1));
+    /// store.dispatch_with_ttl(Action::Click(2), Duration::from_secs(20));
+    ///
+    /// // Processing the first action takes 10s, so the first click is
+    /// // stale by then.
+    /// store.resume();
+    /// assert_eq!(
+    ///     store.state(),
+    ///     &[
+    ///         Action::Slow,
+    ///         Action::Expired(Duration::from_secs(9)),
+    ///         Action::Click(2),
+    ///     ]
+    /// );
+    ///
+    /// // Not queued, so processed right away whatever the ttl.
+    /// assert!(store.dispatch_with_ttl(Action::Click(3), Duration::ZERO));
+    /// ```
+    pub fn dispatch_with_ttl<T>(&mut self, action: T, ttl: Duration) -> bool
+    where
+        T: Into<Action> + EnablingCondition<State>,
+    {
+        let expires_at = self.service.monotonic_time() + ttl;
+        self.dispatch_expiring(action, Some(expires_at), None)
+            .is_accepted()
+    }
+
+    fn dispatch_expiring<T>(
+        &mut self,
+        action: T,
+        expires_at: Option<Instant>,
+        provenance: Option<Provenance>,
+    ) -> DispatchOutcome
+    where
+        T: Into<Action> + EnablingCondition<State>,
+    {
+        let provenance = provenance.unwrap_or_else(|| self.implicit_provenance());
+
+        if let Some(reason) = self.unavailable_reason() {
+            return self.rejected::<T>(reason, None, None);
+        }
+
+        if self.pause.queues_dispatch() {
+            return self.enqueue_paused::<T>(action.into(), expires_at, provenance);
+        }
+
+        let cache = self.condition_cache.as_ref();
+        let (action, condition) = crate::condition_cache::convert(cache, action, self.state.get());
+        // Duplicates are rejected as such, whether enabled or not.
+        let seen = match self.check_duplicate(&action) {
+            Ok(seen) => seen,
+            Err(()) => return self.rejected::<T>("duplicate", None, Some(&action)),
+        };
+        if !condition.evaluate(self.condition_cache.as_mut(), &action, self.state.get()) {
+            return self.rejected::<T>("not enabled", None, Some(&action));
+        }
+
+        // Dropped actions neither get an id nor move the clock.
+        let sampled = self.sample_clock();
+        let now = sampled.unwrap_or(self.clock.now);
+        let recovered = match self.rate_limit(&action, now) {
+            Ok(recovered) => recovered,
+            Err(()) => return self.rejected::<T>("rate limited", None, Some(&action)),
+        };
+
+        let action = self.with_next_meta(action, provenance, sampled);
+
+        let action = match self.intercept::<T>(action) {
+            Ok(action) => action,
+            Err(outcome) => return outcome,
+        };
+
+        if let (Some(dedup), Some(seen)) = (self.dedup.as_mut(), seen) {
+            dedup.remember(seen);
+        }
+        self.recording.record(&action);
+        self.process::<T>(&action);
+
+        #[cfg(feature = "memory")]
+        if action.allocated_sampled {
+            self.check_memory(action.total_allocated);
+        }
+
+        if let Some(kind) = recovered {
+            self.dispatch_rate_limit_action(kind, crate::RateLimitChange::Recovered);
+        }
+
+        DispatchOutcome::Processed(action.id)
+    }
+
+    /// Why the store doesn't accept any actions right now, if it doesn't.
+    fn unavailable_reason(&self) -> Option<&'static str> {
+        if self.is_poisoned() {
+            Some("store poisoned")
+        } else if self.shut_down {
+            Some("store shut down")
+        } else if self.is_detached() {
+            Some("store detached by time travel")
+        } else {
+            None
+        }
+    }
+
+    /// Trace, log and count the rejected action. Its `id` is only known
+    /// once it got one, `action` is only counted if given.
+    fn rejected<T>(
+        &self,
+        #[cfg_attr(
+            not(any(feature = "tracing", feature = "log")),
+            allow(unused_variables)
+        )]
+        reason: &'static str,
+        #[cfg_attr(
+            not(any(feature = "tracing", feature = "log")),
+            allow(unused_variables)
+        )]
+        id: Option<ActionId>,
+        #[cfg_attr(not(feature = "prometheus"), allow(unused_variables))] action: Option<&Action>,
+    ) -> DispatchOutcome {
+        #[cfg(feature = "tracing")]
+        match id {
+            Some(id) => tracing::debug!(
+                kind = core::any::type_name::<T>(),
+                id = u64::from(id),
+                reason,
+                "action rejected"
+            ),
+            None => tracing::debug!(
+                kind = core::any::type_name::<T>(),
+                reason,
+                "action rejected"
+            ),
+        }
+        #[cfg(feature = "log")]
+        log::debug!(
+            target: crate::LOG_TARGET_DISPATCH,
+            "rejected {} at action {}: {}",
+            core::any::type_name::<T>(),
+            u64::from(id.unwrap_or(self.last_action_id)),
+            reason
+        );
+        #[cfg(feature = "prometheus")]
+        if let (Some((metrics, classifier)), Some(action)) = (&self.metrics, action) {
+            metrics.observe_rejected(classifier(action));
+        }
+        DispatchOutcome::Rejected
+    }
+
+    /// Assign the next id to the enabled action, `sampled` being the
+    /// clock sampled for it, see [Store::sample_clock].
+    fn with_next_meta(
+        &mut self,
+        action: Action,
+        provenance: Provenance,
+        sampled: Option<Instant>,
+    ) -> ActionWithMeta<Action> {
+        let time_passed = match sampled {
+            Some(sampled) => self.clock.advance(sampled, self.last_action_id),
+            None => Duration::ZERO,
+        };
+        let prev_action_id = self.last_action_id;
+        self.last_action_id = prev_action_id.next(time_passed.as_nanos() as u64);
+
+        #[cfg(feature = "memory")]
+        let (total_allocated, allocated_sampled) = self.allocated.sample(self.clock.now);
+
+        ActionWithMeta {
+            id: self.last_action_id,
+            depth: self.recursion_depth,
+            elapsed_since_prev: self.last_action_id.duration_since(prev_action_id),
+            provenance,
+            module: self.module_of(&action),
+            namespace: self.namespace(),
+            #[cfg(feature = "memory")]
+            total_allocated,
+            #[cfg(feature = "memory")]
+            allocated_sampled,
+
+            action,
+        }
+    }
+
+    /// Run the dispatch interceptor, if any. `Err` with the outcome if
+    /// the action isn't to be processed.
+    fn intercept<T>(
+        &mut self,
+        action: ActionWithMeta<Action>,
+    ) -> Result<ActionWithMeta<Action>, DispatchOutcome> {
+        let interceptor = match &self.dispatch_interceptor {
+            Some(interceptor) => interceptor,
+            None => return Ok(action),
+        };
+        match interceptor.intercept(self.state(), &action) {
+            Interception::Proceed => Ok(action),
+            Interception::Reject => Err(self.rejected::<T>(
+                "rejected by interceptor",
+                Some(action.id),
+                Some(&action.action),
+            )),
+            Interception::Capture => {
+                let id = action.id;
+                self.intercepted.push(action);
+                Err(DispatchOutcome::Captured(id))
+            }
+        }
+    }
+
+    /// Run reducer and effects of the action, with it as the current
+    /// one for nested dispatches, spans, panic context, service call log
+    /// and cascade statistics. Returns `false` if the reducer panicked
+    /// and the panic was caught.
+    #[inline(always)]
+    fn process<T>(&mut self, action: &ActionWithMeta<Action>) -> bool {
+        #[cfg(feature = "log")]
+        log::trace!(
+            target: crate::LOG_TARGET_DISPATCH,
+            "processing {} as action {} at depth {}",
+            core::any::type_name::<T>(),
+            u64::from(action.id),
+            action.depth
+        );
+
+        #[cfg(feature = "tracing")]
+        let _span = self.dispatch_span::<T>(action).entered();
+        let parent_action_id = self.current_action_id.replace(action.id);
+
+        #[cfg(feature = "panic_context")]
+        let _panic_context = self.panic_context.guard(action);
+
+        #[cfg(feature = "std")]
+        let parent_call_action_id = self
+            .service_call_log
+            .as_ref()
+            .map(|log| log.enter(Some(action.id)));
+
+        #[cfg(feature = "stats")]
+        self.stats.start_action(action);
+        self.update_pending();
+
+        self.recursion_depth += 1;
+
+        let reduced = self.dispatch_reducer_and_effects(action);
+
+        self.recursion_depth -= 1;
+
+        #[cfg(feature = "stats")]
+        self.stats.finish_action(action);
+
+        #[cfg(feature = "std")]
+        if let (Some(log), Some(parent)) = (&self.service_call_log, parent_call_action_id) {
+            log.enter(parent);
+        }
+
+        self.current_action_id = parent_action_id;
+        reduced
+    }
+
+    /// Dispatch `actions` as a single unit: either all of them are
+    /// applied, or none.
+    ///
+    /// The state is copied once for a rollback, then each action is
+    /// dispatched as usual, deduplicated, checked for being enabled in
+    /// the state left by the ones before it and rate limited, except
+    /// that its effects are deferred. If any action is dropped, its
+    /// reducer panics (and the panic is caught, see
+    /// [Store::set_reducer_panic_policy]) or the state violates the
+    /// safety condition, if enabled with [Store::enable_safety_check],
+    /// the copy is restored, along with the last action id, and the
+    /// error tells which action failed.
+    ///
+    /// Once all actions are applied, the transaction commits: they are
+    /// recorded and only then effects run for each, in order. Subsystems
+    /// run after the reducer, e.g. subscribers, see the actions as they
+    /// are applied, even if rolled back later. Dispatch interceptor and
+    /// per-action timings are bypassed.
+    ///
+    /// Transactions can't be nested, calling this from effects of the
+    /// transaction's actions returns [TransactionError::Nested].
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Store, TransactionError};
+    ///
+    /// #[derive(Debug, Clone, Default, PartialEq)]
+    /// struct State {
+    ///     free: Vec<u32>,
+    ///     assigned: Vec<(u32, &'static str)>,
+    ///     notified: Vec<&'static str>,
+    /// }
+    ///
+    /// #[derive(Debug)]
+    /// enum Action {
+    ///     Reserve(u32),
+    ///     Assign(u32, &'static str),
+    ///     Notified(&'static str),
+    /// }
+    ///
+    /// impl EnablingCondition<State> for Action {
+    ///     fn is_enabled(&self, state: &State) -> bool {
+    ///         match self {
+    ///             Action::Reserve(slot) => state.free.contains(slot),
+    ///             Action::Assign(slot, _) => state.assigned.iter().all(|(s, _)| s != slot),
+    ///             Action::Notified(_) => true,
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// fn reducer(state: &mut State, action: &ActionWithMeta<Action>) {
+    ///     match &action.action {
+    ///         Action::Reserve(slot) => state.free.retain(|s| s != slot),
+    ///         Action::Assign(slot, owner) => state.assigned.push((*slot, owner)),
+    ///         Action::Notified(owner) => state.notified.push(owner),
+    ///     }
+    /// }
+    ///
+    /// fn effects(store: &mut Store<State, (), Action>, action: &ActionWithMeta<Action>) {
+    ///     if let Action::Assign(slot, owner) = &action.action {
+    ///         // Transaction's reducers all ran before its effects.
+    ///         assert!(!store.state().free.contains(slot));
+    ///         assert_eq!(
+    ///             store.dispatch_transaction(vec![]),
+    ///             Err(TransactionError::Nested)
+    ///         );
+    ///         store.dispatch(Action::Notified(owner));
+    ///     }
+    /// }
+    ///
+    /// let initial = State {
+    ///     free: vec![1, 2],
+    ///     ..State::default()
+    /// };
+    /// let mut store = Store::simple(reducer, effects, initial.clone());
+    ///
+    /// // Commits.
+    /// store
+    ///     .dispatch_transaction(vec![Action::Reserve(1), Action::Assign(1, "alice")])
+    ///     .unwrap();
+    /// assert_eq!(store.state().assigned, [(1, "alice")]);
+    /// assert_eq!(store.state().notified, ["alice"]);
+    ///
+    /// // Slot 1 is no longer free, so the second action is rejected and
+    /// // reservation of slot 2 is rolled back.
+    /// let before = store.state().clone();
+    /// let last_id = store.last_action_id();
+    /// assert_eq!(
+    ///     store.dispatch_transaction(vec![
+    ///         Action::Reserve(2),
+    ///         Action::Reserve(1),
+    ///         Action::Assign(2, "bob"),
+    ///     ]),
+    ///     Err(TransactionError::NotEnabled { index: 1 })
+    /// );
+    /// assert_eq!(store.state(), &before);
+    /// // Effects saw nothing: no notification, nothing dispatched.
+    /// assert_eq!(store.state().notified, ["alice"]);
+    /// assert_eq!(store.actions_processed(), 3);
+    /// // Nor are ids of the rolled back actions taken.
+    /// assert_eq!(store.last_action_id(), last_id);
+    /// ```
+    ///
+    /// Safety condition violation rolls back as well:
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, SafetyCondition, Store, TransactionError};
+    ///
+    /// #[derive(Debug, Clone, PartialEq)]
+    /// struct Balance(i64);
+    ///
+    /// impl SafetyCondition for Balance {
+    ///     type Error = i64;
+    ///
+    ///     fn check_safety_condition(&self) -> Result<(), i64> {
+    ///         if self.0 < 0 {
+    ///             return Err(self.0);
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// struct Transfer(i64);
+    /// impl EnablingCondition<Balance> for Transfer {}
+    ///
+    /// fn reducer(state: &mut Balance, action: &ActionWithMeta<Transfer>) {
+    ///     state.0 += action.action.0;
+    /// }
+    ///
+    /// fn effects(_: &mut Store<Balance, (), Transfer>, _: &ActionWithMeta<Transfer>) {
+    ///     panic!("effects must not run on rollback");
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, effects, Balance(10));
+    /// store.enable_safety_check();
+    ///
+    /// assert_eq!(
+    ///     store.dispatch_transaction(vec![Transfer(-20), Transfer(15)]),
+    ///     Err(TransactionError::SafetyViolation {
+    ///         index: 0,
+    ///         error: "-10".to_owned()
+    ///     })
+    /// );
+    /// assert_eq!(store.state(), &Balance(10));
+    /// ```
+    ///
+    /// So does a caught reducer panic, even though the reducer already
+    /// changed the state:
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, ReducerPanicPolicy, Store, TransactionError};
+    ///
+    /// struct Push(u32);
+    /// impl EnablingCondition<Vec<u32>> for Push {}
+    ///
+    /// fn reducer(state: &mut Vec<u32>, action: &ActionWithMeta<Push>) {
+    ///     state.push(action.action.0);
+    ///     if action.action.0 == 13 {
+    ///         panic!("unlucky number");
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, |_, _| {}, vec![]);
+    /// store.set_reducer_panic_policy(ReducerPanicPolicy::SkipAction);
+    /// store.dispatch(Push(1));
+    /// let last_id = store.last_action_id();
+    ///
+    /// assert_eq!(
+    ///     store.dispatch_transaction(vec![Push(2), Push(13), Push(3)]),
+    ///     Err(TransactionError::ReducerPanicked { index: 1 })
+    /// );
+    /// assert_eq!(store.state(), &[1]);
+    /// assert_eq!(store.last_action_id(), last_id);
+    /// assert_eq!(store.caught_panics(), 1);
+    ///
+    /// store.dispatch_transaction(vec![Push(2), Push(3)]).unwrap();
+    /// assert_eq!(store.state(), &[1, 2, 3]);
+    /// ```
+    pub fn dispatch_transaction(
+        &mut self,
+        actions: Vec<Action>,
+    ) -> Result<(), crate::TransactionError>
+    where
+        State: Clone,
+        Action: EnablingCondition<State>,
+    {
+        if self.in_transaction {
+            return Err(crate::TransactionError::Nested);
+        }
+        if self.is_poisoned() || self.shut_down || self.is_detached() || self.is_paused() {
+            return Err(crate::TransactionError::Rejected);
+        }
+
+        let rollback = self.state.get().clone();
+        let (last_action_id, clock_now) = (self.last_action_id, self.clock.now);
+        let actions_processed = self.actions_processed;
+
+        self.in_transaction = true;
+        let mut staged = Vec::with_capacity(actions.len());
+        let mut failed = None;
+        for (index, action) in actions.into_iter().enumerate() {
+            match self.stage(action, index) {
+                Ok(action) => staged.push(action),
+                Err(error) => {
+                    failed = Some(error);
+                    break;
+                }
+            }
+        }
+        if let Some(error) = failed {
+            *self.state.get_mut() = rollback;
+            self.last_action_id = last_action_id;
+            self.clock.now = clock_now;
+            self.actions_processed = actions_processed;
+            self.in_transaction = false;
+            return Err(error);
+        }
+        drop(rollback);
+
+        for (action, seen, _) in staged.iter_mut() {
+            if let (Some(dedup), Some(seen)) = (self.dedup.as_mut(), seen.take()) {
+                dedup.remember(seen);
+            }
+            self.recording.record(action);
+        }
+        for (action, _, recovered) in &staged {
+            let parent_action_id = self.current_action_id.replace(action.id);
+            #[cfg(feature = "panic_context")]
+            let _panic_context = self.panic_context.guard(action);
+            self.recursion_depth += 1;
+            self.dispatch_effects(action);
+            self.recursion_depth -= 1;
+            self.current_action_id = parent_action_id;
+
+            if let Some(kind) = recovered {
+                self.dispatch_rate_limit_action(kind, crate::RateLimitChange::Recovered);
+            }
+        }
+        self.in_transaction = false;
+
+        Ok(())
+    }
+
+    /// Apply action at `index` of a transaction to the state, deferring
+    /// its effects. Returns it, its dedup key to remember and the kind
+    /// which recovered from rate limiting, if any, once committed.
+    fn stage(
+        &mut self,
+        action: Action,
+        index: usize,
+    ) -> Result<crate::transaction::Staged<Action>, crate::TransactionError>
+    where
+        Action: EnablingCondition<State>,
+    {
+        let provenance = self.implicit_provenance();
+        let cache = self.condition_cache.as_ref();
+        let (action, condition) = crate::condition_cache::convert(cache, action, self.state.get());
+        let seen = match self.check_duplicate(&action) {
+            Ok(seen) => seen,
+            Err(()) => {
+                self.rejected::<Action>("duplicate", None, Some(&action));
+                return Err(crate::TransactionError::Duplicate { index });
+            }
+        };
+        if !condition.evaluate(self.condition_cache.as_mut(), &action, self.state.get()) {
+            self.rejected::<Action>("not enabled", None, Some(&action));
+            return Err(crate::TransactionError::NotEnabled { index });
+        }
+        let sampled = self.sample_clock();
+        let now = sampled.unwrap_or(self.clock.now);
+        let recovered = match self.rate_limit(&action, now) {
+            Ok(recovered) => recovered,
+            Err(()) => {
+                self.rejected::<Action>("rate limited", None, Some(&action));
+                return Err(crate::TransactionError::RateLimited { index });
+            }
+        };
+
+        let action = self.with_next_meta(action, provenance, sampled);
+        self.defer_effects = true;
+        if !self.process::<Action>(&action) {
+            return Err(crate::TransactionError::ReducerPanicked { index });
+        }
+        if let Some(check) = self.safety_check {
+            if let Err(error) = check(self.state.get()) {
+                return Err(crate::TransactionError::SafetyViolation { index, error });
+            }
+        }
+        Ok((action, seen, recovered))
+    }
+
+    /// What dispatching `action` right now would do, without doing it:
+    /// whether it's enabled, the state it would produce and whether
+    /// that state would violate the safety condition, if enabled with
+    /// [Store::enable_safety_check].
+    ///
+    /// Reducer (and reducers of modules) run on a copy of the state,
+    /// with the id the action would get. Nothing else runs: effects,
+    /// recorder, subscribers and other subsystems don't see the action,
+    /// and the id isn't taken, so the store is left exactly as it was.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, BoundedRecorder, EnablingCondition, SafetyCondition, Store};
+    ///
+    /// #[derive(Debug, Clone, PartialEq)]
+    /// struct Balance(i64);
+    ///
+    /// impl SafetyCondition for Balance {
+    ///     type Error = i64;
+    ///
+    ///     fn check_safety_condition(&self) -> Result<(), i64> {
+    ///         if self.0 < 0 {
+    ///             return Err(self.0);
+    ///         }
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// #[derive(Clone)]
+    /// enum Action {
+    ///     Deposit(i64),
+    ///     Withdraw(i64),
+    /// }
+    ///
+    /// impl EnablingCondition<Balance> for Action {
+    ///     fn is_enabled(&self, _: &Balance) -> bool {
+    ///         !matches!(self, Action::Deposit(0))
+    ///     }
+    /// }
+    ///
+    /// fn reducer(state: &mut Balance, action: &ActionWithMeta<Action>) {
+    ///     match action.action {
+    ///         Action::Deposit(amount) => state.0 += amount,
+    ///         Action::Withdraw(amount) => state.0 -= amount,
+    ///     }
+    /// }
+    ///
+    /// fn effects(store: &mut Store<Balance, (), Action>, action: &ActionWithMeta<Action>) {
+    ///     if let Action::Deposit(10) = action.action {
+    ///         store.dispatch(Action::Deposit(1));
+    ///     }
+    /// }
+    ///
+    /// let mut store = Store::simple(reducer, effects, Balance(100));
+    /// store.set_recorder(Some(Box::new(BoundedRecorder::new(16))));
+    /// store.enable_safety_check();
+    /// store.dispatch(Action::Deposit(5));
+    ///
+    /// let id = store.last_action_id();
+    /// let result = store.dry_run(&Action::Deposit(10));
+    /// assert!(result.is_ok());
+    /// // Effects didn't run.
+    /// assert_eq!(result.state, Some(Balance(115)));
+    /// assert!(result.id > id);
+    ///
+    /// let overdraft = store.dry_run(&Action::Withdraw(200));
+    /// assert_eq!(overdraft.safety_violation.as_deref(), Some("-95"));
+    /// assert!(!store.dry_run(&Action::Deposit(0)).enabled);
+    /// let balance = store.dry_run_projected(&Action::Withdraw(5), |state| state.0);
+    /// assert_eq!(balance.state, Some(100));
+    ///
+    /// // Nothing happened.
+    /// assert_eq!(*store.state(), Balance(105));
+    /// assert_eq!(store.last_action_id(), id);
+    /// assert_eq!(store.recent_actions().count(), 1);
+    ///
+    /// // Same as dispatching for real.
+    /// let mut real = store.clone();
+    /// real.replace_effects(|_, _| {});
+    /// real.dispatch(Action::Withdraw(50));
+    /// assert_eq!(store.dry_run(&Action::Withdraw(50)).state.as_ref(), Some(real.state()));
+    /// ```
+    pub fn dry_run(&self, action: &Action) -> crate::DryRunResult<State>
+    where
+        State: Clone,
+        Action: Clone + EnablingCondition<State>,
+    {
+        self.dry_run_projected(action, State::clone)
+    }
+
+    /// Same as [Store::dry_run], keeping only `project`ion of the state
+    /// the action would produce.
+    ///
+    /// Whole state still gets copied for the reducer, but the copy is
+    /// dropped right away, so a dry run of an action touching a small
+    /// part of a large state doesn't hand the whole copy around.
+    pub fn dry_run_projected<T>(
+        &self,
+        action: &Action,
+        project: impl FnOnce(&State) -> T,
+    ) -> crate::DryRunResult<T>
+    where
+        State: Clone,
+        Action: Clone + EnablingCondition<State>,
+    {
+        let id = self.last_action_id.next(0);
+        let mut result = crate::DryRunResult {
+            id,
+            enabled: action.is_enabled(self.state.get()),
+            state: None,
+            safety_violation: None,
+        };
+        if !result.enabled {
+            return result;
+        }
+
+        let action = ActionWithMeta {
+            id,
+            depth: self.recursion_depth,
+            elapsed_since_prev: id.duration_since(self.last_action_id),
+            provenance: self.implicit_provenance(),
+            module: self.module_of(action),
+            namespace: self.namespace(),
+            #[cfg(feature = "memory")]
+            total_allocated: 0,
+            #[cfg(feature = "memory")]
+            allocated_sampled: false,
+
+            action: action.clone(),
+        };
+        let mut state = self.state.get().clone();
+        crate::reducer::call(self.reducer, &mut state, &action);
+        let modules = self.modules.as_ref().map(|modules| &modules.reducers);
+        crate::module::reduce(modules, &mut state, &action);
+        if let Some(check) = self.safety_check {
+            result.safety_violation = check(&state).err();
+        }
+        result.state = Some(project(&state));
+        result
+    }
+
+    #[cfg(feature = "tracing")]
+    fn dispatch_span<T>(&self, action: &ActionWithMeta<Action>) -> tracing::Span {
+        let span = tracing::debug_span!(
+            "dispatch",
+            kind = core::any::type_name::<T>(),
+            id = u64::from(action.id),
+            depth = action.depth,
+            parent_id = tracing::field::Empty,
+        );
+        if let Some(parent_id) = self.current_action_id {
+            span.record("parent_id", u64::from(parent_id));
+        }
+        span
+    }
+
+    /// Update pending work counts in statistics and metrics, if enabled,
+    /// see [crate::DispatchStats::paused_actions].
+    fn update_pending(&mut self) {
+        #[cfg(any(feature = "stats", feature = "prometheus"))]
+        if self.stats_enabled() || self.metrics_enabled() {
+            let (paused, timers) = (self.pause.pending(), self.timers.once.len());
+            #[cfg(feature = "stats")]
+            self.stats.set_pending(paused, timers);
+            #[cfg(feature = "prometheus")]
+            if let Some((metrics, _)) = &self.metrics {
+                metrics.set_pending(paused, timers);
+            }
+        }
+    }
+
+    /// Sample the clock for the next action, unless skipped according
+    /// to [TimeGranularity].
+    fn sample_clock(&mut self) -> Option<Instant> {
+        if !self.clock.should_sample(self.recursion_depth == 0) {
+            return None;
+        }
+        Some(self.service.monotonic_time())
+    }
+
+    #[cfg(feature = "memory")]
+    fn check_memory(&mut self, allocated: u64) {
+        let (report, hook) = match self.memory_alarm.as_mut() {
+            Some((detector, hook)) => (detector.observe(allocated), *hook),
+            None => return,
+        };
+        if let Some(report) = report {
+            hook(self, &report);
+        }
+    }
+
+    /// Recompute which optional subsystems are active. Must be called
+    /// whenever one is enabled or disabled.
+    fn update_subsystems(&mut self) {
+        self.subsystems.set(
+            Subsystems::TIMINGS,
+            self.stats_enabled() || self.metrics_enabled() || self.slow_action_watchdog.is_some(),
+        );
+        #[cfg(feature = "diff")]
+        let diff_enabled = self.differ.is_some();
+        #[cfg(not(feature = "diff"))]
+        let diff_enabled = false;
+        #[cfg(feature = "persistence")]
+        let persistence_enabled = self.persistence.is_some();
+        #[cfg(not(feature = "persistence"))]
+        let persistence_enabled = false;
+        #[cfg(feature = "audit")]
+        let audit_enabled = self.audit.is_some();
+        #[cfg(not(feature = "audit"))]
+        let audit_enabled = false;
+        #[cfg(all(feature = "async", feature = "std"))]
+        let conditions_enabled = self.conditions.is_some();
+        #[cfg(not(all(feature = "async", feature = "std")))]
+        let conditions_enabled = false;
+        self.subsystems.set(
+            Subsystems::AFTER_REDUCER,
+            self.time_travel.is_some()
+                || self.state_history.is_some()
+                || diff_enabled
+                || persistence_enabled
+                || self.safety_check.is_some()
+                || !self.breakpoints.is_empty()
+                || !self.subscriptions.is_empty()
+                || audit_enabled
+                || conditions_enabled,
+        );
+
+        let mut capabilities = match self.recording.recorder.as_ref() {
+            Some(recorder) => recorder.capabilities(),
+            None => Capabilities::NONE,
+        };
+        for (capability, enabled) in [
+            (Capabilities::STATS, self.stats_enabled()),
+            (Capabilities::METRICS, self.metrics_enabled()),
+            (Capabilities::TIME_TRAVEL, self.time_travel.is_some()),
+            (Capabilities::DETACHED, self.is_detached()),
+            (Capabilities::STATE_HISTORY, self.state_history.is_some()),
+            (Capabilities::SAFETY_CHECK, self.safety_check.is_some()),
+            (Capabilities::LISTENERS, !self.subscriptions.is_empty()),
+            (Capabilities::PAUSED, self.is_paused()),
+            (Capabilities::POISONED, self.is_poisoned()),
+        ] {
+            capabilities.set(capability, enabled);
+        }
+        self.capabilities = capabilities;
+        #[cfg(feature = "stats")]
+        self.stats.set_capabilities(capabilities);
+    }
+
+    #[cfg(feature = "stats")]
+    #[inline(always)]
+    fn stats_enabled(&self) -> bool {
+        self.stats.is_enabled()
+    }
+
+    #[cfg(not(feature = "stats"))]
+    #[inline(always)]
+    fn stats_enabled(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "prometheus")]
+    #[inline(always)]
+    fn metrics_enabled(&self) -> bool {
+        self.metrics.is_some()
+    }
+
+    #[cfg(not(feature = "prometheus"))]
+    #[inline(always)]
+    fn metrics_enabled(&self) -> bool {
+        false
+    }
+
+    /// Returns `false` if the reducer panicked and the panic was caught.
+    fn dispatch_reducer_and_effects(&mut self, action: &ActionWithMeta<Action>) -> bool {
+        // Only for this action, not the ones dispatched meanwhile.
+        if core::mem::take(&mut self.defer_effects) {
+            return self.dispatch_reducer(action);
+        }
+        if !self.subsystems.contains(Subsystems::TIMINGS) {
+            if !self.dispatch_reducer(action) {
+                return false;
+            }
+            self.dispatch_effects(action);
+            return true;
+        }
+
+        #[cfg(feature = "memory")]
+        let allocated_before = crate::memory::total_allocated();
+        let start = self.service.monotonic_time();
+        if !self.dispatch_reducer(action) {
+            return false;
+        }
+        let reducer_end = self.service.monotonic_time();
+        self.dispatch_effects(action);
+        let effects_end = self.service.monotonic_time();
+
+        let timings = DispatchTimings {
+            reducer: reducer_end.saturating_duration_since(start),
+            effects: effects_end.saturating_duration_since(reducer_end),
+            #[cfg(feature = "memory")]
+            allocated: crate::memory::total_allocated() as i64 - allocated_before as i64,
+        };
+
+        #[cfg(feature = "stats")]
+        if let Some(kind) = self.stats.kind(&action.action) {
+            let module = self.module_name(action.module).unwrap_or("root");
+            let scratch = self
+                .recording
+                .recorder
+                .as_ref()
+                .and_then(|r| r.scratch_stats());
+            self.stats.record(kind, module, action, &timings, scratch);
+        }
+
+        #[cfg(feature = "prometheus")]
+        if let Some((metrics, classifier)) = &self.metrics {
+            metrics.observe_processed(classifier(&action.action), &timings);
+        }
+
+        if let Some(watchdog) = &self.slow_action_watchdog {
+            if timings.total() > watchdog.threshold {
+                (watchdog.hook)(self, action, &timings);
+            }
+        }
+        true
+    }
+
+    /// Runs the reducer.
+    ///
+    /// Returns `false` if the reducer panicked and the panic was caught,
+    /// in which case effects must be skipped.
+    #[inline(always)]
+    fn dispatch_reducer(&mut self, action_with_id: &ActionWithMeta<Action>) -> bool {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("reducer").entered();
+        #[cfg(feature = "determinism-check")]
+        let pre_state = match self.determinism_check.as_mut() {
+            Some(check) => check.snapshot(self.state.get()),
+            None => None,
+        };
+        #[cfg(feature = "audit")]
+        if let Some(audit) = self.audit.as_ref() {
+            audit.reset(self.state.get_mut());
+        }
+        if !self.apply_reducer(action_with_id) {
+            return false;
+        }
+        #[cfg(feature = "determinism-check")]
+        if let (Some(mut replayed), Some(check)) = (pre_state, self.determinism_check.as_ref()) {
+            crate::reducer::call(self.reducer, &mut replayed, action_with_id);
+            let modules = self.modules.as_ref().map(|modules| &modules.reducers);
+            crate::module::reduce(modules, &mut replayed, action_with_id);
+            check.compare(action_with_id.id, self.state.get(), &replayed);
+        }
+        if self.subsystems.contains(Subsystems::AFTER_REDUCER) {
+            self.after_reducer(action_with_id);
+        }
+        true
+    }
+
+    /// Runs the reducer and module reducers alone, without any of the
+    /// optional subsystems. Returns `false` if the reducer panicked and
+    /// the panic was caught.
+    #[inline(always)]
+    fn apply_reducer(&mut self, action_with_id: &ActionWithMeta<Action>) -> bool {
+        #[cfg(feature = "std")]
+        if !self.run_reducer(action_with_id) {
+            return false;
+        }
+        #[cfg(not(feature = "std"))]
+        {
+            crate::reducer::call(self.reducer, self.state.get_mut(), action_with_id);
+            self.reduce_modules(action_with_id);
+        }
+        self.actions_processed += 1;
+        true
+    }
+
+    /// Runs optional subsystems interested in the state right after
+    /// the reducer.
+    fn after_reducer(&mut self, action_with_id: &ActionWithMeta<Action>) {
+        #[cfg(feature = "audit")]
+        if let Some(audit) = self.audit.as_mut() {
+            audit.record(self.state.get_mut(), &action_with_id.action);
+        }
+        if let Some(time_travel) = self.time_travel.as_mut() {
+            time_travel.record(action_with_id, self.state.get());
+        }
+        if let Some(state_history) = self.state_history.as_mut() {
+            state_history.record(action_with_id.id, self.state.get());
+        }
+        #[cfg(feature = "diff")]
+        if let Some(differ) = self.differ.as_mut() {
+            differ.update(self.state.get());
+        }
+        if let Some(check) = self.safety_check {
+            if let Err(error) = check(self.state.get()) {
+                #[cfg(feature = "log")]
+                log::warn!(
+                    target: crate::LOG_TARGET_SAFETY,
+                    "safety condition violated after action {}: {}",
+                    u64::from(action_with_id.id),
+                    error
+                );
+                self.report_error(crate::StoreError::SafetyViolation {
+                    action_id: action_with_id.id,
+                    error,
+                });
+            }
+        }
+        #[cfg(feature = "persistence")]
+        if let Some(persistence) = self.persistence.as_mut() {
+            persistence.after_action(self.state.get(), self.clock.now, action_with_id.id);
+        }
+        if !self.breakpoints.is_empty() {
+            self.check_breakpoints(action_with_id);
+        }
+        if !self.subscriptions.is_empty() {
+            self.notify_subscribers(action_with_id);
+        }
+        #[cfg(all(feature = "async", feature = "std"))]
+        if let Some(conditions) = self.conditions.as_ref() {
+            conditions.check(self.state.get());
+        }
+    }
+
+    fn notify_subscribers(&mut self, action_with_id: &ActionWithMeta<Action>) {
+        let failures = self.subscriptions.notify(self.state.get(), action_with_id);
+        if failures.is_empty() {
+            return;
+        }
+        self.update_subsystems();
+        for error in failures {
+            #[cfg(feature = "log")]
+            log::warn!(target: crate::LOG_TARGET_DISPATCH, "{}", error);
+            self.report_error(error);
+        }
+    }
+
+    /// Runs the reducer according to the reducer panic policy.
+    /// Returns `false` if it panicked.
+    #[cfg(feature = "std")]
+    #[inline(always)]
+    fn run_reducer(&mut self, action: &ActionWithMeta<Action>) -> bool {
+        let snapshot = match self.panics.reducer_policy {
+            crate::ReducerPanicPolicy::Propagate => {
+                crate::reducer::call(self.reducer, self.state.get_mut(), action);
+                self.reduce_modules(action);
+                return true;
+            }
+            crate::ReducerPanicPolicy::SkipAction => None,
+            crate::ReducerPanicPolicy::RestoreSnapshot(snapshot) => {
+                Some(snapshot(self.state.get()))
+            }
+        };
+
+        let reducer = self.reducer;
+        let modules = self.modules.as_ref().map(|modules| &modules.reducers);
+        let state = self.state.get_mut();
+        let payload = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            crate::reducer::call(reducer, state, action);
+            crate::module::reduce(modules, state, action);
+        })) {
+            Ok(()) => return true,
+            Err(payload) => payload,
+        };
+
+        if let Some(snapshot) = snapshot {
+            *self.state.get_mut() = snapshot;
+        }
+        self.panic_caught(crate::CaughtPanic {
+            action_id: action.id,
+            source: crate::PanicSource::Reducer,
+            payload,
+        });
+        false
+    }
+
+    /// Runs the effects, catching their panics.
+    #[cfg(feature = "std")]
+    fn run_effects_catching(&mut self, action: &ActionWithMeta<Action>) {
+        // Panic might come from a nested dispatch, which then didn't
+        // get to restore these.
+        let recursion_depth = self.recursion_depth;
+        let current_action_id = self.current_action_id;
+
+        let payload = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            self.run_effects(action);
+        })) {
+            Ok(()) => return,
+            Err(payload) => payload,
+        };
+
+        self.recursion_depth = recursion_depth;
+        self.current_action_id = current_action_id;
+        self.panic_caught(crate::CaughtPanic {
+            action_id: action.id,
+            source: crate::PanicSource::Effects,
+            payload,
+        });
+    }
+
+    #[cfg(feature = "std")]
+    fn panic_caught(&mut self, panic: crate::CaughtPanic) {
+        if let Some(reason) = self.panics.count(&panic) {
+            self.poison(reason);
+        }
+        if let Some(hook) = self.panics.hook {
+            hook(&panic);
+        }
+        let action_id = panic.action_id;
+        let message = panic.message().map(String::from);
+        self.report_error(match panic.source {
+            crate::PanicSource::Reducer => crate::StoreError::ReducerPanic { action_id, message },
+            crate::PanicSource::Effects => crate::StoreError::EffectsPanic { action_id, message },
+        });
+    }
+
+    /// Provenance of an action dispatched without an explicit one: from
+    /// the action being processed, if any.
+    #[inline(always)]
+    fn implicit_provenance(&self) -> Provenance {
+        match self.current_action_id {
+            Some(parent) => Provenance::Effect { parent },
+            None => Provenance::External,
+        }
+    }
+
+    /// Registered module the action belongs to, see [crate::ModuleId].
+    #[inline(always)]
+    fn module_of(&self, action: &Action) -> crate::ModuleId {
+        match self.modules.as_ref() {
+            Some(modules) => crate::module::module_of(&modules.effects, action),
+            None => crate::ModuleId::ROOT,
+        }
+    }
+
+    /// Queue the action dispatched while paused, coalescing it if
+    /// configured, or drop and report it if the queue is full.
+    fn enqueue_paused<T>(
+        &mut self,
+        action: Action,
+        expires_at: Option<Instant>,
+        provenance: Provenance,
+    ) -> DispatchOutcome {
+        match self.pause.enqueue(action, expires_at, provenance) {
+            Enqueued::Queued => {
+                self.update_pending();
+                return DispatchOutcome::Queued;
+            }
+            Enqueued::Coalesced(_index) => {
+                #[cfg(feature = "stats")]
+                if let Some(queued) = self.pause.paused().and_then(|paused| paused.get(_index)) {
+                    self.stats.record_coalesced(queued);
+                }
+                return DispatchOutcome::Queued;
+            }
+            Enqueued::Full => {}
+        }
+        #[cfg(feature = "log")]
+        log::warn!(
+            target: crate::LOG_TARGET_DISPATCH,
+            "paused queue full, dropped {} after action {} ({} dropped while paused)",
+            core::any::type_name::<T>(),
+            u64::from(self.last_action_id),
+            self.pause.paused().map_or(0, |paused| paused.dropped())
+        );
+        self.report_error(crate::StoreError::QueueOverflow);
+        DispatchOutcome::Rejected
+    }
+
+    /// Action taken from the paused queue, along with where it came
+    /// from, unless it expired, in which case it's counted and reported
+    /// instead.
+    fn unexpired(&mut self, queued: QueuedAction<Action>) -> Option<(Action, Provenance)> {
+        let expires_at = match queued.expires_at {
+            Some(expires_at) => expires_at,
+            None => return Some((queued.action, queued.provenance)),
+        };
+        let now = self.service.monotonic_time();
+        if now <= expires_at {
+            return Some((queued.action, queued.provenance));
+        }
+        #[cfg(feature = "stats")]
+        self.stats.record_expired(&queued.action);
+        self.report_error(crate::StoreError::Expired {
+            late_by: now.duration_since(expires_at),
+        });
+        None
+    }
+
+    /// `Err` if `action` duplicates a recent one, in which case it's
+    /// counted and reported, otherwise its key to remember once it's
+    /// processed, if it has one.
+    fn check_duplicate(&mut self, action: &Action) -> Result<Option<crate::dedup::Seen>, ()> {
+        let dedup = match self.dedup.as_mut() {
+            Some(dedup) => dedup,
+            None => return Ok(None),
+        };
+        let service = &mut self.service;
+        let key = match dedup.check(action, || service.monotonic_time()) {
+            Ok(seen) => return Ok(seen),
+            Err(key) => key,
+        };
+        #[cfg(feature = "stats")]
+        self.stats.record_duplicate(action);
+        self.report_error(crate::StoreError::Duplicate { key });
+        Err(())
+    }
+
+    /// `Err` if the rate limiter drops `action` dispatched at `now`,
+    /// otherwise the kind which recovered by letting it through, if any.
+    ///
+    /// Action for a kind getting limited is dispatched right away, the
+    /// one for a recovered kind must be dispatched once `action` is
+    /// processed, so that it's processed after it.
+    fn rate_limit(&mut self, action: &Action, now: Instant) -> Result<Option<&'static str>, ()> {
+        let (kind, change) = match self.rate_limiting.check(action, now) {
+            Ok(recovered) => return Ok(recovered),
+            Err(limited) => limited,
+        };
+        #[cfg(feature = "stats")]
+        self.stats.record_rate_limited(kind);
+        if let Some(change) = change {
+            self.dispatch_rate_limit_action(kind, change);
+        }
+        Err(())
+    }
+
+    fn dispatch_rate_limit_action(&mut self, kind: &'static str, change: crate::RateLimitChange) {
+        if let Some((dispatch, action)) = self.rate_limiting.change_action(kind, change) {
+            dispatch(self, action);
+        }
+    }
+
+    /// Dispatch the action the error maps to, if any, unless already
+    /// dispatching one.
+    fn report_error(&mut self, error: crate::StoreError) {
+        if let Some((dispatch, action)) = self.error_actions.start(error) {
+            dispatch(self, action);
+            self.error_actions.finish();
+        }
+    }
+
+    fn check_breakpoints(&mut self, action: &ActionWithMeta<Action>) {
+        let hits = self.breakpoints.check(self.state.get(), action);
+        if hits.is_empty() {
+            return;
+        }
+        // One-shot breakpoints might have been removed.
+        self.update_subsystems();
+        if !self.is_paused() {
+            self.pause();
+        }
+        for id in hits {
+            #[cfg(feature = "breakpoint_dump")]
+            self.dump_breakpoint(id, action);
+            if let Some(hook) = self.breakpoints.hook {
+                hook(self, id, action);
+            }
+        }
+    }
+
+    #[cfg(feature = "breakpoint_dump")]
+    fn dump_breakpoint(&mut self, id: BreakpointId, action: &ActionWithMeta<Action>) {
+        let mut writer = match self.breakpoints.dump.take() {
+            Some(writer) => writer,
+            None => return,
+        };
+        let dump = crate::BreakpointDump {
+            breakpoint: id,
+            action,
+            state: self.state.get(),
+            recent_actions: self.recent_actions().collect(),
+        };
+        // Dumping is best effort, failing to write must not disturb
+        // processing of actions.
+        let _ = writer.dump(&dump);
+        self.breakpoints.dump = Some(writer);
+    }
+
+    /// Runs the effects.
+    #[inline(always)]
+    fn dispatch_effects(&mut self, action_with_id: &ActionWithMeta<Action>) {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("effects").entered();
+        #[cfg(feature = "std")]
+        if self.panics.catch_effects {
+            self.run_effects_catching(action_with_id);
+            return;
+        }
+        self.run_effects(action_with_id);
+    }
+
+    /// Runs effects of every class, in order, see [crate::EffectOrder].
+    #[inline(always)]
+    fn run_effects(&mut self, action: &ActionWithMeta<Action>) {
+        let ordered = match self.ordered_effects.clone() {
+            Some(ordered) => ordered,
+            None => {
+                crate::effects::call(self.effects, self, action);
+                self.module_effects(action);
+                return;
+            }
+        };
+        let module_start = ordered.partition_point(|effects| effects.order == EffectOrder::Pre);
+        for effects in &ordered[..module_start] {
+            crate::effects::call(effects.effects.into(), self, action);
+        }
+        crate::effects::call(self.effects, self, action);
+        self.module_effects(action);
+        for effects in &ordered[module_start..] {
+            crate::effects::call(effects.effects.into(), self, action);
+        }
+    }
+
+    /// Runs reducers of the modules, after the reducer.
+    #[inline(always)]
+    fn reduce_modules(&mut self, action: &ActionWithMeta<Action>) {
+        let modules = self.modules.as_ref().map(|modules| &modules.reducers);
+        crate::module::reduce(modules, self.state.get_mut(), action);
+    }
+
+    /// Runs effects of the modules, after the effects.
+    #[inline(always)]
+    fn module_effects(&mut self, action: &ActionWithMeta<Action>) {
+        let effects = match self.modules.as_ref() {
+            Some(modules) => modules.effects.clone(),
+            None => return,
+        };
+        for module in effects.iter() {
+            module.effects(self, action);
+        }
+    }
+}
+
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+impl<State, Action> Store<State, (), Action> {
+    /// Creates a new store without a custom service, using the
+    /// default clock and current time.
+    pub fn simple(
+        reducer: Reducer<State, Action>,
+        effects: Effects<State, (), Action>,
+        initial_state: State,
+    ) -> Self {
+        let now = ().system_time();
+        Self::new(reducer, effects, (), now, initial_state)
+    }
+}
+
+/// Direct state manipulation for tests.
+///
+/// Lets tests start from an arbitrary state, instead of replaying a long
+/// sequence of actions to get there. Only available with `testing`
+/// feature, so production code can't bypass the reducer.
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EnablingCondition, Store};
+///
+/// struct Withdraw(u32);
+///
+/// impl EnablingCondition<u32> for Withdraw {
+///     fn is_enabled(&self, balance: &u32) -> bool {
+///         *balance >= self.0
+///     }
+/// }
+///
+/// fn reducer(balance: &mut u32, action: &ActionWithMeta<Withdraw>) {
+///     *balance -= action.action.0;
+/// }
+///
+/// let mut store = Store::simple(reducer, |_, _| {}, 0);
+/// assert!(!store.dispatch(Withdraw(50)));
+///
+/// store.replace_state(100);
+/// assert!(store.dispatch(Withdraw(50)));
+///
+/// store.map_state(|balance| *balance += 1);
+/// assert_eq!(*store.state(), 51);
+/// ```
+#[cfg(feature = "testing")]
+impl<State, Service, Action> Store<State, Service, Action> {
+    /// Replace the state, returning the previous one.
+    pub fn replace_state(&mut self, state: State) -> State {
+        core::mem::replace(self.state.get_mut(), state)
+    }
+
+    /// Mutate the state in place.
+    pub fn map_state<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut State),
+    {
+        f(self.state.get_mut())
+    }
+
+    /// Un-poison the store, see [Store::poison], returning the reason.
+    pub fn clear_poison(&mut self) -> Option<crate::PoisonReason> {
+        self.capabilities.set(Capabilities::POISONED, false);
+        #[cfg(feature = "stats")]
+        self.stats.set_capabilities(self.capabilities);
+        self.poisoned.take()
+    }
+
+    /// Same as [Store::map_state], but checks the safety condition
+    /// of the resulting state.
+    pub fn map_state_checked<F>(&mut self, f: F) -> Result<(), State::Error>
+    where
+        State: crate::SafetyCondition,
+        F: FnOnce(&mut State),
+    {
+        self.map_state(f);
+        self.state.get().check_safety_condition()
+    }
+}
+
+impl<State, Service, Action> Store<State, Service, Action>
+where
+    State: crate::SafetyCondition,
+{
+    /// Check the safety condition of the current state.
+    pub fn check_state(&self) -> Result<(), State::Error> {
+        self.state.get().check_safety_condition()
+    }
+
+    /// Panics if the state violates the safety condition, with the
+    /// error in the message. Meant to be chained right after
+    /// construction, e.g. from a loaded snapshot with
+    /// [Store::from_parts]. [crate::StoreBuilder] has
+    /// [crate::StoreBuilder::check_initial_state] instead.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, SafetyCondition, Store};
+    ///
+    /// struct Sorted(Vec<u32>);
+    ///
+    /// impl SafetyCondition for Sorted {
+    ///     type Error = usize;
+    ///
+    ///     /// Index of the first item out of order.
+    ///     fn check_safety_condition(&self) -> Result<(), usize> {
+    ///         match self.0.windows(2).position(|w| w[0] > w[1]) {
+    ///             Some(i) => Err(i + 1),
+    ///             None => Ok(()),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// struct Action;
+    /// impl EnablingCondition<Sorted> for Action {}
+    ///
+    /// fn reducer(_: &mut Sorted, _: &ActionWithMeta<Action>) {}
+    ///
+    /// let store = Store::simple(reducer, |_, _| {}, Sorted(vec![1, 2, 3])).checked();
+    /// assert!(store.check_state().is_ok());
+    ///
+    /// let result = std::panic::catch_unwind(|| {
+    ///     Store::simple(reducer, |_, _| {}, Sorted(vec![1, 3, 2])).checked()
+    /// });
+    /// assert!(result.is_err());
+    /// ```
+    pub fn checked(self) -> Self
+    where
+        State::Error: fmt::Debug,
+    {
+        if let Err(error) = self.check_state() {
+            panic!("state violates safety condition: {:?}", error);
+        }
+        self
+    }
+}
+
+/// Reducer and effects aren't printed, only state, progress counters
+/// and which optional subsystems are active.
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EnablingCondition, Store};
+///
+/// struct Action;
+/// impl EnablingCondition<Vec<u8>> for Action {}
+///
+/// fn reducer(state: &mut Vec<u8>, _: &ActionWithMeta<Action>) {
+///     state.push(1);
+/// }
+///
+/// let mut store = Store::simple(reducer, |_, _| {}, vec![]);
+/// store.dispatch(Action);
+///
+/// let debug = format!("{:?}", store);
+/// assert!(debug.contains("state: [1]"));
+/// assert!(debug.contains("actions_processed: 1"));
+/// assert!(debug.contains("recorder: false"));
+///
+/// // `StateWrapper` derefs to the state for reads.
+/// assert_eq!(store.state.len(), 1);
+/// ```
+impl<State, Service, Action> fmt::Debug for Store<State, Service, Action>
+where
+    State: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Store")
+            .field("state", &self.state)
+            .field("last_action_id", &self.last_action_id)
+            .field("actions_processed", &self.actions_processed)
+            .field("recursion_depth", &self.recursion_depth)
+            .field("capabilities", &self.capabilities)
+            .field("recorder", &self.recording.recorder.is_some())
+            .field("dispatch_interceptor", &self.dispatch_interceptor.is_some())
+            .field("intercepted", &self.intercepted.len())
+            .field("breakpoints", &self.breakpoints.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Low-level copy of the store, with the same ids, namespace and
+/// statistics, so that actions of the copy and the original can't be
+/// told apart. Use [Store::fork] to continue in a separate timeline.
+impl<State, Service, Action> Clone for Store<State, Service, Action>
+where
     State: Clone,
     Service: Clone,
     Action: Clone,
@@ -179,12 +6631,101 @@ where
             service: self.service.clone(),
             state: self.state.clone(),
 
-            initial_time: self.initial_time.clone(),
+            initial_time_nanos: self.initial_time_nanos,
             initial_monotonic_time: self.initial_monotonic_time.clone(),
-            monotonic_time: self.monotonic_time.clone(),
+            clock: self.clock.clone(),
 
             recursion_depth: self.recursion_depth.clone(),
+            current_action_id: self.current_action_id,
             last_action_id: self.last_action_id.clone(),
+            actions_processed: self.actions_processed,
+
+            subsystems: self.subsystems,
+            // Recorder, metrics, state history and listeners aren't
+            // cloned, see below.
+            capabilities: self.capabilities.without(
+                Capabilities::RECORDER
+                    | Capabilities::DEVTOOLS
+                    | Capabilities::METRICS
+                    | Capabilities::STATE_HISTORY
+                    | Capabilities::LISTENERS,
+            ),
+
+            dispatch_interceptor: self.dispatch_interceptor.clone(),
+            intercepted: self.intercepted.clone(),
+
+            recording: self.recording.clone(),
+            fork: self.fork,
+            rng_seed: self.rng_seed,
+            // Same for state history, since its summary type is erased,
+            // and listeners.
+            state_history: None,
+            subscriptions: Subscriptions::new(),
+            #[cfg(feature = "audit")]
+            audit: self.audit.clone(),
+            // Futures wait for the original.
+            #[cfg(all(feature = "async", feature = "std"))]
+            conditions: None,
+            // Calls made by the clone would interleave with the ones
+            // made by the original.
+            #[cfg(feature = "std")]
+            service_call_log: None,
+
+            #[cfg(feature = "stats")]
+            stats: self.stats.clone(),
+
+            // Metrics would be shared with the original, clone starts without them.
+            #[cfg(feature = "prometheus")]
+            metrics: None,
+
+            slow_action_watchdog: self.slow_action_watchdog.clone(),
+            #[cfg(feature = "std")]
+            idle_watchdog: self.idle_watchdog.clone(),
+
+            time_travel: self.time_travel.clone(),
+
+            pause: self.pause.clone(),
+
+            timers: self.timers.clone(),
+
+            rate_limiting: self.rate_limiting.clone(),
+            dedup: self.dedup.clone(),
+
+            poisoned: self.poisoned.clone(),
+            shut_down: self.shut_down,
+            in_transaction: self.in_transaction,
+            defer_effects: self.defer_effects,
+
+            safety_check: self.safety_check,
+            condition_cache: self.condition_cache.clone(),
+            error_actions: self.error_actions.clone(),
+
+            #[cfg(feature = "std")]
+            panics: self.panics.clone(),
+
+            #[cfg(feature = "panic_context")]
+            panic_context: self.panic_context.clone(),
+
+            breakpoints: self.breakpoints.clone(),
+
+            #[cfg(feature = "diff")]
+            differ: self.differ.clone(),
+
+            #[cfg(feature = "determinism-check")]
+            determinism_check: self.determinism_check.clone(),
+
+            // Sink can't be cloned, clone starts without persistence.
+            #[cfg(feature = "persistence")]
+            persistence: None,
+
+            modules: self.modules.clone(),
+            ordered_effects: self.ordered_effects.clone(),
+            config: self.config.clone(),
+
+            #[cfg(feature = "memory")]
+            allocated: self.allocated.clone(),
+            #[cfg(feature = "memory")]
+            memory_alarm: self.memory_alarm.clone(),
         }
     }
 }
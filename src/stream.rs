@@ -0,0 +1,155 @@
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use crate::{EnablingCondition, Provenance, Store, TimeService};
+
+/// Why [Store::drive_from_stream] returned.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum StreamEnd {
+    /// Stream ended.
+    Exhausted,
+    /// Stop predicate was satisfied by the state.
+    Stopped,
+    /// Store is paused with a full queue. Stream wasn't polled since,
+    /// so calling [Store::drive_from_stream] again after making room
+    /// continues where it left off.
+    QueueFull,
+}
+
+/// Result of [Store::drive_from_stream].
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub struct StreamReport {
+    pub end: StreamEnd,
+    /// Items taken from the stream.
+    pub items: u64,
+    /// Items mapped to an action which was rejected by the store.
+    pub rejected: u64,
+}
+
+impl<State, Service, Action> Store<State, Service, Action>
+where
+    Service: TimeService,
+    Action: EnablingCondition<State>,
+{
+    /// Dispatch actions made from the items of `stream` with `map`
+    /// (items mapped to `None` are skipped), until the stream ends or
+    /// `stop` returns `true` for the state.
+    ///
+    /// Next item is polled only once the previous one has been fully
+    /// processed, and not at all while the store is paused with a full
+    /// queue, so backpressure propagates to the stream.
+    ///
+    /// ```
+    /// use redux_rs::{ActionWithMeta, EnablingCondition, Store, StreamEnd};
+    /// use std::time::Duration;
+    /// use tokio_stream::wrappers::ReceiverStream;
+    ///
+    /// enum Message {
+    ///     Data(u32),
+    ///     Ping,
+    /// }
+    ///
+    /// struct Add(u32);
+    /// impl EnablingCondition<u32> for Add {}
+    ///
+    /// fn reducer(state: &mut u32, action: &ActionWithMeta<Add>) {
+    ///     *state += action.action.0;
+    /// }
+    ///
+    /// fn map(message: Message) -> Option<Add> {
+    ///     match message {
+    ///         Message::Data(value) => Some(Add(value)),
+    ///         Message::Ping => None,
+    ///     }
+    /// }
+    ///
+    /// #[tokio::main(flavor = "current_thread", start_paused = true)]
+    /// async fn main() {
+    ///     let (tx, rx) = tokio::sync::mpsc::channel(1);
+    ///     tokio::spawn(async move {
+    ///         for message in [Message::Data(1), Message::Ping, Message::Data(2), Message::Data(3)] {
+    ///             tokio::time::sleep(Duration::from_secs(1)).await;
+    ///             tx.send(message).await.unwrap();
+    ///         }
+    ///     });
+    ///     let mut messages = ReceiverStream::new(rx);
+    ///
+    ///     let mut store = Store::simple(reducer, |_, _| {}, 0);
+    ///     let report = store.drive_from_stream(&mut messages, map, |_| false).await;
+    ///     assert_eq!(report.end, StreamEnd::Exhausted);
+    ///     assert_eq!(report.items, 4);
+    ///     assert_eq!(*store.state(), 6);
+    ///
+    ///     // Stop predicate, and backpressure of a paused store.
+    ///     let (tx, rx) = tokio::sync::mpsc::channel(8);
+    ///     for value in 1..=8 {
+    ///         tx.send(Message::Data(value)).await.unwrap();
+    ///     }
+    ///     let mut messages = ReceiverStream::new(rx);
+    ///
+    ///     let mut store = Store::simple(reducer, |_, _| {}, 0);
+    ///     let report = store.drive_from_stream(&mut messages, map, |state| *state >= 6).await;
+    ///     assert_eq!((report.end, report.items), (StreamEnd::Stopped, 3));
+    ///
+    ///     store.pause_with_capacity(2);
+    ///     let report = store.drive_from_stream(&mut messages, map, |_| false).await;
+    ///     assert_eq!((report.end, report.items), (StreamEnd::QueueFull, 2));
+    ///
+    ///     store.resume();
+    ///     assert_eq!(*store.state(), 6 + 4 + 5);
+    ///     drop(tx);
+    ///     let report = store.drive_from_stream(&mut messages, map, |_| false).await;
+    ///     assert_eq!((report.end, report.items), (StreamEnd::Exhausted, 3));
+    ///     assert_eq!(*store.state(), 36);
+    /// }
+    /// ```
+    pub async fn drive_from_stream<S, Item>(
+        &mut self,
+        stream: &mut S,
+        map: fn(Item) -> Option<Action>,
+        stop: fn(&State) -> bool,
+    ) -> StreamReport
+    where
+        S: Stream<Item = Item> + Unpin,
+    {
+        let mut report = StreamReport {
+            end: StreamEnd::Exhausted,
+            items: 0,
+            rejected: 0,
+        };
+        loop {
+            if stop(self.state()) {
+                report.end = StreamEnd::Stopped;
+                return report;
+            }
+            if self.is_paused_queue_full() {
+                report.end = StreamEnd::QueueFull;
+                return report;
+            }
+            let item = match Next(stream).await {
+                Some(item) => item,
+                None => return report,
+            };
+            report.items += 1;
+            if let Some(action) = map(item) {
+                if !self.dispatch_from(action, Provenance::Bridge).is_accepted() {
+                    report.rejected += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Future of the next item of the stream.
+struct Next<'a, S>(&'a mut S);
+
+impl<S: Stream + Unpin> Future for Next<'_, S> {
+    type Output = Option<S::Item>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut *self.0).poll_next(cx)
+    }
+}
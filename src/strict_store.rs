@@ -0,0 +1,129 @@
+use core::time::Duration;
+
+use crate::{
+    ActionId, DispatchOutcome, EffectsCtx, EnablingCondition, Store, TimeService, TimerId,
+};
+
+/// Store as seen by effects of a strict store, see
+/// [crate::StrictEffects].
+///
+/// Unlike [Store], it has no `service` field, the service can only be
+/// reached with [StrictStore::service_with], so only with the
+/// [EffectsCtx] of the effects call.
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EffectsCtx, EnablingCondition, ReducerCtx, StrictStore, Store, TimeService};
+/// use std::time::SystemTime;
+///
+/// #[derive(Default)]
+/// struct Service {
+///     sent: Vec<u64>,
+/// }
+///
+/// impl TimeService for Service {}
+///
+/// enum Action {
+///     Request(u64),
+///     Publish(u64),
+/// }
+/// impl EnablingCondition<u64> for Action {}
+///
+/// fn reducer(_: &ReducerCtx, last: &mut u64, action: &ActionWithMeta<Action>) {
+///     if let Action::Request(n) = action.action {
+///         *last = n;
+///     }
+/// }
+///
+/// fn effects(
+///     ctx: &EffectsCtx,
+///     store: &mut StrictStore<u64, Service, Action>,
+///     action: &ActionWithMeta<Action>,
+/// ) {
+///     match action.action {
+///         Action::Request(_) => {
+///             let last = *store.state();
+///             store.dispatch(Action::Publish(last));
+///         }
+///         Action::Publish(n) => store.service_with(ctx).sent.push(n),
+///     }
+/// }
+///
+/// let service = Service::default();
+/// let mut store = Store::new_strict(reducer, effects, service, SystemTime::UNIX_EPOCH, 0);
+/// store.dispatch(Action::Request(1));
+/// store.dispatch(Action::Request(2));
+/// assert_eq!(store.service.sent, [1, 2]);
+/// ```
+pub struct StrictStore<'a, State, Service, Action> {
+    store: &'a mut Store<State, Service, Action>,
+}
+
+impl<'a, State, Service, Action> StrictStore<'a, State, Service, Action> {
+    #[inline(always)]
+    pub(crate) fn new(store: &'a mut Store<State, Service, Action>) -> Self {
+        Self { store }
+    }
+}
+
+impl<'a, State, Service, Action> StrictStore<'a, State, Service, Action>
+where
+    Service: TimeService,
+{
+    /// See [Store::state].
+    #[inline(always)]
+    pub fn state(&self) -> &State {
+        self.store.state()
+    }
+
+    /// Service of the store, for effects holding the `ctx`.
+    #[inline(always)]
+    pub fn service_with(&mut self, _ctx: &EffectsCtx) -> &mut Service {
+        &mut self.store.service
+    }
+
+    /// See [Store::dispatch].
+    pub fn dispatch<T>(&mut self, action: T) -> bool
+    where
+        T: Into<Action> + EnablingCondition<State>,
+    {
+        self.store.dispatch(action)
+    }
+
+    /// See [Store::dispatch_with_outcome].
+    pub fn dispatch_with_outcome<T>(&mut self, action: T) -> DispatchOutcome
+    where
+        T: Into<Action> + EnablingCondition<State>,
+    {
+        self.store.dispatch_with_outcome(action)
+    }
+
+    /// See [Store::dispatch_with_ttl].
+    pub fn dispatch_with_ttl<T>(&mut self, action: T, ttl: Duration) -> bool
+    where
+        T: Into<Action> + EnablingCondition<State>,
+    {
+        self.store.dispatch_with_ttl(action, ttl)
+    }
+
+    /// See [Store::schedule_timer].
+    pub fn schedule_timer(&mut self, delay: Duration, action: Action) -> TimerId {
+        self.store.schedule_timer(delay, action)
+    }
+
+    /// See [Store::cancel_timer].
+    pub fn cancel_timer(&mut self, id: TimerId) -> Option<Action> {
+        self.store.cancel_timer(id)
+    }
+
+    /// See [Store::last_action_id].
+    #[inline(always)]
+    pub fn last_action_id(&self) -> ActionId {
+        self.store.last_action_id()
+    }
+
+    /// See [Store::dispatch_depth].
+    #[inline(always)]
+    pub fn dispatch_depth(&self) -> u32 {
+        self.store.dispatch_depth()
+    }
+}
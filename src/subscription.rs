@@ -0,0 +1,271 @@
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, collections::BTreeMap, format, string::String, vec::Vec};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use crate::{ActionClassifier, ActionWithMeta, StoreError};
+
+/// Id of a listener subscribed with `Store::subscribe`. Ids are never
+/// reused within a store.
+#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone, Copy)]
+pub struct SubscriptionId(u64);
+
+impl From<SubscriptionId> for u64 {
+    fn from(id: SubscriptionId) -> Self {
+        id.0
+    }
+}
+
+impl fmt::Display for SubscriptionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+type Listener<State, Action> =
+    Box<dyn FnMut(&State, &ActionWithMeta<Action>) -> Result<(), String> + Send>;
+
+struct Subscription<State, Action> {
+    id: SubscriptionId,
+    listener: Listener<State, Action>,
+    errors: u32,
+    /// Errors tolerated before the listener is unsubscribed, `None`
+    /// for listeners which can't return one.
+    max_errors: Option<u32>,
+    /// Kinds of actions the listener is notified of, all if empty.
+    kinds: Vec<&'static str>,
+}
+
+impl<State, Action> Subscription<State, Action> {
+    /// Call the listener, returning what went wrong, if anything, and
+    /// whether it must be unsubscribed because of it.
+    fn notify(
+        &mut self,
+        state: &State,
+        action: &ActionWithMeta<Action>,
+    ) -> Option<(StoreError, bool)> {
+        #[cfg(feature = "std")]
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            (self.listener)(state, action)
+        }));
+        #[cfg(not(feature = "std"))]
+        let result = Ok::<_, core::convert::Infallible>((self.listener)(state, action));
+
+        match result {
+            Ok(Ok(())) => None,
+            Ok(Err(error)) => {
+                self.errors += 1;
+                let unsubscribed = matches!(self.max_errors, Some(max) if self.errors > max);
+                let error = StoreError::ListenerError {
+                    subscription: self.id,
+                    action_id: action.id,
+                    error,
+                    unsubscribed,
+                };
+                Some((error, unsubscribed))
+            }
+            #[cfg(feature = "std")]
+            Err(payload) => {
+                let message = crate::recovery::panic_message(&*payload).map(String::from);
+                let error = StoreError::ListenerPanic {
+                    subscription: self.id,
+                    action_id: action.id,
+                    message,
+                };
+                Some((error, true))
+            }
+            #[cfg(not(feature = "std"))]
+            Err(never) => match never {},
+        }
+    }
+}
+
+/// Listeners notified after every action, in the order they
+/// subscribed.
+///
+/// Listeners filtered by kind are indexed by it, so that an action only
+/// walks listeners of its kind, besides the unfiltered ones.
+pub(crate) struct Subscriptions<State, Action> {
+    /// Sorted by id.
+    list: Vec<Subscription<State, Action>>,
+    /// Listeners notified of every action, sorted.
+    unfiltered: Vec<SubscriptionId>,
+    /// Listeners notified of actions of the kind, sorted.
+    by_kind: BTreeMap<&'static str, Vec<SubscriptionId>>,
+    /// Set by the first filtered listener.
+    classifier: Option<ActionClassifier<Action>>,
+    next_id: u64,
+}
+
+impl<State, Action> Subscriptions<State, Action> {
+    pub fn new() -> Self {
+        Self {
+            list: Vec::new(),
+            unfiltered: Vec::new(),
+            by_kind: BTreeMap::new(),
+            classifier: None,
+            next_id: 0,
+        }
+    }
+
+    pub fn subscribe<F>(&mut self, mut listener: F) -> SubscriptionId
+    where
+        F: FnMut(&State, &ActionWithMeta<Action>) + Send + 'static,
+    {
+        self.push(
+            Box::new(move |state, action| {
+                listener(state, action);
+                Ok(())
+            }),
+            None,
+            Vec::new(),
+        )
+    }
+
+    /// Subscribe a listener notified only of actions whose kind, as
+    /// told by `classifier`, is one of `kinds`.
+    pub fn subscribe_filtered<F>(
+        &mut self,
+        classifier: ActionClassifier<Action>,
+        kinds: Vec<&'static str>,
+        mut listener: F,
+    ) -> SubscriptionId
+    where
+        F: FnMut(&State, &ActionWithMeta<Action>) + Send + 'static,
+    {
+        self.classifier = Some(classifier);
+        self.push(
+            Box::new(move |state, action| {
+                listener(state, action);
+                Ok(())
+            }),
+            None,
+            kinds,
+        )
+    }
+
+    pub fn subscribe_fallible<F, E>(&mut self, max_errors: u32, mut listener: F) -> SubscriptionId
+    where
+        F: FnMut(&State, &ActionWithMeta<Action>) -> Result<(), E> + Send + 'static,
+        E: fmt::Debug,
+    {
+        self.push(
+            Box::new(move |state, action| {
+                listener(state, action).map_err(|error| format!("{:?}", error))
+            }),
+            Some(max_errors),
+            Vec::new(),
+        )
+    }
+
+    fn push(
+        &mut self,
+        listener: Listener<State, Action>,
+        max_errors: Option<u32>,
+        mut kinds: Vec<&'static str>,
+    ) -> SubscriptionId {
+        let id = SubscriptionId(self.next_id);
+        self.next_id += 1;
+        kinds.sort_unstable();
+        kinds.dedup();
+        if kinds.is_empty() {
+            self.unfiltered.push(id);
+        }
+        for &kind in &kinds {
+            self.by_kind.entry(kind).or_default().push(id);
+        }
+        self.list.push(Subscription {
+            id,
+            listener,
+            errors: 0,
+            max_errors,
+            kinds,
+        });
+        id
+    }
+
+    pub fn unsubscribe(&mut self, id: SubscriptionId) -> bool {
+        let index = match self
+            .list
+            .binary_search_by_key(&id, |subscription| subscription.id)
+        {
+            Ok(index) => index,
+            Err(_) => return false,
+        };
+        let subscription = self.list.remove(index);
+        if subscription.kinds.is_empty() {
+            self.unfiltered.retain(|other| *other != id);
+        }
+        for kind in subscription.kinds {
+            if let Some(ids) = self.by_kind.get_mut(kind) {
+                ids.retain(|other| *other != id);
+                if ids.is_empty() {
+                    self.by_kind.remove(kind);
+                }
+            }
+        }
+        true
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Call every listener interested in the action, in the order they
+    /// subscribed, returning what went wrong, to be reported by the
+    /// store. Listeners which panicked, or returned more errors than
+    /// they may, are unsubscribed.
+    pub fn notify(&mut self, state: &State, action: &ActionWithMeta<Action>) -> Vec<StoreError> {
+        let Self {
+            list,
+            unfiltered,
+            by_kind,
+            classifier,
+            ..
+        } = self;
+        let of_kind = match classifier {
+            Some(classifier) => by_kind
+                .get(classifier(&action.action))
+                .map_or(&[][..], Vec::as_slice),
+            None => &[],
+        };
+
+        let mut failures = Vec::new();
+        let mut unsubscribed = Vec::new();
+        let (mut all, mut of_kind) = (unfiltered.iter().peekable(), of_kind.iter().peekable());
+        // Both are sorted by id, so merging them keeps the order.
+        loop {
+            let id = match (all.peek(), of_kind.peek()) {
+                (Some(a), Some(b)) if a < b => all.next(),
+                (Some(_), Some(_)) | (None, _) => of_kind.next(),
+                (Some(_), None) => all.next(),
+            };
+            let id = match id {
+                Some(id) => *id,
+                None => break,
+            };
+            let index = match list.binary_search_by_key(&id, |subscription| subscription.id) {
+                Ok(index) => index,
+                Err(_) => continue,
+            };
+            if let Some((error, unsubscribe)) = list[index].notify(state, action) {
+                failures.push(error);
+                if unsubscribe {
+                    unsubscribed.push(id);
+                }
+            }
+        }
+
+        for id in unsubscribed {
+            self.unsubscribe(id);
+        }
+        failures
+    }
+}
@@ -0,0 +1,31 @@
+/// Which optional subsystems of the store are active, so that dispatch
+/// can skip all of them with a single check.
+///
+/// Kept in sync by `Store::update_subsystems`, which every setter of an
+/// optional subsystem calls.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Subsystems(u8);
+
+impl Subsystems {
+    pub const NONE: Self = Self(0);
+    /// Reducer and effects need to be timed: statistics, metrics or
+    /// slow action watchdog.
+    pub const TIMINGS: Self = Self(1);
+    /// Something runs after the reducer: time travel, state history,
+    /// diff, safety check, breakpoints or listeners.
+    pub const AFTER_REDUCER: Self = Self(1 << 1);
+
+    #[inline(always)]
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    #[inline(always)]
+    pub fn set(&mut self, other: Self, enabled: bool) {
+        if enabled {
+            self.0 |= other.0;
+        } else {
+            self.0 &= !other.0;
+        }
+    }
+}
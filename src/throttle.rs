@@ -0,0 +1,234 @@
+use core::time::Duration;
+
+use crate::{ActionId, Store, TimeService, TimerId};
+
+/// Lets through at most one call per `interval`.
+///
+/// Time is taken from action ids, e.g. of the action being handled by
+/// the effects, so it's deterministic with a mock clock and on replay.
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, EnablingCondition, FrozenTime, Instant, Store, Throttle};
+/// use std::time::{Duration, SystemTime};
+///
+/// struct Service {
+///     clock: FrozenTime,
+///     refresh: Throttle,
+///     refreshed: u32,
+/// }
+///
+/// impl redux_rs::TimeService for Service {
+///     fn monotonic_time(&mut self) -> Instant {
+///         self.clock.monotonic_time()
+///     }
+/// }
+///
+/// struct Scroll;
+/// impl EnablingCondition<()> for Scroll {}
+///
+/// fn effects(store: &mut Store<(), Service, Scroll>, action: &ActionWithMeta<Scroll>) {
+///     if store.service.refresh.allow(action.id) {
+///         store.service.refreshed += 1;
+///     }
+/// }
+///
+/// let service = Service {
+///     clock: FrozenTime::new(Instant::now()),
+///     refresh: Throttle::new(Duration::from_millis(500)),
+///     refreshed: 0,
+/// };
+/// let mut store = Store::new(|_, _| {}, effects, service, SystemTime::UNIX_EPOCH, ());
+///
+/// // Leading edge passes, the rest within 500ms doesn't.
+/// for _ in 0..5 {
+///     store.service.clock.advance(Duration::from_millis(100));
+///     store.dispatch(Scroll);
+/// }
+/// assert_eq!(store.service.refreshed, 1);
+///
+/// store.service.clock.advance(Duration::from_millis(100));
+/// store.dispatch(Scroll);
+/// assert_eq!(store.service.refreshed, 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    interval: Duration,
+    last: Option<ActionId>,
+}
+
+impl Throttle {
+    pub const fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last: None,
+        }
+    }
+
+    /// Whether a call at `now` may go through, which is when it's the
+    /// first one, or at least `interval` passed since the last one which
+    /// went through.
+    pub fn allow(&mut self, now: ActionId) -> bool {
+        match self.last {
+            Some(last) if now.duration_since(last) < self.interval => false,
+            _ => {
+                self.last = Some(now);
+                true
+            }
+        }
+    }
+
+    /// Forget the last call, so that the next one goes through.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+/// Waits for `delay` of silence.
+///
+/// [Debounce::allow] tells whether a call is the leading edge, i.e. the
+/// first one after at least `delay` without calls. For the trailing
+/// edge, [Debounce::schedule] dispatches an action with a store timer
+/// once `delay` passes without another call, see [Store::schedule_timer].
+///
+/// Debounce is usually kept in the service, so functions taking the
+/// store get it with `debounce: fn(&mut Service) -> &mut Debounce`.
+///
+/// ```
+/// use redux_rs::{ActionWithMeta, Debounce, EnablingCondition, FrozenTime, Instant, Store};
+/// use std::time::{Duration, SystemTime};
+///
+/// struct Service {
+///     clock: FrozenTime,
+///     search: Debounce,
+/// }
+///
+/// impl redux_rs::TimeService for Service {
+///     fn monotonic_time(&mut self) -> Instant {
+///         self.clock.monotonic_time()
+///     }
+/// }
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// enum Action {
+///     Typed(char),
+///     Cleared,
+///     Search,
+/// }
+///
+/// impl EnablingCondition<Vec<Action>> for Action {}
+///
+/// fn reducer(state: &mut Vec<Action>, action: &ActionWithMeta<Action>) {
+///     state.push(action.action.clone());
+/// }
+///
+/// fn effects(store: &mut Store<Vec<Action>, Service, Action>, action: &ActionWithMeta<Action>) {
+///     match action.action {
+///         Action::Typed(_) => {
+///             Debounce::schedule(store, |s| &mut s.search, Action::Search);
+///         }
+///         Action::Cleared => {
+///             Debounce::cancel(store, |s| &mut s.search);
+///         }
+///         Action::Search => {}
+///     }
+/// }
+///
+/// let service = Service {
+///     clock: FrozenTime::new(Instant::now()),
+///     search: Debounce::new(Duration::from_millis(200)),
+/// };
+/// let mut store = Store::new(reducer, effects, service, SystemTime::UNIX_EPOCH, vec![]);
+///
+/// // Typing faster than 200ms keeps postponing the search.
+/// for c in "abc".chars() {
+///     store.dispatch(Action::Typed(c));
+///     store.service.clock.advance(Duration::from_millis(150));
+///     assert_eq!(store.fire_timers(), 0);
+/// }
+/// assert_eq!(store.pending_timers(), 1);
+///
+/// // Trailing edge, 200ms after the last key.
+/// store.service.clock.advance(Duration::from_millis(50));
+/// assert_eq!(store.fire_timers(), 1);
+/// assert_eq!(store.state().last(), Some(&Action::Search));
+///
+/// // Clearing cancels the pending search.
+/// store.dispatch(Action::Typed('d'));
+/// store.dispatch(Action::Cleared);
+/// store.service.clock.advance(Duration::from_secs(1));
+/// assert_eq!(store.fire_timers(), 0);
+/// assert_eq!(store.state().last(), Some(&Action::Cleared));
+/// ```
+///
+/// Leading edge:
+///
+/// ```
+/// use redux_rs::{ActionId, Debounce};
+/// use std::time::Duration;
+///
+/// let ms = |ms: u64| ActionId::new_unchecked(ms * 1_000_000);
+/// let mut debounce = Debounce::new(Duration::from_millis(200));
+///
+/// assert!(debounce.allow(ms(0)));
+/// assert!(!debounce.allow(ms(150)));
+/// // Silence is measured from the last call, not the last allowed one.
+/// assert!(!debounce.allow(ms(300)));
+/// assert!(debounce.allow(ms(500)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Debounce {
+    delay: Duration,
+    /// Last call of [Debounce::allow].
+    last: Option<ActionId>,
+    /// Timer of the trailing edge, set by [Debounce::schedule].
+    timer: Option<TimerId>,
+}
+
+impl Debounce {
+    pub const fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            last: None,
+            timer: None,
+        }
+    }
+
+    /// Whether a call at `now` is the first one after at least `delay`
+    /// without calls.
+    pub fn allow(&mut self, now: ActionId) -> bool {
+        match self.last.replace(now) {
+            Some(last) => now.duration_since(last) >= self.delay,
+            None => true,
+        }
+    }
+
+    /// Schedule `action` to be dispatched once `delay` passes, cancelling
+    /// the one scheduled by the previous call if it's still pending.
+    pub fn schedule<State, Service, Action>(
+        store: &mut Store<State, Service, Action>,
+        debounce: fn(&mut Service) -> &mut Debounce,
+        action: Action,
+    ) -> TimerId
+    where
+        Service: TimeService,
+    {
+        Self::cancel(store, debounce);
+        let delay = debounce(&mut store.service).delay;
+        let timer = store.schedule_timer(delay, action);
+        debounce(&mut store.service).timer = Some(timer);
+        timer
+    }
+
+    /// Cancel the pending trailing edge action, returning it if it
+    /// didn't fire yet.
+    pub fn cancel<State, Service, Action>(
+        store: &mut Store<State, Service, Action>,
+        debounce: fn(&mut Service) -> &mut Debounce,
+    ) -> Option<Action>
+    where
+        Service: TimeService,
+    {
+        let timer = debounce(&mut store.service).timer.take()?;
+        store.cancel_timer(timer)
+    }
+}
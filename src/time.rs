@@ -0,0 +1,226 @@
+use core::ops::{Add, AddAssign};
+use core::time::Duration;
+
+/// Monotonic time returned by [crate::TimeService].
+///
+/// With `std` feature it's [std::time::Instant], otherwise (or on
+/// `wasm32` targets) it's [MonotonicTime], since `no_std` targets have
+/// no `Instant` and on `wasm32-unknown-unknown` it can't be obtained.
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub type Instant = std::time::Instant;
+
+/// Monotonic time returned by [crate::TimeService].
+///
+/// With `std` feature it's [std::time::Instant], otherwise (or on
+/// `wasm32` targets) it's [MonotonicTime], since `no_std` targets have
+/// no `Instant` and on `wasm32-unknown-unknown` it can't be obtained.
+#[cfg(any(not(feature = "std"), target_arch = "wasm32"))]
+pub type Instant = MonotonicTime;
+
+/// Monotonic time in nanoseconds from some arbitrary (but fixed) point.
+///
+/// Mirrors the subset of [std::time::Instant] API used by the store,
+/// for targets without `std`.
+#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MonotonicTime(u64);
+
+impl MonotonicTime {
+    #[inline(always)]
+    pub const fn from_nanos(nanos: u64) -> Self {
+        Self(nanos)
+    }
+
+    #[inline(always)]
+    pub const fn as_nanos(&self) -> u64 {
+        self.0
+    }
+
+    /// Panics if `earlier` is later than `self`.
+    pub fn duration_since(&self, earlier: Self) -> Duration {
+        self.checked_duration_since(earlier)
+            .expect("supplied time is later than self")
+    }
+
+    pub fn checked_duration_since(&self, earlier: Self) -> Option<Duration> {
+        self.0.checked_sub(earlier.0).map(Duration::from_nanos)
+    }
+
+    pub fn saturating_duration_since(&self, earlier: Self) -> Duration {
+        self.checked_duration_since(earlier).unwrap_or_default()
+    }
+}
+
+impl From<u64> for MonotonicTime {
+    fn from(nanos: u64) -> Self {
+        Self(nanos)
+    }
+}
+
+impl From<MonotonicTime> for u64 {
+    fn from(time: MonotonicTime) -> Self {
+        time.0
+    }
+}
+
+impl Add<Duration> for MonotonicTime {
+    type Output = Self;
+
+    fn add(self, rhs: Duration) -> Self {
+        Self(self.0 + rhs.as_nanos() as u64)
+    }
+}
+
+impl AddAssign<Duration> for MonotonicTime {
+    fn add_assign(&mut self, rhs: Duration) {
+        *self = *self + rhs;
+    }
+}
+
+/// How often the store samples the monotonic clock when dispatching.
+///
+/// When clock isn't sampled, action id is just increased by 1 compared
+/// to the previous one, so ids stay strictly increasing.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+pub enum TimeGranularity {
+    /// Sample for every action (default).
+    #[default]
+    PerAction,
+    /// Sample only for top level (not dispatched from effects) actions.
+    PerBatch,
+    /// Sample for every n-th action.
+    Every(u32),
+}
+
+/// Unexpected monotonic clock behavior noticed by the store.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum ClockAnomaly {
+    /// [crate::TimeService] returned time earlier than the previous one.
+    /// Treated as no time passed.
+    Backwards { by: Duration },
+    /// Time passed since the previous action exceeded the configured
+    /// maximum step and was capped to it.
+    Jump { by: Duration, capped_to: Duration },
+}
+
+/// Function signature for a hook called on every [ClockAnomaly].
+pub type ClockAnomalyHook = fn(&ClockAnomaly);
+
+/// Counters of clock anomalies noticed by the store.
+#[derive(Debug, Default, Eq, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClockDiagnostics {
+    /// Number of times clock went backwards.
+    pub backwards: u64,
+    /// Number of times time step was capped.
+    pub capped: u64,
+}
+
+/// Monotonic clock as seen by the store: time of the last sample,
+/// guarded against anomalies, and how often it's sampled.
+#[derive(Clone)]
+pub(crate) struct SampledClock {
+    /// Latest sampled time, never moves backwards.
+    pub now: Instant,
+    pub max_time_step: Option<Duration>,
+    pub diagnostics: ClockDiagnostics,
+    pub anomaly_hook: Option<ClockAnomalyHook>,
+    granularity: TimeGranularity,
+    /// Number of actions since the clock was last sampled.
+    samples_skipped: u32,
+}
+
+impl SampledClock {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            now,
+            max_time_step: None,
+            diagnostics: ClockDiagnostics::default(),
+            anomaly_hook: None,
+            granularity: TimeGranularity::default(),
+            samples_skipped: 0,
+        }
+    }
+
+    pub fn set_granularity(&mut self, granularity: TimeGranularity) {
+        self.granularity = granularity;
+        self.samples_skipped = 0;
+    }
+
+    /// Whether the clock should be sampled for the next action.
+    pub fn should_sample(&mut self, top_level: bool) -> bool {
+        match self.granularity {
+            TimeGranularity::PerAction => true,
+            TimeGranularity::PerBatch => top_level,
+            TimeGranularity::Every(n) => {
+                if self.samples_skipped + 1 >= n {
+                    self.samples_skipped = 0;
+                    true
+                } else {
+                    self.samples_skipped += 1;
+                    false
+                }
+            }
+        }
+    }
+
+    /// Take the `sampled` time and return time passed since the last
+    /// sample, guarding against clocks going backwards or jumping too
+    /// far. `last_action_id` is only for diagnostics.
+    pub fn advance(&mut self, sampled: Instant, _last_action_id: crate::ActionId) -> Duration {
+        let anomaly = if sampled < self.now {
+            self.diagnostics.backwards += 1;
+            Some(ClockAnomaly::Backwards {
+                by: self.now.duration_since(sampled),
+            })
+        } else {
+            None
+        };
+
+        let mut time_passed = sampled.saturating_duration_since(self.now);
+        // Never move backwards, so that uptime and time conversions stay monotonic.
+        self.now = self.now.max(sampled);
+
+        let anomaly = match self.max_time_step {
+            Some(max) if time_passed > max => {
+                self.diagnostics.capped += 1;
+                let by = time_passed;
+                time_passed = max;
+                Some(ClockAnomaly::Jump { by, capped_to: max })
+            }
+            _ => anomaly,
+        };
+
+        #[cfg(feature = "log")]
+        match &anomaly {
+            Some(ClockAnomaly::Backwards { by }) => log::warn!(
+                target: crate::LOG_TARGET_CLOCK,
+                "clock went backwards by {:?} after action {}",
+                by,
+                u64::from(_last_action_id)
+            ),
+            Some(ClockAnomaly::Jump { by, capped_to }) => log::warn!(
+                target: crate::LOG_TARGET_CLOCK,
+                "clock jumped by {:?} after action {}, capped to {:?}",
+                by,
+                u64::from(_last_action_id),
+                capped_to
+            ),
+            None => {}
+        }
+
+        if let (Some(anomaly), Some(hook)) = (anomaly, self.anomaly_hook) {
+            hook(&anomaly);
+        }
+
+        time_passed
+    }
+}
+
+/// Nanoseconds since [std::time::UNIX_EPOCH], or 0 if `time` is before it.
+#[cfg(feature = "std")]
+pub(crate) fn system_time_to_nanos(time: std::time::SystemTime) -> u64 {
+    time.duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .map(|x| x.as_nanos() as u64)
+        .unwrap_or(0)
+}
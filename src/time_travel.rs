@@ -0,0 +1,172 @@
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+use core::fmt;
+
+use crate::module::ModuleReducers;
+use crate::reducer::AnyReducer;
+use crate::{ActionId, ActionWithMeta};
+
+/// Error returned by `Store::travel_to`.
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum TimeTravelError {
+    /// Time travel wasn't enabled with `Store::enable_time_travel`.
+    Disabled,
+    /// No action with the given id was processed since time travel
+    /// got enabled.
+    UnknownAction(ActionId),
+}
+
+impl fmt::Display for TimeTravelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Disabled => write!(f, "time travel is not enabled"),
+            Self::UnknownAction(id) => {
+                write!(f, "action {} is not in time travel log", u64::from(*id))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TimeTravelError {}
+
+/// Log of processed actions with periodic state checkpoints, from
+/// which the state as of any logged action can be reconstructed.
+pub(crate) struct TimeTravel<State, Action> {
+    /// Checkpoint is taken after every `interval` actions.
+    interval: usize,
+    /// Actions which went through the reducer, in order.
+    log: Vec<ActionWithMeta<Action>>,
+    /// State after the first `n` actions of the log, sorted by `n`.
+    checkpoints: Vec<(usize, State)>,
+    /// Live state and id of the action the store traveled to, while
+    /// detached.
+    detached: Option<(State, ActionId)>,
+    /// Reducer used for actions starting from the given log index,
+    /// sorted by index.
+    reducers: Vec<(usize, AnyReducer<State, Action>)>,
+    /// Reducers of the modules, which run after the reducer.
+    modules: Option<ModuleReducers<State, Action>>,
+
+    clone_state: fn(&State) -> State,
+    clone_action: fn(&ActionWithMeta<Action>) -> ActionWithMeta<Action>,
+}
+
+impl<State, Action> TimeTravel<State, Action> {
+    pub fn new(
+        interval: usize,
+        reducer: AnyReducer<State, Action>,
+        modules: Option<ModuleReducers<State, Action>>,
+        state: &State,
+    ) -> Self
+    where
+        State: Clone,
+        Action: Clone,
+    {
+        Self {
+            interval: interval.max(1),
+            log: Vec::new(),
+            checkpoints: vec![(0, state.clone())],
+            detached: None,
+            reducers: vec![(0, reducer)],
+            modules,
+            clone_state: State::clone,
+            clone_action: ActionWithMeta::clone,
+        }
+    }
+
+    #[inline(always)]
+    pub fn detached_at(&self) -> Option<ActionId> {
+        self.detached.as_ref().map(|(_, id)| *id)
+    }
+
+    /// Record `action`, with `state` being the state after reducer.
+    pub fn record(&mut self, action: &ActionWithMeta<Action>, state: &State) {
+        self.log.push((self.clone_action)(action));
+        if self.log.len() % self.interval == 0 {
+            self.checkpoints
+                .push((self.log.len(), (self.clone_state)(state)));
+        }
+    }
+
+    /// Actions recorded from now on are processed by `reducer`.
+    pub fn reducer_replaced(&mut self, reducer: AnyReducer<State, Action>) {
+        self.reducers.push((self.log.len(), reducer));
+    }
+
+    /// Reducer which processed the action at `index` of the log.
+    fn reducer_at(&self, index: usize) -> AnyReducer<State, Action> {
+        // First one is at 0, so there's always a match.
+        let i = self.reducers.partition_point(|(start, _)| *start <= index);
+        self.reducers[i - 1].1
+    }
+
+    /// Reconstruct state as of action `id`, replacing `state` with it.
+    /// Live state is kept aside until [TimeTravel::return_to_latest].
+    pub fn travel_to(&mut self, id: ActionId, state: &mut State) -> Result<(), TimeTravelError> {
+        let index = self
+            .log
+            .binary_search_by_key(&id, |action| action.id)
+            .map_err(|_| TimeTravelError::UnknownAction(id))?;
+        let target_len = index + 1;
+
+        // Checkpoints are sorted and the first one is at 0.
+        let checkpoint = match self
+            .checkpoints
+            .binary_search_by_key(&target_len, |(len, _)| *len)
+        {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        let (checkpoint_len, checkpoint_state) = &self.checkpoints[checkpoint];
+
+        let mut traveled = (self.clone_state)(checkpoint_state);
+        for (index, action) in self
+            .log
+            .iter()
+            .enumerate()
+            .take(target_len)
+            .skip(*checkpoint_len)
+        {
+            crate::reducer::call(self.reducer_at(index), &mut traveled, action);
+            crate::module::reduce(self.modules.as_ref(), &mut traveled, action);
+        }
+
+        let live = core::mem::replace(state, traveled);
+        let live = match self.detached.take() {
+            Some((live, _)) => live,
+            None => live,
+        };
+        self.detached = Some((live, id));
+        Ok(())
+    }
+
+    /// Put back live state, if detached.
+    pub fn return_to_latest(&mut self, state: &mut State) {
+        if let Some((live, _)) = self.detached.take() {
+            *state = live;
+        }
+    }
+}
+
+impl<State, Action> Clone for TimeTravel<State, Action> {
+    fn clone(&self) -> Self {
+        Self {
+            interval: self.interval,
+            log: self.log.iter().map(self.clone_action).collect(),
+            checkpoints: self
+                .checkpoints
+                .iter()
+                .map(|(len, state)| (*len, (self.clone_state)(state)))
+                .collect(),
+            detached: self
+                .detached
+                .as_ref()
+                .map(|(state, id)| ((self.clone_state)(state), *id)),
+            reducers: self.reducers.clone(),
+            modules: self.modules.clone(),
+            clone_state: self.clone_state,
+            clone_action: self.clone_action,
+        }
+    }
+}
@@ -0,0 +1,248 @@
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+use crate::Instant;
+
+/// Id of a timer scheduled with `Store::schedule_timer`. Ids are never
+/// reused within a store.
+#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimerId(u64);
+
+/// Timers and recurring actions of the store, fired by
+/// `Store::fire_timers`.
+#[derive(Clone)]
+pub(crate) struct Timers<Action> {
+    pub once: OneShotTimers<Action>,
+    #[cfg(feature = "std")]
+    pub recurring: RecurringTimers<Action>,
+}
+
+impl<Action> Timers<Action> {
+    pub fn new() -> Self {
+        Self {
+            once: OneShotTimers::new(),
+            #[cfg(feature = "std")]
+            recurring: RecurringTimers::new(),
+        }
+    }
+
+    /// Deadline of the earliest timer or recurring action.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        let deadline = self.once.next_deadline();
+        #[cfg(feature = "std")]
+        let deadline = deadline
+            .into_iter()
+            .chain(self.recurring.next_deadline())
+            .min();
+        deadline
+    }
+
+    /// Take the earliest timer due at `now` and scheduled before
+    /// `before`, or once there are none, the earliest due recurring
+    /// action, skipping missed ticks according to [CatchUp].
+    pub fn pop_due(&mut self, now: Instant, before: TimerId) -> Option<Fired<Action>> {
+        if let Some((id, action)) = self.once.pop_due(now, before) {
+            return Some(Fired::Timer(id, action));
+        }
+        #[cfg(feature = "std")]
+        while let Some(tick) = self.recurring.pop_due(now) {
+            if let Tick::Fire(id, at, make_action) = tick {
+                return Some(Fired::Recurring(id, at, make_action));
+            }
+        }
+        None
+    }
+}
+
+/// Taken by [Timers::pop_due].
+pub(crate) enum Fired<Action> {
+    Timer(TimerId, Action),
+    /// Recurring action to be made for the scheduled time of the tick.
+    #[cfg(feature = "std")]
+    Recurring(RecurringId, Instant, fn(SystemTime) -> Action),
+}
+
+/// Actions waiting to be dispatched once, see `Store::schedule_timer`.
+#[derive(Clone)]
+pub(crate) struct OneShotTimers<Action> {
+    /// Sorted by deadline, then by id, so timers with the same deadline
+    /// fire in the order they were scheduled.
+    pending: Vec<(Instant, TimerId, Action)>,
+    next_id: u64,
+}
+
+impl<Action> OneShotTimers<Action> {
+    pub fn new() -> Self {
+        Self {
+            pending: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn schedule(&mut self, deadline: Instant, action: Action) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+        let index = self
+            .pending
+            .partition_point(|(other, ..)| *other <= deadline);
+        self.pending.insert(index, (deadline, id, action));
+        id
+    }
+
+    pub fn cancel(&mut self, id: TimerId) -> Option<Action> {
+        let index = self.pending.iter().position(|(_, other, _)| *other == id)?;
+        Some(self.pending.remove(index).2)
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    #[inline(always)]
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.pending.first().map(|(deadline, ..)| *deadline)
+    }
+
+    /// Id which the next scheduled timer will get. Timers with lower
+    /// ids were scheduled before.
+    #[inline(always)]
+    pub fn next_id(&self) -> TimerId {
+        TimerId(self.next_id)
+    }
+
+    /// Take the earliest timer if it's due at `now` and was scheduled
+    /// before `before`.
+    pub fn pop_due(&mut self, now: Instant, before: TimerId) -> Option<(TimerId, Action)> {
+        match self.pending.first() {
+            Some((deadline, id, _)) if *deadline <= now && *id < before => {
+                let (_, id, action) = self.pending.remove(0);
+                Some((id, action))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Id of a recurring action scheduled with `Store::schedule_recurring`.
+/// Ids are never reused within a store.
+#[cfg(feature = "std")]
+#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecurringId(u64);
+
+/// What a recurring action does about ticks missed while
+/// `Store::fire_timers` wasn't called, e.g. because the node stalled.
+///
+/// Ticks are missed when more than one of them is due at once. Either
+/// way, later ticks stay on the original schedule.
+#[cfg(feature = "std")]
+#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+pub enum CatchUp {
+    /// Don't fire for missed ticks, wait for the next one.
+    Skip,
+    /// Fire once for all the missed ticks, with the time of the latest.
+    FireOnceForAll,
+}
+
+#[cfg(feature = "std")]
+#[derive(Clone)]
+struct Recurring<Action> {
+    id: RecurringId,
+    interval: Duration,
+    catch_up: CatchUp,
+    make_action: fn(SystemTime) -> Action,
+    /// Scheduled time of the next tick.
+    next: Instant,
+}
+
+/// Actions dispatched every interval by `Store::fire_timers`.
+#[cfg(feature = "std")]
+#[derive(Clone)]
+pub(crate) struct RecurringTimers<Action> {
+    list: Vec<Recurring<Action>>,
+    next_id: u64,
+}
+
+#[cfg(feature = "std")]
+impl<Action> RecurringTimers<Action> {
+    pub fn new() -> Self {
+        Self {
+            list: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    pub fn schedule(
+        &mut self,
+        now: Instant,
+        interval: Duration,
+        catch_up: CatchUp,
+        make_action: fn(SystemTime) -> Action,
+    ) -> RecurringId {
+        let id = RecurringId(self.next_id);
+        self.next_id += 1;
+        // Zero interval would fire on every call without ever catching up.
+        let interval = interval.max(Duration::from_nanos(1));
+        self.list.push(Recurring {
+            id,
+            interval,
+            catch_up,
+            make_action,
+            next: now + interval,
+        });
+        id
+    }
+
+    pub fn cancel(&mut self, id: RecurringId) -> bool {
+        let len = self.list.len();
+        self.list.retain(|recurring| recurring.id != id);
+        self.list.len() != len
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.list.iter().map(|recurring| recurring.next).min()
+    }
+
+    /// Take the earliest due tick, ties broken by id, and schedule the
+    /// next one a whole number of intervals after it, so that the
+    /// schedule doesn't drift however late the ticks are taken.
+    pub fn pop_due(&mut self, now: Instant) -> Option<Tick<Action>> {
+        let recurring = self
+            .list
+            .iter_mut()
+            .filter(|recurring| recurring.next <= now)
+            .min_by_key(|recurring| (recurring.next, recurring.id))?;
+
+        let interval = recurring.interval.as_nanos();
+        let due = now.duration_since(recurring.next).as_nanos() / interval + 1;
+        let latest = recurring.next + Duration::from_nanos(((due - 1) * interval) as u64);
+        recurring.next = latest + recurring.interval;
+
+        Some(match (due, recurring.catch_up) {
+            (1, _) | (_, CatchUp::FireOnceForAll) => {
+                Tick::Fire(recurring.id, latest, recurring.make_action)
+            }
+            (_, CatchUp::Skip) => Tick::Skipped,
+        })
+    }
+}
+
+/// Tick taken by [RecurringTimers::pop_due].
+#[cfg(feature = "std")]
+pub(crate) enum Tick<Action> {
+    /// Fire the action made for the scheduled time of the tick.
+    Fire(RecurringId, Instant, fn(SystemTime) -> Action),
+    /// Missed ticks skipped, see [CatchUp::Skip].
+    Skipped,
+}
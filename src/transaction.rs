@@ -0,0 +1,64 @@
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+use core::fmt;
+
+use crate::dedup::Seen;
+use crate::ActionWithMeta;
+
+/// Action applied by a transaction, with its dedup key to remember and
+/// the kind which recovered from rate limiting, if any, once committed.
+pub(crate) type Staged<Action> = (ActionWithMeta<Action>, Option<Seen>, Option<&'static str>);
+
+/// Error returned by `Store::dispatch_transaction`. None of the
+/// transaction's actions got applied.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum TransactionError {
+    /// Action at `index` wasn't enabled in the state left by the
+    /// actions before it.
+    NotEnabled { index: usize },
+    /// Action at `index` duplicated a recent one, see `Store::set_dedup`.
+    Duplicate { index: usize },
+    /// Action at `index` was dropped by the rate limiter, see
+    /// `Store::set_rate_limiter`.
+    RateLimited { index: usize },
+    /// Reducer panicked on action at `index` and the panic was caught,
+    /// see `Store::set_reducer_panic_policy`.
+    ReducerPanicked { index: usize },
+    /// State after action at `index` violated the safety condition, see
+    /// `Store::enable_safety_check`. Error is formatted with `Debug`.
+    SafetyViolation { index: usize, error: String },
+    /// Another transaction is in progress, e.g. called from effects of
+    /// the transaction's actions.
+    Nested,
+    /// Store is poisoned, shut down, detached by time travel or paused.
+    Rejected,
+}
+
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotEnabled { index } => {
+                write!(f, "action {} of the transaction is not enabled", index)
+            }
+            Self::Duplicate { index } => {
+                write!(f, "action {} of the transaction is a duplicate", index)
+            }
+            Self::RateLimited { index } => {
+                write!(f, "action {} of the transaction was rate limited", index)
+            }
+            Self::ReducerPanicked { index } => {
+                write!(f, "reducer panicked on action {} of the transaction", index)
+            }
+            Self::SafetyViolation { index, error } => write!(
+                f,
+                "safety condition violated after action {} of the transaction: {}",
+                index, error
+            ),
+            Self::Nested => write!(f, "nested transactions are not supported"),
+            Self::Rejected => write!(f, "store is not accepting actions"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TransactionError {}
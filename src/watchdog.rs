@@ -0,0 +1,133 @@
+use core::time::Duration;
+#[cfg(feature = "std")]
+use std::time::SystemTime;
+
+#[cfg(feature = "std")]
+use crate::Instant;
+use crate::{ActionWithMeta, Store};
+
+/// Measurements of a single processed action.
+///
+/// Measured with the store's [crate::TimeService] and only when something
+/// needs them (statistics, slow action watchdog), since it means reading
+/// the clock three more times per action: before the reducer, after it
+/// and after the effects. Follow-up actions dispatched from the effects
+/// are measured too.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DispatchTimings {
+    pub reducer: Duration,
+    /// Includes processing of actions dispatched from the effects.
+    pub effects: Duration,
+    /// Change of total allocated bytes between reducer start and effects end.
+    #[cfg(feature = "memory")]
+    pub allocated: i64,
+}
+
+impl DispatchTimings {
+    #[inline(always)]
+    pub fn total(&self) -> Duration {
+        self.reducer + self.effects
+    }
+}
+
+/// Function signature for a hook called when processing of an action
+/// took longer than the configured threshold.
+///
+/// Gets the store after the action was processed, so it can inspect the
+/// state or statistics, the action and its timings.
+pub type SlowActionHook<State, Service, Action> =
+    fn(&Store<State, Service, Action>, &ActionWithMeta<Action>, &DispatchTimings);
+
+pub(crate) struct SlowActionWatchdog<State, Service, Action> {
+    pub threshold: Duration,
+    pub hook: SlowActionHook<State, Service, Action>,
+}
+
+impl<State, Service, Action> Clone for SlowActionWatchdog<State, Service, Action> {
+    fn clone(&self) -> Self {
+        Self {
+            threshold: self.threshold,
+            hook: self.hook,
+        }
+    }
+}
+
+/// Watchdog dispatching an action when no action got processed for
+/// `threshold`, see `Store::set_idle_watchdog`.
+#[cfg(feature = "std")]
+pub(crate) struct IdleWatchdog<Action> {
+    threshold: Duration,
+    pub make_action: fn(SystemTime) -> Action,
+    /// `Store::actions_processed` as of the last check.
+    processed: u64,
+    /// Time of the last activity seen by the last check.
+    since: Instant,
+}
+
+#[cfg(feature = "std")]
+impl<Action> IdleWatchdog<Action> {
+    pub fn new(
+        threshold: Duration,
+        make_action: fn(SystemTime) -> Action,
+        processed: u64,
+        now: Instant,
+    ) -> Self {
+        Self {
+            threshold,
+            make_action,
+            processed,
+            since: now,
+        }
+    }
+
+    /// When the watchdog fires, given the current number of processed
+    /// actions and time of the last one.
+    pub fn deadline(&self, processed: u64, last_action_time: Instant) -> Instant {
+        if processed != self.processed {
+            return last_action_time + self.threshold;
+        }
+        self.since + self.threshold
+    }
+
+    /// Whether the watchdog should fire at `now`, given the current
+    /// number of processed actions, time of the last one and whether
+    /// the store can't process actions now. If not, it's reset to count
+    /// from the latest activity, or from `now` if unavailable.
+    pub fn is_due(
+        &mut self,
+        now: Instant,
+        processed: u64,
+        last_action_time: Instant,
+        unavailable: bool,
+    ) -> bool {
+        if now < self.deadline(processed, last_action_time) {
+            if processed != self.processed {
+                self.reset(processed, last_action_time);
+            }
+            return false;
+        }
+        if unavailable {
+            self.reset(processed, now);
+            return false;
+        }
+        true
+    }
+
+    /// Count time from `since`.
+    pub fn reset(&mut self, processed: u64, since: Instant) {
+        self.processed = processed;
+        self.since = since;
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Action> Clone for IdleWatchdog<Action> {
+    fn clone(&self) -> Self {
+        Self {
+            threshold: self.threshold,
+            make_action: self.make_action,
+            processed: self.processed,
+            since: self.since,
+        }
+    }
+}
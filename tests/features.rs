@@ -0,0 +1,52 @@
+//! Builds of the crate under feature combinations other than the one
+//! the tests run with.
+
+use std::path::Path;
+use std::process::Command;
+
+/// `cargo check` the library with `args`, in its own target directory
+/// so it doesn't wait for the lock held by the outer `cargo test`.
+fn check(name: &str, args: &[&str]) {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let output = Command::new(env!("CARGO"))
+        .current_dir(manifest_dir)
+        .args(["check", "--lib", "--quiet"])
+        .args(args)
+        .env(
+            "CARGO_TARGET_DIR",
+            manifest_dir.join("target/features").join(name),
+        )
+        .output()
+        .expect("failed to run cargo");
+    assert!(
+        output.status.success(),
+        "`cargo check {}` failed:\n{}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+/// Without `std` the crate is `no_std`, so any use of `std` fails.
+#[test]
+fn no_std() {
+    check("no_std", &["--no-default-features"]);
+}
+
+/// Target without `std` at all, so dependencies needing it fail too.
+/// Needs `rustup target add thumbv7em-none-eabihf`.
+#[test]
+#[ignore]
+fn no_std_embedded() {
+    check(
+        "no_std_embedded",
+        &["--no-default-features", "--target", "thumbv7em-none-eabihf"],
+    );
+}
+
+/// Combinations which used to drift apart.
+#[test]
+fn feature_matrix() {
+    for features in ["serde", "memory", "memory,jemallocator", "memory-counter"] {
+        check(&features.replace(',', "-"), &["--features", features]);
+    }
+}
@@ -0,0 +1,5 @@
+#[test]
+fn strict_capabilities() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/strict/*.rs");
+}
@@ -0,0 +1,154 @@
+//! Spans and events emitted with `tracing` feature, captured with a
+//! `tracing_subscriber` layer.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use redux_rs::{ActionWithMeta, EnablingCondition, Instant, Store, TimeService};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::{LookupSpan, Registry};
+
+/// Fields of a span or an event, sorted by name.
+#[derive(Default)]
+struct Fields(Vec<(&'static str, String)>);
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.push((field.name(), value.to_owned()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.push((field.name(), format!("{:?}", value)));
+    }
+}
+
+impl fmt::Display for Fields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut fields = self.0.clone();
+        fields.sort();
+        for (name, value) in fields {
+            write!(f, " {}={}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Records new spans, recorded fields and events, each prefixed with
+/// the path of span names from the root.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+impl Recorder {
+    fn push(&self, path: String, kind: &str, fields: Fields) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("{} {}{}", path, kind, fields));
+    }
+
+    fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+fn span_path<S>(id: &Id, ctx: &Context<'_, S>) -> String
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let span = ctx.span(id).unwrap();
+    let names: Vec<_> = span.scope().from_root().map(|span| span.name()).collect();
+    names.join("/")
+}
+
+impl<S> Layer<S> for Recorder
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        self.push(span_path(id, &ctx), "new", fields);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        values.record(&mut fields);
+        self.push(span_path(id, &ctx), "record", fields);
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let path = ctx
+            .event_scope(event)
+            .map(|scope| {
+                let names: Vec<_> = scope.from_root().map(|span| span.name()).collect();
+                names.join("/")
+            })
+            .unwrap_or_default();
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        self.push(path, "event", fields);
+    }
+}
+
+/// Clock which never advances, so action ids are 1, 2, ...
+struct Clock(Instant);
+
+impl TimeService for Clock {
+    fn monotonic_time(&mut self) -> Instant {
+        self.0
+    }
+}
+
+enum Action {
+    Cascade,
+    Child,
+    Disabled,
+}
+
+impl EnablingCondition<()> for Action {
+    fn is_enabled(&self, _: &()) -> bool {
+        !matches!(self, Action::Disabled)
+    }
+}
+
+fn effects(store: &mut Store<(), Clock, Action>, action: &ActionWithMeta<Action>) {
+    if let Action::Cascade = action.action {
+        store.dispatch(Action::Child);
+        store.dispatch(Action::Disabled);
+    }
+}
+
+#[test]
+fn cascading_dispatch_spans() {
+    let recorder = Recorder::default();
+    let subscriber = Registry::default().with(recorder.clone());
+
+    tracing::subscriber::with_default(subscriber, || {
+        let reducer = |_: &mut (), _: &ActionWithMeta<Action>| {};
+        let clock = Clock(Instant::now());
+        let mut store = Store::new(reducer, effects, clock, SystemTime::UNIX_EPOCH, ());
+        store.dispatch(Action::Cascade);
+    });
+
+    let kind = "kind=tracing_spans::Action";
+    assert_eq!(
+        recorder.take(),
+        [
+            format!("dispatch new depth=0 id=1 {}", kind),
+            "dispatch/reducer new".to_owned(),
+            "dispatch/effects new".to_owned(),
+            format!("dispatch/effects/dispatch new depth=1 id=2 {}", kind),
+            "dispatch/effects/dispatch record parent_id=1".to_owned(),
+            "dispatch/effects/dispatch/reducer new".to_owned(),
+            "dispatch/effects/dispatch/effects new".to_owned(),
+            format!(
+                "dispatch/effects event {} message=action rejected reason=not enabled",
+                kind
+            ),
+        ]
+    );
+}
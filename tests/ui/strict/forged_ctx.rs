@@ -0,0 +1,5 @@
+use redux_rs::EffectsCtx;
+
+fn main() {
+    let _ctx = EffectsCtx { _private: () };
+}
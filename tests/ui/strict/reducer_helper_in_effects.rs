@@ -0,0 +1,15 @@
+use redux_rs::{ActionWithMeta, EffectsCtx, EnablingCondition, ReducerCtx, StrictStore};
+
+struct Deposit(u64);
+impl EnablingCondition<u64> for Deposit {}
+
+fn add(_: &ReducerCtx, balance: &mut u64, amount: u64) {
+    *balance += amount;
+}
+
+fn effects(ctx: &EffectsCtx, _: &mut StrictStore<u64, (), Deposit>, action: &ActionWithMeta<Deposit>) {
+    let mut balance = 0;
+    add(ctx, &mut balance, action.action.0);
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use redux_rs::{ActionWithMeta, EffectsCtx, EnablingCondition, StrictStore, TimeService};
+
+struct Service {
+    sent: Vec<u64>,
+}
+
+impl TimeService for Service {}
+
+struct Publish(u64);
+impl EnablingCondition<()> for Publish {}
+
+fn effects(_: &EffectsCtx, store: &mut StrictStore<(), Service, Publish>, action: &ActionWithMeta<Publish>) {
+    store.service.sent.push(action.action.0);
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use redux_rs::{ActionWithMeta, EffectsCtx, EnablingCondition, StrictStore, TimeService};
+
+struct Service {
+    ctx: Option<&'static EffectsCtx>,
+}
+
+impl TimeService for Service {}
+
+struct Publish;
+impl EnablingCondition<()> for Publish {}
+
+fn effects(ctx: &EffectsCtx, store: &mut StrictStore<(), Service, Publish>, _: &ActionWithMeta<Publish>) {
+    store.service_with(ctx).ctx = Some(ctx);
+}
+
+fn main() {}
@@ -0,0 +1,54 @@
+//! Store in the browser, with [PerformanceTimeService] as the clock.
+//!
+//! Run with `wasm-pack test --headless --firefox -- --features wasm`.
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use std::time::SystemTime;
+
+use redux_rs::{ActionId, ActionWithMeta, EnablingCondition, PerformanceTimeService, Store};
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+enum Action {
+    Record,
+    RecordTwice,
+}
+
+impl EnablingCondition<Vec<ActionId>> for Action {}
+
+fn reducer(ids: &mut Vec<ActionId>, action: &ActionWithMeta<Action>) {
+    ids.push(action.id);
+}
+
+fn effects(
+    store: &mut Store<Vec<ActionId>, PerformanceTimeService, Action>,
+    action: &ActionWithMeta<Action>,
+) {
+    if let Action::RecordTwice = action.action {
+        store.dispatch(Action::Record);
+    }
+}
+
+#[wasm_bindgen_test]
+fn dispatch_with_performance_clock() {
+    let service = PerformanceTimeService::new().unwrap();
+    // `SystemTime::now()` panics on `wasm32-unknown-unknown`.
+    let mut store = Store::new(
+        reducer,
+        effects,
+        service,
+        SystemTime::UNIX_EPOCH,
+        Vec::new(),
+    );
+
+    for _ in 0..100 {
+        assert!(store.dispatch(Action::Record));
+        assert!(store.dispatch(Action::RecordTwice));
+    }
+
+    let ids = store.state();
+    assert_eq!(ids.len(), 300);
+    assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(store.last_action_id(), ids[299]);
+}